@@ -38,6 +38,9 @@ fn main() {
                     println!("Cursor pos: ({} , {})", xshift, yshift);
                     // dont override the default mouse handler
                 }
+                WindowEvent::FileDropped(ref path) => {
+                    println!("You dropped the file: {}", path.display());
+                }
                 _ => {}
             }
         }
@@ -4,7 +4,7 @@ extern crate rand;
 
 use kiss3d::light::Light;
 use kiss3d::post_processing::SobelEdgeHighlight;
-use kiss3d::post_processing::{Grayscales, Waves};
+use kiss3d::post_processing::{DepthOfField, Grayscales, Waves};
 use kiss3d::window::Window;
 use na::Translation3;
 use rand::random;
@@ -33,10 +33,13 @@ fn main() {
     let mut sobel = SobelEdgeHighlight::new(4.0);
     let mut waves = Waves::new();
     let mut grays = Grayscales::new();
+    let mut dof = DepthOfField::new();
+    dof.set_focus_distance(3.0);
+    dof.set_focal_range(4.0);
 
     window.set_background_color(1.0, 1.0, 1.0);
     window.set_light(Light::StickToCamera);
-    window.set_framerate_limit(Some(60));
+    window.set_framerate_limit(Some(60.0));
 
     let mut time = 0usize;
     let mut counter = 0usize;
@@ -44,7 +47,7 @@ fn main() {
     while !window.should_close() {
         if time % 200 == 0 {
             time = 0;
-            counter = (counter + 1) % 4;
+            counter = (counter + 1) % 5;
         }
 
         time = time + 1;
@@ -54,6 +57,7 @@ fn main() {
             1 => window.render_with_effect(&mut grays),
             2 => window.render_with_effect(&mut waves),
             3 => window.render_with_effect(&mut sobel),
+            4 => window.render_with_effect(&mut dof),
             _ => unreachable!(),
         };
     }
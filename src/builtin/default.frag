@@ -5,31 +5,171 @@
    precision mediump float;
 #endif
 
-varying vec3 local_light_position;
+#define MAX_LIGHTS 4
+#define MAX_SHADOWS 2
+
 varying vec2 tex_coord_v;
 varying vec3 normalInterp;
-varying vec3 vertPos;
+varying vec4 tangentInterp;
+varying vec3 worldPos;
+varying vec4 shadowCoords[MAX_SHADOWS];
+varying float fogDepth;
 
 uniform vec3 color;
+// The opacity of the object, multiplied by the alpha channel of the texture.
+uniform float alpha;
+uniform vec3 eye;
+// A light position with w = 1, or the direction toward a directional light with w = 0.
+uniform vec4 light_positions[MAX_LIGHTS];
+// The light colors multiplied by their intensities. Unused lights are black.
+uniform vec3 light_colors[MAX_LIGHTS];
+// The direction a spotlight is shining toward.
+uniform vec3 light_directions[MAX_LIGHTS];
+// The cosines of the inner and outer angles of a spotlight, and its range, 0 for other lights.
+uniform vec3 light_cones[MAX_LIGHTS];
 uniform sampler2D tex;
+// The tangent-space normal map, used only if use_normal_map is 1.
+uniform sampler2D normal_map;
+uniform float use_normal_map;
+// The color emitted regardless of the lighting, multiplied by emissive_texture.
+uniform vec3 emissive;
+uniform sampler2D emissive_texture;
+// The environment reflected by the object, used only if use_reflection_map is 1, and the
+// fraction of it reflected when looking at the surface head-on.
+uniform samplerCube reflection_map;
+uniform float use_reflection_map;
+uniform float reflectivity;
+// The indices of the lights casting shadows, or -1 for the unused shadow maps.
+uniform float shadow_lights[MAX_SHADOWS];
+uniform float shadow_biases[MAX_SHADOWS];
+uniform sampler2D shadow_maps[MAX_SHADOWS];
+uniform float shadow_texel;
+// The radius of the filter of the shadows, in texels.
+uniform float shadow_softness;
+// 0 without fog, 1 for a linear fog between fog_params.x and fog_params.y, and 2 for an
+// exponential fog of density fog_params.x.
+uniform float fog_mode;
+uniform vec3 fog_color;
+uniform vec2 fog_params;
+// 1 if the lighting is computed in linear space, with the colors and sRGB textures decoded and
+// the output encoded to sRGB.
+uniform float gamma_correction;
+uniform float tex_srgb;
+uniform float emissive_srgb;
 const vec3 specColor = vec3(0.4, 0.4, 0.4);
 
+vec3 to_linear(vec3 srgb, float decode) {
+  return decode > 0.5 ? pow(srgb, vec3(2.2)) : srgb;
+}
+
+float unpack_depth(vec4 rgba) {
+  return dot(rgba, vec4(1.0, 1.0 / 255.0, 1.0 / 65025.0, 1.0 / 16581375.0));
+}
+
+// The fraction of the light of a shadow map reaching this fragment, filtered over 3x3 samples.
+float shadow_visibility(sampler2D shadow_map, vec4 shadow_coord, float bias) {
+  vec3 coord = shadow_coord.xyz / shadow_coord.w * 0.5 + 0.5;
+
+  if (coord.x < 0.0 || coord.x > 1.0 || coord.y < 0.0 || coord.y > 1.0 || coord.z > 1.0) {
+    return 1.0;
+  }
+
+  float lit = 0.0;
+
+  for (int x = -1; x <= 1; x++) {
+    for (int y = -1; y <= 1; y++) {
+      vec2 offset = vec2(float(x), float(y)) * shadow_texel * shadow_softness;
+      float depth = unpack_depth(texture2D(shadow_map, coord.xy + offset));
+      lit += coord.z - bias > depth ? 0.0 : 1.0;
+    }
+  }
+
+  return lit / 9.0;
+}
+
+// The attenuation of a light by its cone and its range, 1 for the lights which are not
+// spotlights. `to_light` goes from the fragment to the light.
+float spot_attenuation(vec3 direction, vec3 cone, vec3 to_light) {
+  if (cone.z <= 0.0) {
+    return 1.0;
+  }
+
+  float dist = length(to_light);
+  float falloff = smoothstep(cone.y, cone.x, dot(direction, -to_light / max(dist, 0.0001)));
+  float fade = clamp(1.0 - pow(dist / cone.z, 4.0), 0.0, 1.0);
+
+  return falloff * fade * fade;
+}
+
 void main() {
   vec3 normal = normalize(normalInterp);
-  vec3 lightDir = normalize(local_light_position - vertPos);
+  vec3 base_color = to_linear(color, gamma_correction);
 
-  float lambertian = max(dot(lightDir, normal), 0.0);
-  float specular = 0.0;
+  if (use_normal_map > 0.5) {
+    vec3 tangent = normalize(tangentInterp.xyz - normal * dot(normal, tangentInterp.xyz));
+    vec3 bitangent = cross(normal, tangent) * tangentInterp.w;
+    vec3 mapped = texture2D(normal_map, tex_coord_v).xyz * 2.0 - 1.0;
+    normal = normalize(mat3(tangent, bitangent, normal) * mapped);
+  }
+
+  vec3 viewDir = normalize(eye - worldPos);
+  vec3 lighting = vec3(0.0, 0.0, 0.0);
+
+  for (int i = 0; i < MAX_LIGHTS; i++) {
+    vec4 light = light_positions[i];
+    vec3 toLight = light.xyz - worldPos * light.w;
+    vec3 lightDir = normalize(toLight);
+    vec3 lightColor = light_colors[i] * spot_attenuation(light_directions[i], light_cones[i], toLight);
+
+    float lambertian = max(dot(lightDir, normal), 0.0);
+    float specular = 0.0;
+
+    if(lambertian > 0.0) {
+      vec3 halfDir = normalize(lightDir + viewDir);
+      float specAngle = max(dot(halfDir, normal), 0.0);
+      specular = pow(specAngle, 30.0);
+    }
 
-  if(lambertian > 0.0) {
-    vec3 viewDir = normalize(-vertPos);
-    vec3 halfDir = normalize(lightDir + viewDir);
-    float specAngle = max(dot(halfDir, normal), 0.0);
-    specular = pow(specAngle, 30.0);
+    float visibility = 1.0;
+
+    for (int k = 0; k < MAX_SHADOWS; k++) {
+      if (float(i) == shadow_lights[k]) {
+        visibility = shadow_visibility(shadow_maps[k], shadowCoords[k], shadow_biases[k]);
+      }
+    }
+
+    lighting += visibility * lightColor * (lambertian * base_color + specular * specColor);
   }
 
   vec4 tex_color = texture2D(tex, tex_coord_v);
-  gl_FragColor = tex_color * vec4(color / 3.0 +
-                                  lambertian * color / 3.0 +
-                                  specular * specColor / 3.0, 1.0);
+  tex_color.rgb = to_linear(tex_color.rgb, gamma_correction * tex_srgb);
+  vec3 emitted = to_linear(emissive, gamma_correction) *
+    to_linear(texture2D(emissive_texture, tex_coord_v).rgb, gamma_correction * emissive_srgb);
+  vec4 frag_color = tex_color * vec4(base_color / 3.0 + lighting / 3.0, 1.0);
+
+  if (use_reflection_map > 0.5) {
+    vec3 reflected = to_linear(textureCube(reflection_map, reflect(-viewDir, normal)).rgb, gamma_correction);
+    // Schlick's approximation: the surface reflects more at grazing angles.
+    float fresnel = reflectivity + (1.0 - reflectivity) * pow(1.0 - max(dot(normal, viewDir), 0.0), 5.0);
+    frag_color.rgb = mix(frag_color.rgb, reflected, fresnel);
+  }
+
+  frag_color.rgb += emitted;
+
+  // The fraction of the fragment color which is not hidden by the fog.
+  float fog_visibility = 1.0;
+
+  if (fog_mode > 1.5) {
+    fog_visibility = exp(-fog_params.x * fogDepth);
+  } else if (fog_mode > 0.5) {
+    fog_visibility = (fog_params.y - fogDepth) / max(fog_params.y - fog_params.x, 0.0001);
+  }
+
+  vec3 fogged = mix(to_linear(fog_color, gamma_correction), frag_color.rgb, clamp(fog_visibility, 0.0, 1.0));
+
+  if (gamma_correction > 0.5) {
+    fogged = pow(fogged, vec3(1.0 / 2.2));
+  }
+
+  gl_FragColor = vec4(fogged, frag_color.a * alpha);
 }
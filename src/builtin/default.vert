@@ -2,21 +2,34 @@
 attribute vec3 position;
 attribute vec2 tex_coord;
 attribute vec3 normal;
+// The w component is the handedness of the tangent space.
+attribute vec4 tangent;
+
+#define MAX_SHADOWS 2
 
 uniform mat3 ntransform, scale;
 uniform mat4 proj, view, transform;
-uniform vec3 light_position;
+// The transformations from world coordinates to the clip spaces of the lights casting shadows.
+uniform mat4 light_transforms[MAX_SHADOWS];
 
-varying vec3 local_light_position;
 varying vec2 tex_coord_v;
 varying vec3 normalInterp;
-varying vec3 vertPos;
+varying vec4 tangentInterp;
+varying vec3 worldPos;
+varying vec4 shadowCoords[MAX_SHADOWS];
+varying float fogDepth;
 
 void main(){
-    gl_Position = proj * view * transform * vec4(scale * position, 1.0);
-    vec4 vertPos4 = view * transform * vec4(scale * position, 1.0);
-    vertPos = vec3(vertPos4) / vertPos4.w;
-    normalInterp = mat3(view) * ntransform * normal;
+    vec4 worldPos4 = transform * vec4(scale * position, 1.0);
+    vec4 viewPos = view * worldPos4;
+    gl_Position = proj * viewPos;
+    fogDepth = -viewPos.z;
+    worldPos = vec3(worldPos4) / worldPos4.w;
+    normalInterp = ntransform * normal;
+    tangentInterp = vec4(ntransform * (scale * tangent.xyz), tangent.w);
     tex_coord_v = tex_coord;
-    local_light_position = (view * vec4(light_position, 1.0)).xyz;
+
+    for (int k = 0; k < MAX_SHADOWS; k++) {
+        shadowCoords[k] = light_transforms[k] * worldPos4;
+    }
 }
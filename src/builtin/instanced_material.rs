@@ -0,0 +1,317 @@
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::light::Lights;
+use crate::resource::Material;
+use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
+use crate::scene::{Instances, ObjectData};
+use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3, Vector4};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[path = "../error.rs"]
+mod error;
+
+/// A material that draws all the instances of an `InstancedNode` at once.
+///
+/// Objects that are not part of an `InstancedNode` are drawn as a single instance.
+pub struct InstancedMaterial {
+    effect: Effect,
+    instancing: bool,
+    pos: ShaderAttribute<Point3<f32>>,
+    normal: ShaderAttribute<Vector3<f32>>,
+    tex_coord: ShaderAttribute<Point2<f32>>,
+    inst_translation: ShaderAttribute<Point3<f32>>,
+    inst_rotation: ShaderAttribute<Vector4<f32>>,
+    inst_color: ShaderAttribute<Point3<f32>>,
+    light: ShaderUniform<Point3<f32>>,
+    color: ShaderUniform<Point3<f32>>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    ntransform: ShaderUniform<Matrix3<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    view: ShaderUniform<Matrix4<f32>>,
+}
+
+impl InstancedMaterial {
+    /// Creates a new `InstancedMaterial`.
+    pub fn new() -> InstancedMaterial {
+        let mut effect = Effect::new_from_str(INSTANCED_VERTEX_SRC, INSTANCED_FRAGMENT_SRC);
+
+        effect.use_program();
+
+        InstancedMaterial {
+            instancing: Context::get().supports_instancing(),
+            pos: effect.attribute("position"),
+            normal: effect.attribute("normal"),
+            tex_coord: effect.attribute("tex_coord"),
+            inst_translation: effect.attribute("inst_translation"),
+            inst_rotation: effect.attribute("inst_rotation"),
+            inst_color: effect.attribute("inst_color"),
+            light: effect.uniform("light_position"),
+            color: effect.uniform("color"),
+            transform: effect.uniform("transform"),
+            scale: effect.uniform("scale"),
+            ntransform: effect.uniform("ntransform"),
+            view: effect.uniform("view"),
+            proj: effect.uniform("proj"),
+            effect,
+        }
+    }
+
+    // Issues one instanced draw call, or one draw call per instance if instancing is not
+    // available.
+    fn draw(&mut self, instances: Option<&Instances>, mode: u32, count: i32) {
+        let ctxt = Context::get();
+
+        match instances {
+            Some(instances) if self.instancing => {
+                verify!(ctxt.draw_elements_instanced(
+                    mode,
+                    count,
+                    Context::UNSIGNED_SHORT,
+                    0,
+                    instances.len() as i32
+                ));
+            }
+            Some(instances) => {
+                for i in 0..instances.len() {
+                    let (translation, rotation, color) = instances.get(i);
+
+                    self.inst_translation.set_constant(&translation);
+                    self.inst_rotation.set_constant(&rotation);
+                    self.inst_color.set_constant(&color);
+                    verify!(ctxt.draw_elements(mode, count, Context::UNSIGNED_SHORT, 0));
+                }
+            }
+            None => {
+                self.inst_translation.set_constant(&Point3::origin());
+                self.inst_rotation
+                    .set_constant(&Vector4::new(0.0, 0.0, 0.0, 1.0));
+                self.inst_color.set_constant(&Point3::new(1.0, 1.0, 1.0));
+                verify!(ctxt.draw_elements(mode, count, Context::UNSIGNED_SHORT, 0));
+            }
+        }
+    }
+}
+
+impl Default for InstancedMaterial {
+    fn default() -> Self {
+        InstancedMaterial::new()
+    }
+}
+
+impl Material for InstancedMaterial {
+    fn render(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        data: &ObjectData,
+        mesh: &mut Mesh,
+    ) {
+        let ctxt = Context::get();
+        self.effect.use_program();
+        self.pos.enable();
+        self.normal.enable();
+        self.tex_coord.enable();
+
+        /*
+         *
+         * Setup camera and light.
+         *
+         */
+        camera.upload(pass, &mut self.proj, &mut self.view);
+
+        // NOTE: only the first point light is supported by this material.
+        let pos = lights
+            .iter()
+            .find_map(|l| l.position(&*camera))
+            .unwrap_or_else(|| camera.eye());
+
+        self.light.upload(&pos);
+
+        /*
+         *
+         * Setup object-related stuffs.
+         *
+         */
+        let formated_transform = transform.to_homogeneous();
+        let formated_ntransform = transform.rotation.to_rotation_matrix().into_inner();
+        let formated_scale = Matrix3::from_diagonal(&Vector3::new(scale.x, scale.y, scale.z));
+
+        self.transform.upload(&formated_transform);
+        self.ntransform.upload(&formated_ntransform);
+        self.scale.upload(&formated_scale);
+
+        mesh.bind(&mut self.pos, &mut self.normal, &mut self.tex_coord);
+
+        /*
+         *
+         * Setup instances.
+         *
+         */
+        let instances = data
+            .user_data()
+            .downcast_ref::<Rc<RefCell<Instances>>>()
+            .cloned();
+        let mut instances = instances.as_ref().map(|i| i.borrow_mut());
+
+        if self.instancing {
+            if let Some(ref mut instances) = instances {
+                self.inst_translation.enable();
+                self.inst_rotation.enable();
+                self.inst_color.enable();
+                self.inst_translation.bind(instances.translations_mut());
+                self.inst_rotation.bind(instances.rotations_mut());
+                self.inst_color.bind(instances.colors_mut());
+                self.inst_translation.set_divisor(1);
+                self.inst_rotation.set_divisor(1);
+                self.inst_color.set_divisor(1);
+            }
+        }
+
+        let instances = instances.as_deref();
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(data.texture())));
+
+        if data.surface_rendering_active() {
+            self.color.upload(data.color());
+
+            if data.backface_culling_enabled() {
+                verify!(ctxt.enable(Context::CULL_FACE));
+            } else {
+                verify!(ctxt.disable(Context::CULL_FACE));
+            }
+
+            let _ = verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::FILL));
+            self.draw(instances, Context::TRIANGLES, mesh.num_pts() as i32);
+        }
+
+        if data.lines_width() != 0.0 {
+            self.color
+                .upload(data.lines_color().unwrap_or(data.color()));
+
+            verify!(ctxt.disable(Context::CULL_FACE));
+            ignore!(ctxt.line_width(data.lines_width()));
+
+            if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
+                self.draw(instances, Context::TRIANGLES, mesh.num_pts() as i32);
+            } else {
+                mesh.bind_edges();
+                self.draw(instances, Context::LINES, mesh.num_pts() as i32 * 2);
+            }
+            ctxt.line_width(1.0);
+        }
+
+        if data.points_size() != 0.0 {
+            self.color.upload(data.color());
+
+            verify!(ctxt.disable(Context::CULL_FACE));
+            ctxt.point_size(data.points_size());
+            if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT)) {
+                self.draw(instances, Context::TRIANGLES, mesh.num_pts() as i32);
+            } else {
+                self.draw(instances, Context::POINTS, mesh.num_pts() as i32);
+            }
+            ctxt.point_size(1.0);
+        }
+
+        mesh.unbind();
+
+        // NOTE: the divisors must be reset since attribute locations are shared between programs.
+        if self.instancing {
+            self.inst_translation.set_divisor(0);
+            self.inst_rotation.set_divisor(0);
+            self.inst_color.set_divisor(0);
+        }
+
+        self.pos.disable();
+        self.normal.disable();
+        self.tex_coord.disable();
+        self.inst_translation.disable();
+        self.inst_rotation.disable();
+        self.inst_color.disable();
+    }
+}
+
+/// Vertex shader of the instanced material.
+pub static INSTANCED_VERTEX_SRC: &str = A_VERY_LONG_STRING;
+/// Fragment shader of the instanced material.
+pub static INSTANCED_FRAGMENT_SRC: &str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &str = "#version 100
+attribute vec3 position;
+attribute vec2 tex_coord;
+attribute vec3 normal;
+attribute vec3 inst_translation;
+attribute vec4 inst_rotation;
+attribute vec3 inst_color;
+
+uniform mat3 ntransform, scale;
+uniform mat4 proj, view, transform;
+uniform vec3 light_position;
+
+varying vec3 local_light_position;
+varying vec2 tex_coord_v;
+varying vec3 normalInterp;
+varying vec3 vertPos;
+varying vec3 inst_color_v;
+
+// Rotates `v` by the unit quaternion `q`.
+vec3 rotate(vec4 q, vec3 v) {
+    return v + 2.0 * cross(q.xyz, cross(q.xyz, v) + q.w * v);
+}
+
+void main(){
+    vec3 inst_position = rotate(inst_rotation, scale * position) + inst_translation;
+    vec4 vertPos4 = view * transform * vec4(inst_position, 1.0);
+    gl_Position = proj * vertPos4;
+    vertPos = vec3(vertPos4) / vertPos4.w;
+    normalInterp = mat3(view) * ntransform * rotate(inst_rotation, normal);
+    tex_coord_v = tex_coord;
+    local_light_position = (view * vec4(light_position, 1.0)).xyz;
+    inst_color_v = inst_color;
+}
+";
+
+const ANOTHER_VERY_LONG_STRING: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+varying vec3 local_light_position;
+varying vec2 tex_coord_v;
+varying vec3 normalInterp;
+varying vec3 vertPos;
+varying vec3 inst_color_v;
+
+uniform vec3 color;
+uniform sampler2D tex;
+const vec3 specColor = vec3(0.4, 0.4, 0.4);
+
+void main() {
+  vec3 normal = normalize(normalInterp);
+  vec3 lightDir = normalize(local_light_position - vertPos);
+
+  float lambertian = max(dot(lightDir, normal), 0.0);
+  float specular = 0.0;
+
+  if(lambertian > 0.0) {
+    vec3 viewDir = normalize(-vertPos);
+    vec3 halfDir = normalize(lightDir + viewDir);
+    float specAngle = max(dot(halfDir, normal), 0.0);
+    specular = pow(specAngle, 30.0);
+  }
+
+  vec3 base_color = color * inst_color_v;
+  vec4 tex_color = texture2D(tex, tex_coord_v);
+  gl_FragColor = tex_color * vec4(base_color / 3.0 +
+                                  lambertian * base_color / 3.0 +
+                                  specular * specColor / 3.0, 1.0);
+}
+";
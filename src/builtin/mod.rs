@@ -1,13 +1,31 @@
 //! Built-in geometries, shaders and effects.
 
+pub use self::instanced_material::{
+    InstancedMaterial, INSTANCED_FRAGMENT_SRC, INSTANCED_VERTEX_SRC,
+};
 pub use self::normals_material::{NormalsMaterial, NORMAL_FRAGMENT_SRC, NORMAL_VERTEX_SRC};
 pub use self::object_material::{ObjectMaterial, OBJECT_FRAGMENT_SRC, OBJECT_VERTEX_SRC};
+pub use self::pbr_material::{PbrMaterial, PBR_FRAGMENT_SRC, PBR_VERTEX_SRC};
+pub use self::point_cloud_material::{
+    PointCloudMaterial, POINT_CLOUD_FRAGMENT_SRC, POINT_CLOUD_VERTEX_SRC,
+};
+#[cfg(feature = "hot-reload")]
+pub(crate) use self::shader_material::poll_shader_files;
+pub use self::shader_material::ShaderMaterial;
 pub use self::uvs_material::{UvsMaterial, UVS_FRAGMENT_SRC, UVS_VERTEX_SRC};
+pub use self::vertex_color_material::{
+    VertexColorMaterial, VERTEX_COLOR_FRAGMENT_SRC, VERTEX_COLOR_VERTEX_SRC,
+};
 
 pub use self::planar_object_material::PlanarObjectMaterial;
 
+mod instanced_material;
 mod normals_material;
 mod object_material;
+mod pbr_material;
+mod point_cloud_material;
+mod shader_material;
 mod uvs_material;
+mod vertex_color_material;
 
 mod planar_object_material;
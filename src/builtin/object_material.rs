@@ -1,10 +1,12 @@
 use crate::camera::Camera;
 use crate::context::Context;
-use crate::light::Light;
+use crate::fog::FogMode;
+use crate::light::{Light, Lights, MAX_LIGHTS, MAX_SHADOWS};
+use crate::renderer::wide_line_renderer::WideLineRenderer;
 use crate::resource::Material;
-use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
-use crate::scene::ObjectData;
-use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3};
+use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform, TextureManager};
+use crate::scene::{self, ObjectData, WireframeMode};
+use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector2, Vector3, Vector4};
 
 #[path = "../error.rs"]
 mod error;
@@ -15,13 +17,34 @@ pub struct ObjectMaterial {
     pos: ShaderAttribute<Point3<f32>>,
     normal: ShaderAttribute<Vector3<f32>>,
     tex_coord: ShaderAttribute<Point2<f32>>,
-    light: ShaderUniform<Point3<f32>>,
+    tangent: ShaderAttribute<Vector4<f32>>,
+    normal_map: ShaderUniform<i32>,
+    use_normal_map: ShaderUniform<f32>,
+    reflection_map: ShaderUniform<i32>,
+    use_reflection_map: ShaderUniform<f32>,
+    reflectivity: ShaderUniform<f32>,
+    // NOTE: those may be optimized out by the shader compiler.
+    emissive: Option<ShaderUniform<Point3<f32>>>,
+    emissive_texture: Option<ShaderUniform<i32>>,
+    fog_mode: ShaderUniform<f32>,
+    fog_color: ShaderUniform<Point3<f32>>,
+    fog_params: ShaderUniform<Vector2<f32>>,
+    gamma_correction: ShaderUniform<f32>,
+    tex_srgb: ShaderUniform<f32>,
+    emissive_srgb: Option<ShaderUniform<f32>>,
+    eye: ShaderUniform<Point3<f32>>,
+    lights: Vec<LightUniforms>,
+    shadows: Vec<ShadowUniforms>,
+    shadow_texel: ShaderUniform<f32>,
+    shadow_softness: ShaderUniform<f32>,
     color: ShaderUniform<Point3<f32>>,
+    alpha: ShaderUniform<f32>,
     transform: ShaderUniform<Matrix4<f32>>,
     scale: ShaderUniform<Matrix3<f32>>,
     ntransform: ShaderUniform<Matrix3<f32>>,
     proj: ShaderUniform<Matrix4<f32>>,
     view: ShaderUniform<Matrix4<f32>>,
+    wide_lines: WideLineRenderer,
 }
 
 impl ObjectMaterial {
@@ -34,17 +57,41 @@ impl ObjectMaterial {
 
         // get the variables locations
         ObjectMaterial {
-            pos: effect.get_attrib("position").unwrap(),
-            normal: effect.get_attrib("normal").unwrap(),
-            tex_coord: effect.get_attrib("tex_coord").unwrap(),
-            light: effect.get_uniform("light_position").unwrap(),
-            color: effect.get_uniform("color").unwrap(),
-            transform: effect.get_uniform("transform").unwrap(),
-            scale: effect.get_uniform("scale").unwrap(),
-            ntransform: effect.get_uniform("ntransform").unwrap(),
-            view: effect.get_uniform("view").unwrap(),
-            proj: effect.get_uniform("proj").unwrap(),
+            pos: effect.attribute("position"),
+            normal: effect.attribute("normal"),
+            tex_coord: effect.attribute("tex_coord"),
+            tangent: effect.attribute("tangent"),
+            normal_map: effect.uniform("normal_map"),
+            use_normal_map: effect.uniform("use_normal_map"),
+            reflection_map: effect.uniform("reflection_map"),
+            use_reflection_map: effect.uniform("use_reflection_map"),
+            reflectivity: effect.uniform("reflectivity"),
+            emissive: effect.get_uniform("emissive"),
+            emissive_texture: effect.get_uniform("emissive_texture"),
+            fog_mode: effect.uniform("fog_mode"),
+            fog_color: effect.uniform("fog_color"),
+            fog_params: effect.uniform("fog_params"),
+            gamma_correction: effect.uniform("gamma_correction"),
+            tex_srgb: effect.uniform("tex_srgb"),
+            emissive_srgb: effect.get_uniform("emissive_srgb"),
+            eye: effect.uniform("eye"),
+            lights: (0..MAX_LIGHTS)
+                .map(|i| LightUniforms::new(&effect, i))
+                .collect(),
+            shadows: (0..MAX_SHADOWS)
+                .map(|k| ShadowUniforms::new(&effect, k))
+                .collect(),
+            shadow_texel: effect.uniform("shadow_texel"),
+            shadow_softness: effect.uniform("shadow_softness"),
+            color: effect.uniform("color"),
+            alpha: effect.uniform("alpha"),
+            transform: effect.uniform("transform"),
+            scale: effect.uniform("scale"),
+            ntransform: effect.uniform("ntransform"),
+            view: effect.uniform("view"),
+            proj: effect.uniform("proj"),
             effect: effect,
+            wide_lines: WideLineRenderer::new(),
         }
     }
 
@@ -59,6 +106,7 @@ impl ObjectMaterial {
         self.pos.disable();
         self.normal.disable();
         self.tex_coord.disable();
+        self.tangent.disable();
     }
 }
 
@@ -69,7 +117,7 @@ impl Material for ObjectMaterial {
         transform: &Isometry3<f32>,
         scale: &Vector3<f32>,
         camera: &mut dyn Camera,
-        light: &Light,
+        lights: &Lights,
         data: &ObjectData,
         mesh: &mut Mesh,
     ) {
@@ -83,12 +131,53 @@ impl Material for ObjectMaterial {
          */
         camera.upload(pass, &mut self.proj, &mut self.view);
 
-        let pos = match *light {
-            Light::Absolute(ref p) => p.clone(),
-            Light::StickToCamera => camera.eye(),
-        };
+        self.eye.upload(&camera.eye());
 
-        self.light.upload(&pos);
+        let mut light_iter = lights.iter();
+
+        for uniforms in self.lights.iter_mut() {
+            uniforms.upload(light_iter.next(), &*camera);
+        }
+
+        match lights.fog() {
+            Some(fog) => {
+                let (mode, params) = match fog.mode {
+                    FogMode::Linear { start, end } => (1.0, Vector2::new(start, end)),
+                    FogMode::Exponential { density } => (2.0, Vector2::new(density, 0.0)),
+                };
+
+                self.fog_mode.upload(&mode);
+                self.fog_color.upload(&fog.color);
+                self.fog_params.upload(&params);
+            }
+            None => self.fog_mode.upload(&0.0),
+        }
+
+        self.gamma_correction
+            .upload(&(lights.gamma_correction() as i32 as f32));
+
+        let mut shadow_maps = lights.shadow_maps().iter();
+
+        for (shadow, &(unit, index)) in self.shadows.iter_mut().zip(SHADOW_TEXTURE_UNITS.iter()) {
+            shadow.map.upload(&index);
+
+            match shadow_maps.next() {
+                Some(shadow_map) => {
+                    verify!(ctxt.active_texture(unit));
+                    verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(shadow_map.texture())));
+
+                    shadow.light.upload(&(shadow_map.light_index() as f32));
+                    shadow.bias.upload(&shadow_map.bias());
+                    shadow.transform.upload(shadow_map.light_transform());
+
+                    // All the shadow maps share the settings of the shadow map renderer.
+                    self.shadow_texel
+                        .upload(&(1.0 / shadow_map.resolution() as f32));
+                    self.shadow_softness.upload(&shadow_map.softness());
+                }
+                None => shadow.light.upload(&-1.0),
+            }
+        }
 
         /*
          *
@@ -106,8 +195,62 @@ impl Material for ObjectMaterial {
 
             mesh.bind(&mut self.pos, &mut self.normal, &mut self.tex_coord);
 
+            // The tangents are only needed, and bound, if there is a normal map.
+            self.normal_map.upload(&2);
+
+            match data.normal_map() {
+                Some(normal_map) if mesh.bind_tangents(&mut self.tangent) => {
+                    self.tangent.enable();
+                    self.use_normal_map.upload(&1.0);
+
+                    verify!(ctxt.active_texture(Context::TEXTURE2));
+                    verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&**normal_map)));
+                }
+                _ => {
+                    self.tangent.disable();
+                    self.use_normal_map.upload(&0.0);
+                }
+            }
+
+            self.reflection_map.upload(&4);
+            self.reflectivity.upload(&data.reflectivity());
+
+            match data.reflection_map() {
+                Some(reflection_map) => {
+                    self.use_reflection_map.upload(&1.0);
+
+                    verify!(ctxt.active_texture(Context::TEXTURE4));
+                    verify!(ctxt
+                        .bind_texture(Context::TEXTURE_CUBE_MAP, Some(reflection_map.texture())));
+                }
+                None => self.use_reflection_map.upload(&0.0),
+            }
+
+            if let Some(ref mut emissive) = self.emissive {
+                emissive.upload(data.emissive_color());
+            }
+
+            if let Some(ref mut emissive_texture) = self.emissive_texture {
+                // Without emissive texture, the emissive color is multiplied by the white texture.
+                let texture = data
+                    .emissive_texture()
+                    .cloned()
+                    .unwrap_or_else(|| TextureManager::get_global_manager(|tm| tm.get_default()));
+
+                if let Some(ref mut emissive_srgb) = self.emissive_srgb {
+                    emissive_srgb.upload(&(texture.is_srgb() as i32 as f32));
+                }
+
+                emissive_texture.upload(&3);
+                verify!(ctxt.active_texture(Context::TEXTURE3));
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*texture)));
+            }
+
             verify!(ctxt.active_texture(Context::TEXTURE0));
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*data.texture())));
+            self.tex_srgb
+                .upload(&(data.texture().is_srgb() as i32 as f32));
+            self.alpha.upload(&data.alpha());
 
             if data.surface_rendering_active() {
                 self.color.upload(data.color());
@@ -118,6 +261,19 @@ impl Material for ObjectMaterial {
                     verify!(ctxt.disable(Context::CULL_FACE));
                 }
 
+                let (mut factor, mut units) = data.polygon_offset().unwrap_or((0.0, 0.0));
+
+                // Push the surface back so the wireframe drawn on top of it does not z-fight.
+                if data.wireframe_overlay().is_some() {
+                    factor += 1.0;
+                    units += 1.0;
+                }
+
+                if factor != 0.0 || units != 0.0 {
+                    verify!(ctxt.enable(Context::POLYGON_OFFSET_FILL));
+                    scene::set_polygon_offset(factor, units);
+                }
+
                 let _ = verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::FILL));
                 verify!(ctxt.draw_elements(
                     Context::TRIANGLES,
@@ -125,34 +281,31 @@ impl Material for ObjectMaterial {
                     Context::UNSIGNED_SHORT,
                     0
                 ));
+
+                verify!(ctxt.disable(Context::POLYGON_OFFSET_FILL));
             }
 
-            if data.lines_width() != 0.0 {
+            // Lines wider than one pixel are drawn after this material is deactivated.
+            let wide_lines = data.lines_width() > 1.0
+                && WideLineRenderer::prepare_edges(mesh, data.wireframe_mode());
+
+            if data.lines_width() != 0.0 && !wide_lines {
                 self.color
                     .upload(data.lines_color().unwrap_or(data.color()));
 
                 verify!(ctxt.disable(Context::CULL_FACE));
                 ignore!(ctxt.line_width(data.lines_width()));
-
-                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
-                    verify!(ctxt.draw_elements(
-                        Context::TRIANGLES,
-                        mesh.num_pts() as i32,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
-                } else {
-                    mesh.bind_edges();
-                    verify!(ctxt.draw_elements(
-                        Context::LINES,
-                        mesh.num_pts() as i32 * 2,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
-                }
+                draw_edges(mesh, data.wireframe_mode());
                 ctxt.line_width(1.0);
             }
 
+            if let Some(color) = data.wireframe_overlay() {
+                self.color.upload(color);
+
+                verify!(ctxt.disable(Context::CULL_FACE));
+                draw_edges(mesh, data.wireframe_mode());
+            }
+
             if data.points_size() != 0.0 {
                 self.color.upload(data.color());
 
@@ -179,6 +332,149 @@ impl Material for ObjectMaterial {
 
         mesh.unbind();
         self.deactivate();
+
+        // Nothing is drawn if the edges could not be prepared.
+        if data.lines_width() > 1.0 {
+            self.wide_lines.render_edges(
+                pass,
+                camera,
+                mesh,
+                data.wireframe_mode(),
+                data.lines_color().unwrap_or(data.color()),
+                transform,
+                scale,
+                data.lines_width(),
+                false,
+            );
+        }
+    }
+}
+
+// Draws the edges of the triangles of `mesh`, whose buffers must already be bound.
+fn draw_edges(mesh: &mut Mesh, mode: WireframeMode) {
+    let ctxt = Context::get();
+
+    if mode == WireframeMode::Edges {
+        let num_edges = mesh.bind_wireframe_edges();
+        verify!(ctxt.draw_elements(
+            Context::LINES,
+            num_edges as i32 * 2,
+            Context::UNSIGNED_SHORT,
+            0
+        ));
+        mesh.bind_faces();
+    } else if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
+        verify!(ctxt.draw_elements(
+            Context::TRIANGLES,
+            mesh.num_pts() as i32,
+            Context::UNSIGNED_SHORT,
+            0
+        ));
+    } else {
+        mesh.bind_edges();
+        verify!(ctxt.draw_elements(
+            Context::LINES,
+            mesh.num_pts() as i32 * 2,
+            Context::UNSIGNED_SHORT,
+            0
+        ));
+    }
+}
+
+// The texture units of the shadow maps, and their indices.
+const SHADOW_TEXTURE_UNITS: [(u32, i32); MAX_SHADOWS] =
+    [(Context::TEXTURE1, 1), (Context::TEXTURE5, 5)];
+
+// The uniforms describing one of the shadow maps.
+struct ShadowUniforms {
+    map: ShaderUniform<i32>,
+    // The index of the light casting the shadows, or -1 if this shadow map is unused.
+    light: ShaderUniform<f32>,
+    bias: ShaderUniform<f32>,
+    transform: ShaderUniform<Matrix4<f32>>,
+}
+
+impl ShadowUniforms {
+    fn new(effect: &Effect, k: usize) -> ShadowUniforms {
+        ShadowUniforms {
+            map: effect.uniform(&format!("shadow_maps[{}]", k)),
+            light: effect.uniform(&format!("shadow_lights[{}]", k)),
+            bias: effect.uniform(&format!("shadow_biases[{}]", k)),
+            transform: effect.uniform(&format!("light_transforms[{}]", k)),
+        }
+    }
+}
+
+// The uniforms describing one of the lights, see the `light` module for their layout.
+pub(crate) struct LightUniforms {
+    position: ShaderUniform<Vector4<f32>>,
+    color: ShaderUniform<Vector3<f32>>,
+    direction: ShaderUniform<Vector3<f32>>,
+    cone: ShaderUniform<Vector3<f32>>,
+}
+
+impl LightUniforms {
+    // The uniforms of the `i`-th light of `effect`.
+    pub fn new(effect: &Effect, i: usize) -> LightUniforms {
+        LightUniforms {
+            position: effect.uniform(&format!("light_positions[{}]", i)),
+            color: effect.uniform(&format!("light_colors[{}]", i)),
+            direction: effect.uniform(&format!("light_directions[{}]", i)),
+            cone: effect.uniform(&format!("light_cones[{}]", i)),
+        }
+    }
+
+    pub fn upload(&mut self, light: Option<&Light>, camera: &dyn Camera) {
+        match light {
+            Some(light) => {
+                let (direction, cone) = light_cone(light);
+
+                self.position.upload(&light_position(light, camera));
+                self.color.upload(&light.radiance());
+                self.direction.upload(&direction);
+                self.cone.upload(&cone);
+            }
+            None => {
+                // An unused light is black, with an arbitrary valid direction.
+                self.position.upload(&Vector4::y());
+                self.color.upload(&Vector3::zeros());
+                self.direction.upload(&Vector3::y());
+                self.cone.upload(&Vector3::zeros());
+            }
+        }
+    }
+}
+
+// The light position with w = 1, or the direction toward the light with w = 0 if it is
+// directional.
+fn light_position(light: &Light, camera: &dyn Camera) -> Vector4<f32> {
+    match light.position(camera) {
+        Some(pos) => pos.to_homogeneous(),
+        None => (-light.direction().unwrap().normalize()).to_homogeneous(),
+    }
+}
+
+// The direction a spotlight is shining toward, and the cosines of its inner and outer angles
+// and its range. Other lights have a range of zero.
+fn light_cone(light: &Light) -> (Vector3<f32>, Vector3<f32>) {
+    match *light {
+        Light::Spot {
+            ref direction,
+            inner_angle,
+            outer_angle,
+            range,
+            ..
+        } => {
+            let inner = inner_angle.cos();
+            // The falloff is undefined if both angles are equal.
+            let outer = outer_angle.cos().min(inner - 1.0e-4);
+
+            (
+                direction.normalize(),
+                Vector3::new(inner, outer, range.max(1.0e-6)),
+            )
+        }
+        _ => (Vector3::y(), Vector3::zeros()),
     }
 }
 
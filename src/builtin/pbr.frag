@@ -0,0 +1,146 @@
+#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+#define MAX_LIGHTS 4
+#define PI 3.14159265359
+// The fraction of the base color lighting the surface when there is no environment map.
+#define AMBIENT 0.1
+
+varying vec2 tex_coord_v;
+varying vec3 normalInterp;
+varying vec4 tangentInterp;
+varying vec3 worldPos;
+
+uniform vec3 base_color;
+uniform float metallic;
+uniform float roughness;
+uniform vec3 eye;
+// A light position with w = 1, or the direction toward a directional light with w = 0.
+uniform vec4 light_positions[MAX_LIGHTS];
+// The light colors multiplied by their intensities. Unused lights are black.
+uniform vec3 light_colors[MAX_LIGHTS];
+// The direction a spotlight is shining toward.
+uniform vec3 light_directions[MAX_LIGHTS];
+// The cosines of the inner and outer angles of a spotlight, and its range, 0 for other lights.
+uniform vec3 light_cones[MAX_LIGHTS];
+uniform sampler2D base_color_texture;
+// The roughness is in the green channel, and the metallic factor in the blue channel.
+uniform sampler2D metallic_roughness_texture;
+// The tangent-space normal map, used only if use_normal_map is 1.
+uniform sampler2D normal_map;
+uniform float use_normal_map;
+// The environment cubemap, used only if use_environment_map is 1.
+uniform samplerCube environment_map;
+uniform float use_environment_map;
+
+vec3 to_linear(vec3 color) {
+  return pow(color, vec3(2.2));
+}
+
+float distribution_ggx(float n_dot_h, float alpha) {
+  float alpha2 = alpha * alpha;
+  float denom = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+  return alpha2 / (PI * denom * denom);
+}
+
+float geometry_smith(float n_dot_v, float n_dot_l, float rough) {
+  float k = (rough + 1.0) * (rough + 1.0) / 8.0;
+  float gv = n_dot_v / (n_dot_v * (1.0 - k) + k);
+  float gl = n_dot_l / (n_dot_l * (1.0 - k) + k);
+  return gv * gl;
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+  return f0 + (1.0 - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+// Analytic approximation of the pre-integrated specular BRDF (Karis, "Physically Based
+// Shading on Mobile").
+vec3 env_brdf_approx(vec3 f0, float rough, float n_dot_v) {
+  vec4 c0 = vec4(-1.0, -0.0275, -0.572, 0.022);
+  vec4 c1 = vec4(1.0, 0.0425, 1.04, -0.04);
+  vec4 r = rough * c0 + c1;
+  float a004 = min(r.x * r.x, exp2(-9.28 * n_dot_v)) * r.x + r.y;
+  vec2 ab = vec2(-1.04, 1.04) * a004 + r.zw;
+  return f0 * ab.x + ab.y;
+}
+
+// The attenuation of a light by its cone and its range, 1 for the lights which are not
+// spotlights. `to_light` goes from the fragment to the light.
+float spot_attenuation(vec3 direction, vec3 cone, vec3 to_light) {
+  if (cone.z <= 0.0) {
+    return 1.0;
+  }
+
+  float dist = length(to_light);
+  float falloff = smoothstep(cone.y, cone.x, dot(direction, -to_light / max(dist, 0.0001)));
+  float fade = clamp(1.0 - pow(dist / cone.z, 4.0), 0.0, 1.0);
+
+  return falloff * fade * fade;
+}
+
+void main() {
+  vec3 normal = normalize(normalInterp);
+
+  if (use_normal_map > 0.5) {
+    vec3 tangent = normalize(tangentInterp.xyz - normal * dot(normal, tangentInterp.xyz));
+    vec3 bitangent = cross(normal, tangent) * tangentInterp.w;
+    vec3 mapped = texture2D(normal_map, tex_coord_v).xyz * 2.0 - 1.0;
+    normal = normalize(mat3(tangent, bitangent, normal) * mapped);
+  }
+
+  vec4 albedo_tex = texture2D(base_color_texture, tex_coord_v);
+  vec3 albedo = to_linear(base_color * albedo_tex.rgb);
+  vec4 mr = texture2D(metallic_roughness_texture, tex_coord_v);
+  float metal = clamp(metallic * mr.b, 0.0, 1.0);
+  // Very low roughnesses make the highlights of analytic lights vanish.
+  float rough = clamp(roughness * mr.g, 0.04, 1.0);
+  float alpha = rough * rough;
+
+  vec3 viewDir = normalize(eye - worldPos);
+  float n_dot_v = max(dot(normal, viewDir), 0.0001);
+  vec3 f0 = mix(vec3(0.04), albedo, metal);
+  vec3 lighting = vec3(0.0);
+
+  for (int i = 0; i < MAX_LIGHTS; i++) {
+    vec4 light = light_positions[i];
+    vec3 toLight = light.xyz - worldPos * light.w;
+    vec3 lightDir = normalize(toLight);
+    vec3 lightColor = light_colors[i] * spot_attenuation(light_directions[i], light_cones[i], toLight);
+    vec3 halfDir = normalize(lightDir + viewDir);
+    float n_dot_l = max(dot(normal, lightDir), 0.0);
+    float n_dot_h = max(dot(normal, halfDir), 0.0);
+
+    vec3 f = fresnel_schlick(max(dot(halfDir, viewDir), 0.0), f0);
+    float d = distribution_ggx(n_dot_h, alpha);
+    float g = geometry_smith(n_dot_v, n_dot_l, rough);
+    vec3 specular = d * g * f / (4.0 * n_dot_v * max(n_dot_l, 0.0001));
+    vec3 diffuse = (1.0 - f) * (1.0 - metal) * albedo / PI;
+
+    // The light colors are the irradiance of a surface facing the light, hence the factor PI.
+    lighting += PI * (diffuse + specular) * lightColor * n_dot_l;
+  }
+
+  vec3 ambient;
+
+  if (use_environment_map > 0.5) {
+    // NOTE: the cubemap is not prefiltered, so its sharp reflection is blended with the
+    // irradiance coming from the normal direction as the roughness increases.
+    vec3 reflected = reflect(-viewDir, normal);
+    vec3 irradiance = to_linear(textureCube(environment_map, normal).rgb);
+    vec3 radiance = mix(to_linear(textureCube(environment_map, reflected).rgb), irradiance, rough);
+    vec3 kd = (1.0 - fresnel_schlick(n_dot_v, f0)) * (1.0 - metal);
+    ambient = kd * albedo * irradiance + env_brdf_approx(f0, rough, n_dot_v) * radiance;
+  } else {
+    ambient = AMBIENT * albedo;
+  }
+
+  // Reinhard tone mapping, then gamma correction.
+  vec3 color = ambient + lighting;
+  color = color / (color + 1.0);
+  gl_FragColor = vec4(pow(color, vec3(1.0 / 2.2)), albedo_tex.a);
+}
@@ -0,0 +1,23 @@
+#version 100
+attribute vec3 position;
+attribute vec2 tex_coord;
+attribute vec3 normal;
+// The w component is the handedness of the tangent space.
+attribute vec4 tangent;
+
+uniform mat3 ntransform, scale;
+uniform mat4 proj, view, transform;
+
+varying vec2 tex_coord_v;
+varying vec3 normalInterp;
+varying vec4 tangentInterp;
+varying vec3 worldPos;
+
+void main(){
+    vec4 worldPos4 = transform * vec4(scale * position, 1.0);
+    gl_Position = proj * view * worldPos4;
+    worldPos = vec3(worldPos4) / worldPos4.w;
+    normalInterp = ntransform * normal;
+    tangentInterp = vec4(ntransform * (scale * tangent.xyz), tangent.w);
+    tex_coord_v = tex_coord;
+}
@@ -0,0 +1,351 @@
+use crate::builtin::object_material::LightUniforms;
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::light::{Lights, MAX_LIGHTS};
+use crate::resource::Material;
+use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform, Texture, TextureManager};
+use crate::scene::ObjectData;
+use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3, Vector4};
+use std::rc::Rc;
+
+#[path = "../error.rs"]
+mod error;
+
+/// A physically-based material following the metallic-roughness workflow.
+///
+/// The surface is shaded with a Cook-Torrance GGX BRDF, lit by the lights of the scene and, if the
+/// window has an environment map, by that cubemap. The base color is multiplied by the color and,
+/// unless a base color texture is set on this material, by the texture of each object.
+pub struct PbrMaterial {
+    base_color: Point3<f32>,
+    metallic: f32,
+    roughness: f32,
+    base_color_texture: Option<Rc<Texture>>,
+    metallic_roughness_texture: Option<Rc<Texture>>,
+    normal_map: Option<Rc<Texture>>,
+    effect: Effect,
+    pos: ShaderAttribute<Point3<f32>>,
+    normal: ShaderAttribute<Vector3<f32>>,
+    tex_coord: ShaderAttribute<Point2<f32>>,
+    tangent: ShaderAttribute<Vector4<f32>>,
+    gl_base_color: ShaderUniform<Point3<f32>>,
+    gl_metallic: ShaderUniform<f32>,
+    gl_roughness: ShaderUniform<f32>,
+    gl_base_color_texture: ShaderUniform<i32>,
+    gl_metallic_roughness_texture: ShaderUniform<i32>,
+    gl_normal_map: ShaderUniform<i32>,
+    gl_use_normal_map: ShaderUniform<f32>,
+    gl_environment_map: ShaderUniform<i32>,
+    gl_use_environment_map: ShaderUniform<f32>,
+    eye: ShaderUniform<Point3<f32>>,
+    lights: Vec<LightUniforms>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    ntransform: ShaderUniform<Matrix3<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    view: ShaderUniform<Matrix4<f32>>,
+}
+
+impl PbrMaterial {
+    /// Creates a new `PbrMaterial`.
+    ///
+    /// By default, the base color is white, the surface is not metallic, and its roughness is 0.5.
+    pub fn new() -> PbrMaterial {
+        let mut effect = Effect::new_from_str(PBR_VERTEX_SRC, PBR_FRAGMENT_SRC);
+
+        effect.use_program();
+
+        PbrMaterial {
+            base_color: Point3::new(1.0, 1.0, 1.0),
+            metallic: 0.0,
+            roughness: 0.5,
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            normal_map: None,
+            pos: effect.attribute("position"),
+            normal: effect.attribute("normal"),
+            tex_coord: effect.attribute("tex_coord"),
+            tangent: effect.attribute("tangent"),
+            gl_base_color: effect.uniform("base_color"),
+            gl_metallic: effect.uniform("metallic"),
+            gl_roughness: effect.uniform("roughness"),
+            gl_base_color_texture: effect.uniform("base_color_texture"),
+            gl_metallic_roughness_texture: effect.uniform("metallic_roughness_texture"),
+            gl_normal_map: effect.uniform("normal_map"),
+            gl_use_normal_map: effect.uniform("use_normal_map"),
+            gl_environment_map: effect.uniform("environment_map"),
+            gl_use_environment_map: effect.uniform("use_environment_map"),
+            eye: effect.uniform("eye"),
+            lights: (0..MAX_LIGHTS)
+                .map(|i| LightUniforms::new(&effect, i))
+                .collect(),
+            transform: effect.uniform("transform"),
+            scale: effect.uniform("scale"),
+            ntransform: effect.uniform("ntransform"),
+            view: effect.uniform("view"),
+            proj: effect.uniform("proj"),
+            effect,
+        }
+    }
+
+    /// The base color of this material, multiplied by the color of each object.
+    #[inline]
+    pub fn base_color(&self) -> &Point3<f32> {
+        &self.base_color
+    }
+
+    /// Sets the base color of this material, multiplied by the color of each object.
+    ///
+    /// Colors components must be on the range `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_base_color(&mut self, r: f32, g: f32, b: f32) {
+        self.base_color = Point3::new(r, g, b);
+    }
+
+    /// How metallic the surface is, from `0.0` (dielectric) to `1.0` (metal).
+    #[inline]
+    pub fn metallic(&self) -> f32 {
+        self.metallic
+    }
+
+    /// Sets how metallic the surface is, clamped to `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_metallic(&mut self, metallic: f32) {
+        self.metallic = na::clamp(metallic, 0.0, 1.0);
+    }
+
+    /// The roughness of the surface, from `0.0` (mirror-like) to `1.0` (completely rough).
+    #[inline]
+    pub fn roughness(&self) -> f32 {
+        self.roughness
+    }
+
+    /// Sets the roughness of the surface, clamped to `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_roughness(&mut self, roughness: f32) {
+        self.roughness = na::clamp(roughness, 0.0, 1.0);
+    }
+
+    /// Sets the texture multiplied by the base color.
+    ///
+    /// If `None`, the texture of each object is used instead.
+    #[inline]
+    pub fn set_base_color_texture(&mut self, texture: Option<Rc<Texture>>) {
+        self.base_color_texture = texture;
+    }
+
+    /// Sets the texture multiplied by the metallic and roughness factors.
+    ///
+    /// As in glTF, the roughness is read from the green channel and the metallic factor from
+    /// the blue channel.
+    #[inline]
+    pub fn set_metallic_roughness_texture(&mut self, texture: Option<Rc<Texture>>) {
+        self.metallic_roughness_texture = texture;
+    }
+
+    /// Sets the tangent-space normal map of this material.
+    ///
+    /// If `None`, the normal map of each object is used instead, if any. The meshes must have
+    /// tangents, see `Mesh::recompute_tangents`.
+    #[inline]
+    pub fn set_normal_map(&mut self, texture: Option<Rc<Texture>>) {
+        self.normal_map = texture;
+    }
+
+    fn activate(&mut self) {
+        self.effect.use_program();
+        self.pos.enable();
+        self.normal.enable();
+        self.tex_coord.enable();
+    }
+
+    fn deactivate(&mut self) {
+        self.pos.disable();
+        self.normal.disable();
+        self.tex_coord.disable();
+        self.tangent.disable();
+    }
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        PbrMaterial::new()
+    }
+}
+
+impl Material for PbrMaterial {
+    fn render(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        data: &ObjectData,
+        mesh: &mut Mesh,
+    ) {
+        let ctxt = Context::get();
+        self.activate();
+
+        /*
+         *
+         * Setup camera and lights.
+         *
+         */
+        camera.upload(pass, &mut self.proj, &mut self.view);
+
+        self.eye.upload(&camera.eye());
+
+        let mut light_iter = lights.iter();
+
+        for uniforms in self.lights.iter_mut() {
+            uniforms.upload(light_iter.next(), &*camera);
+        }
+
+        self.gl_environment_map.upload(&3);
+
+        match lights.environment_map() {
+            Some(environment_map) => {
+                verify!(ctxt.active_texture(Context::TEXTURE3));
+                verify!(
+                    ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(environment_map.texture()))
+                );
+                self.gl_use_environment_map.upload(&1.0);
+            }
+            None => self.gl_use_environment_map.upload(&0.0),
+        }
+
+        /*
+         *
+         * Setup object-related stuffs.
+         *
+         */
+        let formated_transform = transform.to_homogeneous();
+        let formated_ntransform = transform.rotation.to_rotation_matrix().into_inner();
+        let formated_scale = Matrix3::from_diagonal(&Vector3::new(scale.x, scale.y, scale.z));
+        let base_color = self.base_color.coords.component_mul(&data.color().coords);
+
+        self.transform.upload(&formated_transform);
+        self.ntransform.upload(&formated_ntransform);
+        self.scale.upload(&formated_scale);
+        self.gl_base_color.upload(&Point3::from(base_color));
+        self.gl_metallic.upload(&self.metallic);
+        self.gl_roughness.upload(&self.roughness);
+
+        mesh.bind(&mut self.pos, &mut self.normal, &mut self.tex_coord);
+
+        // The textures which are not set are replaced by the default white texture.
+        let default_texture = TextureManager::get_global_manager(|tm| tm.get_default());
+        let base_color_texture = self
+            .base_color_texture
+            .as_ref()
+            .unwrap_or_else(|| data.texture());
+        let metallic_roughness_texture = self
+            .metallic_roughness_texture
+            .as_ref()
+            .unwrap_or(&default_texture);
+
+        self.gl_base_color_texture.upload(&0);
+        self.gl_metallic_roughness_texture.upload(&1);
+        self.gl_normal_map.upload(&2);
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&**metallic_roughness_texture)));
+
+        match self.normal_map.as_ref().or_else(|| data.normal_map()) {
+            Some(normal_map) if mesh.bind_tangents(&mut self.tangent) => {
+                self.tangent.enable();
+                self.gl_use_normal_map.upload(&1.0);
+
+                verify!(ctxt.active_texture(Context::TEXTURE2));
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&**normal_map)));
+            }
+            _ => {
+                self.tangent.disable();
+                self.gl_use_normal_map.upload(&0.0);
+            }
+        }
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&**base_color_texture)));
+
+        if data.surface_rendering_active() {
+            if data.backface_culling_enabled() {
+                verify!(ctxt.enable(Context::CULL_FACE));
+            } else {
+                verify!(ctxt.disable(Context::CULL_FACE));
+            }
+
+            let _ = verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::FILL));
+            verify!(ctxt.draw_elements(
+                Context::TRIANGLES,
+                mesh.num_pts() as i32,
+                Context::UNSIGNED_SHORT,
+                0
+            ));
+        }
+
+        if data.lines_width() != 0.0 {
+            if let Some(lines_color) = data.lines_color() {
+                self.gl_base_color.upload(lines_color);
+            }
+
+            verify!(ctxt.disable(Context::CULL_FACE));
+            ignore!(ctxt.line_width(data.lines_width()));
+
+            if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
+                verify!(ctxt.draw_elements(
+                    Context::TRIANGLES,
+                    mesh.num_pts() as i32,
+                    Context::UNSIGNED_SHORT,
+                    0
+                ));
+            } else {
+                mesh.bind_edges();
+                verify!(ctxt.draw_elements(
+                    Context::LINES,
+                    mesh.num_pts() as i32 * 2,
+                    Context::UNSIGNED_SHORT,
+                    0
+                ));
+            }
+            ctxt.line_width(1.0);
+        }
+
+        if data.points_size() != 0.0 {
+            self.gl_base_color.upload(&Point3::from(base_color));
+
+            verify!(ctxt.disable(Context::CULL_FACE));
+            ctxt.point_size(data.points_size());
+            if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT)) {
+                verify!(ctxt.draw_elements(
+                    Context::TRIANGLES,
+                    mesh.num_pts() as i32,
+                    Context::UNSIGNED_SHORT,
+                    0
+                ));
+            } else {
+                verify!(ctxt.draw_elements(
+                    Context::POINTS,
+                    mesh.num_pts() as i32,
+                    Context::UNSIGNED_SHORT,
+                    0
+                ));
+            }
+            ctxt.point_size(1.0);
+        }
+
+        mesh.unbind();
+        self.deactivate();
+    }
+}
+
+/// Vertex shader of the physically-based material.
+pub static PBR_VERTEX_SRC: &str = A_VERY_LONG_STRING;
+/// Fragment shader of the physically-based material.
+pub static PBR_FRAGMENT_SRC: &str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &str = include_str!("pbr.vert");
+
+// Cook-Torrance GGX BRDF, following the glTF 2.0 specification.
+const ANOTHER_VERY_LONG_STRING: &str = include_str!("pbr.frag");
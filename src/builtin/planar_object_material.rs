@@ -30,13 +30,13 @@ impl PlanarObjectMaterial {
 
         // get the variables locations
         PlanarObjectMaterial {
-            pos: effect.get_attrib("position").unwrap(),
-            tex_coord: effect.get_attrib("tex_coord").unwrap(),
-            color: effect.get_uniform("color").unwrap(),
-            scale: effect.get_uniform("scale").unwrap(),
-            model: effect.get_uniform("model").unwrap(),
-            view: effect.get_uniform("view").unwrap(),
-            proj: effect.get_uniform("proj").unwrap(),
+            pos: effect.attribute("position"),
+            tex_coord: effect.attribute("tex_coord"),
+            color: effect.uniform("color"),
+            scale: effect.uniform("scale"),
+            model: effect.uniform("model"),
+            view: effect.uniform("view"),
+            proj: effect.uniform("proj"),
             effect: effect,
         }
     }
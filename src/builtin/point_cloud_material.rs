@@ -0,0 +1,150 @@
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::light::Lights;
+use crate::resource::Material;
+use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
+use crate::scene::{ObjectData, PointCloud};
+use na::{Isometry3, Matrix3, Matrix4, Point3, Vector3};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[path = "../error.rs"]
+mod error;
+
+/// A material that draws the points of a `PointCloudNode`.
+///
+/// The point size is set by the shader so it is not clamped by the driver default. Objects that
+/// are not part of a `PointCloudNode` are not drawn.
+pub struct PointCloudMaterial {
+    effect: Effect,
+    pos: ShaderAttribute<Point3<f32>>,
+    point_color: ShaderAttribute<Point3<f32>>,
+    color: ShaderUniform<Point3<f32>>,
+    point_size: ShaderUniform<f32>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    view: ShaderUniform<Matrix4<f32>>,
+}
+
+impl PointCloudMaterial {
+    /// Creates a new `PointCloudMaterial`.
+    pub fn new() -> PointCloudMaterial {
+        let mut effect = Effect::new_from_str(POINT_CLOUD_VERTEX_SRC, POINT_CLOUD_FRAGMENT_SRC);
+
+        effect.use_program();
+
+        PointCloudMaterial {
+            pos: effect.attribute("position"),
+            point_color: effect.attribute("point_color"),
+            color: effect.uniform("color"),
+            point_size: effect.uniform("point_size"),
+            transform: effect.uniform("transform"),
+            scale: effect.uniform("scale"),
+            view: effect.uniform("view"),
+            proj: effect.uniform("proj"),
+            effect,
+        }
+    }
+}
+
+impl Default for PointCloudMaterial {
+    fn default() -> Self {
+        PointCloudMaterial::new()
+    }
+}
+
+impl Material for PointCloudMaterial {
+    fn render(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        _: &Lights,
+        data: &ObjectData,
+        _: &mut Mesh,
+    ) {
+        let cloud = match data.user_data().downcast_ref::<Rc<RefCell<PointCloud>>>() {
+            Some(cloud) => cloud.clone(),
+            None => return,
+        };
+        let mut cloud = cloud.borrow_mut();
+
+        if cloud.is_empty() {
+            return;
+        }
+
+        let ctxt = Context::get();
+        self.effect.use_program();
+        self.pos.enable();
+        self.point_color.enable();
+
+        /*
+         *
+         * Setup camera.
+         *
+         */
+        camera.upload(pass, &mut self.proj, &mut self.view);
+
+        /*
+         *
+         * Setup object-related stuffs.
+         *
+         */
+        let formated_transform = transform.to_homogeneous();
+        let formated_scale = Matrix3::from_diagonal(&Vector3::new(scale.x, scale.y, scale.z));
+
+        self.transform.upload(&formated_transform);
+        self.scale.upload(&formated_scale);
+        self.color.upload(data.color());
+        self.point_size.upload(&cloud.point_size());
+
+        self.pos.bind(cloud.points_mut());
+        self.point_color.bind(cloud.colors_mut());
+
+        verify!(ctxt.draw_arrays(Context::POINTS, 0, cloud.len() as i32));
+
+        cloud.points_mut().unbind();
+
+        self.pos.disable();
+        self.point_color.disable();
+    }
+}
+
+/// Vertex shader of the point cloud material.
+pub static POINT_CLOUD_VERTEX_SRC: &str = A_VERY_LONG_STRING;
+/// Fragment shader of the point cloud material.
+pub static POINT_CLOUD_FRAGMENT_SRC: &str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &str = "#version 100
+attribute vec3 position;
+attribute vec3 point_color;
+
+uniform mat3 scale;
+uniform mat4 proj, view, transform;
+uniform vec3 color;
+uniform float point_size;
+
+varying vec3 point_color_v;
+
+void main(){
+    gl_Position = proj * view * transform * vec4(scale * position, 1.0);
+    gl_PointSize = point_size;
+    point_color_v = color * point_color;
+}
+";
+
+const ANOTHER_VERY_LONG_STRING: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+varying vec3 point_color_v;
+
+void main() {
+  gl_FragColor = vec4(point_color_v, 1.0);
+}
+";
@@ -0,0 +1,461 @@
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::light::Lights;
+use crate::resource::Material;
+use crate::resource::{Effect, GLPrimitive, Mesh, ShaderAttribute, ShaderUniform};
+use crate::scene::ObjectData;
+use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3};
+#[cfg(feature = "hot-reload")]
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::any::Any;
+#[cfg(feature = "hot-reload")]
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+#[cfg(feature = "hot-reload")]
+use std::path::PathBuf;
+#[cfg(feature = "hot-reload")]
+use std::rc::{Rc, Weak};
+#[cfg(feature = "hot-reload")]
+use std::sync::mpsc::{self, Receiver};
+#[cfg(feature = "hot-reload")]
+use std::time::Duration;
+
+#[path = "../error.rs"]
+mod error;
+
+/// A material drawing objects with user-supplied shaders.
+///
+/// The following inputs of the shaders are set automatically if they are declared:
+///   * `attribute vec3 position`, `attribute vec3 normal`, and `attribute vec2 tex_coord`: the
+///     vertices, normals, and texture coordinates of the mesh.
+///   * `uniform mat4 proj` and `uniform mat4 view`: the projection and view matrices of the
+///     camera. They are set only if both are declared.
+///   * `uniform mat4 transform`, `uniform mat3 scale`, and `uniform mat3 ntransform`: the
+///     transformation of the object, its scale, and the rotation of its normals.
+///   * `uniform vec3 color` and `uniform sampler2D tex`: the color and texture of the object.
+///
+/// Any other uniform is set with `set_uniform`.
+///
+/// ```no_run
+/// # use kiss3d::builtin::ShaderMaterial;
+/// # use kiss3d::resource::Material;
+/// # use kiss3d::window::Window;
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # let vertex_src = "";
+/// # let fragment_src = "";
+/// let mut window = Window::new("Custom shader");
+/// let mut material = ShaderMaterial::new(vertex_src, fragment_src).unwrap();
+/// let _ = material.set_uniform("time", 0.0f32);
+/// let material: Box<dyn Material> = Box::new(material);
+///
+/// let mut cube = window.add_cube(1.0, 1.0, 1.0);
+/// cube.set_material(Rc::new(RefCell::new(material)));
+/// ```
+pub struct ShaderMaterial {
+    effect: Effect,
+    pos: Option<ShaderAttribute<Point3<f32>>>,
+    normal: Option<ShaderAttribute<Vector3<f32>>>,
+    tex_coord: Option<ShaderAttribute<Point2<f32>>>,
+    proj: Option<ShaderUniform<Matrix4<f32>>>,
+    view: Option<ShaderUniform<Matrix4<f32>>>,
+    transform: Option<ShaderUniform<Matrix4<f32>>>,
+    scale: Option<ShaderUniform<Matrix3<f32>>>,
+    ntransform: Option<ShaderUniform<Matrix3<f32>>>,
+    color: Option<ShaderUniform<Point3<f32>>>,
+    tex: Option<ShaderUniform<i32>>,
+    uniforms: HashMap<String, Box<dyn UserUniform>>,
+    #[cfg(feature = "hot-reload")]
+    watched: Option<WatchedFiles>,
+}
+
+// The shader files a material is reloaded from when they are modified.
+#[cfg(feature = "hot-reload")]
+struct WatchedFiles {
+    paths: [PathBuf; 2],
+    // Set by `poll_shader_files` when one of the files is modified.
+    modified: Rc<Cell<bool>>,
+}
+
+// The watcher of the shader files of all the materials created by `ShaderMaterial::from_files`.
+#[cfg(feature = "hot-reload")]
+struct ShaderWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<DebouncedEvent>,
+    // The modification flags of the materials using each watched file.
+    materials: HashMap<PathBuf, Vec<Weak<Cell<bool>>>>,
+}
+
+// The time the file system events are buffered for, so a file saved in several writes is
+// reloaded once.
+#[cfg(feature = "hot-reload")]
+const WATCH_DELAY: Duration = Duration::from_millis(100);
+
+#[cfg(feature = "hot-reload")]
+thread_local! {
+    static SHADER_WATCHER: RefCell<Option<ShaderWatcher>> = const { RefCell::new(None) };
+}
+
+impl ShaderMaterial {
+    /// Creates a new material from the source of its vertex and fragment shaders.
+    ///
+    /// Returns the information log of the context if the shaders fail to compile or link.
+    pub fn new(vertex_src: &str, fragment_src: &str) -> Result<ShaderMaterial, String> {
+        let effect = Effect::try_new_from_str(vertex_src, fragment_src)?;
+
+        Ok(ShaderMaterial::from_effect(effect))
+    }
+
+    /// Creates a new material from the files containing its vertex and fragment shaders.
+    ///
+    /// With the `hot-reload` feature, these files are watched and the shaders are recompiled at
+    /// the first frame following a modification of one of them. If the new shaders fail to
+    /// compile, their information log is printed and the previous ones are kept. Returns an error message if the files cannot be read or the
+    /// shaders fail to compile or link.
+    pub fn from_files(vertex_path: &Path, fragment_path: &Path) -> Result<ShaderMaterial, String> {
+        let (vertex_src, fragment_src) = read_shaders(vertex_path, fragment_path)?;
+        #[allow(unused_mut)]
+        let mut material = ShaderMaterial::new(&vertex_src, &fragment_src)?;
+
+        #[cfg(feature = "hot-reload")]
+        {
+            // The events of the watcher refer to absolute paths.
+            let absolute =
+                |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+            let paths = [absolute(vertex_path), absolute(fragment_path)];
+
+            material.watched = Some(WatchedFiles {
+                modified: watch(&paths),
+                paths,
+            });
+        }
+
+        Ok(material)
+    }
+
+    fn from_effect(mut effect: Effect) -> ShaderMaterial {
+        effect.use_program();
+
+        ShaderMaterial {
+            pos: effect.get_attrib("position"),
+            normal: effect.get_attrib("normal"),
+            tex_coord: effect.get_attrib("tex_coord"),
+            proj: effect.get_uniform("proj"),
+            view: effect.get_uniform("view"),
+            transform: effect.get_uniform("transform"),
+            scale: effect.get_uniform("scale"),
+            ntransform: effect.get_uniform("ntransform"),
+            color: effect.get_uniform("color"),
+            tex: effect.get_uniform("tex"),
+            uniforms: HashMap::new(),
+            #[cfg(feature = "hot-reload")]
+            watched: None,
+            effect,
+        }
+    }
+
+    // Recompiles the shaders if `poll_shader_files` found their files modified.
+    #[cfg(feature = "hot-reload")]
+    fn reload_if_modified(&mut self) {
+        let watched = match self.watched {
+            Some(ref watched) if watched.modified.replace(false) => watched,
+            _ => return,
+        };
+
+        let effect = read_shaders(&watched.paths[0], &watched.paths[1]).and_then(
+            |(vertex_src, fragment_src)| Effect::try_new_from_str(&vertex_src, &fragment_src),
+        );
+
+        match effect {
+            Ok(effect) => {
+                let watched = self.watched.take();
+                let mut uniforms = std::mem::take(&mut self.uniforms);
+                *self = ShaderMaterial::from_effect(effect);
+
+                // The values of the uniforms that still exist are kept.
+                let effect = &self.effect;
+                uniforms.retain(|name, uniform| uniform.relocate(effect, name));
+                self.uniforms = uniforms;
+                self.watched = watched;
+            }
+            Err(log) => println!("Warning: failed to reload the shaders: {}", log),
+        }
+    }
+
+    /// Sets the value of a uniform of the shaders, uploaded each time an object is drawn.
+    ///
+    /// Returns `false` if the shaders have no active uniform named `name`, e.g., if it has been
+    /// optimized out by the shader compiler. The type of `value` must match the type of the
+    /// uniform.
+    pub fn set_uniform<T: GLPrimitive + 'static>(&mut self, name: &str, value: T) -> bool {
+        if let Some(uniform) = self
+            .uniforms
+            .get_mut(name)
+            .and_then(|u| u.as_any_mut().downcast_mut::<UserUniformValue<T>>())
+        {
+            uniform.value = value;
+            return true;
+        }
+
+        match self.effect.get_uniform(name) {
+            Some(uniform) => {
+                let _ = self.uniforms.insert(
+                    name.to_string(),
+                    Box::new(UserUniformValue { uniform, value }),
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops uploading the uniform `name` set by `set_uniform`.
+    ///
+    /// The uniform keeps its last uploaded value. Returns `false` if it was not set.
+    pub fn unset_uniform(&mut self, name: &str) -> bool {
+        self.uniforms.remove(name).is_some()
+    }
+}
+
+impl Material for ShaderMaterial {
+    fn render(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        _: &Lights,
+        data: &ObjectData,
+        mesh: &mut Mesh,
+    ) {
+        #[cfg(feature = "hot-reload")]
+        self.reload_if_modified();
+
+        if !data.surface_rendering_active() {
+            return;
+        }
+
+        let ctxt = Context::get();
+
+        if data.backface_culling_enabled() {
+            verify!(ctxt.enable(Context::CULL_FACE));
+        } else {
+            verify!(ctxt.disable(Context::CULL_FACE));
+        }
+
+        self.effect.use_program();
+
+        /*
+         *
+         * Setup camera and object-related stuffs.
+         *
+         */
+        if let (Some(proj), Some(view)) = (&mut self.proj, &mut self.view) {
+            camera.upload(pass, proj, view);
+        }
+
+        if let Some(ref mut uniform) = self.transform {
+            uniform.upload(&transform.to_homogeneous());
+        }
+
+        if let Some(ref mut uniform) = self.scale {
+            uniform.upload(&Matrix3::from_diagonal(scale));
+        }
+
+        if let Some(ref mut uniform) = self.ntransform {
+            uniform.upload(&transform.rotation.to_rotation_matrix().into_inner());
+        }
+
+        if let Some(ref mut uniform) = self.color {
+            uniform.upload(data.color());
+        }
+
+        if let Some(ref mut uniform) = self.tex {
+            uniform.upload(&0);
+            verify!(ctxt.active_texture(Context::TEXTURE0));
+            verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&**data.texture())));
+        }
+
+        for uniform in self.uniforms.values_mut() {
+            uniform.upload();
+        }
+
+        if let Some(ref mut pos) = self.pos {
+            pos.enable();
+            mesh.bind_coords(pos);
+        }
+
+        if let Some(ref mut normal) = self.normal {
+            normal.enable();
+            mesh.bind_normals(normal);
+        }
+
+        if let Some(ref mut tex_coord) = self.tex_coord {
+            tex_coord.enable();
+            mesh.bind_uvs(tex_coord);
+        }
+
+        mesh.bind_faces();
+
+        let _ = verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::FILL));
+        verify!(ctxt.draw_elements(
+            Context::TRIANGLES,
+            mesh.num_pts() as i32,
+            Context::UNSIGNED_SHORT,
+            0
+        ));
+
+        mesh.unbind();
+
+        if let Some(ref mut pos) = self.pos {
+            pos.disable();
+        }
+
+        if let Some(ref mut normal) = self.normal {
+            normal.disable();
+        }
+
+        if let Some(ref mut tex_coord) = self.tex_coord {
+            tex_coord.disable();
+        }
+    }
+}
+
+// A uniform set by the user, of any type.
+trait UserUniform {
+    fn upload(&mut self);
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    // Finds the uniform named `name` in `effect`, and returns `false` if it does not exist.
+    #[cfg(feature = "hot-reload")]
+    fn relocate(&mut self, effect: &Effect, name: &str) -> bool;
+}
+
+struct UserUniformValue<T> {
+    uniform: ShaderUniform<T>,
+    value: T,
+}
+
+impl<T: GLPrimitive + 'static> UserUniform for UserUniformValue<T> {
+    fn upload(&mut self) {
+        self.uniform.upload(&self.value)
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    #[cfg(feature = "hot-reload")]
+    fn relocate(&mut self, effect: &Effect, name: &str) -> bool {
+        match effect.get_uniform(name) {
+            Some(uniform) => {
+                self.uniform = uniform;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn read_shaders(vertex_path: &Path, fragment_path: &Path) -> Result<(String, String), String> {
+    let read = |path: &Path| {
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    };
+
+    Ok((read(vertex_path)?, read(fragment_path)?))
+}
+
+// Watches the given files, and returns the flag set when one of them is modified.
+#[cfg(feature = "hot-reload")]
+fn watch(paths: &[PathBuf; 2]) -> Rc<Cell<bool>> {
+    let modified = Rc::new(Cell::new(false));
+
+    SHADER_WATCHER.with(|watcher| {
+        let mut watcher = watcher.borrow_mut();
+
+        if watcher.is_none() {
+            let (sender, events) = mpsc::channel();
+
+            match notify::watcher(sender, WATCH_DELAY) {
+                Ok(w) => {
+                    *watcher = Some(ShaderWatcher {
+                        watcher: w,
+                        events,
+                        materials: HashMap::new(),
+                    })
+                }
+                Err(e) => println!("Warning: failed to watch the shader files: {}", e),
+            }
+        }
+
+        if let Some(ref mut watcher) = *watcher {
+            for path in paths.iter() {
+                if let Err(e) = watcher.watcher.watch(path, RecursiveMode::NonRecursive) {
+                    println!("Warning: failed to watch {}: {}", path.display(), e);
+                }
+
+                watcher
+                    .materials
+                    .entry(path.clone())
+                    .or_insert_with(Vec::new)
+                    .push(Rc::downgrade(&modified));
+            }
+        }
+    });
+
+    modified
+}
+
+/// Flags the materials whose shader files have been modified since the last call, so they are
+/// reloaded the next time they are drawn.
+///
+/// This drains the events of the file watcher, and is called once per frame by the window.
+#[cfg(feature = "hot-reload")]
+pub(crate) fn poll_shader_files() {
+    SHADER_WATCHER.with(|watcher| {
+        let mut watcher = watcher.borrow_mut();
+        let ShaderWatcher {
+            watcher,
+            events,
+            materials,
+        } = match *watcher {
+            Some(ref mut watcher) => watcher,
+            None => return,
+        };
+
+        for event in events.try_iter() {
+            let path = match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Chmod(path)
+                | DebouncedEvent::Remove(path)
+                | DebouncedEvent::Rename(_, path) => path,
+                DebouncedEvent::Error(e, _) => {
+                    println!("Warning: failed to watch the shader files: {}", e);
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if let Some(flags) = materials.get_mut(&path) {
+                flags.retain(|flag| match flag.upgrade() {
+                    Some(flag) => {
+                        flag.set(true);
+                        true
+                    }
+                    None => false,
+                });
+
+                if flags.is_empty() {
+                    let _ = materials.remove(&path);
+                    let _ = watcher.unwatch(&path);
+                } else {
+                    // The file is watched again in case it has been replaced, e.g., by an editor
+                    // saving to a temporary file first.
+                    let _ = watcher.watch(&path, RecursiveMode::NonRecursive);
+                }
+            }
+        }
+    });
+}
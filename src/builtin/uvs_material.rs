@@ -1,6 +1,6 @@
 use crate::camera::Camera;
 use crate::context::Context;
-use crate::light::Light;
+use crate::light::Lights;
 use crate::resource::Material;
 use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
 use crate::scene::ObjectData;
@@ -28,12 +28,12 @@ impl UvsMaterial {
         shader.use_program();
 
         UvsMaterial {
-            position: shader.get_attrib("position").unwrap(),
-            uvs: shader.get_attrib("uvs").unwrap(),
-            transform: shader.get_uniform("transform").unwrap(),
-            scale: shader.get_uniform("scale").unwrap(),
-            view: shader.get_uniform("view").unwrap(),
-            proj: shader.get_uniform("proj").unwrap(),
+            position: shader.attribute("position"),
+            uvs: shader.attribute("uvs"),
+            transform: shader.uniform("transform"),
+            scale: shader.uniform("scale"),
+            view: shader.uniform("view"),
+            proj: shader.uniform("proj"),
             shader: shader,
         }
     }
@@ -46,7 +46,7 @@ impl Material for UvsMaterial {
         transform: &Isometry3<f32>,
         scale: &Vector3<f32>,
         camera: &mut dyn Camera,
-        _: &Light,
+        _: &Lights,
         data: &ObjectData,
         mesh: &mut Mesh,
     ) {
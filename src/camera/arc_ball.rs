@@ -1,10 +1,62 @@
+use crate::camera::camera;
 use crate::camera::Camera;
-use crate::event::{Action, Key, Modifiers, MouseButton, WindowEvent};
+use crate::event::{Action, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
+use instant::Instant;
 use na::{self, Isometry3, Matrix4, Perspective3, Point3, Unit, UnitQuaternion, Vector2, Vector3};
 use std::f32;
 
+// A smooth transition of the focus point and distance of an arc-ball camera.
+#[derive(Clone, Debug)]
+struct FocusAnimation {
+    start: Instant,
+    duration: f32,
+    from_at: Point3<f32>,
+    from_dist: f32,
+    to_at: Point3<f32>,
+    to_dist: f32,
+}
+
+/// The pose and projection of an `ArcBall` camera.
+///
+/// This is obtained with `ArcBall::state` and restored with `ArcBall::set_state`. The aspect ratio
+/// of the projection is not part of the state since it follows the size of the window.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct ArcBallState {
+    /// The focus point.
+    pub at: Point3<f32>,
+    /// The rotation of the camera around the up axis, in radians.
+    pub yaw: f32,
+    /// The angle between the up axis and the direction from the focus point to the camera, in
+    /// radians.
+    pub pitch: f32,
+    /// The distance from the camera to the focus point.
+    pub dist: f32,
+    /// The up axis of the camera.
+    pub up: Vector3<f32>,
+    /// The vertical field of view, in radians.
+    pub fovy: f32,
+    /// The distance to the near clipping plane.
+    pub znear: f32,
+    /// The distance to the far clipping plane.
+    pub zfar: f32,
+    /// Whether the far clipping plane is moved to infinity, ignoring `zfar`.
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    pub infinite_far: bool,
+    /// The projection matrix used instead of the one derived from the field of view, if any.
+    pub custom_projection: Option<Matrix4<f32>>,
+}
+
+impl ArcBallState {
+    /// The position of the camera.
+    pub fn eye(&self) -> Point3<f32> {
+        let coord_system = CoordSystemRh::from_up_axis(Unit::new_normalize(self.up));
+        arc_ball_eye(&coord_system, &self.at, self.yaw, self.pitch, self.dist)
+    }
+}
+
 /// Arc-ball camera mode.
 ///
 /// An arc-ball camera is a camera rotating around a fixed point (the focus point) and always
@@ -15,6 +67,9 @@ use std::f32;
 /// direction
 /// * Scroll in/out - zoom in/out
 /// * Enter key - set the focus point to the origin
+/// * One-finger drag - rotates the camera around the focus point
+/// * Two-finger drag - translates the focus point
+/// * Two-finger pinch - zoom in/out
 #[derive(Clone, Debug)]
 pub struct ArcBall {
     /// The focus point.
@@ -29,6 +84,13 @@ pub struct ArcBall {
     min_dist: f32,
     /// Maximum distance from the camera to the `at` focus point.
     max_dist: f32,
+    /// The yaw, pitch, and distance the camera is moving to when damping is enabled.
+    target_yaw: f32,
+    target_pitch: f32,
+    target_dist: f32,
+    /// The time constant of the exponential moves toward the target values, in seconds.
+    damping: f32,
+    last_update: Option<Instant>,
 
     /// Increment of the yaw per unit mouse movement. The default value is 0.005.
     yaw_step: f32,
@@ -40,6 +102,8 @@ pub struct ArcBall {
     max_pitch: f32,
     /// Increment of the distance per unit scrolling. The default value is 40.0.
     dist_step: f32,
+    /// Whether scrolling in moves the camera away from the focus point.
+    invert_scroll: bool,
     rotate_button: Option<MouseButton>,
     rotate_modifiers: Option<Modifiers>,
     drag_button: Option<MouseButton>,
@@ -47,12 +111,17 @@ pub struct ArcBall {
     reset_key: Option<Key>,
 
     projection: Perspective3<f32>,
+    infinite_far: bool,
+    custom_projection: Option<Matrix4<f32>>,
     view: Matrix4<f32>,
     proj: Matrix4<f32>,
     proj_view: Matrix4<f32>,
     inverse_proj_view: Matrix4<f32>,
     last_cursor_pos: Vector2<f32>,
+    // The identifiers and positions of the fingers touching the screen.
+    touches: Vec<(u64, Vector2<f32>)>,
     coord_system: CoordSystemRh,
+    focus_animation: Option<FocusAnimation>,
 }
 
 impl ArcBall {
@@ -76,23 +145,33 @@ impl ArcBall {
             dist: 0.0,
             min_dist: 0.00001,
             max_dist: std::f32::MAX,
+            target_yaw: 0.0,
+            target_pitch: 0.0,
+            target_dist: 0.0,
+            damping: 0.0,
+            last_update: None,
             yaw_step: 0.005,
             pitch_step: 0.005,
             min_pitch: 0.01,
             max_pitch: std::f32::consts::PI - 0.01,
             dist_step: 40.0,
+            invert_scroll: false,
             rotate_button: Some(MouseButton::Button1),
             rotate_modifiers: None,
             drag_button: Some(MouseButton::Button2),
             drag_modifiers: None,
             reset_key: Some(Key::Return),
             projection: Perspective3::new(800.0 / 600.0, fov, znear, zfar),
+            infinite_far: false,
+            custom_projection: None,
             view: na::zero(),
             proj: na::zero(),
             proj_view: na::zero(),
             inverse_proj_view: na::zero(),
             last_cursor_pos: na::zero(),
+            touches: Vec::new(),
             coord_system: CoordSystemRh::from_up_axis(Vector3::y_axis()),
+            focus_animation: None,
         };
 
         res.look_at(eye, at);
@@ -107,6 +186,7 @@ impl ArcBall {
 
     /// Get a mutable reference to the point the camera is looking at.
     pub fn set_at(&mut self, at: Point3<f32>) {
+        self.focus_animation = None;
         self.at = at;
         self.update_projviews();
     }
@@ -119,6 +199,7 @@ impl ArcBall {
     /// Sets the camera `yaw`. Change this to modify the rotation along the `up` axis.
     pub fn set_yaw(&mut self, yaw: f32) {
         self.yaw = yaw;
+        self.target_yaw = yaw;
 
         self.update_restrictions();
         self.update_projviews();
@@ -132,6 +213,7 @@ impl ArcBall {
     /// Sets the camera `pitch`.
     pub fn set_pitch(&mut self, pitch: f32) {
         self.pitch = pitch;
+        self.target_pitch = pitch;
 
         self.update_restrictions();
         self.update_projviews();
@@ -164,7 +246,9 @@ impl ArcBall {
 
     /// Move the camera such that it is at a given distance from the view point.
     pub fn set_dist(&mut self, dist: f32) {
+        self.focus_animation = None;
         self.dist = dist;
+        self.target_dist = dist;
 
         self.update_restrictions();
         self.update_projviews();
@@ -176,8 +260,12 @@ impl ArcBall {
     }
 
     /// Set the minimum distance from the camera position to its view point.
+    ///
+    /// The current distance is clamped immediately, and zooming cannot go past it afterwards.
     pub fn set_min_dist(&mut self, min_dist: f32) {
         self.min_dist = min_dist;
+        self.update_restrictions();
+        self.update_projviews();
     }
 
     /// The maximum distance from the camera position to its view point.
@@ -186,15 +274,121 @@ impl ArcBall {
     }
 
     /// Set the maximum distance from the camera position to its view point.
+    ///
+    /// The current distance is clamped immediately, and zooming cannot go past it afterwards.
     pub fn set_max_dist(&mut self, max_dist: f32) {
         self.max_dist = max_dist;
+        self.update_restrictions();
+        self.update_projviews();
+    }
+
+    /// The projection matrix used instead of the one derived from the field of view, if any.
+    #[inline]
+    pub fn custom_projection(&self) -> Option<&Matrix4<f32>> {
+        self.custom_projection.as_ref()
+    }
+
+    /// Sets the projection matrix of this camera, e.g., for oblique near-plane clipping.
+    ///
+    /// The matrix is used verbatim instead of the projection derived from the field of view,
+    /// until `unset_projection` is called. The view transformation is still computed from the
+    /// position and orientation of this camera. Note that `clip_planes` still returns the
+    /// clipping planes given at the creation of this camera, which may not match `projection`.
+    pub fn set_projection(&mut self, projection: Matrix4<f32>) {
+        self.custom_projection = Some(projection);
+        self.update_projviews();
+    }
+
+    /// Reverts to the projection derived from the field of view.
+    pub fn unset_projection(&mut self) {
+        self.custom_projection = None;
+        self.update_projviews();
+    }
+
+    /// Whether the far clipping plane of this camera is at infinity.
+    pub fn infinite_far(&self) -> bool {
+        self.infinite_far
+    }
+
+    /// Sets whether the far clipping plane of this camera is moved to infinity (default at
+    /// `false`).
+    ///
+    /// Objects are then never clipped however far they are, at the cost of a slightly lower depth
+    /// precision. `clip_planes` then returns `f32::INFINITY` as `zfar`. This has no effect on a
+    /// projection set with `set_projection`.
+    pub fn set_infinite_far(&mut self, infinite_far: bool) {
+        self.infinite_far = infinite_far;
+        self.update_projviews();
+    }
+
+    /// The increment of the distance for a unit scroll.
+    pub fn dist_step(&self) -> f32 {
+        self.dist_step
     }
 
     /// Set the increment for a unit scroll (default at 40).
+    ///
+    /// This sets the zoom speed of the mouse wheel. Whatever its value, the distance stays within
+    /// the bounds set by `set_min_dist` and `set_max_dist`.
     pub fn set_dist_step(&mut self, dist_step: f32) {
         self.dist_step = dist_step;
     }
 
+    /// Whether scrolling in moves the camera away from the focus point.
+    pub fn invert_scroll(&self) -> bool {
+        self.invert_scroll
+    }
+
+    /// Sets whether scrolling in moves the camera away from the focus point instead of toward it
+    /// (default at `false`).
+    pub fn set_invert_scroll(&mut self, invert_scroll: bool) {
+        self.invert_scroll = invert_scroll;
+    }
+
+    /// The pose and projection of this camera.
+    pub fn state(&self) -> ArcBallState {
+        ArcBallState {
+            at: self.at,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            dist: self.dist,
+            up: self.coord_system.up_axis.into_inner(),
+            fovy: self.projection.fovy(),
+            znear: self.projection.znear(),
+            zfar: self.projection.zfar(),
+            infinite_far: self.infinite_far,
+            custom_projection: self.custom_projection,
+        }
+    }
+
+    /// Restores the pose and projection of this camera.
+    ///
+    /// This reproduces the view and projection matrices of the camera the state was taken from,
+    /// provided they have the same aspect ratio. The distance and pitch are still restricted to
+    /// the limits of this camera, and any transition of the focus point is canceled.
+    pub fn set_state(&mut self, state: &ArcBallState) {
+        self.focus_animation = None;
+        self.coord_system = CoordSystemRh::from_up_axis(Unit::new_normalize(state.up));
+        self.at = state.at;
+        self.yaw = state.yaw;
+        self.pitch = state.pitch;
+        self.dist = state.dist;
+        self.target_yaw = state.yaw;
+        self.target_pitch = state.pitch;
+        self.target_dist = state.dist;
+        self.projection = Perspective3::new(
+            self.projection.aspect(),
+            state.fovy,
+            state.znear,
+            state.zfar,
+        );
+        self.infinite_far = state.infinite_far;
+        self.custom_projection = state.custom_projection;
+
+        self.update_restrictions();
+        self.update_projviews();
+    }
+
     /// Move and orient the camera such that it looks at a specific point.
     pub fn look_at(&mut self, eye: Point3<f32>, at: Point3<f32>) {
         let dist = (eye - at).norm();
@@ -204,31 +398,169 @@ impl ArcBall {
         let pitch = ((view_eye.y - view_at.y) / dist).acos();
         let yaw = (view_eye.z - view_at.z).atan2(view_eye.x - view_at.x);
 
+        self.focus_animation = None;
         self.at = at;
         self.dist = dist;
         self.yaw = yaw;
         self.pitch = pitch;
+        self.target_dist = dist;
+        self.target_yaw = yaw;
+        self.target_pitch = pitch;
 
         self.update_restrictions();
         self.update_projviews();
     }
 
-    /// Transformation applied by the camera without perspective.
-    fn update_restrictions(&mut self) {
-        if self.dist < self.min_dist {
-            self.dist = self.min_dist
+    /// Moves the focus point to `target` smoothly, over `duration` seconds.
+    ///
+    /// The camera keeps its orientation and distance to the focus point. The transition is
+    /// advanced at each frame. It is canceled if the user moves the camera, if another one
+    /// starts, or if the focus point or distance is set explicitly.
+    pub fn look_at_smooth(&mut self, target: Point3<f32>, duration: f32) {
+        self.animate_focus(target, self.dist, duration)
+    }
+
+    /// Moves the focus point to the center of an axis-aligned box, and the camera close enough
+    /// for the whole box to be visible, smoothly over `duration` seconds.
+    ///
+    /// The camera keeps its orientation. The distance fits the bounding sphere of the box in the
+    /// field of view, and is restricted to the minimum and maximum distances of this camera. See
+    /// `look_at_smooth` for details about the transition.
+    pub fn frame_bounding_box(&mut self, mins: Point3<f32>, maxs: Point3<f32>, duration: f32) {
+        let radius = (maxs - mins).norm() / 2.0;
+        let half_fovy = self.projection.fovy() / 2.0;
+        let half_fovx = (half_fovy.tan() * self.projection.aspect()).atan();
+        let dist = radius / half_fovy.min(half_fovx).sin();
+
+        self.animate_focus(na::center(&mins, &maxs), dist, duration)
+    }
+
+    /// Whether the focus point is being moved by `look_at_smooth` or `frame_bounding_box`.
+    pub fn is_animating_focus(&self) -> bool {
+        self.focus_animation.is_some()
+    }
+
+    fn animate_focus(&mut self, at: Point3<f32>, dist: f32, duration: f32) {
+        let to_dist = dist.max(self.min_dist).min(self.max_dist);
+
+        if duration <= 0.0 {
+            self.focus_animation = None;
+            self.at = at;
+            self.dist = to_dist;
+            self.target_dist = to_dist;
+            self.update_projviews();
+        } else {
+            self.focus_animation = Some(FocusAnimation {
+                start: Instant::now(),
+                duration,
+                from_at: self.at,
+                from_dist: self.dist,
+                to_at: at,
+                to_dist,
+            });
         }
+    }
 
-        if self.dist > self.max_dist {
-            self.dist = self.max_dist
+    fn update_focus_animation(&mut self) {
+        if let Some(ref anim) = self.focus_animation {
+            let t = (anim.start.elapsed().as_secs_f32() / anim.duration).min(1.0);
+            // Ease in and out.
+            let s = t * t * (3.0 - 2.0 * t);
+
+            self.at = anim.from_at + (anim.to_at - anim.from_at) * s;
+            self.dist = anim.from_dist + (anim.to_dist - anim.from_dist) * s;
+            self.target_dist = self.dist;
+
+            if t >= 1.0 {
+                self.focus_animation = None;
+            }
+
+            self.update_restrictions();
+            self.update_projviews();
         }
+    }
+
+    /// The time constant of the damping of the user inputs, in seconds.
+    pub fn damping(&self) -> f32 {
+        self.damping
+    }
 
-        if self.pitch <= self.min_pitch {
-            self.pitch = self.min_pitch
+    /// Sets the time constant of the damping of the user inputs, in seconds.
+    ///
+    /// If this is greater than zero, the rotations and zoom requested by the user are not applied
+    /// instantly. Instead, the yaw, pitch, and distance of the camera approach exponentially their
+    /// requested values at each frame, covering about 63% of the remaining way every `factor`
+    /// seconds. Successive inputs accumulate into the requested values. The values set
+    /// explicitly, e.g., with `set_yaw` or `look_at`, are still applied instantly. The default
+    /// value is 0, which disables damping.
+    pub fn set_damping(&mut self, factor: f32) {
+        self.damping = factor.max(0.0);
+
+        if self.damping == 0.0 {
+            self.snap_to_target();
         }
+    }
+
+    fn snap_to_target(&mut self) {
+        self.yaw = self.target_yaw;
+        self.pitch = self.target_pitch;
+        self.dist = self.target_dist;
+        self.update_projviews();
+    }
+
+    fn update_damping(&mut self) {
+        let now = Instant::now();
+        let dt = self
+            .last_update
+            .map_or(0.0, |last| (now - last).as_secs_f32());
+        self.last_update = Some(now);
+
+        if self.damping == 0.0
+            || (self.yaw == self.target_yaw
+                && self.pitch == self.target_pitch
+                && self.dist == self.target_dist)
+        {
+            return;
+        }
+
+        let alpha = 1.0 - (-dt / self.damping).exp();
+        let approach = |curr: &mut f32, target: f32| {
+            *curr += (target - *curr) * alpha;
+
+            if (target - *curr).abs() <= 1.0e-5 * target.abs().max(1.0) {
+                *curr = target
+            }
+        };
+
+        approach(&mut self.yaw, self.target_yaw);
+        approach(&mut self.pitch, self.target_pitch);
+        approach(&mut self.dist, self.target_dist);
+        self.update_projviews();
+    }
+
+    /// Transformation applied by the camera without perspective.
+    fn update_restrictions(&mut self) {
+        let (min_dist, max_dist) = (self.min_dist, self.max_dist);
+        let (min_pitch, max_pitch) = (self.min_pitch, self.max_pitch);
+
+        for dist in [&mut self.dist, &mut self.target_dist].iter_mut() {
+            if **dist < min_dist {
+                **dist = min_dist
+            }
+
+            if **dist > max_dist {
+                **dist = max_dist
+            }
+        }
+
+        for pitch in [&mut self.pitch, &mut self.target_pitch].iter_mut() {
+            if **pitch <= min_pitch {
+                **pitch = min_pitch
+            }
 
-        if self.pitch > self.max_pitch {
-            self.pitch = self.max_pitch
+            if **pitch > max_pitch {
+                **pitch = max_pitch
+            }
         }
     }
 
@@ -294,14 +626,19 @@ impl ArcBall {
     }
 
     fn handle_left_button_displacement(&mut self, dpos: &Vector2<f32>) {
-        self.yaw = self.yaw + dpos.x * self.yaw_step;
-        self.pitch = self.pitch - dpos.y * self.pitch_step;
+        self.focus_animation = None;
+        self.target_yaw += dpos.x * self.yaw_step;
+        self.target_pitch -= dpos.y * self.pitch_step;
 
         self.update_restrictions();
-        self.update_projviews();
+
+        if self.damping == 0.0 {
+            self.snap_to_target();
+        }
     }
 
     fn handle_right_button_displacement(&mut self, dpos: &Vector2<f32>) {
+        self.focus_animation = None;
         let eye = self.eye();
         let dir = (self.at - eye).normalize();
         let tangent = self.coord_system.up_axis.cross(&dir).normalize();
@@ -313,16 +650,69 @@ impl ArcBall {
     }
 
     fn handle_scroll(&mut self, off: f32) {
-        self.dist = self.dist + self.dist_step * (off) / 120.0;
+        self.focus_animation = None;
+        let off = if self.invert_scroll { -off } else { off };
+        self.target_dist += self.dist_step * (off) / 120.0;
         self.update_restrictions();
-        self.update_projviews();
+
+        if self.damping == 0.0 {
+            self.snap_to_target();
+        }
+    }
+
+    fn handle_touch(&mut self, id: u64, pos: Vector2<f32>, action: TouchAction) {
+        let i = self.touches.iter().position(|t| t.0 == id);
+
+        match (action, i) {
+            (TouchAction::Start, None) => self.touches.push((id, pos)),
+            (TouchAction::Move, Some(i)) => {
+                let last_pos = self.touches[i].1;
+
+                match self.touches.len() {
+                    1 => self.handle_left_button_displacement(&(pos - last_pos)),
+                    2 => {
+                        let other = self.touches[1 - i].1;
+                        let last_center = (last_pos + other) / 2.0;
+                        let center = (pos + other) / 2.0;
+                        let last_spread = (last_pos - other).norm();
+                        let spread = (pos - other).norm();
+
+                        self.handle_right_button_displacement(&(center - last_center));
+
+                        if last_spread > 0.0 && spread > 0.0 {
+                            // Spreading the fingers apart zooms in.
+                            self.focus_animation = None;
+                            self.target_dist *= last_spread / spread;
+                            self.update_restrictions();
+
+                            if self.damping == 0.0 {
+                                self.snap_to_target();
+                            }
+                        }
+                    }
+                    // Gestures with more fingers are ignored.
+                    _ => {}
+                }
+
+                self.touches[i].1 = pos;
+            }
+            (TouchAction::End, Some(i)) | (TouchAction::Cancel, Some(i)) => {
+                let _ = self.touches.remove(i);
+            }
+            _ => {}
+        }
     }
 
     fn update_projviews(&mut self) {
-        self.proj = *self.projection.as_matrix();
+        self.proj = self
+            .custom_projection
+            .unwrap_or_else(|| camera::perspective_matrix(&self.projection, self.infinite_far));
         self.view = self.view_transform().to_homogeneous();
         self.proj_view = self.proj * self.view;
-        self.inverse_proj_view = self.proj_view.try_inverse().unwrap();
+        let _ = self
+            .proj_view
+            .try_inverse()
+            .map(|inverse_proj| self.inverse_proj_view = inverse_proj);
     }
 
     /// Sets the up vector of this camera. Prefer using [`set_up_axis_dir`](#method.set_up_axis_dir)
@@ -349,7 +739,11 @@ impl ArcBall {
 
 impl Camera for ArcBall {
     fn clip_planes(&self) -> (f32, f32) {
-        (self.projection.znear(), self.projection.zfar())
+        if self.infinite_far {
+            (self.projection.znear(), f32::INFINITY)
+        } else {
+            (self.projection.znear(), self.projection.zfar())
+        }
     }
 
     fn view_transform(&self) -> Isometry3<f32> {
@@ -357,11 +751,13 @@ impl Camera for ArcBall {
     }
 
     fn eye(&self) -> Point3<f32> {
-        let view_at = self.coord_system.rotation_to_y_up * self.at;
-        let px = view_at.x + self.dist * self.yaw.cos() * self.pitch.sin();
-        let py = view_at.y + self.dist * self.pitch.cos();
-        let pz = view_at.z + self.dist * self.yaw.sin() * self.pitch.sin();
-        self.coord_system.rotation_to_y_up.inverse() * Point3::new(px, py, pz)
+        arc_ball_eye(
+            &self.coord_system,
+            &self.at,
+            self.yaw,
+            self.pitch,
+            self.dist,
+        )
     }
 
     fn handle_event(&mut self, canvas: &Canvas, event: &WindowEvent) {
@@ -393,10 +789,14 @@ impl Camera for ArcBall {
                 self.last_cursor_pos = curr_pos;
             }
             WindowEvent::Key(key, Action::Press, _) if Some(key) == self.reset_key => {
+                self.focus_animation = None;
                 self.at = Point3::origin();
                 self.update_projviews();
             }
             WindowEvent::Scroll(_, off, _) => self.handle_scroll(off as f32),
+            WindowEvent::Touch(id, x, y, action, _) => {
+                self.handle_touch(id, Vector2::new(x as f32, y as f32), action)
+            }
             WindowEvent::FramebufferSize(w, h) => {
                 self.projection.set_aspect(w as f32 / h as f32);
                 self.update_projviews();
@@ -412,19 +812,36 @@ impl Camera for ArcBall {
         proj: &mut ShaderUniform<Matrix4<f32>>,
         view: &mut ShaderUniform<Matrix4<f32>>,
     ) {
-        proj.upload(&self.proj);
+        proj.upload(&(camera::depth_reversal() * self.proj));
         view.upload(&self.view);
     }
 
     fn transformation(&self) -> Matrix4<f32> {
-        self.proj_view
+        camera::depth_reversal() * self.proj_view
     }
 
     fn inverse_transformation(&self) -> Matrix4<f32> {
-        self.inverse_proj_view
+        self.inverse_proj_view * camera::inverse_depth_reversal()
+    }
+
+    fn update(&mut self, _: &Canvas) {
+        self.update_focus_animation();
+        self.update_damping();
     }
+}
 
-    fn update(&mut self, _: &Canvas) {}
+fn arc_ball_eye(
+    coord_system: &CoordSystemRh,
+    at: &Point3<f32>,
+    yaw: f32,
+    pitch: f32,
+    dist: f32,
+) -> Point3<f32> {
+    let view_at = coord_system.rotation_to_y_up * at;
+    let px = view_at.x + dist * yaw.cos() * pitch.sin();
+    let py = view_at.y + dist * pitch.cos();
+    let pz = view_at.z + dist * yaw.sin() * pitch.sin();
+    coord_system.rotation_to_y_up.inverse() * Point3::new(px, py, pz)
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -1,7 +1,85 @@
 use crate::event::WindowEvent;
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
-use na::{Isometry3, Matrix4, Point2, Point3, Point4, Vector2, Vector3};
+use na::{Isometry3, Matrix4, Perspective3, Point2, Point3, Point4, Vector2, Vector3, Vector4};
+use std::cell::Cell;
+
+thread_local! {
+    // Whether the depth is reversed, see `Window::set_reversed_z`.
+    static REVERSED_Z: Cell<bool> = const { Cell::new(false) };
+    // Whether the reversed depth is on the range `[0, 1]` in normalized device coordinates.
+    static ZERO_TO_ONE_DEPTH: Cell<bool> = const { Cell::new(false) };
+}
+
+// Sets whether the depth is reversed, and whether it is then on the range `[0, 1]` instead of
+// `[-1, 1]` in normalized device coordinates.
+pub(crate) fn set_reversed_z(reversed: bool, zero_to_one: bool) {
+    REVERSED_Z.with(|r| r.set(reversed));
+    ZERO_TO_ONE_DEPTH.with(|z| z.set(reversed && zero_to_one));
+}
+
+// Whether the reversed depth is on the range `[0, 1]` in normalized device coordinates.
+pub(crate) fn zero_to_one_depth() -> bool {
+    ZERO_TO_ONE_DEPTH.with(|z| z.get())
+}
+
+/// Whether the depth is reversed, i.e., the near plane is mapped to a depth of 1.0 and the far
+/// plane to a depth of 0.0.
+///
+/// See `Window::set_reversed_z`. Cameras must then multiply their projection by
+/// `depth_reversal()`, and its inverse by `inverse_depth_reversal()`, as the builtin cameras do.
+pub fn reversed_z() -> bool {
+    REVERSED_Z.with(|r| r.get())
+}
+
+/// The matrix converting device coordinates to the current depth convention.
+///
+/// If `reversed_z()` is `true`, it maps the `z` coordinate from `[-1, 1]` to `[1, 0]` when the
+/// window uses `glClipControl`, and negates it otherwise. It is the identity if the depth is not
+/// reversed. Its inverse is `inverse_depth_reversal()`.
+pub fn depth_reversal() -> Matrix4<f32> {
+    if zero_to_one_depth() {
+        // z' = (w - z) / 2
+        let mut m = Matrix4::identity();
+        m[(2, 2)] = -0.5;
+        m[(2, 3)] = 0.5;
+        m
+    } else if reversed_z() {
+        Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 1.0, -1.0))
+    } else {
+        Matrix4::identity()
+    }
+}
+
+/// The inverse of `depth_reversal()`, converting device coordinates in the current depth
+/// convention back to the standard one.
+pub fn inverse_depth_reversal() -> Matrix4<f32> {
+    if zero_to_one_depth() {
+        // z = w - 2 z'
+        let mut m = Matrix4::identity();
+        m[(2, 2)] = -2.0;
+        m[(2, 3)] = 1.0;
+        m
+    } else {
+        depth_reversal()
+    }
+}
+
+// The matrix of `projection`, with its far plane moved to infinity if `infinite_far` is `true`.
+pub(crate) fn perspective_matrix(
+    projection: &Perspective3<f32>,
+    infinite_far: bool,
+) -> Matrix4<f32> {
+    let mut matrix = *projection.as_matrix();
+
+    if infinite_far {
+        // The limits of the depth terms of the perspective matrix when `zfar` tends to infinity.
+        matrix[(2, 2)] = -1.0;
+        matrix[(2, 3)] = -2.0 * projection.znear();
+    }
+
+    matrix
+}
 
 /// Trait every camera must implement.
 pub trait Camera {
@@ -25,8 +103,22 @@ pub trait Camera {
     /// point in world coordinate.
     fn inverse_transformation(&self) -> Matrix4<f32>;
     /// The clipping planes, aka. (`znear`, `zfar`).
+    ///
+    /// `zfar` is `f32::INFINITY` if the projection has no far plane.
     fn clip_planes(&self) -> (f32, f32); // FIXME: should this be here?
 
+    /// The six planes bounding the volume seen by the camera, in world coordinates.
+    ///
+    /// Each plane `p` is given such that a point `x` is inside of the frustum if
+    /// `p.x * x.x + p.y * x.y + p.z * x.z + p.w >= 0` for all planes. The normals `(p.x, p.y, p.z)`
+    /// are unit vectors. The planes are given in the order: left, right, bottom, top, near, far.
+    ///
+    /// The default implementation extracts them from `self.transformation()`. Cameras rendering
+    /// several passes with different transformations must return planes enclosing all of them.
+    fn frustum_planes(&self) -> [Vector4<f32>; 6] {
+        frustum_planes_from_transformation(&(inverse_depth_reversal() * self.transformation()))
+    }
+
     /*
      * Update & upload
      */
@@ -57,7 +149,30 @@ pub trait Camera {
     fn render_complete(&self, _canvas: &Canvas) {}
 
     /// Converts a 3d point to 2d screen coordinates, assuming the screen has the size `size`.
-    fn project(&self, world_coord: &Point3<f32>, size: &Vector2<f32>) -> Vector2<f32> {
+    ///
+    /// The coordinates are in the unit of `size`, e.g., physical pixels with `Window::size`, from
+    /// the bottom-left corner of the screen. Returns `None` if the point is behind the camera,
+    /// i.e., if its clip-space `w` coordinate is not positive.
+    fn project(&self, world_coord: &Point3<f32>, size: &Vector2<f32>) -> Option<Vector2<f32>> {
+        let h_normalized_coord = self.transformation() * world_coord.to_homogeneous();
+
+        if h_normalized_coord[3] <= 0.0 {
+            return None;
+        }
+
+        let normalized_coord = Point3::from_homogeneous(h_normalized_coord)?;
+
+        Some(Vector2::new(
+            (1.0 + normalized_coord.x) * size.x / 2.0,
+            (1.0 + normalized_coord.y) * size.y / 2.0,
+        ))
+    }
+
+    /// Converts a 3d point to 2d screen coordinates, assuming the screen has the size `size`.
+    ///
+    /// Unlike `project`, this returns a meaningless position instead of failing if the point is
+    /// behind the camera.
+    fn project_unchecked(&self, world_coord: &Point3<f32>, size: &Vector2<f32>) -> Vector2<f32> {
         let h_world_coord = world_coord.to_homogeneous();
         let transform = self.transformation();
         let mut h_normalized_coord = transform * h_world_coord;
@@ -74,7 +189,9 @@ pub trait Camera {
 
     /// Converts a point in 2d screen coordinates to a ray (a 3d position and a direction).
     ///
-    /// The screen is assumed to have a size given by `size`.
+    /// The screen is assumed to have a size given by `size`. Both must be in the same unit, e.g.,
+    /// a position given by `WindowEvent::CursorPos` and `Window::size`, which are in physical
+    /// pixels.
     fn unproject(
         &self,
         window_coord: &Point2<f32>,
@@ -85,8 +202,17 @@ pub trait Camera {
             2.0 * -window_coord.y / size.y + 1.0,
         );
 
-        let normalized_begin = Point4::new(normalized_coord.x, normalized_coord.y, -1.0, 1.0);
-        let normalized_end = Point4::new(normalized_coord.x, normalized_coord.y, 1.0, 1.0);
+        // The near plane is at a depth of 1.0 if the depth is reversed. The ray ends halfway to
+        // the far plane rather than on it, since it is at infinity for an infinite projection.
+        let (near, middle) = if zero_to_one_depth() {
+            (1.0, 0.5)
+        } else if reversed_z() {
+            (1.0, 0.0)
+        } else {
+            (-1.0, 0.0)
+        };
+        let normalized_begin = Point4::new(normalized_coord.x, normalized_coord.y, near, 1.0);
+        let normalized_end = Point4::new(normalized_coord.x, normalized_coord.y, middle, 1.0);
 
         let cam = self.inverse_transformation();
 
@@ -105,3 +231,50 @@ pub trait Camera {
         )
     }
 }
+
+/// Extracts the six frustum planes of a world-to-device transformation.
+///
+/// See `Camera::frustum_planes` for the conventions used.
+pub fn frustum_planes_from_transformation(transformation: &Matrix4<f32>) -> [Vector4<f32>; 6] {
+    let row = |i: usize| transformation.row(i).transpose();
+    let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+    let mut planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2];
+
+    for plane in planes.iter_mut() {
+        let norm = plane.xyz().norm();
+
+        if norm != 0.0 {
+            *plane /= norm;
+        }
+    }
+
+    planes
+}
+
+#[cfg(test)]
+mod test {
+    use super::{depth_reversal, inverse_depth_reversal, set_reversed_z};
+    use na::{Matrix4, Vector4};
+
+    #[test]
+    fn depth_reversal_is_inverted() {
+        for &(reversed, zero_to_one) in &[(false, false), (true, false), (true, true)] {
+            set_reversed_z(reversed, zero_to_one);
+            let m = inverse_depth_reversal() * depth_reversal();
+            assert!((m - Matrix4::identity()).norm() < 1.0e-6);
+        }
+
+        set_reversed_z(false, false);
+    }
+
+    #[test]
+    fn zero_to_one_depth_reversal_maps_near_to_one_and_far_to_zero() {
+        set_reversed_z(true, true);
+        let near = depth_reversal() * Vector4::new(0.0, 0.0, -2.0, 2.0);
+        let far = depth_reversal() * Vector4::new(0.0, 0.0, 2.0, 2.0);
+        set_reversed_z(false, false);
+
+        assert_eq!(near.z / near.w, 1.0);
+        assert_eq!(far.z / far.w, 0.0);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::camera::{ArcBall, FirstPerson};
+use na::{Isometry3, Point3, Translation3, UnitQuaternion, Vector3};
+use std::time::Duration;
+
+/// A keyframe of a `CameraPath`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraKeyframe {
+    /// The position of the camera.
+    pub eye: Point3<f32>,
+    /// The point the camera is looking at.
+    pub at: Point3<f32>,
+    /// The time of this keyframe, in seconds.
+    pub time: f32,
+}
+
+/// An animation of a camera interpolating between keyframes.
+///
+/// The positions of the camera are interpolated with a Catmull-Rom spline, and its orientations
+/// with a spherical linear interpolation. Before the first keyframe and after the last one, the
+/// camera holds the pose of the nearest keyframe, unless the path is looping, in which case the
+/// animation restarts from the first keyframe; the last keyframe should then be the same as the
+/// first one for the loop to be seamless.
+///
+/// The path is typically advanced by `window.frame_delta()` at each frame, and then applied to
+/// the camera used to render the scene:
+///
+/// ```no_run
+/// # use kiss3d::camera::{ArcBall, CameraPath};
+/// # use kiss3d::window::Window;
+/// # use nalgebra::Point3;
+/// let mut window = Window::new("Camera path");
+/// let mut camera = ArcBall::new(Point3::new(0.0, 0.0, 10.0), Point3::origin());
+/// let mut path = CameraPath::new();
+/// path.add_keyframe(Point3::new(0.0, 0.0, 10.0), Point3::origin(), 0.0);
+/// path.add_keyframe(Point3::new(10.0, 5.0, 0.0), Point3::origin(), 4.0);
+///
+/// while window.render_with_camera(&mut camera) {
+///     path.advance(window.frame_delta());
+///     path.apply_to_arc_ball(&mut camera);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+    time: f32,
+    looping: bool,
+}
+
+impl CameraPath {
+    /// Creates a non-looping path without any keyframe.
+    pub fn new() -> CameraPath {
+        CameraPath {
+            keyframes: Vec::new(),
+            time: 0.0,
+            looping: false,
+        }
+    }
+
+    /// Adds a keyframe to this path.
+    ///
+    /// The keyframes are kept sorted by time, so they can be added in any order.
+    pub fn add_keyframe(&mut self, eye: Point3<f32>, at: Point3<f32>, time: f32) {
+        let i = self.keyframes.partition_point(|k| k.time <= time);
+        self.keyframes.insert(i, CameraKeyframe { eye, at, time });
+    }
+
+    /// The keyframes of this path, sorted by time.
+    #[inline]
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    /// Removes all the keyframes of this path.
+    pub fn clear(&mut self) {
+        self.keyframes.clear()
+    }
+
+    /// Whether this path restarts from its first keyframe after its last one.
+    #[inline]
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Sets whether this path restarts from its first keyframe after its last one.
+    #[inline]
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping
+    }
+
+    /// The time between the first and last keyframes, in seconds.
+    pub fn duration(&self) -> f32 {
+        match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    /// The current time of this path, in seconds.
+    #[inline]
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Sets the current time of this path, in seconds.
+    #[inline]
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time
+    }
+
+    /// Advances the current time of this path.
+    #[inline]
+    pub fn advance(&mut self, delta: Duration) {
+        self.time += delta.as_secs_f32()
+    }
+
+    /// Whether the current time is past the last keyframe of this non-looping path.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.keyframes.last().is_none_or(|k| self.time >= k.time)
+    }
+
+    /// The interpolated position of the camera and point it looks at, at the given time.
+    ///
+    /// Returns `None` if this path has no keyframe.
+    pub fn eye_and_at(&self, time: f32) -> Option<(Point3<f32>, Point3<f32>)> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+
+        if self.keyframes.len() == 1 || last.time <= first.time {
+            return Some((first.eye, first.at));
+        }
+
+        let time = if self.looping {
+            first.time + (time - first.time).rem_euclid(last.time - first.time)
+        } else {
+            time.max(first.time).min(last.time)
+        };
+
+        // The segment containing `time`.
+        let i = self
+            .keyframes
+            .partition_point(|k| k.time <= time)
+            .max(1)
+            .min(self.keyframes.len() - 1)
+            - 1;
+        let (k1, k2) = (&self.keyframes[i], &self.keyframes[i + 1]);
+        let dt = k2.time - k1.time;
+
+        if dt <= 0.0 {
+            return Some((k2.eye, k2.at));
+        }
+
+        let s = (time - k1.time) / dt;
+
+        // The position follows a Catmull-Rom spline...
+        let eye = hermite(
+            &k1.eye.coords,
+            &(self.tangent(i) * dt),
+            &k2.eye.coords,
+            &(self.tangent(i + 1) * dt),
+            s,
+        );
+
+        // ... while the orientation and distance to the target are interpolated separately so the
+        // camera turns smoothly.
+        let (r1, d1) = orientation_and_distance(k1);
+        let (r2, d2) = orientation_and_distance(k2);
+        let rotation = r1.try_slerp(&r2, s, 1.0e-6).unwrap_or(r1);
+        let distance = d1 + (d2 - d1) * s;
+
+        Some((
+            Point3::from(eye),
+            Point3::from(eye + rotation * Vector3::z() * distance),
+        ))
+    }
+
+    /// The interpolated transformation of the camera at the given time.
+    ///
+    /// This maps the local frame of the camera, looking toward its `z` axis, to world-space.
+    /// Returns `None` if this path has no keyframe.
+    pub fn pose(&self, time: f32) -> Option<Isometry3<f32>> {
+        let (eye, at) = self.eye_and_at(time)?;
+        let rotation = look_rotation(&eye, &at);
+
+        Some(Isometry3::from_parts(
+            Translation3::from(eye.coords),
+            rotation,
+        ))
+    }
+
+    /// Makes `camera` look from the interpolated eye toward the interpolated target at the
+    /// current time of this path.
+    ///
+    /// This does nothing if this path has no keyframe.
+    pub fn apply_to_arc_ball(&self, camera: &mut ArcBall) {
+        if let Some((eye, at)) = self.eye_and_at(self.time) {
+            camera.look_at(eye, at)
+        }
+    }
+
+    /// Makes `camera` look from the interpolated eye toward the interpolated target at the
+    /// current time of this path.
+    ///
+    /// This does nothing if this path has no keyframe.
+    pub fn apply_to_first_person(&self, camera: &mut FirstPerson) {
+        if let Some((eye, at)) = self.eye_and_at(self.time) {
+            camera.look_at(eye, at)
+        }
+    }
+
+    // The velocity of the eye at the `i`-th keyframe, estimated from its neighbors.
+    fn tangent(&self, i: usize) -> Vector3<f32> {
+        let n = self.keyframes.len();
+        let duration = self.duration();
+        let key = |j: usize, shift: f32| {
+            let k = &self.keyframes[j];
+            (k.eye.coords, k.time + shift)
+        };
+
+        // With looping paths, the last keyframe is considered to be the same as the first one.
+        let (prev, next) = match (i, self.looping && n > 2) {
+            (0, true) => (key(n - 2, -duration), key(1, 0.0)),
+            (i, true) if i == n - 1 => (key(n - 2, 0.0), key(1, duration)),
+            (0, false) => (key(0, 0.0), key(1, 0.0)),
+            (i, false) if i == n - 1 => (key(n - 2, 0.0), key(n - 1, 0.0)),
+            (i, _) => (key(i - 1, 0.0), key(i + 1, 0.0)),
+        };
+
+        if next.1 > prev.1 {
+            (next.0 - prev.0) / (next.1 - prev.1)
+        } else {
+            Vector3::zeros()
+        }
+    }
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        CameraPath::new()
+    }
+}
+
+// The rotation mapping the `z` axis to the direction from `eye` to `at`.
+fn look_rotation(eye: &Point3<f32>, at: &Point3<f32>) -> UnitQuaternion<f32> {
+    let dir = at - eye;
+
+    if dir.norm_squared() == 0.0 {
+        return UnitQuaternion::identity();
+    }
+
+    // Use another up vector when looking straight up or down.
+    let up = if dir.cross(&Vector3::y()).norm_squared() > 1.0e-12 * dir.norm_squared() {
+        Vector3::y()
+    } else {
+        Vector3::z()
+    };
+
+    UnitQuaternion::face_towards(&dir, &up)
+}
+
+fn orientation_and_distance(k: &CameraKeyframe) -> (UnitQuaternion<f32>, f32) {
+    (look_rotation(&k.eye, &k.at), (k.at - k.eye).norm())
+}
+
+// Evaluates the cubic Hermite curve from `p1` to `p2` with the tangents `m1` and `m2`.
+fn hermite(
+    p1: &Vector3<f32>,
+    m1: &Vector3<f32>,
+    p2: &Vector3<f32>,
+    m2: &Vector3<f32>,
+    s: f32,
+) -> Vector3<f32> {
+    let s2 = s * s;
+    let s3 = s2 * s;
+
+    p1 * (2.0 * s3 - 3.0 * s2 + 1.0)
+        + m1 * (s3 - 2.0 * s2 + s)
+        + p2 * (-2.0 * s3 + 3.0 * s2)
+        + m2 * (s3 - s2)
+}
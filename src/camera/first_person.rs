@@ -1,14 +1,106 @@
+use crate::camera::camera;
 use crate::camera::Camera;
-use crate::event::{Action, Key, MouseButton, WindowEvent};
+use crate::event::{Action, GamepadAxis, Key, MouseButton, WindowEvent};
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
 use na::{
-    self, Isometry3, Matrix4, Perspective3, Point3, Translation3, Unit, UnitQuaternion, Vector2,
-    Vector3,
+    self, Isometry3, Matrix4, Orthographic3, Perspective3, Point3, Translation3, Unit,
+    UnitQuaternion, Vector2, Vector3,
 };
 use num::Zero;
 use std::f32;
 
+// The mouse displacement per frame, in pixels, equivalent to a fully tilted right stick.
+const GAMEPAD_LOOK_SPEED: f32 = 8.0;
+
+/// The projection of a camera.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum ProjectionMode {
+    /// A perspective projection.
+    Perspective {
+        /// The vertical field of view, in radians.
+        fovy: f32,
+    },
+    /// An orthographic projection, where the apparent size of objects does not depend on their
+    /// distance to the camera.
+    Orthographic {
+        /// The height of the area seen by the camera, in world units.
+        scale: f32,
+    },
+}
+
+/// The pose and projection of a `FirstPerson` camera.
+///
+/// This is obtained with `FirstPerson::state` and restored with `FirstPerson::set_state`. The
+/// aspect ratio of the projection is not part of the state since it follows the size of the
+/// window.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct FirstPersonState {
+    /// The position of the camera.
+    pub eye: Point3<f32>,
+    /// The rotation of the camera around the up axis, in radians.
+    pub yaw: f32,
+    /// The angle between the up axis and the view direction, in radians.
+    pub pitch: f32,
+    /// The up axis of the camera.
+    pub up: Vector3<f32>,
+    /// The projection of the camera.
+    pub projection_mode: ProjectionMode,
+    /// The distance to the near clipping plane.
+    pub znear: f32,
+    /// The distance to the far clipping plane.
+    pub zfar: f32,
+    /// Whether the far clipping plane of a perspective projection is moved to infinity, ignoring
+    /// `zfar`.
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    pub infinite_far: bool,
+    /// The projection matrix used instead of the one derived from the projection mode, if any.
+    pub custom_projection: Option<Matrix4<f32>>,
+}
+
+impl FirstPersonState {
+    /// The point at unit distance the camera is looking at.
+    pub fn at(&self) -> Point3<f32> {
+        let coord_system = CoordSystemRh::from_up_axis(Unit::new_normalize(self.up));
+        first_person_at(&coord_system, &self.eye, self.yaw, self.pitch)
+    }
+}
+
+/// The keys moving a `FirstPerson` camera.
+///
+/// Use `None` to disable the movement in a direction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FirstPersonKeys {
+    /// The key moving the camera toward the direction it is looking at.
+    pub forward: Option<Key>,
+    /// The key moving the camera away from the direction it is looking at.
+    pub backward: Option<Key>,
+    /// The key moving the camera to its left.
+    pub left: Option<Key>,
+    /// The key moving the camera to its right.
+    pub right: Option<Key>,
+    /// The key moving the camera along its up axis.
+    pub up: Option<Key>,
+    /// The key moving the camera opposite to its up axis.
+    pub down: Option<Key>,
+}
+
+impl Default for FirstPersonKeys {
+    /// The arrow keys, without any vertical movement.
+    fn default() -> FirstPersonKeys {
+        FirstPersonKeys {
+            forward: Some(Key::Up),
+            backward: Some(Key::Down),
+            left: Some(Key::Left),
+            right: Some(Key::Right),
+            up: None,
+            down: None,
+        }
+    }
+}
+
 /// First-person camera mode.
 ///
 ///   * Left button press + drag - look around
@@ -26,12 +118,16 @@ pub struct FirstPerson {
     move_step: f32,
     rotate_button: Option<MouseButton>,
     drag_button: Option<MouseButton>,
-    up_key: Option<Key>,
-    down_key: Option<Key>,
-    left_key: Option<Key>,
-    right_key: Option<Key>,
+    keys: FirstPersonKeys,
+    gamepad_enabled: bool,
+    // The last positions of the left and right sticks of the gamepads.
+    left_stick: Vector2<f32>,
+    right_stick: Vector2<f32>,
 
     projection: Perspective3<f32>,
+    infinite_far: bool,
+    custom_projection: Option<Matrix4<f32>>,
+    projection_mode: ProjectionMode,
     proj: Matrix4<f32>,
     view: Matrix4<f32>,
     proj_view: Matrix4<f32>,
@@ -63,11 +159,14 @@ impl FirstPerson {
             move_step: 0.5,
             rotate_button: Some(MouseButton::Button1),
             drag_button: Some(MouseButton::Button2),
-            up_key: Some(Key::Up),
-            down_key: Some(Key::Down),
-            left_key: Some(Key::Left),
-            right_key: Some(Key::Right),
+            keys: FirstPersonKeys::default(),
+            gamepad_enabled: false,
+            left_stick: na::zero(),
+            right_stick: na::zero(),
             projection: Perspective3::new(800.0 / 600.0, fov, znear, zfar),
+            infinite_far: false,
+            custom_projection: None,
+            projection_mode: ProjectionMode::Perspective { fovy: fov },
             proj: na::zero(),
             view: na::zero(),
             proj_view: na::zero(),
@@ -105,6 +204,15 @@ impl FirstPerson {
         self.yaw_step = step;
     }
 
+    /// Sets both the yaw and pitch increments per mouse movement.
+    ///
+    /// The default value is 0.005.
+    #[inline]
+    pub fn set_rotate_step(&mut self, step: f32) {
+        self.yaw_step = step;
+        self.pitch_step = step;
+    }
+
     /// Gets the translational increment per arrow press.
     #[inline]
     pub fn move_step(&self) -> f32 {
@@ -123,6 +231,104 @@ impl FirstPerson {
         self.yaw_step
     }
 
+    /// The projection of this camera.
+    #[inline]
+    pub fn projection_mode(&self) -> ProjectionMode {
+        self.projection_mode
+    }
+
+    /// Sets the projection of this camera.
+    ///
+    /// The default is a perspective projection. The clipping planes are kept when switching to
+    /// an orthographic projection, so objects behind the camera are not visible either. Moving
+    /// the camera forward or backward does not change the apparent size of objects with an
+    /// orthographic projection, change its `scale` instead.
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) {
+        if let ProjectionMode::Perspective { fovy } = mode {
+            self.projection.set_fovy(fovy);
+        }
+
+        self.projection_mode = mode;
+        self.update_projviews();
+    }
+
+    /// Whether the far clipping plane of the perspective projection of this camera is at
+    /// infinity.
+    pub fn infinite_far(&self) -> bool {
+        self.infinite_far
+    }
+
+    /// Sets whether the far clipping plane of the perspective projection of this camera is moved
+    /// to infinity (default at `false`).
+    ///
+    /// Objects are then never clipped however far they are, at the cost of a slightly lower depth
+    /// precision. `clip_planes` then returns `f32::INFINITY` as `zfar`. This has no effect on
+    /// orthographic projections, nor on a projection set with `set_projection`.
+    pub fn set_infinite_far(&mut self, infinite_far: bool) {
+        self.infinite_far = infinite_far;
+        self.update_projviews();
+    }
+
+    /// The projection matrix used instead of the one derived from the field of view, if any.
+    #[inline]
+    pub fn custom_projection(&self) -> Option<&Matrix4<f32>> {
+        self.custom_projection.as_ref()
+    }
+
+    /// Sets the projection matrix of this camera, e.g., for oblique near-plane clipping.
+    ///
+    /// The matrix is used verbatim instead of the projection derived from the field of view and the projection mode,
+    /// until `unset_projection` is called. The view transformation is still computed from the
+    /// position and orientation of this camera. Note that `clip_planes` still returns the
+    /// clipping planes given at the creation of this camera, which may not match `projection`.
+    pub fn set_projection(&mut self, projection: Matrix4<f32>) {
+        self.custom_projection = Some(projection);
+        self.update_projviews();
+    }
+
+    /// Reverts to the projection derived from the field of view and the projection mode.
+    pub fn unset_projection(&mut self) {
+        self.custom_projection = None;
+        self.update_projviews();
+    }
+
+    /// The pose and projection of this camera.
+    pub fn state(&self) -> FirstPersonState {
+        FirstPersonState {
+            eye: self.eye,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            up: self.coord_system.up_axis.into_inner(),
+            projection_mode: self.projection_mode,
+            znear: self.projection.znear(),
+            zfar: self.projection.zfar(),
+            infinite_far: self.infinite_far,
+            custom_projection: self.custom_projection,
+        }
+    }
+
+    /// Restores the pose and projection of this camera.
+    ///
+    /// This reproduces the view and projection matrices of the camera the state was taken from,
+    /// provided they have the same aspect ratio.
+    pub fn set_state(&mut self, state: &FirstPersonState) {
+        let fovy = match state.projection_mode {
+            ProjectionMode::Perspective { fovy } => fovy,
+            ProjectionMode::Orthographic { .. } => self.projection.fovy(),
+        };
+
+        self.coord_system = CoordSystemRh::from_up_axis(Unit::new_normalize(state.up));
+        self.eye = state.eye;
+        self.yaw = state.yaw;
+        self.pitch = state.pitch;
+        self.projection =
+            Perspective3::new(self.projection.aspect(), fovy, state.znear, state.zfar);
+        self.projection_mode = state.projection_mode;
+        self.infinite_far = state.infinite_far;
+        self.custom_projection = state.custom_projection;
+        self.update_projviews();
+    }
+
     /// Changes the orientation and position of the camera to look at the specified point.
     pub fn look_at(&mut self, eye: Point3<f32>, at: Point3<f32>) {
         let dist = (eye - at).norm();
@@ -140,11 +346,7 @@ impl FirstPerson {
 
     /// The point the camera is looking at.
     pub fn at(&self) -> Point3<f32> {
-        let view_eye = self.coord_system.rotation_to_y_up * self.eye;
-        let ax = view_eye.x + self.yaw.cos() * self.pitch.sin();
-        let ay = view_eye.y + self.pitch.cos();
-        let az = view_eye.z + self.yaw.sin() * self.pitch.sin();
-        self.coord_system.rotation_to_y_up.inverse() * Point3::new(ax, ay, az)
+        first_person_at(&self.coord_system, &self.eye, self.yaw, self.pitch)
     }
 
     fn update_restrictions(&mut self) {
@@ -180,56 +382,88 @@ impl FirstPerson {
         self.drag_button = new_button;
     }
 
+    /// The keys moving this camera.
+    #[inline]
+    pub fn keys(&self) -> FirstPersonKeys {
+        self.keys
+    }
+
+    /// Sets the keys moving this camera.
+    #[inline]
+    pub fn rebind_keys(&mut self, keys: FirstPersonKeys) {
+        self.keys = keys;
+    }
+
+    /// Whether this camera is controlled by the gamepads.
+    #[inline]
+    pub fn is_gamepad_enabled(&self) -> bool {
+        self.gamepad_enabled
+    }
+
+    /// Enables or disables the control of this camera by the gamepads. Disabled by default.
+    ///
+    /// When enabled, the left stick moves the camera by up to `move_step` per frame, and the right
+    /// stick makes it look around. This requires the `gamepad` feature.
+    pub fn set_gamepad_enabled(&mut self, enabled: bool) {
+        self.gamepad_enabled = enabled;
+        self.left_stick = na::zero();
+        self.right_stick = na::zero();
+    }
+
     /// The movement button for up.
     pub fn up_key(&self) -> Option<Key> {
-        self.up_key
+        self.keys.forward
     }
 
     /// The movement button for down.
     pub fn down_key(&self) -> Option<Key> {
-        self.down_key
+        self.keys.backward
     }
 
     /// The movement button for left.
     pub fn left_key(&self) -> Option<Key> {
-        self.left_key
+        self.keys.left
     }
 
     /// The movement button for right.
     pub fn right_key(&self) -> Option<Key> {
-        self.right_key
+        self.keys.right
     }
 
     /// Set the movement button for up.
     /// Use None to disable movement in this direction.
     pub fn rebind_up_key(&mut self, new_key: Option<Key>) {
-        self.up_key = new_key;
+        self.keys.forward = new_key;
     }
 
     /// Set the movement button for down.
     /// Use None to disable movement in this direction.
     pub fn rebind_down_key(&mut self, new_key: Option<Key>) {
-        self.down_key = new_key;
+        self.keys.backward = new_key;
     }
 
     /// Set the movement button for left.
     /// Use None to disable movement in this direction.
     pub fn rebind_left_key(&mut self, new_key: Option<Key>) {
-        self.left_key = new_key;
+        self.keys.left = new_key;
     }
 
     /// Set the movement button for right.
     /// Use None to disable movement in this direction.
     pub fn rebind_right_key(&mut self, new_key: Option<Key>) {
-        self.right_key = new_key;
+        self.keys.right = new_key;
     }
 
-    /// Disable the movement buttons for up, down, left and right.
+    /// Disable all the movement keys.
     pub fn unbind_movement_keys(&mut self) {
-        self.up_key = None;
-        self.down_key = None;
-        self.left_key = None;
-        self.right_key = None;
+        self.keys = FirstPersonKeys {
+            forward: None,
+            backward: None,
+            left: None,
+            right: None,
+            up: None,
+            down: None,
+        };
     }
 
     #[doc(hidden)]
@@ -265,7 +499,26 @@ impl FirstPerson {
 
     fn update_projviews(&mut self) {
         self.view = self.view_transform().to_homogeneous();
-        self.proj = *self.projection.as_matrix();
+        self.proj = match (self.custom_projection, self.projection_mode) {
+            (Some(projection), _) => projection,
+            (None, ProjectionMode::Perspective { .. }) => {
+                camera::perspective_matrix(&self.projection, self.infinite_far)
+            }
+            (None, ProjectionMode::Orthographic { scale }) => {
+                let half_height = scale / 2.0;
+                let half_width = half_height * self.projection.aspect();
+
+                *Orthographic3::new(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.projection.znear(),
+                    self.projection.zfar(),
+                )
+                .as_matrix()
+            }
+        };
         self.proj_view = self.proj * self.view;
         let _ = self
             .proj_view
@@ -362,7 +615,12 @@ impl FirstPerson {
 
 impl Camera for FirstPerson {
     fn clip_planes(&self) -> (f32, f32) {
-        (self.projection.znear(), self.projection.zfar())
+        match self.projection_mode {
+            ProjectionMode::Perspective { .. } if self.infinite_far => {
+                (self.projection.znear(), f32::INFINITY)
+            }
+            _ => (self.projection.znear(), self.projection.zfar()),
+        }
     }
 
     /// The camera view transformation (i-e transformation without projection).
@@ -396,6 +654,17 @@ impl Camera for FirstPerson {
                 self.projection.set_aspect(w as f32 / h as f32);
                 self.update_projviews();
             }
+            WindowEvent::GamepadAxis(_, axis, value) if self.gamepad_enabled => match axis {
+                GamepadAxis::LeftStickX => self.left_stick.x = value as f32,
+                GamepadAxis::LeftStickY => self.left_stick.y = value as f32,
+                GamepadAxis::RightStickX => self.right_stick.x = value as f32,
+                GamepadAxis::RightStickY => self.right_stick.y = value as f32,
+                _ => {}
+            },
+            WindowEvent::GamepadDisconnected(_) => {
+                self.left_stick = na::zero();
+                self.right_stick = na::zero();
+            }
             _ => {}
         }
     }
@@ -405,11 +674,11 @@ impl Camera for FirstPerson {
     }
 
     fn transformation(&self) -> Matrix4<f32> {
-        self.proj_view
+        camera::depth_reversal() * self.proj_view
     }
 
     fn inverse_transformation(&self) -> Matrix4<f32> {
-        self.inverse_proj_view
+        self.inverse_proj_view * camera::inverse_depth_reversal()
     }
 
     #[inline]
@@ -419,16 +688,39 @@ impl Camera for FirstPerson {
         proj: &mut ShaderUniform<Matrix4<f32>>,
         view: &mut ShaderUniform<Matrix4<f32>>,
     ) {
-        proj.upload(&self.proj);
+        proj.upload(&(camera::depth_reversal() * self.proj));
         view.upload(&self.view);
     }
 
     fn update(&mut self, canvas: &Canvas) {
-        let up = check_optional_key_state(canvas, self.up_key, Action::Press);
-        let down = check_optional_key_state(canvas, self.down_key, Action::Press);
-        let right = check_optional_key_state(canvas, self.right_key, Action::Press);
-        let left = check_optional_key_state(canvas, self.left_key, Action::Press);
-        let dir = self.move_dir(up, down, right, left);
+        let pressed = |key| check_optional_key_state(canvas, key, Action::Press);
+        let forward = pressed(self.keys.forward);
+        let backward = pressed(self.keys.backward);
+        let right = pressed(self.keys.right);
+        let left = pressed(self.keys.left);
+        let mut dir = self.move_dir(forward, backward, right, left);
+
+        let vertical = pressed(self.keys.up) as i32 - pressed(self.keys.down) as i32;
+
+        if vertical != 0 {
+            dir = (dir + self.coord_system.up_axis.into_inner() * vertical as f32).normalize();
+        }
+
+        if self.gamepad_enabled {
+            let t = self.observer_frame();
+            let stick_dir =
+                t * Vector3::z() * self.left_stick.y - t * Vector3::x() * self.left_stick.x;
+
+            // The stick is added to the keys so the speed is not normalized.
+            dir += stick_dir;
+
+            if !self.right_stick.is_zero() {
+                // The stick pointing up makes the camera look up.
+                let dpos =
+                    Vector2::new(self.right_stick.x, -self.right_stick.y) * GAMEPAD_LOOK_SPEED;
+                self.handle_left_button_displacement(&dpos);
+            }
+        }
 
         let move_amount = dir * self.move_step;
         self.translate_mut(&Translation3::from(move_amount));
@@ -443,6 +735,19 @@ fn check_optional_key_state(canvas: &Canvas, key: Option<Key>, key_state: Action
     }
 }
 
+fn first_person_at(
+    coord_system: &CoordSystemRh,
+    eye: &Point3<f32>,
+    yaw: f32,
+    pitch: f32,
+) -> Point3<f32> {
+    let view_eye = coord_system.rotation_to_y_up * eye;
+    let ax = view_eye.x + yaw.cos() * pitch.sin();
+    let ay = view_eye.y + pitch.cos();
+    let az = view_eye.z + yaw.sin() * pitch.sin();
+    coord_system.rotation_to_y_up.inverse() * Point3::new(ax, ay, az)
+}
+
 #[derive(Clone, Copy, Debug)]
 struct CoordSystemRh {
     up_axis: Unit<Vector3<f32>>,
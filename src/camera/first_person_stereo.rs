@@ -1,7 +1,8 @@
 use std::f32;
 
-use na::{self, Isometry3, Matrix4, Perspective3, Point2, Point3, Vector2, Vector3};
+use na::{self, Isometry3, Matrix4, Perspective3, Point2, Point3, Vector2, Vector3, Vector4};
 
+use crate::camera::camera;
 use crate::camera::Camera;
 use crate::context::Context;
 use crate::event::{Action, Key, MouseButton, WindowEvent};
@@ -41,6 +42,7 @@ pub struct FirstPersonStereo {
 
     /// Low level datas
     projection: Perspective3<f32>,
+    infinite_far: bool,
     view_left: Matrix4<f32>,
     view_right: Matrix4<f32>,
     proj: Matrix4<f32>,
@@ -76,6 +78,7 @@ impl FirstPersonStereo {
             pitch_step: 0.005,
             move_step: 0.5,
             projection: Perspective3::new(800.0 / 600.0, fov, znear, zfar),
+            infinite_far: false,
             proj_view: na::zero(),
             inverse_proj_view: na::zero(),
             last_cursor_pos: Point2::origin(),
@@ -169,9 +172,9 @@ impl FirstPersonStereo {
     }
 
     fn update_projviews(&mut self) {
-        self.proj_view = *self.projection.as_matrix() * self.view_transform().to_homogeneous();
+        self.proj = camera::perspective_matrix(&self.projection, self.infinite_far);
+        self.proj_view = self.proj * self.view_transform().to_homogeneous();
         self.inverse_proj_view = self.proj_view.try_inverse().unwrap();
-        self.proj = *self.projection.as_matrix();
         self.view_left = self.view_transform_left().to_homogeneous();
         self.view_right = self.view_transform_right().to_homogeneous();
     }
@@ -194,6 +197,21 @@ impl FirstPersonStereo {
         Isometry3::look_at_rh(&self.eye_right, &self.at(), &Vector3::y())
     }
 
+    /// Whether the far clipping plane of this camera is at infinity.
+    pub fn infinite_far(&self) -> bool {
+        self.infinite_far
+    }
+
+    /// Sets whether the far clipping plane of this camera is moved to infinity (default at
+    /// `false`).
+    ///
+    /// Objects are then never clipped however far they are, at the cost of a slightly lower depth
+    /// precision. `clip_planes` then returns `f32::INFINITY` as `zfar`.
+    pub fn set_infinite_far(&mut self, infinite_far: bool) {
+        self.infinite_far = infinite_far;
+        self.update_projviews();
+    }
+
     /// return Inter Pupilary Distance
     pub fn ipd(&self) -> f32 {
         self.ipd
@@ -211,7 +229,11 @@ impl FirstPersonStereo {
 
 impl Camera for FirstPersonStereo {
     fn clip_planes(&self) -> (f32, f32) {
-        (self.projection.znear(), self.projection.zfar())
+        if self.infinite_far {
+            (self.projection.znear(), f32::INFINITY)
+        } else {
+            (self.projection.znear(), self.projection.zfar())
+        }
     }
 
     /// The imaginary middle eye camera view transformation (i-e transformation without projection).
@@ -250,11 +272,24 @@ impl Camera for FirstPersonStereo {
     }
 
     fn transformation(&self) -> Matrix4<f32> {
-        self.proj_view
+        camera::depth_reversal() * self.proj_view
     }
 
     fn inverse_transformation(&self) -> Matrix4<f32> {
-        self.inverse_proj_view
+        self.inverse_proj_view * camera::inverse_depth_reversal()
+    }
+
+    fn frustum_planes(&self) -> [Vector4<f32>; 6] {
+        // The frustum of each eye is the central frustum shifted by half the ipd, so we push the
+        // central planes outward enough to enclose both of them.
+        let shift = self.eye_right - self.eye;
+        let mut planes = camera::frustum_planes_from_transformation(&self.proj_view);
+
+        for plane in planes.iter_mut() {
+            plane.w += plane.xyz().dot(&shift).abs();
+        }
+
+        planes
     }
 
     fn update(&mut self, canvas: &Canvas) {
@@ -290,7 +325,7 @@ impl Camera for FirstPersonStereo {
         view: &mut ShaderUniform<Matrix4<f32>>,
     ) {
         view.upload(&self.view_eye(pass));
-        proj.upload(&self.proj);
+        proj.upload(&(camera::depth_reversal() * self.proj));
     }
 
     fn num_passes(&self) -> usize {
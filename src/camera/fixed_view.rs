@@ -1,3 +1,4 @@
+use crate::camera::camera;
 use crate::camera::Camera;
 use crate::event::WindowEvent;
 use crate::resource::ShaderUniform;
@@ -9,6 +10,7 @@ use std::f32;
 #[derive(Clone, Debug)]
 pub struct FixedView {
     projection: Perspective3<f32>,
+    infinite_far: bool,
     proj: Matrix4<f32>,
     inv_proj: Matrix4<f32>,
 }
@@ -23,13 +25,29 @@ impl FixedView {
     pub fn new_with_frustrum(fov: f32, znear: f32, zfar: f32) -> FixedView {
         FixedView {
             projection: Perspective3::new(800.0 / 600.0, fov, znear, zfar),
+            infinite_far: false,
             proj: na::one(),
             inv_proj: na::one(),
         }
     }
 
+    /// Whether the far clipping plane of this camera is at infinity.
+    pub fn infinite_far(&self) -> bool {
+        self.infinite_far
+    }
+
+    /// Sets whether the far clipping plane of this camera is moved to infinity (default at
+    /// `false`).
+    ///
+    /// Objects are then never clipped however far they are, at the cost of a slightly lower depth
+    /// precision. `clip_planes` then returns `f32::INFINITY` as `zfar`.
+    pub fn set_infinite_far(&mut self, infinite_far: bool) {
+        self.infinite_far = infinite_far;
+        self.update_projviews();
+    }
+
     fn update_projviews(&mut self) {
-        self.proj = *self.projection.as_matrix();
+        self.proj = camera::perspective_matrix(&self.projection, self.infinite_far);
         let _ = self
             .proj
             .try_inverse()
@@ -39,7 +57,11 @@ impl FixedView {
 
 impl Camera for FixedView {
     fn clip_planes(&self) -> (f32, f32) {
-        (self.projection.znear(), self.projection.zfar())
+        if self.infinite_far {
+            (self.projection.znear(), f32::INFINITY)
+        } else {
+            (self.projection.znear(), self.projection.zfar())
+        }
     }
 
     fn view_transform(&self) -> Isometry3<f32> {
@@ -68,16 +90,16 @@ impl Camera for FixedView {
         view: &mut ShaderUniform<Matrix4<f32>>,
     ) {
         let view_mat = Matrix4::identity();
-        proj.upload(&self.proj);
+        proj.upload(&(camera::depth_reversal() * self.proj));
         view.upload(&view_mat);
     }
 
     fn transformation(&self) -> Matrix4<f32> {
-        self.proj
+        camera::depth_reversal() * self.proj
     }
 
     fn inverse_transformation(&self) -> Matrix4<f32> {
-        self.inv_proj
+        self.inv_proj * camera::inverse_depth_reversal()
     }
 
     fn update(&mut self, _: &Canvas) {}
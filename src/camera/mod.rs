@@ -1,14 +1,18 @@
 //! Camera trait with some common implementations.
 
-pub use self::arc_ball::ArcBall;
-pub use self::camera::Camera;
-pub use self::first_person::FirstPerson;
+pub use self::arc_ball::{ArcBall, ArcBallState};
+pub use self::camera::{depth_reversal, inverse_depth_reversal, reversed_z, Camera};
+pub use self::camera_path::{CameraKeyframe, CameraPath};
+pub use self::first_person::{FirstPerson, FirstPersonKeys, FirstPersonState, ProjectionMode};
 pub use self::first_person_stereo::FirstPersonStereo;
 pub use self::fixed_view::FixedView;
+pub use self::stereo::{StereoCamera, StereoMode};
 
 mod arc_ball;
 #[doc(hidden)]
 pub mod camera;
+mod camera_path;
 mod first_person;
 mod first_person_stereo;
 mod fixed_view;
+mod stereo;
@@ -0,0 +1,249 @@
+use na::{Isometry3, Matrix4, Point3, Translation3, Vector3, Vector4};
+
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::event::WindowEvent;
+use crate::resource::ShaderUniform;
+use crate::window::Canvas;
+
+#[path = "../error.rs"]
+mod error;
+
+/// The way a `StereoCamera` renders the two eyes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StereoMode {
+    /// The scene is rendered once, from the point of view of the wrapped camera.
+    Off,
+    /// The left eye is rendered into the red channel and the right eye into the green and blue
+    /// channels, for red-cyan glasses.
+    Anaglyph {
+        /// The distance between the two eyes, in world units.
+        eye_separation: f32,
+    },
+    /// The left eye is rendered on the left half of the window and the right eye on its right
+    /// half.
+    SideBySide {
+        /// The distance between the two eyes, in world units.
+        eye_separation: f32,
+    },
+}
+
+impl StereoMode {
+    fn eye_separation(&self) -> f32 {
+        match *self {
+            StereoMode::Off => 0.0,
+            StereoMode::Anaglyph { eye_separation } | StereoMode::SideBySide { eye_separation } => {
+                eye_separation
+            }
+        }
+    }
+}
+
+/// A camera rendering the scene seen by another camera from two eyes.
+///
+/// The eyes are offset from the position of the wrapped camera along its horizontal axis and
+/// look in the same direction. The events and updates are forwarded to the wrapped camera, which
+/// must render the scene in a single pass.
+///
+/// ```no_run
+/// # use kiss3d::camera::{ArcBall, StereoCamera, StereoMode};
+/// # use kiss3d::window::Window;
+/// # use nalgebra::Point3;
+/// let mut window = Window::new("Stereo");
+/// let arc_ball = ArcBall::new(Point3::new(0.0, 0.0, 10.0), Point3::origin());
+/// let mut camera = StereoCamera::new(arc_ball, StereoMode::Anaglyph { eye_separation: 0.3 });
+///
+/// while window.render_with_camera(&mut camera) {}
+/// ```
+#[derive(Debug)]
+pub struct StereoCamera<C> {
+    camera: C,
+    mode: StereoMode,
+    proj: Matrix4<f32>,
+    views: [Matrix4<f32>; 2],
+}
+
+impl<C: Camera> StereoCamera<C> {
+    /// Wraps `camera` to render the scene with the given stereo mode.
+    pub fn new(camera: C, mode: StereoMode) -> StereoCamera<C> {
+        let mut res = StereoCamera {
+            camera,
+            mode,
+            proj: na::zero(),
+            views: [na::zero(); 2],
+        };
+
+        res.update_projviews();
+
+        res
+    }
+
+    /// The wrapped camera.
+    #[inline]
+    pub fn camera(&self) -> &C {
+        &self.camera
+    }
+
+    /// The wrapped camera.
+    ///
+    /// Call `update_projviews` after modifying it outside of the render loop so the eyes follow
+    /// it immediately.
+    #[inline]
+    pub fn camera_mut(&mut self) -> &mut C {
+        &mut self.camera
+    }
+
+    /// Unwraps the wrapped camera.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.camera
+    }
+
+    /// The stereo mode of this camera.
+    #[inline]
+    pub fn mode(&self) -> StereoMode {
+        self.mode
+    }
+
+    /// Sets the stereo mode of this camera.
+    pub fn set_mode(&mut self, mode: StereoMode) {
+        self.mode = mode;
+        self.update_projviews();
+    }
+
+    /// Recomputes the view and projection of each eye from the wrapped camera.
+    pub fn update_projviews(&mut self) {
+        let view = self.camera.view_transform();
+        let separation = self.mode.eye_separation();
+
+        // The wrapped camera does not expose its projection directly.
+        self.proj = self.camera.transformation() * view.inverse().to_homogeneous();
+        let view = view.to_homogeneous();
+
+        // The view-space is translated in the opposite direction of each eye.
+        self.views = [
+            Translation3::new(separation / 2.0, 0.0, 0.0).to_homogeneous() * view,
+            Translation3::new(-separation / 2.0, 0.0, 0.0).to_homogeneous() * view,
+        ];
+    }
+
+    // The viewport of the given pass, in physical pixels.
+    fn pass_viewport(&self, pass: usize, canvas: &Canvas) -> (i32, i32, i32, i32) {
+        let (w, h) = canvas.size();
+
+        match (self.mode, pass) {
+            (StereoMode::SideBySide { .. }, 0) => (0, 0, (w / 2) as i32, h as i32),
+            (StereoMode::SideBySide { .. }, _) => ((w / 2) as i32, 0, (w / 2) as i32, h as i32),
+            _ => (0, 0, w as i32, h as i32),
+        }
+    }
+}
+
+impl<C: Camera> Camera for StereoCamera<C> {
+    fn handle_event(&mut self, canvas: &Canvas, event: &WindowEvent) {
+        match (event, self.mode) {
+            // Each eye is rendered on half of the window.
+            (&WindowEvent::FramebufferSize(w, h), StereoMode::SideBySide { .. }) => self
+                .camera
+                .handle_event(canvas, &WindowEvent::FramebufferSize(w / 2, h)),
+            _ => self.camera.handle_event(canvas, event),
+        }
+
+        self.update_projviews();
+    }
+
+    fn eye(&self) -> Point3<f32> {
+        self.camera.eye()
+    }
+
+    fn view_transform(&self) -> Isometry3<f32> {
+        self.camera.view_transform()
+    }
+
+    fn transformation(&self) -> Matrix4<f32> {
+        self.camera.transformation()
+    }
+
+    fn inverse_transformation(&self) -> Matrix4<f32> {
+        self.camera.inverse_transformation()
+    }
+
+    fn clip_planes(&self) -> (f32, f32) {
+        self.camera.clip_planes()
+    }
+
+    fn frustum_planes(&self) -> [Vector4<f32>; 6] {
+        let mut planes = self.camera.frustum_planes();
+
+        if self.mode != StereoMode::Off {
+            // Push the planes outward enough to enclose the frustums of both eyes.
+            let shift = self.camera.view_transform().inverse()
+                * Vector3::x()
+                * (self.mode.eye_separation() / 2.0);
+
+            for plane in planes.iter_mut() {
+                plane.w += plane.xyz().dot(&shift).abs();
+            }
+        }
+
+        planes
+    }
+
+    fn update(&mut self, canvas: &Canvas) {
+        self.camera.update(canvas);
+        self.update_projviews();
+    }
+
+    fn upload(
+        &self,
+        pass: usize,
+        proj: &mut ShaderUniform<Matrix4<f32>>,
+        view: &mut ShaderUniform<Matrix4<f32>>,
+    ) {
+        if self.mode == StereoMode::Off {
+            self.camera.upload(pass, proj, view)
+        } else {
+            proj.upload(&self.proj);
+            view.upload(&self.views[pass.min(1)]);
+        }
+    }
+
+    fn num_passes(&self) -> usize {
+        match self.mode {
+            StereoMode::Off => self.camera.num_passes(),
+            _ => 2,
+        }
+    }
+
+    fn start_pass(&self, pass: usize, canvas: &Canvas) {
+        let ctxt = Context::get();
+
+        match self.mode {
+            StereoMode::Off => self.camera.start_pass(pass, canvas),
+            StereoMode::Anaglyph { .. } => {
+                // The clearing of each pass is masked too, so the second eye does not erase the
+                // first one.
+                verify!(ctxt.color_mask(pass == 0, pass != 0, pass != 0, true));
+            }
+            StereoMode::SideBySide { .. } => {
+                let (x, y, w, h) = self.pass_viewport(pass, canvas);
+                verify!(ctxt.viewport(x, y, w, h));
+                verify!(ctxt.scissor(x, y, w, h));
+            }
+        }
+    }
+
+    fn render_complete(&self, canvas: &Canvas) {
+        let ctxt = Context::get();
+        let (w, h) = canvas.size();
+
+        match self.mode {
+            StereoMode::Off => self.camera.render_complete(canvas),
+            StereoMode::Anaglyph { .. } => verify!(ctxt.color_mask(true, true, true, true)),
+            StereoMode::SideBySide { .. } => {
+                verify!(ctxt.viewport(0, 0, w as i32, h as i32));
+                verify!(ctxt.scissor(0, 0, w as i32, h as i32));
+            }
+        }
+    }
+}
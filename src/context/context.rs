@@ -1,7 +1,11 @@
 #![allow(missing_docs)]
 
+use std::cell::Cell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::os::raw::c_void;
 use std::sync::Once;
 
+use crate::context::render_stats;
 use crate::context::GLContext as ContextImpl;
 use crate::resource::GLPrimitive;
 use na::{Matrix2, Matrix3, Matrix4};
@@ -12,15 +16,58 @@ mod error;
 pub type GLenum = u32;
 pub type GLintptr = isize;
 pub type GLsizeiptr = isize;
+#[derive(Clone)]
 pub struct UniformLocation(<ContextImpl as AbstractContext>::UniformLocation);
 pub struct Buffer(<ContextImpl as AbstractContext>::Buffer);
 pub struct Program(<ContextImpl as AbstractContext>::Program);
 pub struct Shader(<ContextImpl as AbstractContext>::Shader);
 pub struct Framebuffer(<ContextImpl as AbstractContext>::Framebuffer);
 pub struct Renderbuffer(<ContextImpl as AbstractContext>::Renderbuffer);
-pub struct Texture(<ContextImpl as AbstractContext>::Texture);
+pub struct Texture(
+    <ContextImpl as AbstractContext>::Texture,
+    // The width, height and format of the last image uploaded, if known.
+    Cell<(u32, u32, GLenum)>,
+    // Whether the texture is sampled with mipmaps.
+    Cell<bool>,
+    // Whether the texture contains sRGB-encoded colors.
+    Cell<bool>,
+);
 pub struct VertexArray(<ContextImpl as AbstractContext>::VertexArray);
 
+impl Texture {
+    pub(crate) fn image_layout(&self) -> (u32, u32, GLenum) {
+        self.1.get()
+    }
+
+    pub(crate) fn set_image_layout(&self, width: u32, height: u32, format: GLenum) {
+        self.1.set((width, height, format))
+    }
+
+    pub(crate) fn uses_mipmaps(&self) -> bool {
+        self.2.get()
+    }
+
+    pub(crate) fn set_uses_mipmaps(&self, uses_mipmaps: bool) {
+        self.2.set(uses_mipmaps)
+    }
+
+    /// Whether this texture contains sRGB-encoded colors, decoded when gamma correction is
+    /// enabled.
+    ///
+    /// This is the case of every texture unless they are marked as linear with `set_srgb`.
+    pub fn is_srgb(&self) -> bool {
+        self.3.get()
+    }
+
+    /// Sets whether this texture contains sRGB-encoded colors.
+    ///
+    /// Textures containing data instead of colors should not be decoded, and normal maps are
+    /// always considered linear.
+    pub fn set_srgb(&self, srgb: bool) {
+        self.3.set(srgb)
+    }
+}
+
 impl Drop for Buffer {
     fn drop(&mut self) {
         unsafe {
@@ -35,6 +82,11 @@ impl Drop for Buffer {
 static mut CONTEXT_SINGLETON: Option<Context> = None;
 static CONTEXT_INIT: Once = Once::new();
 
+thread_local! {
+    // The viewport cannot be queried from the context, so the last one set is kept here.
+    static VIEWPORT: Cell<(i32, i32, i32, i32)> = const { Cell::new((0, 0, 0, 0)) };
+}
+
 #[derive(Clone)]
 pub struct Context {
     pub ctxt: ContextImpl,
@@ -53,13 +105,34 @@ impl Context {
     pub const VERTEX_SHADER: u32 = ContextImpl::VERTEX_SHADER;
     pub const FRAGMENT_SHADER: u32 = ContextImpl::FRAGMENT_SHADER;
     pub const COMPILE_STATUS: u32 = ContextImpl::COMPILE_STATUS;
+    pub const LINK_STATUS: u32 = ContextImpl::LINK_STATUS;
     pub const FRAMEBUFFER: u32 = ContextImpl::FRAMEBUFFER;
+    pub const READ_FRAMEBUFFER: u32 = ContextImpl::READ_FRAMEBUFFER;
+    pub const DRAW_FRAMEBUFFER: u32 = ContextImpl::DRAW_FRAMEBUFFER;
     pub const RENDERBUFFER: u32 = ContextImpl::RENDERBUFFER;
     pub const DEPTH_ATTACHMENT: u32 = ContextImpl::DEPTH_ATTACHMENT;
+    pub const STENCIL_ATTACHMENT: u32 = ContextImpl::STENCIL_ATTACHMENT;
     pub const COLOR_ATTACHMENT0: u32 = ContextImpl::COLOR_ATTACHMENT0;
     pub const TEXTURE_2D: u32 = ContextImpl::TEXTURE_2D;
+    pub const TEXTURE_CUBE_MAP: u32 = ContextImpl::TEXTURE_CUBE_MAP;
+    pub const TEXTURE_CUBE_MAP_POSITIVE_X: u32 = ContextImpl::TEXTURE_CUBE_MAP_POSITIVE_X;
     pub const DEPTH_COMPONENT: u32 = ContextImpl::DEPTH_COMPONENT;
     pub const DEPTH_COMPONENT16: u32 = ContextImpl::DEPTH_COMPONENT16;
+    pub const DEPTH_COMPONENT24: u32 = ContextImpl::DEPTH_COMPONENT24;
+    pub const DEPTH24_STENCIL8: u32 = ContextImpl::DEPTH24_STENCIL8;
+    pub const DEPTH_COMPONENT32F: u32 = ContextImpl::DEPTH_COMPONENT32F;
+    pub const DEPTH32F_STENCIL8: u32 = ContextImpl::DEPTH32F_STENCIL8;
+    pub const DEPTH_STENCIL: u32 = ContextImpl::DEPTH_STENCIL;
+    pub const DEPTH_STENCIL_ATTACHMENT: u32 = ContextImpl::DEPTH_STENCIL_ATTACHMENT;
+    pub const UNSIGNED_INT_24_8: u32 = ContextImpl::UNSIGNED_INT_24_8;
+    pub const FLOAT_32_UNSIGNED_INT_24_8_REV: u32 = ContextImpl::FLOAT_32_UNSIGNED_INT_24_8_REV;
+    pub const LOWER_LEFT: u32 = ContextImpl::LOWER_LEFT;
+    pub const NEGATIVE_ONE_TO_ONE: u32 = ContextImpl::NEGATIVE_ONE_TO_ONE;
+    pub const ZERO_TO_ONE: u32 = ContextImpl::ZERO_TO_ONE;
+    pub const STENCIL_TEST: u32 = ContextImpl::STENCIL_TEST;
+    pub const STENCIL_BUFFER_BIT: u32 = ContextImpl::STENCIL_BUFFER_BIT;
+    pub const KEEP: u32 = ContextImpl::KEEP;
+    pub const REPLACE: u32 = ContextImpl::REPLACE;
     pub const UNSIGNED_BYTE: u32 = ContextImpl::UNSIGNED_BYTE;
     pub const TEXTURE_WRAP_S: u32 = ContextImpl::TEXTURE_WRAP_S;
     pub const TEXTURE_WRAP_T: u32 = ContextImpl::TEXTURE_WRAP_T;
@@ -70,11 +143,20 @@ impl Context {
     pub const CLAMP_TO_EDGE: u32 = ContextImpl::CLAMP_TO_EDGE;
     pub const RGB: u32 = ContextImpl::RGB;
     pub const RGBA: u32 = ContextImpl::RGBA;
+    pub const RGBA8: u32 = ContextImpl::RGBA8;
     pub const TEXTURE0: u32 = ContextImpl::TEXTURE0;
     pub const TEXTURE1: u32 = ContextImpl::TEXTURE1;
+    pub const TEXTURE2: u32 = ContextImpl::TEXTURE2;
+    pub const TEXTURE3: u32 = ContextImpl::TEXTURE3;
+    pub const TEXTURE4: u32 = ContextImpl::TEXTURE4;
+    pub const TEXTURE5: u32 = ContextImpl::TEXTURE5;
     pub const REPEAT: u32 = ContextImpl::REPEAT;
     pub const MIRRORED_REPEAT: u32 = ContextImpl::MIRRORED_REPEAT;
     pub const LINEAR_MIPMAP_LINEAR: u32 = ContextImpl::LINEAR_MIPMAP_LINEAR;
+    pub const LINEAR_MIPMAP_NEAREST: u32 = ContextImpl::LINEAR_MIPMAP_NEAREST;
+    pub const NEAREST_MIPMAP_LINEAR: u32 = ContextImpl::NEAREST_MIPMAP_LINEAR;
+    pub const NEAREST_MIPMAP_NEAREST: u32 = ContextImpl::NEAREST_MIPMAP_NEAREST;
+    pub const TEXTURE_MAX_ANISOTROPY: u32 = ContextImpl::TEXTURE_MAX_ANISOTROPY;
     pub const TRIANGLES: u32 = ContextImpl::TRIANGLES;
     pub const CULL_FACE: u32 = ContextImpl::CULL_FACE;
     pub const FRONT_AND_BACK: u32 = ContextImpl::FRONT_AND_BACK;
@@ -91,6 +173,13 @@ impl Context {
     pub const SCISSOR_TEST: u32 = ContextImpl::SCISSOR_TEST;
     pub const PROGRAM_POINT_SIZE: u32 = ContextImpl::PROGRAM_POINT_SIZE;
     pub const LEQUAL: u32 = ContextImpl::LEQUAL;
+    pub const GEQUAL: u32 = ContextImpl::GEQUAL;
+    pub const NEVER: u32 = ContextImpl::NEVER;
+    pub const LESS: u32 = ContextImpl::LESS;
+    pub const EQUAL: u32 = ContextImpl::EQUAL;
+    pub const GREATER: u32 = ContextImpl::GREATER;
+    pub const NOTEQUAL: u32 = ContextImpl::NOTEQUAL;
+    pub const ALWAYS: u32 = ContextImpl::ALWAYS;
     pub const BACK: u32 = ContextImpl::BACK;
     pub const PACK_ALIGNMENT: u32 = ContextImpl::PACK_ALIGNMENT;
     pub const BLEND: u32 = ContextImpl::BLEND;
@@ -100,14 +189,26 @@ impl Context {
     pub const UNPACK_ALIGNMENT: u32 = ContextImpl::UNPACK_ALIGNMENT;
     pub const ALPHA: u32 = ContextImpl::ALPHA;
     pub const RED: u32 = ContextImpl::RED;
+    pub const POLYGON_OFFSET_FILL: u32 = ContextImpl::POLYGON_OFFSET_FILL;
 
     pub fn init(get_ctxt: impl Fn() -> glow::Context) {
+        Self::init_impl(|| ContextImpl::new(get_ctxt()))
+    }
+
+    /// Initializes the context like `init`, with `get_proc_address` loading the OpenGL functions
+    /// which are not exposed by glow, e.g., the one of `clip_control`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn init_with_proc_address(
+        get_ctxt: impl Fn() -> glow::Context,
+        get_proc_address: impl Fn(&str) -> *const c_void,
+    ) {
+        Self::init_impl(|| ContextImpl::with_proc_address(get_ctxt(), &get_proc_address))
+    }
+
+    fn init_impl(get_ctxt: impl Fn() -> ContextImpl) {
         unsafe {
             CONTEXT_INIT.call_once(|| {
-                let ctxt = get_ctxt();
-                CONTEXT_SINGLETON = Some(Context {
-                    ctxt: ContextImpl::new(ctxt),
-                });
+                CONTEXT_SINGLETON = Some(Context { ctxt: get_ctxt() });
             });
         }
     }
@@ -273,6 +374,14 @@ impl Context {
         self.ctxt.get_shader_info_log(&shader.0)
     }
 
+    pub fn get_program_parameter_int(&self, program: &Program, pname: GLenum) -> Option<i32> {
+        self.ctxt.get_program_parameter_int(&program.0, pname)
+    }
+
+    pub fn get_program_info_log(&self, program: &Program) -> Option<String> {
+        self.ctxt.get_program_info_log(&program.0)
+    }
+
     pub fn vertex_attrib_pointer(
         &self,
         index: u32,
@@ -286,6 +395,18 @@ impl Context {
             .vertex_attrib_pointer(index, size, type_, normalized, stride, offset)
     }
 
+    pub fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        self.ctxt.vertex_attrib_divisor(index, divisor)
+    }
+
+    pub fn vertex_attrib3f(&self, index: u32, x: f32, y: f32, z: f32) {
+        self.ctxt.vertex_attrib3f(index, x, y, z)
+    }
+
+    pub fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        self.ctxt.vertex_attrib4f(index, x, y, z, w)
+    }
+
     pub fn enable_vertex_attrib_array(&self, index: u32) {
         self.ctxt.enable_vertex_attrib_array(index)
     }
@@ -305,9 +426,15 @@ impl Context {
     }
 
     pub fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        VIEWPORT.with(|v| v.set((x, y, width, height)));
         self.ctxt.viewport(x, y, width, height)
     }
 
+    /// The last viewport set with `viewport`, as `(x, y, width, height)`.
+    pub fn current_viewport(&self) -> (i32, i32, i32, i32) {
+        VIEWPORT.with(|v| v.get())
+    }
+
     pub fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
         self.ctxt.scissor(x, y, width, height)
     }
@@ -362,6 +489,33 @@ impl Context {
             .renderbuffer_storage(internal_format, width, height)
     }
 
+    pub fn renderbuffer_storage_multisample(
+        &self,
+        samples: i32,
+        internal_format: GLenum,
+        width: i32,
+        height: i32,
+    ) {
+        self.ctxt
+            .renderbuffer_storage_multisample(samples, internal_format, width, height)
+    }
+
+    pub fn blit_framebuffer(
+        &self,
+        src: (i32, i32, i32, i32),
+        dst: (i32, i32, i32, i32),
+        mask: GLenum,
+        filter: GLenum,
+    ) {
+        self.ctxt.blit_framebuffer(src, dst, mask, filter)
+    }
+
+    /// The maximum number of samples of multisampled renderbuffers, or `0` if they are not
+    /// supported.
+    pub fn max_samples(&self) -> i32 {
+        self.ctxt.max_samples()
+    }
+
     pub fn framebuffer_renderbuffer(
         &self,
         attachment: GLenum,
@@ -372,6 +526,9 @@ impl Context {
     }
 
     pub fn bind_texture(&self, target: GLenum, texture: Option<&Texture>) {
+        if texture.is_some() {
+            render_stats::update(|stats| stats.texture_binds += 1);
+        }
         self.ctxt.bind_texture(target, texture.map(|e| &e.0))
     }
 
@@ -421,6 +578,17 @@ impl Context {
         )
     }
 
+    pub fn tex_image2d_depth_stencil(&self, target: GLenum, width: i32, height: i32) {
+        self.ctxt.tex_image2d_depth_stencil(target, width, height)
+    }
+
+    /// Allocates a 32-bits floating-point depth texture, combined with a stencil buffer if
+    /// `stencil` is `true`.
+    pub fn tex_image2d_float_depth(&self, target: GLenum, width: i32, height: i32, stencil: bool) {
+        self.ctxt
+            .tex_image2d_float_depth(target, width, height, stencil)
+    }
+
     pub fn tex_sub_image2d(
         &self,
         target: GLenum,
@@ -446,7 +614,9 @@ impl Context {
     }
 
     pub fn create_texture(&self) -> Option<Texture> {
-        self.ctxt.create_texture().map(|e| Texture(e))
+        self.ctxt
+            .create_texture()
+            .map(|e| Texture(e, Cell::new((0, 0, 0)), Cell::new(false), Cell::new(true)))
     }
 
     pub fn delete_texture(&self, texture: Option<&Texture>) {
@@ -466,13 +636,32 @@ impl Context {
     }
 
     pub fn draw_elements(&self, mode: GLenum, count: i32, type_: GLenum, offset: GLintptr) {
+        count_draw_call(mode, count, 1);
         self.ctxt.draw_elements(mode, count, type_, offset)
     }
 
     pub fn draw_arrays(&self, mode: GLenum, first: i32, count: i32) {
+        count_draw_call(mode, count, 1);
         self.ctxt.draw_arrays(mode, first, count)
     }
 
+    pub fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instance_count: i32,
+    ) {
+        count_draw_call(mode, count, instance_count);
+        self.ctxt
+            .draw_elements_instanced(mode, count, type_, offset, instance_count)
+    }
+
+    pub fn supports_instancing(&self) -> bool {
+        self.ctxt.supports_instancing()
+    }
+
     pub fn point_size(&self, size: f32) {
         self.ctxt.point_size(size)
     }
@@ -481,10 +670,34 @@ impl Context {
         self.ctxt.line_width(size)
     }
 
+    pub fn polygon_offset(&self, factor: f32, units: f32) {
+        self.ctxt.polygon_offset(factor, units)
+    }
+
     pub fn clear(&self, mask: u32) {
         self.ctxt.clear(mask)
     }
 
+    pub fn clear_depth(&self, depth: f32) {
+        self.ctxt.clear_depth(depth)
+    }
+
+    pub fn clear_stencil(&self, stencil: i32) {
+        self.ctxt.clear_stencil(stencil)
+    }
+
+    pub fn stencil_func(&self, func: GLenum, reference: i32, mask: u32) {
+        self.ctxt.stencil_func(func, reference, mask)
+    }
+
+    pub fn stencil_op(&self, stencil_fail: GLenum, depth_fail: GLenum, pass: GLenum) {
+        self.ctxt.stencil_op(stencil_fail, depth_fail, pass)
+    }
+
+    pub fn stencil_mask(&self, mask: u32) {
+        self.ctxt.stencil_mask(mask)
+    }
+
     pub fn clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
         self.ctxt.clear_color(r, g, b, a)
     }
@@ -501,6 +714,14 @@ impl Context {
         self.ctxt.depth_func(mode)
     }
 
+    pub fn depth_mask(&self, flag: bool) {
+        self.ctxt.depth_mask(flag)
+    }
+
+    pub fn color_mask(&self, r: bool, g: bool, b: bool, a: bool) {
+        self.ctxt.color_mask(r, g, b, a)
+    }
+
     pub fn cull_face(&self, mode: GLenum) {
         self.ctxt.cull_face(mode)
     }
@@ -517,10 +738,42 @@ impl Context {
         self.ctxt.read_pixels(x, y, width, height, format, pixels)
     }
 
+    pub fn read_depth_pixels(&self, x: i32, y: i32, width: i32, height: i32, pixels: &mut [f32]) {
+        self.ctxt.read_depth_pixels(x, y, width, height, pixels)
+    }
+
     pub fn pixel_storei(&self, pname: GLenum, param: i32) {
         self.ctxt.pixel_storei(pname, param)
     }
 
+    pub fn generate_mipmap(&self, target: GLenum) {
+        self.ctxt.generate_mipmap(target)
+    }
+
+    pub fn tex_parameterf(&self, target: GLenum, pname: GLenum, param: f32) {
+        self.ctxt.tex_parameterf(target, pname, param)
+    }
+
+    /// The maximum anisotropy of the texture filtering, or `None` if anisotropic filtering is not
+    /// supported.
+    pub fn max_anisotropy(&self) -> Option<f32> {
+        self.ctxt.max_anisotropy()
+    }
+
+    /// Whether `clip_control` is supported, i.e., with OpenGL 4.5 or the `ARB_clip_control`
+    /// extension. It is never supported by OpenGL ES nor WebGL.
+    pub fn supports_clip_control(&self) -> bool {
+        self.ctxt.supports_clip_control()
+    }
+
+    /// Sets the origin of the window coordinates and the range of the depth in normalized
+    /// device coordinates, e.g., `ZERO_TO_ONE` instead of the default `NEGATIVE_ONE_TO_ONE`.
+    ///
+    /// This does nothing if `supports_clip_control` is `false`.
+    pub fn clip_control(&self, origin: GLenum, depth: GLenum) {
+        self.ctxt.clip_control(origin, depth)
+    }
+
     pub fn blend_func_separate(
         &self,
         src_rgb: GLenum,
@@ -533,6 +786,19 @@ impl Context {
     }
 }
 
+fn count_draw_call(mode: GLenum, count: i32, instance_count: i32) {
+    let triangles = match mode {
+        Context::TRIANGLES => count / 3,
+        Context::TRIANGLE_STRIP => (count - 2).max(0),
+        _ => 0,
+    };
+
+    render_stats::update(|stats| {
+        stats.draw_calls += 1;
+        stats.triangles += (triangles * instance_count.max(0)) as usize;
+    });
+}
+
 pub(crate) trait AbstractContextConst {
     const FLOAT: u32;
     const INT: u32;
@@ -546,13 +812,34 @@ pub(crate) trait AbstractContextConst {
     const VERTEX_SHADER: u32;
     const FRAGMENT_SHADER: u32;
     const COMPILE_STATUS: u32;
+    const LINK_STATUS: u32;
     const FRAMEBUFFER: u32;
+    const READ_FRAMEBUFFER: u32;
+    const DRAW_FRAMEBUFFER: u32;
     const RENDERBUFFER: u32;
     const DEPTH_ATTACHMENT: u32;
+    const STENCIL_ATTACHMENT: u32;
     const COLOR_ATTACHMENT0: u32;
     const TEXTURE_2D: u32;
+    const TEXTURE_CUBE_MAP: u32;
+    const TEXTURE_CUBE_MAP_POSITIVE_X: u32;
     const DEPTH_COMPONENT: u32;
     const DEPTH_COMPONENT16: u32;
+    const DEPTH_COMPONENT24: u32;
+    const DEPTH24_STENCIL8: u32;
+    const DEPTH_COMPONENT32F: u32;
+    const DEPTH32F_STENCIL8: u32;
+    const DEPTH_STENCIL: u32;
+    const DEPTH_STENCIL_ATTACHMENT: u32;
+    const UNSIGNED_INT_24_8: u32;
+    const FLOAT_32_UNSIGNED_INT_24_8_REV: u32;
+    const LOWER_LEFT: u32;
+    const NEGATIVE_ONE_TO_ONE: u32;
+    const ZERO_TO_ONE: u32;
+    const STENCIL_TEST: u32;
+    const STENCIL_BUFFER_BIT: u32;
+    const KEEP: u32;
+    const REPLACE: u32;
     const UNSIGNED_BYTE: u32;
     const TEXTURE_WRAP_S: u32;
     const TEXTURE_WRAP_T: u32;
@@ -563,11 +850,20 @@ pub(crate) trait AbstractContextConst {
     const CLAMP_TO_EDGE: u32;
     const RGB: u32;
     const RGBA: u32;
+    const RGBA8: u32;
     const TEXTURE0: u32;
     const TEXTURE1: u32;
+    const TEXTURE2: u32;
+    const TEXTURE3: u32;
+    const TEXTURE4: u32;
+    const TEXTURE5: u32;
     const REPEAT: u32;
     const MIRRORED_REPEAT: u32;
     const LINEAR_MIPMAP_LINEAR: u32;
+    const LINEAR_MIPMAP_NEAREST: u32;
+    const NEAREST_MIPMAP_LINEAR: u32;
+    const NEAREST_MIPMAP_NEAREST: u32;
+    const TEXTURE_MAX_ANISOTROPY: u32;
     const TRIANGLES: u32;
     const CULL_FACE: u32;
     const FRONT_AND_BACK: u32;
@@ -584,6 +880,13 @@ pub(crate) trait AbstractContextConst {
     const SCISSOR_TEST: u32;
     const PROGRAM_POINT_SIZE: u32;
     const LEQUAL: u32;
+    const GEQUAL: u32;
+    const NEVER: u32;
+    const LESS: u32;
+    const EQUAL: u32;
+    const GREATER: u32;
+    const NOTEQUAL: u32;
+    const ALWAYS: u32;
     const BACK: u32;
     const PACK_ALIGNMENT: u32;
     const BLEND: u32;
@@ -593,6 +896,7 @@ pub(crate) trait AbstractContextConst {
     const UNPACK_ALIGNMENT: u32;
     const ALPHA: u32;
     const RED: u32;
+    const POLYGON_OFFSET_FILL: u32;
 }
 
 pub(crate) trait AbstractContext {
@@ -656,6 +960,8 @@ pub(crate) trait AbstractContext {
     fn attach_shader(&self, program: &Self::Program, shader: &Self::Shader);
     fn get_shader_parameter_int(&self, shader: &Self::Shader, pname: GLenum) -> Option<i32>;
     fn get_shader_info_log(&self, shader: &Self::Shader) -> Option<String>;
+    fn get_program_parameter_int(&self, program: &Self::Program, pname: GLenum) -> Option<i32>;
+    fn get_program_info_log(&self, program: &Self::Program) -> Option<String>;
     fn vertex_attrib_pointer(
         &self,
         index: u32,
@@ -665,6 +971,9 @@ pub(crate) trait AbstractContext {
         stride: i32,
         offset: GLintptr,
     );
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32);
+    fn vertex_attrib3f(&self, index: u32, x: f32, y: f32, z: f32);
+    fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32);
     fn enable_vertex_attrib_array(&self, index: u32);
     fn disable_vertex_attrib_array(&self, index: u32);
 
@@ -695,6 +1004,21 @@ pub(crate) trait AbstractContext {
     fn delete_renderbuffer(&self, buffer: Option<&Self::Renderbuffer>);
     fn bind_renderbuffer(&self, buffer: Option<&Self::Renderbuffer>);
     fn renderbuffer_storage(&self, internal_format: GLenum, width: i32, height: i32);
+    fn renderbuffer_storage_multisample(
+        &self,
+        samples: i32,
+        internal_format: GLenum,
+        width: i32,
+        height: i32,
+    );
+    fn blit_framebuffer(
+        &self,
+        src: (i32, i32, i32, i32),
+        dst: (i32, i32, i32, i32),
+        mask: GLenum,
+        filter: GLenum,
+    );
+    fn max_samples(&self) -> i32;
     fn framebuffer_renderbuffer(
         &self,
         attachment: GLenum,
@@ -724,6 +1048,8 @@ pub(crate) trait AbstractContext {
         format: GLenum,
         pixels: Option<&[i32]>,
     );
+    fn tex_image2d_depth_stencil(&self, target: GLenum, width: i32, height: i32);
+    fn tex_image2d_float_depth(&self, target: GLenum, width: i32, height: i32, stencil: bool);
     fn tex_sub_image2d(
         &self,
         target: GLenum,
@@ -746,17 +1072,34 @@ pub(crate) trait AbstractContext {
 
     fn draw_elements(&self, mode: GLenum, count: i32, type_: GLenum, offset: GLintptr);
     fn draw_arrays(&self, mode: GLenum, first: i32, count: i32);
+    fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instance_count: i32,
+    );
+    fn supports_instancing(&self) -> bool;
 
     fn point_size(&self, size: f32);
     fn line_width(&self, size: f32);
+    fn polygon_offset(&self, factor: f32, units: f32);
 
     fn clear(&self, mask: u32);
     fn clear_color(&self, r: f32, g: f32, b: f32, a: f32);
+    fn clear_depth(&self, depth: f32);
+    fn clear_stencil(&self, stencil: i32);
+    fn stencil_func(&self, func: GLenum, reference: i32, mask: u32);
+    fn stencil_op(&self, stencil_fail: GLenum, depth_fail: GLenum, pass: GLenum);
+    fn stencil_mask(&self, mask: u32);
 
     fn polygon_mode(&self, face: GLenum, mode: GLenum) -> bool;
 
     fn front_face(&self, mode: GLenum);
     fn depth_func(&self, mode: GLenum);
+    fn depth_mask(&self, flag: bool);
+    fn color_mask(&self, r: bool, g: bool, b: bool, a: bool);
     fn cull_face(&self, mode: GLenum);
 
     fn read_pixels(
@@ -768,7 +1111,13 @@ pub(crate) trait AbstractContext {
         format: GLenum,
         pixels: Option<&mut [u8]>,
     );
+    fn read_depth_pixels(&self, x: i32, y: i32, width: i32, height: i32, pixels: &mut [f32]);
     fn pixel_storei(&self, pname: GLenum, param: i32);
+    fn generate_mipmap(&self, target: GLenum);
+    fn tex_parameterf(&self, target: GLenum, pname: GLenum, param: f32);
+    fn max_anisotropy(&self) -> Option<f32>;
+    fn supports_clip_control(&self) -> bool;
+    fn clip_control(&self, origin: GLenum, depth: GLenum);
 
     fn blend_func_separate(
         &self,
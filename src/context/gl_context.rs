@@ -1,4 +1,6 @@
 use std::mem;
+#[cfg(not(target_arch = "wasm32"))]
+use std::os::raw::c_void;
 use std::sync::Arc;
 
 use crate::context::{AbstractContext, AbstractContextConst, GLenum, GLintptr};
@@ -10,11 +12,18 @@ use na::{Matrix2, Matrix3, Matrix4};
 #[path = "../error.rs"]
 mod error;
 
+// The signature of `glClipControl`, which is not exposed by glow.
+#[cfg(not(target_arch = "wasm32"))]
+type ClipControlFn = extern "system" fn(GLenum, GLenum);
+
 /// An OpenGL context.
 #[derive(Clone)]
 pub struct GLContext {
     /// The underlying glow context.
     pub context: Arc<Context>,
+    // `glClipControl`, if it is supported.
+    #[cfg(not(target_arch = "wasm32"))]
+    clip_control: Option<ClipControlFn>,
 }
 
 impl GLContext {
@@ -22,8 +31,96 @@ impl GLContext {
     pub fn new(ctxt: Context) -> Self {
         Self {
             context: Arc::new(ctxt),
+            #[cfg(not(target_arch = "wasm32"))]
+            clip_control: None,
+        }
+    }
+
+    /// Creates a new OpenGL context, with `get_proc_address` loading the functions which are not
+    /// exposed by glow.
+    ///
+    /// The context must be current.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_proc_address(
+        ctxt: Context,
+        get_proc_address: &dyn Fn(&str) -> *const c_void,
+    ) -> Self {
+        let mut result = Self::new(ctxt);
+        let (api, major, minor) = result.version();
+        let clip_control = (api == Api::OpenGl && (major, minor) >= (4, 5))
+            || result.has_extension(|ext| ext == "GL_ARB_clip_control");
+
+        if clip_control {
+            let f = get_proc_address("glClipControl");
+
+            if !f.is_null() {
+                result.clip_control =
+                    Some(unsafe { mem::transmute::<*const c_void, ClipControlFn>(f) });
+            }
         }
+
+        result
+    }
+
+    // The API and version of the context.
+    fn version(&self) -> (Api, u32, u32) {
+        // The version string has the form "4.6.0 NVIDIA ...", "OpenGL ES 3.0 ...", or
+        // "WebGL 1.0 ...".
+        let version = unsafe { self.context.get_parameter_string(glow::VERSION) };
+        let number = version
+            .split_whitespace()
+            .find(|w| w.starts_with(|c: char| c.is_ascii_digit()));
+        let mut numbers = number
+            .into_iter()
+            .flat_map(|w| w.split('.'))
+            .map(|n| n.parse::<u32>().unwrap_or(0));
+        let major = numbers.next().unwrap_or(0);
+        let minor = numbers.next().unwrap_or(0);
+        let api = if version.starts_with("WebGL") {
+            Api::WebGl
+        } else if version.starts_with("OpenGL ES") {
+            Api::OpenGlEs
+        } else {
+            Api::OpenGl
+        };
+
+        (api, major, minor)
     }
+
+    // Whether one of the extensions of the context satisfies `is_extension`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn has_extension(&self, is_extension: impl Fn(&str) -> bool) -> bool {
+        unsafe {
+            // The extensions are listed one by one since OpenGL 3.0, and in a single string
+            // before that.
+            let num_extensions = self.context.get_parameter_i32(glow::NUM_EXTENSIONS);
+
+            if num_extensions > 0 {
+                (0..num_extensions as u32).any(|i| {
+                    is_extension(
+                        &self
+                            .context
+                            .get_parameter_indexed_string(glow::EXTENSIONS, i),
+                    )
+                })
+            } else {
+                // Clear the error of the unknown parameter.
+                let _ = self.context.get_error();
+                self.context
+                    .get_parameter_string(glow::EXTENSIONS)
+                    .split_whitespace()
+                    .any(is_extension)
+            }
+        }
+    }
+}
+
+// The API implemented by a context.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Api {
+    OpenGl,
+    OpenGlEs,
+    WebGl,
 }
 
 impl AbstractContextConst for GLContext {
@@ -39,13 +136,34 @@ impl AbstractContextConst for GLContext {
     const VERTEX_SHADER: u32 = glow::VERTEX_SHADER;
     const FRAGMENT_SHADER: u32 = glow::FRAGMENT_SHADER;
     const COMPILE_STATUS: u32 = glow::COMPILE_STATUS;
+    const LINK_STATUS: u32 = glow::LINK_STATUS;
     const FRAMEBUFFER: u32 = glow::FRAMEBUFFER;
+    const READ_FRAMEBUFFER: u32 = glow::READ_FRAMEBUFFER;
+    const DRAW_FRAMEBUFFER: u32 = glow::DRAW_FRAMEBUFFER;
     const RENDERBUFFER: u32 = glow::RENDERBUFFER;
     const DEPTH_ATTACHMENT: u32 = glow::DEPTH_ATTACHMENT;
+    const STENCIL_ATTACHMENT: u32 = glow::STENCIL_ATTACHMENT;
     const COLOR_ATTACHMENT0: u32 = glow::COLOR_ATTACHMENT0;
     const TEXTURE_2D: u32 = glow::TEXTURE_2D;
+    const TEXTURE_CUBE_MAP: u32 = glow::TEXTURE_CUBE_MAP;
+    const TEXTURE_CUBE_MAP_POSITIVE_X: u32 = glow::TEXTURE_CUBE_MAP_POSITIVE_X;
     const DEPTH_COMPONENT: u32 = glow::DEPTH_COMPONENT;
     const DEPTH_COMPONENT16: u32 = glow::DEPTH_COMPONENT16;
+    const DEPTH_COMPONENT24: u32 = glow::DEPTH_COMPONENT24;
+    const DEPTH24_STENCIL8: u32 = glow::DEPTH24_STENCIL8;
+    const DEPTH_COMPONENT32F: u32 = glow::DEPTH_COMPONENT32F;
+    const DEPTH32F_STENCIL8: u32 = glow::DEPTH32F_STENCIL8;
+    const DEPTH_STENCIL: u32 = glow::DEPTH_STENCIL;
+    const DEPTH_STENCIL_ATTACHMENT: u32 = glow::DEPTH_STENCIL_ATTACHMENT;
+    const UNSIGNED_INT_24_8: u32 = glow::UNSIGNED_INT_24_8;
+    const FLOAT_32_UNSIGNED_INT_24_8_REV: u32 = glow::FLOAT_32_UNSIGNED_INT_24_8_REV;
+    const LOWER_LEFT: u32 = glow::LOWER_LEFT;
+    const NEGATIVE_ONE_TO_ONE: u32 = glow::NEGATIVE_ONE_TO_ONE;
+    const ZERO_TO_ONE: u32 = glow::ZERO_TO_ONE;
+    const STENCIL_TEST: u32 = glow::STENCIL_TEST;
+    const STENCIL_BUFFER_BIT: u32 = glow::STENCIL_BUFFER_BIT;
+    const KEEP: u32 = glow::KEEP;
+    const REPLACE: u32 = glow::REPLACE;
     const UNSIGNED_BYTE: u32 = glow::UNSIGNED_BYTE;
     const TEXTURE_WRAP_S: u32 = glow::TEXTURE_WRAP_S;
     const TEXTURE_WRAP_T: u32 = glow::TEXTURE_WRAP_T;
@@ -56,11 +174,20 @@ impl AbstractContextConst for GLContext {
     const CLAMP_TO_EDGE: u32 = glow::CLAMP_TO_EDGE;
     const RGB: u32 = glow::RGB;
     const RGBA: u32 = glow::RGBA;
+    const RGBA8: u32 = glow::RGBA8;
     const TEXTURE0: u32 = glow::TEXTURE0;
     const TEXTURE1: u32 = glow::TEXTURE1;
+    const TEXTURE2: u32 = glow::TEXTURE2;
+    const TEXTURE3: u32 = glow::TEXTURE3;
+    const TEXTURE4: u32 = glow::TEXTURE4;
+    const TEXTURE5: u32 = glow::TEXTURE5;
     const REPEAT: u32 = glow::REPEAT;
     const MIRRORED_REPEAT: u32 = glow::MIRRORED_REPEAT;
     const LINEAR_MIPMAP_LINEAR: u32 = glow::LINEAR_MIPMAP_LINEAR;
+    const LINEAR_MIPMAP_NEAREST: u32 = glow::LINEAR_MIPMAP_NEAREST;
+    const NEAREST_MIPMAP_LINEAR: u32 = glow::NEAREST_MIPMAP_LINEAR;
+    const NEAREST_MIPMAP_NEAREST: u32 = glow::NEAREST_MIPMAP_NEAREST;
+    const TEXTURE_MAX_ANISOTROPY: u32 = glow::TEXTURE_MAX_ANISOTROPY;
     const TRIANGLES: u32 = glow::TRIANGLES;
     const CULL_FACE: u32 = glow::CULL_FACE;
     const FRONT_AND_BACK: u32 = glow::FRONT_AND_BACK;
@@ -73,6 +200,13 @@ impl AbstractContextConst for GLContext {
     const DEPTH_TEST: u32 = glow::DEPTH_TEST;
     const SCISSOR_TEST: u32 = glow::SCISSOR_TEST;
     const LEQUAL: u32 = glow::LEQUAL;
+    const GEQUAL: u32 = glow::GEQUAL;
+    const NEVER: u32 = glow::NEVER;
+    const LESS: u32 = glow::LESS;
+    const EQUAL: u32 = glow::EQUAL;
+    const GREATER: u32 = glow::GREATER;
+    const NOTEQUAL: u32 = glow::NOTEQUAL;
+    const ALWAYS: u32 = glow::ALWAYS;
     const BACK: u32 = glow::BACK;
     const PACK_ALIGNMENT: u32 = glow::PACK_ALIGNMENT;
     const PROGRAM_POINT_SIZE: u32 = glow::PROGRAM_POINT_SIZE;
@@ -89,6 +223,7 @@ impl AbstractContextConst for GLContext {
     const RED: u32 = glow::RED;
     #[cfg(target_arch = "wasm32")]
     const RED: u32 = glow::LUMINANCE; // WebGL 1
+    const POLYGON_OFFSET_FILL: u32 = glow::POLYGON_OFFSET_FILL;
 }
 
 impl AbstractContext for GLContext {
@@ -307,6 +442,20 @@ impl AbstractContext for GLContext {
         unsafe { Some(self.context.get_shader_info_log(*shader)) }
     }
 
+    fn get_program_parameter_int(&self, program: &Self::Program, _pname: GLenum) -> Option<i32> {
+        unsafe {
+            if self.context.get_program_link_status(*program) {
+                Some(1)
+            } else {
+                Some(0)
+            }
+        }
+    }
+
+    fn get_program_info_log(&self, program: &Self::Program) -> Option<String> {
+        unsafe { Some(self.context.get_program_info_log(*program)) }
+    }
+
     fn vertex_attrib_pointer(
         &self,
         index: u32,
@@ -328,6 +477,18 @@ impl AbstractContext for GLContext {
         }
     }
 
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        unsafe { self.context.vertex_attrib_divisor(index, divisor) }
+    }
+
+    fn vertex_attrib3f(&self, index: u32, x: f32, y: f32, z: f32) {
+        unsafe { self.context.vertex_attrib_3_f32(index, x, y, z) }
+    }
+
+    fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        unsafe { self.context.vertex_attrib_4_f32(index, x, y, z, w) }
+    }
+
     fn enable_vertex_attrib_array(&self, index: u32) {
         unsafe { self.context.enable_vertex_attrib_array(index) }
     }
@@ -425,6 +586,58 @@ impl AbstractContext for GLContext {
         }
     }
 
+    fn renderbuffer_storage_multisample(
+        &self,
+        samples: i32,
+        internal_format: GLenum,
+        width: i32,
+        height: i32,
+    ) {
+        unsafe {
+            self.context.renderbuffer_storage_multisample(
+                Self::RENDERBUFFER,
+                samples,
+                internal_format,
+                width,
+                height,
+            )
+        }
+    }
+
+    fn blit_framebuffer(
+        &self,
+        src: (i32, i32, i32, i32),
+        dst: (i32, i32, i32, i32),
+        mask: GLenum,
+        filter: GLenum,
+    ) {
+        unsafe {
+            self.context.blit_framebuffer(
+                src.0, src.1, src.2, src.3, dst.0, dst.1, dst.2, dst.3, mask, filter,
+            )
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn max_samples(&self) -> i32 {
+        unsafe {
+            let samples = self.context.get_parameter_i32(glow::MAX_SAMPLES);
+
+            // Multisampled renderbuffers are not supported by OpenGL ES 2.0.
+            if self.context.get_error() != 0 {
+                0
+            } else {
+                samples
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn max_samples(&self) -> i32 {
+        // Not supported by WebGL 1.
+        0
+    }
+
     fn framebuffer_renderbuffer(
         &self,
         attachment: GLenum,
@@ -500,6 +713,48 @@ impl AbstractContext for GLContext {
         }
     }
 
+    fn tex_image2d_depth_stencil(&self, target: GLenum, width: i32, height: i32) {
+        unsafe {
+            self.context.tex_image_2d(
+                target,
+                0,
+                Self::DEPTH24_STENCIL8 as i32,
+                width,
+                height,
+                0,
+                Self::DEPTH_STENCIL,
+                Self::UNSIGNED_INT_24_8,
+                None,
+            )
+        }
+    }
+
+    fn tex_image2d_float_depth(&self, target: GLenum, width: i32, height: i32, stencil: bool) {
+        let (internal_format, format, type_) = if stencil {
+            (
+                Self::DEPTH32F_STENCIL8,
+                Self::DEPTH_STENCIL,
+                Self::FLOAT_32_UNSIGNED_INT_24_8_REV,
+            )
+        } else {
+            (Self::DEPTH_COMPONENT32F, Self::DEPTH_COMPONENT, Self::FLOAT)
+        };
+
+        unsafe {
+            self.context.tex_image_2d(
+                target,
+                0,
+                internal_format as i32,
+                width,
+                height,
+                0,
+                format,
+                type_,
+                None,
+            )
+        }
+    }
+
     fn tex_sub_image2d(
         &self,
         target: GLenum,
@@ -573,6 +828,29 @@ impl AbstractContext for GLContext {
         unsafe { self.context.draw_arrays(mode, first, count) }
     }
 
+    fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instance_count: i32,
+    ) {
+        unsafe {
+            self.context
+                .draw_elements_instanced(mode, count, type_, offset as i32, instance_count)
+        }
+    }
+
+    fn supports_instancing(&self) -> bool {
+        // Instanced arrays are core since OpenGL 3.3, OpenGL ES 3.0, and WebGL 2.
+        match self.version() {
+            (Api::WebGl, major, _) => major >= 2,
+            (Api::OpenGlEs, major, _) => major >= 3,
+            (Api::OpenGl, major, minor) => (major, minor) >= (3, 3),
+        }
+    }
+
     fn point_size(&self, _size: f32) {
         //        unsafe { self.context.point_size(size) }
     }
@@ -581,6 +859,10 @@ impl AbstractContext for GLContext {
         unsafe { self.context.line_width(width) }
     }
 
+    fn polygon_offset(&self, factor: f32, units: f32) {
+        unsafe { self.context.polygon_offset(factor, units) }
+    }
+
     fn clear(&self, mask: u32) {
         unsafe { self.context.clear(mask) }
     }
@@ -589,6 +871,26 @@ impl AbstractContext for GLContext {
         unsafe { self.context.clear_color(r, g, b, a) }
     }
 
+    fn clear_depth(&self, depth: f32) {
+        unsafe { self.context.clear_depth_f32(depth) }
+    }
+
+    fn clear_stencil(&self, stencil: i32) {
+        unsafe { self.context.clear_stencil(stencil) }
+    }
+
+    fn stencil_func(&self, func: GLenum, reference: i32, mask: u32) {
+        unsafe { self.context.stencil_func(func, reference, mask) }
+    }
+
+    fn stencil_op(&self, stencil_fail: GLenum, depth_fail: GLenum, pass: GLenum) {
+        unsafe { self.context.stencil_op(stencil_fail, depth_fail, pass) }
+    }
+
+    fn stencil_mask(&self, mask: u32) {
+        unsafe { self.context.stencil_mask(mask) }
+    }
+
     fn polygon_mode(&self, face: GLenum, mode: GLenum) -> bool {
         unsafe {
             self.context.polygon_mode(face, mode);
@@ -605,6 +907,14 @@ impl AbstractContext for GLContext {
         unsafe { self.context.depth_func(mode) }
     }
 
+    fn depth_mask(&self, flag: bool) {
+        unsafe { self.context.depth_mask(flag) }
+    }
+
+    fn color_mask(&self, r: bool, g: bool, b: bool, a: bool) {
+        unsafe { self.context.color_mask(r, g, b, a) }
+    }
+
     fn cull_face(&self, mode: GLenum) {
         unsafe { self.context.cull_face(mode) }
     }
@@ -634,10 +944,76 @@ impl AbstractContext for GLContext {
         }
     }
 
+    fn read_depth_pixels(&self, x: i32, y: i32, width: i32, height: i32, pixels: &mut [f32]) {
+        let mut bytes = vec![0u8; pixels.len() * 4];
+
+        unsafe {
+            self.context.read_pixels(
+                x,
+                y,
+                width,
+                height,
+                Self::DEPTH_COMPONENT,
+                Self::FLOAT,
+                glow::PixelPackData::Slice(&mut bytes[..]),
+            );
+        }
+
+        for (pixel, b) in pixels.iter_mut().zip(bytes.chunks_exact(4)) {
+            *pixel = f32::from_ne_bytes([b[0], b[1], b[2], b[3]]);
+        }
+    }
+
     fn pixel_storei(&self, pname: GLenum, param: i32) {
         unsafe { self.context.pixel_store_i32(pname, param) }
     }
 
+    fn generate_mipmap(&self, target: GLenum) {
+        unsafe { self.context.generate_mipmap(target) }
+    }
+
+    fn tex_parameterf(&self, target: GLenum, pname: GLenum, param: f32) {
+        unsafe { self.context.tex_parameter_f32(target, pname, param) }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn max_anisotropy(&self) -> Option<f32> {
+        if self.has_extension(|ext| ext.ends_with("_texture_filter_anisotropic")) {
+            Some(unsafe {
+                self.context
+                    .get_parameter_i32(glow::MAX_TEXTURE_MAX_ANISOTROPY) as f32
+            })
+        } else {
+            None
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn max_anisotropy(&self) -> Option<f32> {
+        // Not supported: the extension would have to be enabled explicitly.
+        None
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn supports_clip_control(&self) -> bool {
+        self.clip_control.is_some()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn supports_clip_control(&self) -> bool {
+        false
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn clip_control(&self, origin: GLenum, depth: GLenum) {
+        if let Some(clip_control) = self.clip_control {
+            clip_control(origin, depth)
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn clip_control(&self, _origin: GLenum, _depth: GLenum) {}
+
     fn blend_func_separate(
         &self,
         src_rgb: GLenum,
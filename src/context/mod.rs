@@ -2,5 +2,7 @@
 
 pub use self::context::*;
 pub use self::gl_context::GLContext;
+pub use self::render_stats::RenderStats;
 mod context;
 mod gl_context;
+pub(crate) mod render_stats;
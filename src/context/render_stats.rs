@@ -0,0 +1,46 @@
+use std::cell::Cell;
+
+/// Statistics about the work done to render a frame.
+///
+/// The counters are reset at the beginning of each frame. Objects drawn by cameras with several
+/// passes are counted once per pass.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// The number of draw calls issued, including the ones of the renderers and post-processing
+    /// effects.
+    pub draw_calls: usize,
+    /// The number of triangles submitted by the draw calls.
+    pub triangles: usize,
+    /// The number of objects of the scene graph drawn.
+    pub visible_nodes: usize,
+    /// The number of objects of the scene graph skipped by frustum culling.
+    pub culled_nodes: usize,
+    /// The number of textures bound.
+    pub texture_binds: usize,
+}
+
+thread_local! {
+    static STATS: Cell<RenderStats> = Cell::new(RenderStats::default());
+}
+
+// Applies `f` to the statistics of the current frame.
+pub(crate) fn update(f: impl FnOnce(&mut RenderStats)) {
+    STATS.with(|stats| {
+        let mut curr = stats.get();
+        f(&mut curr);
+        stats.set(curr);
+    })
+}
+
+// Calls `f` without counting the work it does in the statistics of the current frame.
+pub(crate) fn uncounted<T>(f: impl FnOnce() -> T) -> T {
+    let stats = take();
+    let res = f();
+    STATS.with(|s| s.set(stats));
+    res
+}
+
+// Returns the statistics of the current frame and resets them.
+pub(crate) fn take() -> RenderStats {
+    STATS.with(|stats| stats.replace(RenderStats::default()))
+}
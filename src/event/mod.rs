@@ -1,7 +1,9 @@
 //! Window event handling.
 
 pub use self::event_manager::{Event, EventManager, Events};
-pub use self::window_event::{Action, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
+pub use self::window_event::{
+    Action, GamepadAxis, GamepadButton, Key, Modifiers, MouseButton, TouchAction, WindowEvent,
+};
 
 mod event_manager;
 mod window_event;
@@ -1,22 +1,47 @@
 #![allow(missing_docs)]
 
-#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Serialize, Deserialize)]
+use std::path::PathBuf;
+
+#[derive(Clone, PartialEq, PartialOrd, Debug, Serialize, Deserialize)]
 pub enum WindowEvent {
     Pos(i32, i32),
+    /// The new size of the window, in physical pixels.
     Size(u32, u32),
     Close,
     Refresh,
     Focus(bool),
     Iconify(bool),
+    /// The new size of the framebuffer, in physical pixels.
     FramebufferSize(u32, u32),
     MouseButton(MouseButton, Action, Modifiers),
+    /// The position of the cursor from the top-left corner of the window, in physical pixels.
     CursorPos(f64, f64, Modifiers),
+    /// The relative motion of the mouse, not limited by the borders of the window nor affected
+    /// by cursor grabbing. This is only received while the window has the focus.
+    MouseMotion(f64, f64),
     CursorEnter(bool),
     Scroll(f64, f64, Modifiers),
     Key(Key, Action, Modifiers),
     Char(char),
     CharModifiers(char, Modifiers),
+    /// A touch with the given id, at a position in physical pixels.
     Touch(u64, f64, f64, TouchAction, Modifiers),
+    /// A button of the gamepad with the given id has been pressed or released. The gamepad
+    /// events are only received with the `gamepad` feature.
+    GamepadButton(usize, GamepadButton, Action),
+    /// An axis of the gamepad with the given id has moved, to a value in `[-1.0, 1.0]`.
+    GamepadAxis(usize, GamepadAxis, f64),
+    /// A gamepad has been connected.
+    GamepadConnected(usize),
+    /// A gamepad has been disconnected.
+    GamepadDisconnected(usize),
+    /// A file has been dropped on the window. One event is received for each dropped file. The
+    /// file events are not received on the web.
+    FileDropped(PathBuf),
+    /// A file is being dragged over the window. One event is received for each dragged file.
+    FileHovered(PathBuf),
+    /// The files dragged over the window have left it without being dropped.
+    FileHoveredCancelled,
 }
 
 impl WindowEvent {
@@ -33,6 +58,7 @@ impl WindowEvent {
         match self {
             WindowEvent::MouseButton(..)
             | WindowEvent::CursorPos(..)
+            | WindowEvent::MouseMotion(..)
             | WindowEvent::CursorEnter(..)
             | WindowEvent::Scroll(..) => true,
             _ => false,
@@ -46,6 +72,27 @@ impl WindowEvent {
             _ => false,
         }
     }
+
+    /// Tests if this event is related to files dragged and dropped on the window.
+    pub fn is_file_event(&self) -> bool {
+        matches!(
+            self,
+            WindowEvent::FileDropped(..)
+                | WindowEvent::FileHovered(..)
+                | WindowEvent::FileHoveredCancelled
+        )
+    }
+
+    /// Tests if this event is related to a gamepad.
+    pub fn is_gamepad_event(&self) -> bool {
+        matches!(
+            self,
+            WindowEvent::GamepadButton(..)
+                | WindowEvent::GamepadAxis(..)
+                | WindowEvent::GamepadConnected(..)
+                | WindowEvent::GamepadDisconnected(..)
+        )
+    }
 }
 
 // NOTE: list of keys inspired from glutin.
@@ -232,6 +279,44 @@ pub enum Action {
     Press,
 }
 
+// NOTE: list of buttons and axes inspired from gilrs.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    C,
+    Z,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Unknown,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    LeftZ,
+    RightStickX,
+    RightStickY,
+    RightZ,
+    DPadX,
+    DPadY,
+    Unknown,
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub enum TouchAction {
     Start,
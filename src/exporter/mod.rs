@@ -0,0 +1,3 @@
+//! File export.
+
+pub mod obj;
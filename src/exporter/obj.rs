@@ -0,0 +1,203 @@
+//! Simplistic obj exporter.
+
+use crate::resource::TextureManager;
+use crate::scene::SceneNode;
+use na::{Isometry3, Point3, Vector3};
+use std::fs::{self, File};
+use std::io::{BufWriter, Result as IoResult, Write};
+use std::path::{Path, PathBuf};
+
+// The properties of an object written to the mtl file.
+#[derive(PartialEq)]
+struct ExportedMaterial {
+    color: Point3<f32>,
+    alpha: Option<f32>,
+    texture: Option<PathBuf>,
+}
+
+/// Writes the visible objects of the subtree rooted by `node` to an obj file and its mtl file.
+///
+/// The vertices and normals are written in world-space. Objects with the same color, opacity
+/// and texture share the same material. Only the textures loaded from a file are referenced by
+/// the materials, using the absolute path of that file. The mtl file is referenced by its path
+/// relative to the directory of the obj file if possible.
+///
+/// The objects of which the vertices are not available in RAM are skipped.
+pub fn write(node: &SceneNode, obj_path: &Path, mtl_path: &Path) -> IoResult<()> {
+    let mut obj = BufWriter::new(File::create(obj_path)?);
+    let mut materials: Vec<ExportedMaterial> = Vec::new();
+
+    let mtl_name = obj_path
+        .parent()
+        .and_then(|dir| mtl_path.strip_prefix(dir).ok())
+        .unwrap_or(mtl_path);
+    writeln!(obj, "mtllib {}", mtl_name.display())?;
+
+    // The obj indices are 1-based and global to the file.
+    let mut num_coords = 0;
+    let mut num_normals = 0;
+    let mut num_uvs = 0;
+    let mut num_objects = 0;
+
+    let (transform, scale) = node.data().world_transformation_and_scale();
+    let mut stack = vec![(node.clone(), transform, scale)];
+
+    while let Some((node, transform, scale)) = stack.pop() {
+        let data = node.data();
+
+        if !data.is_visible() {
+            continue;
+        }
+
+        for c in data.children().iter().rev() {
+            let cdata = c.data();
+            stack.push((
+                c.clone(),
+                transform * cdata.local_transformation(),
+                scale.component_mul(&cdata.local_scale()),
+            ));
+        }
+
+        let object = match data.object() {
+            Some(object) => object,
+            None => continue,
+        };
+
+        let mesh = object.mesh().borrow();
+        let coords = mesh.coords().read().unwrap();
+        let normals = mesh.normals().read().unwrap();
+        let uvs = mesh.uvs().read().unwrap();
+        let faces = mesh.faces().read().unwrap();
+
+        let (coords, faces) = match (coords.data(), faces.data()) {
+            (Some(coords), Some(faces)) => (coords, faces),
+            _ => {
+                println!("Warning: the vertices of an object are not available in RAM. It will not be exported.");
+                continue;
+            }
+        };
+        let normals = normals.data().as_ref().filter(|n| n.len() == coords.len());
+        let uvs = uvs.data().as_ref().filter(|u| u.len() == coords.len());
+
+        /*
+         * Find the material of the object.
+         */
+        let odata = object.data();
+        let texture = TextureManager::get_global_manager(|tm| {
+            tm.file_path(odata.texture()).map(|p| p.to_path_buf())
+        });
+        let material = ExportedMaterial {
+            color: *odata.color(),
+            alpha: if odata.transparent() {
+                Some(odata.alpha())
+            } else {
+                None
+            },
+            texture: texture.map(|p| fs::canonicalize(&p).unwrap_or(p)),
+        };
+        let material_id = match materials.iter().position(|m| *m == material) {
+            Some(id) => id,
+            None => {
+                materials.push(material);
+                materials.len() - 1
+            }
+        };
+
+        /*
+         * Write the geometry.
+         */
+        writeln!(obj, "g object_{}", num_objects)?;
+        writeln!(obj, "usemtl material_{}", material_id)?;
+        num_objects += 1;
+
+        for pt in coords.iter() {
+            let pt = world_point(&transform, &scale, pt);
+            writeln!(obj, "v {} {} {}", pt.x, pt.y, pt.z)?;
+        }
+
+        if let Some(normals) = normals {
+            for n in normals.iter() {
+                let n = world_normal(&transform, &scale, n);
+                writeln!(obj, "vn {} {} {}", n.x, n.y, n.z)?;
+            }
+        }
+
+        if let Some(uvs) = uvs {
+            for uv in uvs.iter() {
+                writeln!(obj, "vt {} {}", uv.x, uv.y)?;
+            }
+        }
+
+        for face in faces.iter() {
+            write!(obj, "f")?;
+
+            for i in face.iter() {
+                let i = *i as usize;
+
+                match (uvs.is_some(), normals.is_some()) {
+                    (true, true) => write!(
+                        obj,
+                        " {}/{}/{}",
+                        num_coords + i + 1,
+                        num_uvs + i + 1,
+                        num_normals + i + 1
+                    )?,
+                    (true, false) => write!(obj, " {}/{}", num_coords + i + 1, num_uvs + i + 1)?,
+                    (false, true) => {
+                        write!(obj, " {}//{}", num_coords + i + 1, num_normals + i + 1)?
+                    }
+                    (false, false) => write!(obj, " {}", num_coords + i + 1)?,
+                }
+            }
+
+            writeln!(obj)?;
+        }
+
+        num_coords += coords.len();
+        num_normals += normals.map_or(0, |n| n.len());
+        num_uvs += uvs.map_or(0, |u| u.len());
+    }
+
+    obj.flush()?;
+
+    /*
+     * Write the materials.
+     */
+    let mut mtl = BufWriter::new(File::create(mtl_path)?);
+
+    for (i, material) in materials.iter().enumerate() {
+        writeln!(mtl, "newmtl material_{}", i)?;
+        writeln!(
+            mtl,
+            "Kd {} {} {}",
+            material.color.x, material.color.y, material.color.z
+        )?;
+
+        if let Some(alpha) = material.alpha {
+            writeln!(mtl, "d {}", alpha)?;
+        }
+
+        if let Some(ref texture) = material.texture {
+            writeln!(mtl, "map_Kd {}", texture.display())?;
+        }
+
+        writeln!(mtl)?;
+    }
+
+    mtl.flush()
+}
+
+// The render path applies the scale before the transformation.
+fn world_point(transform: &Isometry3<f32>, scale: &Vector3<f32>, pt: &Point3<f32>) -> Point3<f32> {
+    transform * Point3::from(pt.coords.component_mul(scale))
+}
+
+fn world_normal(
+    transform: &Isometry3<f32>,
+    scale: &Vector3<f32>,
+    n: &Vector3<f32>,
+) -> Vector3<f32> {
+    // The normals are transformed by the inverse transpose of the scale.
+    let n = n.zip_map(scale, |n, s| if s != 0.0 { n / s } else { n });
+    (transform * n).try_normalize(1.0e-8).unwrap_or(n)
+}
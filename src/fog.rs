@@ -0,0 +1,47 @@
+//! Fog.
+
+use na::Point3;
+
+/// The way the fog density increases with the distance to the camera.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FogMode {
+    /// No fog at `start`, full fog at `end` and beyond.
+    Linear {
+        /// The view-space depth where the fog starts.
+        start: f32,
+        /// The view-space depth where the fog completely hides the objects.
+        end: f32,
+    },
+    /// The visibility decreases exponentially with the view-space depth.
+    Exponential {
+        /// The rate at which the visibility decreases.
+        density: f32,
+    },
+}
+
+/// A fog blending the objects with a uniform color depending on their depth.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fog {
+    /// The color of the fog.
+    pub color: Point3<f32>,
+    /// How the fog density increases with the depth.
+    pub mode: FogMode,
+}
+
+impl Fog {
+    /// Creates a linear fog of the given color.
+    pub fn linear(color: Point3<f32>, start: f32, end: f32) -> Fog {
+        Fog {
+            color,
+            mode: FogMode::Linear { start, end },
+        }
+    }
+
+    /// Creates an exponential fog of the given color.
+    pub fn exponential(color: Point3<f32>, density: f32) -> Fog {
+        Fog {
+            color,
+            mode: FogMode::Exponential { density },
+        }
+    }
+}
@@ -160,11 +160,14 @@ pub mod camera;
 pub mod context;
 mod error;
 pub mod event;
+pub mod exporter;
+pub mod fog;
 pub mod light;
 pub mod loader;
 pub mod planar_camera;
 pub mod planar_line_renderer;
 pub mod post_processing;
+pub mod procedural;
 pub mod renderer;
 pub mod resource;
 pub mod scene;
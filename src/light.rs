@@ -1,12 +1,335 @@
 //! Lights.
+//!
+//! The default materials receive the lights through the following uniform arrays of
+//! `MAX_LIGHTS` elements, in the order of `Lights::iter`, which custom materials can declare too:
+//!
+//! * `vec4 light_positions[i]`: the position of the light with `w = 1.0`, or the direction toward
+//!   a directional light with `w = 0.0`.
+//! * `vec3 light_colors[i]`: the color of the light multiplied by its intensity. Unused lights
+//!   are black.
+//! * `vec3 light_directions[i]`: the normalized direction a spotlight is shining toward.
+//! * `vec3 light_cones[i]`: the cosines of the inner and outer angles of a spotlight, and its
+//!   range. The range is `0.0` for the lights which are not spotlights.
 
-use na::Point3;
+use crate::camera::Camera;
+use crate::fog::Fog;
+use crate::renderer::ShadowMap;
+use crate::resource::CubemapTexture;
+use na::{Point3, Vector3};
+use std::rc::Rc;
+
+// NOTE: this must match `MAX_LIGHTS` in the shaders of the `ObjectMaterial`.
+/// The maximum number of lights taken into account at the same time.
+pub const MAX_LIGHTS: usize = 4;
+
+// NOTE: this must match `MAX_SHADOWS` in the shaders of the `ObjectMaterial`.
+/// The maximum number of lights casting shadows at the same time.
+pub const MAX_SHADOWS: usize = 2;
 
 /// The light configuration.
 #[derive(Clone)]
 pub enum Light {
-    /// A light with an absolute world position.
+    /// A white light with an absolute world position.
     Absolute(Point3<f32>),
-    /// A light superimposed with the camera position.
+    /// A white light superimposed with the camera position.
     StickToCamera,
+    /// A colored light with an absolute world position.
+    Point {
+        /// The world position of the light.
+        position: Point3<f32>,
+        /// The color of the light.
+        color: Point3<f32>,
+        /// The factor applied to the color of the light.
+        intensity: f32,
+    },
+    /// A colored light infinitely far away, e.g., the sun.
+    Directional {
+        /// The direction the light is shining toward.
+        direction: Vector3<f32>,
+        /// The color of the light.
+        color: Point3<f32>,
+        /// The factor applied to the color of the light.
+        intensity: f32,
+    },
+    /// A colored light shining in a cone from an absolute world position, e.g., a flashlight.
+    ///
+    /// The light is full inside of the inner angle, fades smoothly to zero between the inner and
+    /// outer angles, and fades to zero as well at its range.
+    Spot {
+        /// The world position of the light.
+        position: Point3<f32>,
+        /// The direction the light is shining toward.
+        direction: Vector3<f32>,
+        /// The angle between the direction and the border of the fully lit cone, in radians.
+        inner_angle: f32,
+        /// The angle between the direction and the border of the lit cone, in radians.
+        outer_angle: f32,
+        /// The color of the light.
+        color: Point3<f32>,
+        /// The factor applied to the color of the light.
+        intensity: f32,
+        /// The distance from the position beyond which nothing is lit.
+        range: f32,
+    },
+}
+
+impl Light {
+    /// The world position of this light, or `None` if it is directional.
+    pub fn position(&self, camera: &dyn Camera) -> Option<Point3<f32>> {
+        match *self {
+            Light::Absolute(ref p) => Some(*p),
+            Light::StickToCamera => Some(camera.eye()),
+            Light::Point { ref position, .. } | Light::Spot { ref position, .. } => Some(*position),
+            Light::Directional { .. } => None,
+        }
+    }
+
+    /// The direction this light is shining toward, or `None` if it shines in every direction.
+    pub fn direction(&self) -> Option<Vector3<f32>> {
+        match *self {
+            Light::Directional { ref direction, .. } | Light::Spot { ref direction, .. } => {
+                Some(*direction)
+            }
+            _ => None,
+        }
+    }
+
+    /// The color of this light multiplied by its intensity.
+    pub fn radiance(&self) -> Vector3<f32> {
+        match *self {
+            Light::Absolute(_) | Light::StickToCamera => Vector3::repeat(1.0),
+            Light::Point {
+                ref color,
+                intensity,
+                ..
+            }
+            | Light::Directional {
+                ref color,
+                intensity,
+                ..
+            }
+            | Light::Spot {
+                ref color,
+                intensity,
+                ..
+            } => color.coords * intensity,
+        }
+    }
+}
+
+/// The identifier of a light added to a `Lights` collection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LightId(usize);
+
+// A light of a `Lights` set, with its shadow settings.
+#[derive(Clone)]
+struct LightEntry {
+    id: LightId,
+    light: Light,
+    casts_shadow: bool,
+    // `None` to use the bias of the shadow map renderer.
+    shadow_bias: Option<f32>,
+}
+
+/// The set of lights illuminating a scene.
+#[derive(Clone, Default)]
+pub struct Lights {
+    lights: Vec<LightEntry>,
+    next_id: usize,
+    shadow_maps: Vec<ShadowMap>,
+    environment_map: Option<Rc<CubemapTexture>>,
+    fog: Option<Fog>,
+    gamma_correction: bool,
+}
+
+impl Lights {
+    /// Creates an empty set of lights.
+    pub fn new() -> Lights {
+        Lights {
+            lights: Vec::new(),
+            next_id: 0,
+            shadow_maps: Vec::new(),
+            environment_map: None,
+            fog: None,
+            gamma_correction: false,
+        }
+    }
+
+    /// Adds a light to this set.
+    ///
+    /// Returns an error message, and leaves this set unchanged, if it already contains
+    /// `MAX_LIGHTS` lights.
+    pub fn add(&mut self, light: Light) -> Result<LightId, String> {
+        if self.lights.len() >= MAX_LIGHTS {
+            return Err(format!(
+                "There cannot be more than {} lights at the same time.",
+                MAX_LIGHTS
+            ));
+        }
+
+        let id = LightId(self.next_id);
+        self.next_id += 1;
+        self.lights.push(LightEntry {
+            id,
+            light,
+            casts_shadow: true,
+            shadow_bias: None,
+        });
+
+        Ok(id)
+    }
+
+    /// Removes a light from this set, and returns it.
+    ///
+    /// Returns `None` if this light has already been removed.
+    pub fn remove(&mut self, id: LightId) -> Option<Light> {
+        let i = self.lights.iter().position(|l| l.id == id)?;
+        Some(self.lights.remove(i).light)
+    }
+
+    /// Removes all the lights of this set.
+    pub fn clear(&mut self) {
+        self.lights.clear()
+    }
+
+    /// The light with the given identifier.
+    pub fn get(&self, id: LightId) -> Option<&Light> {
+        self.entry(id).map(|l| &l.light)
+    }
+
+    /// The light with the given identifier.
+    pub fn get_mut(&mut self, id: LightId) -> Option<&mut Light> {
+        self.entry_mut(id).map(|l| &mut l.light)
+    }
+
+    fn entry(&self, id: LightId) -> Option<&LightEntry> {
+        self.lights.iter().find(|l| l.id == id)
+    }
+
+    fn entry_mut(&mut self, id: LightId) -> Option<&mut LightEntry> {
+        self.lights.iter_mut().find(|l| l.id == id)
+    }
+
+    /// Whether the light with the given identifier casts shadows, if shadows are enabled.
+    ///
+    /// Returns `false` if this light has been removed.
+    pub fn casts_shadow(&self, id: LightId) -> bool {
+        self.entry(id).is_some_and(|l| l.casts_shadow)
+    }
+
+    /// Sets whether the light with the given identifier casts shadows, if shadows are enabled.
+    ///
+    /// Only directional lights and spotlights cast shadows, and only the first `MAX_SHADOWS` of
+    /// them which have this enabled, in the order of `Lights::iter`. This is enabled by default.
+    /// Does nothing if this light has been removed.
+    pub fn set_casts_shadow(&mut self, id: LightId, casts_shadow: bool) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.casts_shadow = casts_shadow;
+        }
+    }
+
+    /// The depth offset of the shadows of the light with the given identifier, if it overrides
+    /// the one of the shadow map renderer.
+    pub fn shadow_bias(&self, id: LightId) -> Option<f32> {
+        self.entry(id).and_then(|l| l.shadow_bias)
+    }
+
+    /// Sets the depth offset of the shadows of the light with the given identifier.
+    ///
+    /// Increase it if shadowed stripes appear on lit surfaces ("shadow acne"), and decrease it
+    /// if the shadows detach from their casters ("peter-panning"). Use `None` to use the bias of
+    /// the shadow map renderer, which is the default. Does nothing if this light has been
+    /// removed.
+    pub fn set_shadow_bias(&mut self, id: LightId, bias: Option<f32>) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.shadow_bias = bias;
+        }
+    }
+
+    /// The number of lights in this set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    /// Whether this set contains no light.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Iterates through the lights of this set, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &Light> {
+        self.lights.iter().map(|l| &l.light)
+    }
+
+    // The lights which cast shadows, with their index in the order of `Lights::iter` and their
+    // shadow bias, up to `MAX_SHADOWS`.
+    pub(crate) fn shadow_casters(&self) -> impl Iterator<Item = (usize, &Light, Option<f32>)> {
+        self.lights
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| l.casts_shadow && l.light.direction().is_some())
+            .map(|(i, l)| (i, &l.light, l.shadow_bias))
+            .take(MAX_SHADOWS)
+    }
+
+    /// The shadow maps of the lights of this set casting shadows, if shadows are enabled.
+    #[inline]
+    pub fn shadow_maps(&self) -> &[ShadowMap] {
+        &self.shadow_maps
+    }
+
+    /// Sets the shadow maps of the lights of this set casting shadows.
+    ///
+    /// This is called by the window before each frame. Only the first `MAX_SHADOWS` shadow maps
+    /// are used by the materials.
+    #[inline]
+    pub fn set_shadow_maps(&mut self, shadow_maps: Vec<ShadowMap>) {
+        self.shadow_maps = shadow_maps;
+    }
+
+    /// The cubemap used by some materials for image-based lighting, if any.
+    #[inline]
+    pub fn environment_map(&self) -> Option<&Rc<CubemapTexture>> {
+        self.environment_map.as_ref()
+    }
+
+    /// Sets the cubemap used by some materials for image-based lighting.
+    #[inline]
+    pub fn set_environment_map(&mut self, environment_map: Option<Rc<CubemapTexture>>) {
+        self.environment_map = environment_map;
+    }
+
+    /// The fog of the scene, if any.
+    #[inline]
+    pub fn fog(&self) -> Option<&Fog> {
+        self.fog.as_ref()
+    }
+
+    /// Sets the fog of the scene.
+    #[inline]
+    pub fn set_fog(&mut self, fog: Option<Fog>) {
+        self.fog = fog;
+    }
+
+    /// Whether the lighting is computed in linear space and encoded to sRGB.
+    #[inline]
+    pub fn gamma_correction(&self) -> bool {
+        self.gamma_correction
+    }
+
+    /// Sets whether the lighting is computed in linear space and encoded to sRGB.
+    #[inline]
+    pub fn set_gamma_correction(&mut self, enabled: bool) {
+        self.gamma_correction = enabled;
+    }
+}
+
+impl From<Light> for Lights {
+    fn from(light: Light) -> Lights {
+        let mut lights = Lights::new();
+        let _ = lights.add(light);
+        lights
+    }
 }
@@ -0,0 +1,279 @@
+//! Simplistic glTF 2.0 loader.
+//!
+//! Both the `.gltf` (with external or embedded buffers) and the binary `.glb` containers are
+//! supported. Only primitives using the `TRIANGLES` mode can be loaded.
+
+use crate::resource::Mesh;
+use ::gltf::image::Format;
+use ::gltf::mesh::Mode;
+use image::{DynamicImage, ImageBuffer};
+use na::{Isometry3, Point2, Point3, Point4, Quaternion, Translation3, UnitQuaternion, Vector3};
+use std::cell::RefCell;
+use std::io::{Error, ErrorKind, Result as IoResult};
+use std::path::Path;
+use std::rc::Rc;
+
+/// A material read from a glTF file.
+pub struct GltfMaterial {
+    /// The name of the material.
+    pub name: Option<String>,
+    /// The base color factor (RGBA).
+    pub base_color: Point4<f32>,
+    /// Index of the base color texture on the `GltfScene::images` array.
+    pub base_color_texture: Option<usize>,
+}
+
+/// A triangle mesh read from a glTF primitive.
+pub struct GltfPrimitive {
+    /// The geometry of the primitive.
+    pub mesh: Rc<RefCell<Mesh>>,
+    /// Index of the primitive material on the `GltfScene::materials` array.
+    ///
+    /// This is `None` if the primitive uses the glTF default material.
+    pub material: Option<usize>,
+}
+
+/// A node of the glTF node hierarchy.
+pub struct GltfNode {
+    /// The name of the node.
+    pub name: Option<String>,
+    /// The node local transformation (without scaling).
+    pub transform: Isometry3<f32>,
+    /// The node local scale.
+    pub scale: Vector3<f32>,
+    /// Index of the node mesh on the `GltfScene::meshes` array.
+    pub mesh: Option<usize>,
+    /// The children of this node.
+    pub children: Vec<GltfNode>,
+}
+
+/// The content of a glTF file.
+pub struct GltfScene {
+    /// The meshes of the file, each one being a set of primitives.
+    pub meshes: Vec<Vec<GltfPrimitive>>,
+    /// The materials of the file.
+    pub materials: Vec<GltfMaterial>,
+    /// The decoded images of the file.
+    pub images: Vec<DynamicImage>,
+    /// The root nodes of the default scene.
+    pub nodes: Vec<GltfNode>,
+}
+
+fn error<T>(path: &Path, err: &str) -> IoResult<T> {
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!("{}: {}", path.display(), err),
+    ))
+}
+
+/// Loads a `.gltf` or `.glb` file.
+pub fn load(path: &Path) -> IoResult<GltfScene> {
+    load_with_progress(path, |_| {})
+}
+
+/// Loads a `.gltf` or `.glb` file, calling `progress` with the fraction of the file loaded so far.
+///
+/// The fraction is between 0.0 and 1.0. It is reported after the file is read, and then each time
+/// a primitive or an image has been converted.
+pub fn load_with_progress<F: FnMut(f32)>(path: &Path, mut progress: F) -> IoResult<GltfScene> {
+    let (document, buffers, images) = match ::gltf::import(path) {
+        Ok(res) => res,
+        Err(::gltf::Error::Io(e)) => return Err(e),
+        Err(e) => return error(path, &e.to_string()),
+    };
+
+    // Reading the file is accounted for as the first step.
+    let num_steps = 1
+        + document
+            .meshes()
+            .map(|m| m.primitives().len())
+            .sum::<usize>()
+        + images.len();
+    let mut steps = 1;
+    let mut step = || {
+        progress(steps as f32 / num_steps as f32);
+        steps += 1;
+    };
+
+    step();
+
+    let mut meshes = Vec::new();
+
+    for mesh in document.meshes() {
+        let mut primitives = Vec::new();
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != Mode::Triangles {
+                return error(
+                    path,
+                    &format!(
+                        "unsupported primitive mode {:?} on mesh {}, only TRIANGLES can be loaded",
+                        primitive.mode(),
+                        mesh.index()
+                    ),
+                );
+            }
+
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let coords: Vec<Point3<f32>> = match reader.read_positions() {
+                Some(positions) => positions.map(Point3::from).collect(),
+                None => return error(path, "primitive without vertex positions"),
+            };
+
+            if coords.len() > u16::MAX as usize + 1 {
+                return error(
+                    path,
+                    &format!(
+                        "primitive with {} vertices, at most {} are supported",
+                        coords.len(),
+                        u16::MAX as usize + 1
+                    ),
+                );
+            }
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..coords.len() as u32).collect(),
+            };
+
+            let triangles = indices.chunks_exact(3);
+
+            if !triangles.remainder().is_empty()
+                || indices.iter().any(|i| *i as usize >= coords.len())
+            {
+                return error(path, "invalid primitive index buffer");
+            }
+
+            let faces = triangles
+                .map(|f| Point3::new(f[0] as u16, f[1] as u16, f[2] as u16))
+                .collect();
+            let normals = reader
+                .read_normals()
+                .map(|normals| normals.map(Vector3::from).collect());
+            let uv_set = primitive
+                .material()
+                .pbr_metallic_roughness()
+                .base_color_texture()
+                .map(|t| t.tex_coord())
+                .unwrap_or(0);
+            let uvs = reader
+                .read_tex_coords(uv_set)
+                .map(|uvs| uvs.into_f32().map(Point2::from).collect());
+
+            primitives.push(GltfPrimitive {
+                mesh: Rc::new(RefCell::new(Mesh::new(coords, faces, normals, uvs, false))),
+                material: primitive.material().index(),
+            });
+
+            step();
+        }
+
+        meshes.push(primitives);
+    }
+
+    let materials = document
+        .materials()
+        .map(|material| {
+            let pbr = material.pbr_metallic_roughness();
+
+            GltfMaterial {
+                name: material.name().map(|n| n.to_string()),
+                base_color: Point4::from(pbr.base_color_factor()),
+                base_color_texture: pbr
+                    .base_color_texture()
+                    .map(|t| t.texture().source().index()),
+            }
+        })
+        .collect();
+
+    let mut decoded_images = Vec::new();
+
+    for image in images.into_iter() {
+        match convert_image(image) {
+            Some(image) => decoded_images.push(image),
+            None => return error(path, "invalid image data"),
+        }
+
+        step();
+    }
+
+    let nodes = match document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    {
+        Some(scene) => scene.nodes().map(|n| convert_node(&n)).collect(),
+        None => Vec::new(),
+    };
+
+    Ok(GltfScene {
+        meshes,
+        materials,
+        images: decoded_images,
+        nodes,
+    })
+}
+
+fn convert_node(node: &::gltf::Node) -> GltfNode {
+    let (t, r, s) = node.transform().decomposed();
+    let rotation = UnitQuaternion::from_quaternion(Quaternion::new(r[3], r[0], r[1], r[2]));
+    let transform = Isometry3::from_parts(Translation3::new(t[0], t[1], t[2]), rotation);
+
+    GltfNode {
+        name: node.name().map(|n| n.to_string()),
+        transform,
+        scale: Vector3::from(s),
+        mesh: node.mesh().map(|m| m.index()),
+        children: node.children().map(|c| convert_node(&c)).collect(),
+    }
+}
+
+fn convert_image(image: ::gltf::image::Data) -> Option<DynamicImage> {
+    let (width, height) = (image.width, image.height);
+    let pixels = image.pixels;
+
+    match image.format {
+        Format::R8G8B8 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8),
+        Format::R8G8B8A8 => {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+        }
+        format => {
+            // Everything else (luminance, BGR, 16-bits) is converted to RGBA8.
+            let (channels, bytes) = match format {
+                Format::R8 => (1, 1),
+                Format::R8G8 => (2, 1),
+                Format::B8G8R8 => (3, 1),
+                Format::B8G8R8A8 => (4, 1),
+                Format::R16 => (1, 2),
+                Format::R16G16 => (2, 2),
+                Format::R16G16B16 => (3, 2),
+                _ => (4, 2),
+            };
+            let bgr = format == Format::B8G8R8 || format == Format::B8G8R8A8;
+            let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+
+            for pixel in pixels.chunks(channels * bytes) {
+                // For 16-bits formats, we keep the most significant byte.
+                let msb = if cfg!(target_endian = "big") {
+                    0
+                } else {
+                    bytes - 1
+                };
+                let c = |i: usize| pixel[i * bytes + msb];
+                let (r, g, b, a) = match channels {
+                    1 => (c(0), c(0), c(0), 255),
+                    2 => (c(0), c(0), c(0), c(1)),
+                    3 => (c(0), c(1), c(2), 255),
+                    _ => (c(0), c(1), c(2), c(3)),
+                };
+
+                if bgr {
+                    rgba.extend_from_slice(&[b, g, r, a]);
+                } else {
+                    rgba.extend_from_slice(&[r, g, b, a]);
+                }
+            }
+
+            ImageBuffer::from_raw(width, height, rgba).map(DynamicImage::ImageRgba8)
+        }
+    }
+}
@@ -1,4 +1,7 @@
 //! File loading.
 
+pub mod gltf;
 pub mod mtl;
 pub mod obj;
+pub mod ply;
+pub mod stl;
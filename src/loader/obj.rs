@@ -57,12 +57,25 @@ pub fn parse_file(
     path: &Path,
     mtl_base_dir: &Path,
     basename: &str,
+) -> IoResult<Vec<(String, Mesh, Option<MtlMaterial>)>> {
+    parse_file_with_progress(path, mtl_base_dir, basename, |_| {})
+}
+
+/// Parses an obj file, calling `progress` with the fraction of the file parsed so far.
+///
+/// The fraction is between 0.0 and 1.0, and is estimated from the number of bytes parsed. It is
+/// reported about a hundred times, and 1.0 is reported once the meshes are created.
+pub fn parse_file_with_progress<F: FnMut(f32)>(
+    path: &Path,
+    mtl_base_dir: &Path,
+    basename: &str,
+    progress: F,
 ) -> IoResult<Vec<(String, Mesh, Option<MtlMaterial>)>> {
     match File::open(path) {
         Ok(mut file) => {
             let mut sfile = String::new();
             file.read_to_string(&mut sfile)
-                .map(|_| parse(&sfile[..], mtl_base_dir, basename))
+                .map(|_| parse_with_progress(&sfile[..], mtl_base_dir, basename, progress))
         }
         Err(e) => Err(e),
     }
@@ -73,6 +86,19 @@ pub fn parse(
     string: &str,
     mtl_base_dir: &Path,
     basename: &str,
+) -> Vec<(String, Mesh, Option<MtlMaterial>)> {
+    parse_with_progress(string, mtl_base_dir, basename, |_| {})
+}
+
+/// Parses a string representing an obj file, calling `progress` with the fraction of the string
+/// parsed so far.
+///
+/// See `parse_file_with_progress` for details.
+pub fn parse_with_progress<F: FnMut(f32)>(
+    string: &str,
+    mtl_base_dir: &Path,
+    basename: &str,
+    mut progress: F,
 ) -> Vec<(String, Mesh, Option<MtlMaterial>)> {
     let mut coords: Vec<Coord> = Vec::new();
     let mut normals: Vec<Normal> = Vec::new();
@@ -89,7 +115,20 @@ pub fn parse(
     groups_ids.push(Vec::new());
     let _ = groups.insert(basename.to_string(), 0);
 
+    // The number of bytes parsed, and the number reported last.
+    let mut parsed = 0;
+    let mut reported = 0;
+    let report_step = string.len() / 100 + 1;
+
     for (l, line) in string.lines().enumerate() {
+        parsed += line.len() + 1;
+
+        if parsed - reported >= report_step {
+            reported = parsed;
+            // The creation of the meshes is accounted for as the last percent.
+            progress(parsed.min(string.len()) as f32 / string.len() as f32 * 0.99);
+        }
+
         let mut words = split_words(line);
         let tag = words.next();
         match tag {
@@ -155,14 +194,18 @@ pub fn parse(
         println!("Warning: some normals are missing. Dropping normals infos for every vertex.");
     }
 
-    reformat(
+    let meshes = reformat(
         coords,
         if ignore_normals { None } else { Some(normals) },
         if ignore_uvs { None } else { Some(uvs) },
         groups_ids,
         groups,
         group2mtl,
-    )
+    );
+
+    progress(1.0);
+
+    meshes
 }
 
 fn parse_usemtl<'a>(
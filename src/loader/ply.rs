@@ -0,0 +1,592 @@
+//! Simplistic ply loader.
+//!
+//! The `ascii`, `binary_little_endian` and `binary_big_endian` formats are supported. Only the
+//! `vertex` and `face` elements are read, other elements are skipped.
+
+use crate::loader::obj;
+use crate::resource::Mesh;
+use na::{Point3, Vector3};
+use std::fs::File;
+use std::io::Read;
+use std::io::{Error, ErrorKind, Result as IoResult};
+use std::path::Path;
+
+/// The content of a ply file.
+pub struct PlyMesh {
+    /// The vertex coordinates.
+    pub coords: Vec<Point3<f32>>,
+    /// The vertex normals, if the file contains `nx`, `ny`, and `nz` properties.
+    pub normals: Option<Vec<Vector3<f32>>>,
+    /// The vertex colors, with components in `[0.0, 1.0]`, if the file contains `red`, `green`,
+    /// and `blue` properties.
+    pub colors: Option<Vec<Point3<f32>>>,
+    /// The triangles, if the file contains a `face` element. Polygons are triangulated as fans.
+    pub faces: Option<Vec<Point3<u32>>>,
+}
+
+impl PlyMesh {
+    /// Converts this ply data into a triangle mesh.
+    ///
+    /// The vertex colors, if any, are set as the mesh colors.
+    ///
+    /// Returns `None` if the file has no faces (it is then a point cloud) or if it has too many
+    /// vertices to be indexed by a `Mesh`.
+    pub fn to_mesh(&self, dynamic_draw: bool) -> Option<Mesh> {
+        let faces = self.faces.as_ref()?;
+
+        if self.coords.len() > u16::MAX as usize + 1 {
+            return None;
+        }
+
+        let faces = faces
+            .iter()
+            .map(|f| Point3::new(f.x as u16, f.y as u16, f.z as u16))
+            .collect();
+
+        let mut mesh = Mesh::new(
+            self.coords.clone(),
+            faces,
+            self.normals.clone(),
+            None,
+            dynamic_draw,
+        );
+        mesh.set_colors(self.colors.clone());
+
+        Some(mesh)
+    }
+}
+
+fn error<T>(err: String) -> IoResult<T> {
+    Err(Error::new(ErrorKind::InvalidData, err))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Scalar> {
+        match name {
+            "char" | "int8" => Some(Scalar::I8),
+            "uchar" | "uint8" => Some(Scalar::U8),
+            "short" | "int16" => Some(Scalar::I16),
+            "ushort" | "uint16" => Some(Scalar::U16),
+            "int" | "int32" => Some(Scalar::I32),
+            "uint" | "uint32" => Some(Scalar::U32),
+            "float" | "float32" => Some(Scalar::F32),
+            "double" | "float64" => Some(Scalar::F64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    /// The multiplier converting a color component of this type to `[0.0, 1.0]`.
+    fn color_scale(self) -> f64 {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1.0 / 255.0,
+            Scalar::I16 | Scalar::U16 => 1.0 / 65535.0,
+            Scalar::I32 | Scalar::U32 => 1.0 / 4_294_967_295.0,
+            Scalar::F32 | Scalar::F64 => 1.0,
+        }
+    }
+}
+
+enum PropertyType {
+    Scalar(Scalar),
+    List(Scalar, Scalar),
+}
+
+struct Property {
+    name: String,
+    ty: PropertyType,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Reads the scalars of a ply file body, whatever its format.
+struct BodyReader<'a> {
+    format: Format,
+    data: &'a [u8],
+    pos: usize,
+    words: Vec<&'a str>,
+    line: usize,
+}
+
+impl<'a> BodyReader<'a> {
+    /// Starts reading a new element (i.e. a new line for the ascii format).
+    fn start_element(&mut self) -> IoResult<()> {
+        if self.format != Format::Ascii {
+            return Ok(());
+        }
+
+        loop {
+            if self.pos >= self.data.len() {
+                return error("Unexpected end of file: less elements than declared.".to_string());
+            }
+
+            let end = self.data[self.pos..]
+                .iter()
+                .position(|c| *c == b'\n')
+                .map(|i| self.pos + i)
+                .unwrap_or(self.data.len());
+            let line = match std::str::from_utf8(&self.data[self.pos..end]) {
+                Ok(line) => line,
+                Err(_) => return error(format!("At line {}: invalid utf8.", self.line)),
+            };
+
+            self.pos = end + 1;
+            self.line += 1;
+            self.words = obj::split_words(line).collect();
+            self.words.reverse();
+
+            if !self.words.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Ends the current element.
+    fn end_element(&mut self) -> IoResult<()> {
+        if self.format == Format::Ascii && !self.words.is_empty() {
+            return error(format!(
+                "At line {}: more properties than declared.",
+                self.line
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn read(&mut self, ty: Scalar) -> IoResult<f64> {
+        if self.format == Format::Ascii {
+            let word = match self.words.pop() {
+                Some(word) => word,
+                None => {
+                    return error(format!(
+                        "At line {}: less properties than declared.",
+                        self.line
+                    ))
+                }
+            };
+
+            return match word.parse::<f64>() {
+                Ok(val) => Ok(val),
+                Err(_) => error(format!("At line {}: invalid number `{}`.", self.line, word)),
+            };
+        }
+
+        let size = ty.size();
+
+        if self.pos + size > self.data.len() {
+            return error("Unexpected end of file: less elements than declared.".to_string());
+        }
+
+        let mut bytes = [0u8; 8];
+        bytes[..size].copy_from_slice(&self.data[self.pos..self.pos + size]);
+        self.pos += size;
+
+        if self.format == Format::BinaryBigEndian {
+            bytes[..size].reverse();
+        }
+
+        let val = match ty {
+            Scalar::I8 => bytes[0] as i8 as f64,
+            Scalar::U8 => bytes[0] as f64,
+            Scalar::I16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            Scalar::U16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            Scalar::I32 => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            Scalar::U32 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            Scalar::F32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            Scalar::F64 => f64::from_le_bytes(bytes),
+        };
+
+        Ok(val)
+    }
+
+    fn is_at_end(&self) -> bool {
+        if self.format == Format::Ascii {
+            self.data[self.pos.min(self.data.len())..]
+                .iter()
+                .all(|c| c.is_ascii_whitespace())
+        } else {
+            self.pos >= self.data.len()
+        }
+    }
+}
+
+/// Parses a ply file.
+pub fn load(path: &Path) -> IoResult<PlyMesh> {
+    let mut file = File::open(path)?;
+    let mut data = Vec::new();
+    let _ = file.read_to_end(&mut data)?;
+
+    parse(&data[..])
+}
+
+/// Parses the content of a ply file.
+pub fn parse(data: &[u8]) -> IoResult<PlyMesh> {
+    let (format, elements, body_start, header_lines) = parse_header(data)?;
+    let mut reader = BodyReader {
+        format,
+        data: &data[body_start..],
+        pos: 0,
+        words: Vec::new(),
+        line: header_lines,
+    };
+
+    let mut coords = Vec::new();
+    let mut normals = Vec::new();
+    let mut colors = Vec::new();
+    let mut faces = None;
+    let mut has_normals = false;
+    let mut has_colors = false;
+
+    for element in elements.iter() {
+        let index_of = |name: &str| element.properties.iter().position(|p| p.name == name);
+
+        match &element.name[..] {
+            "vertex" => {
+                let position = [index_of("x"), index_of("y"), index_of("z")];
+                let normal = [index_of("nx"), index_of("ny"), index_of("nz")];
+                let color = [index_of("red"), index_of("green"), index_of("blue")];
+
+                if position.iter().any(|i| i.is_none()) {
+                    return error(
+                        "The vertex element must have x, y, and z properties.".to_string(),
+                    );
+                }
+
+                has_normals = normal.iter().all(|i| i.is_some());
+                has_colors = color.iter().all(|i| i.is_some());
+
+                let color_scales: Vec<f64> = element
+                    .properties
+                    .iter()
+                    .map(|p| match p.ty {
+                        PropertyType::Scalar(s) => s.color_scale(),
+                        PropertyType::List(..) => 1.0,
+                    })
+                    .collect();
+                let mut values = vec![0.0f64; element.properties.len()];
+
+                for _ in 0..element.count {
+                    read_element(&mut reader, element, &mut values, |_| ())?;
+
+                    let val = |i: Option<usize>| values[i.unwrap()];
+                    coords.push(Point3::new(
+                        val(position[0]) as f32,
+                        val(position[1]) as f32,
+                        val(position[2]) as f32,
+                    ));
+
+                    if has_normals {
+                        normals.push(Vector3::new(
+                            val(normal[0]) as f32,
+                            val(normal[1]) as f32,
+                            val(normal[2]) as f32,
+                        ));
+                    }
+
+                    if has_colors {
+                        let c = |i: Option<usize>| (val(i) * color_scales[i.unwrap()]) as f32;
+                        colors.push(Point3::new(c(color[0]), c(color[1]), c(color[2])));
+                    }
+                }
+            }
+            "face" => {
+                let indices = match index_of("vertex_indices").or_else(|| index_of("vertex_index"))
+                {
+                    Some(i) => i,
+                    None => {
+                        return error(
+                            "The face element must have a vertex_indices property.".to_string(),
+                        )
+                    }
+                };
+                let mut values = vec![0.0f64; element.properties.len()];
+                let mut triangles = Vec::new();
+                let mut polygon = Vec::new();
+
+                for _ in 0..element.count {
+                    polygon.clear();
+                    read_element(&mut reader, element, &mut values, |(prop, val)| {
+                        if prop == indices {
+                            polygon.push(val as u32)
+                        }
+                    })?;
+
+                    for i in 2..polygon.len() {
+                        triangles.push(Point3::new(polygon[0], polygon[i - 1], polygon[i]));
+                    }
+                }
+
+                faces = Some(triangles);
+            }
+            _ => {
+                let mut values = vec![0.0f64; element.properties.len()];
+
+                for _ in 0..element.count {
+                    read_element(&mut reader, element, &mut values, |_| ())?;
+                }
+            }
+        }
+    }
+
+    if !reader.is_at_end() {
+        return error("More data than the elements declared in the header.".to_string());
+    }
+
+    if let Some(faces) = &faces {
+        if faces
+            .iter()
+            .any(|f| f.iter().any(|i| *i as usize >= coords.len()))
+        {
+            return error("Face referencing a vertex that does not exist.".to_string());
+        }
+    }
+
+    Ok(PlyMesh {
+        coords,
+        normals: if has_normals { Some(normals) } else { None },
+        colors: if has_colors { Some(colors) } else { None },
+        faces,
+    })
+}
+
+/// Reads one element. Scalar properties are written to `values`, list items are given to `f`
+/// with the index of their property.
+fn read_element<F: FnMut((usize, f64))>(
+    reader: &mut BodyReader,
+    element: &Element,
+    values: &mut [f64],
+    mut f: F,
+) -> IoResult<()> {
+    reader.start_element()?;
+
+    for (i, property) in element.properties.iter().enumerate() {
+        match property.ty {
+            PropertyType::Scalar(ty) => values[i] = reader.read(ty)?,
+            PropertyType::List(len_ty, item_ty) => {
+                let len = reader.read(len_ty)?;
+
+                if len < 0.0 {
+                    return error(format!("Negative list length on `{}`.", property.name));
+                }
+
+                for _ in 0..len as usize {
+                    f((i, reader.read(item_ty)?));
+                }
+            }
+        }
+    }
+
+    reader.end_element()
+}
+
+fn parse_header(data: &[u8]) -> IoResult<(Format, Vec<Element>, usize, usize)> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut pos = 0;
+    let mut l = 0;
+
+    loop {
+        if pos >= data.len() {
+            return error("Truncated ply header: missing `end_header`.".to_string());
+        }
+
+        let end = data[pos..]
+            .iter()
+            .position(|c| *c == b'\n')
+            .map(|i| pos + i)
+            .unwrap_or(data.len());
+        let line = match std::str::from_utf8(&data[pos..end]) {
+            Ok(line) => line,
+            Err(_) => return error(format!("At line {}: invalid utf8 on the header.", l + 1)),
+        };
+        pos = end + 1;
+        l += 1;
+
+        let mut words = obj::split_words(line);
+
+        match words.next() {
+            Some("ply") if l == 1 => {}
+            _ if l == 1 => return error("Not a ply file: missing the `ply` magic.".to_string()),
+            Some("format") => {
+                format = match words.next() {
+                    Some("ascii") => Some(Format::Ascii),
+                    Some("binary_little_endian") => Some(Format::BinaryLittleEndian),
+                    Some("binary_big_endian") => Some(Format::BinaryBigEndian),
+                    _ => return error(format!("At line {}: unknown format.", l)),
+                }
+            }
+            Some("element") => {
+                let name = words.next();
+                let count = words.next().and_then(|c| c.parse::<usize>().ok());
+
+                match (name, count) {
+                    (Some(name), Some(count)) => elements.push(Element {
+                        name: name.to_string(),
+                        count,
+                        properties: Vec::new(),
+                    }),
+                    _ => return error(format!("At line {}: invalid element declaration.", l)),
+                }
+            }
+            Some("property") => {
+                let element = match elements.last_mut() {
+                    Some(element) => element,
+                    None => {
+                        return error(format!("At line {}: property outside of an element.", l))
+                    }
+                };
+                let ty = words.next();
+                let ty = if ty == Some("list") {
+                    match (
+                        words.next().and_then(Scalar::parse),
+                        words.next().and_then(Scalar::parse),
+                    ) {
+                        (Some(len_ty), Some(item_ty)) => PropertyType::List(len_ty, item_ty),
+                        _ => return error(format!("At line {}: invalid list property type.", l)),
+                    }
+                } else {
+                    match ty.and_then(Scalar::parse) {
+                        Some(ty) => PropertyType::Scalar(ty),
+                        None => return error(format!("At line {}: invalid property type.", l)),
+                    }
+                };
+
+                match words.next() {
+                    Some(name) => element.properties.push(Property {
+                        name: name.to_string(),
+                        ty,
+                    }),
+                    None => return error(format!("At line {}: unnamed property.", l)),
+                }
+            }
+            Some("end_header") => break,
+            _ => {} // comment, obj_info, etc.
+        }
+    }
+
+    match format {
+        Some(format) => Ok((format, elements, pos.min(data.len()), l)),
+        None => error("Missing format declaration on the ply header.".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, PlyMesh};
+    use na::Point3;
+
+    const HEADER: &str = "element vertex 5
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 2
+property list uchar int vertex_indices
+end_header
+";
+
+    fn check(ply: &PlyMesh) {
+        assert_eq!(
+            ply.coords,
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+                Point3::new(2.0, 0.0, 0.0),
+                Point3::new(2.0, 1.0, 0.0),
+            ]
+        );
+        assert_eq!(ply.colors.as_ref().unwrap()[1], Point3::new(1.0, 0.0, 0.0));
+        assert!(ply.normals.is_none());
+        // The quad is triangulated as a fan.
+        assert_eq!(
+            ply.faces,
+            Some(vec![
+                Point3::new(0, 1, 2),
+                Point3::new(1, 3, 4),
+                Point3::new(1, 4, 2),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_ascii() {
+        let data = format!(
+            "ply\nformat ascii 1.0\n{}{}",
+            HEADER,
+            "0 0 0 255 255 255
+1 0 0 255 0 0
+0 1 0 0 255 0
+2 0 0 0 0 255
+2 1 0 0 0 0
+3 0 1 2
+4 1 3 4 2
+"
+        );
+
+        check(&parse(data.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn parse_binary_little_endian() {
+        let mut data = format!("ply\nformat binary_little_endian 1.0\n{}", HEADER).into_bytes();
+        let vertices = [
+            ([0.0f32, 0.0, 0.0], [255u8, 255, 255]),
+            ([1.0, 0.0, 0.0], [255, 0, 0]),
+            ([0.0, 1.0, 0.0], [0, 255, 0]),
+            ([2.0, 0.0, 0.0], [0, 0, 255]),
+            ([2.0, 1.0, 0.0], [0, 0, 0]),
+        ];
+
+        for (coords, color) in vertices.iter() {
+            for coord in coords.iter() {
+                data.extend_from_slice(&coord.to_le_bytes());
+            }
+
+            data.extend_from_slice(color);
+        }
+
+        for face in [&[0i32, 1, 2][..], &[1, 3, 4, 2][..]].iter() {
+            data.push(face.len() as u8);
+
+            for i in face.iter() {
+                data.extend_from_slice(&i.to_le_bytes());
+            }
+        }
+
+        check(&parse(&data).unwrap());
+    }
+}
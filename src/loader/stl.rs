@@ -0,0 +1,297 @@
+//! Simplistic stl loader.
+//!
+//! Both the ASCII and the binary variants are supported. Because stl files do not store any
+//! topology, vertices closer than a given epsilon are merged together into an indexed mesh.
+
+use crate::loader::obj;
+use crate::resource::Mesh;
+use na::Point3;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::io::{Error, ErrorKind, Result as IoResult};
+use std::path::Path;
+use std::str::FromStr;
+
+/// The default distance under which two vertices are merged.
+pub const DEFAULT_WELD_EPSILON: f32 = 1.0e-5;
+
+fn error<T>(err: String) -> IoResult<T> {
+    Err(Error::new(ErrorKind::InvalidData, err))
+}
+
+/// Parses a stl file, merging vertices closer than `DEFAULT_WELD_EPSILON`.
+pub fn load(path: &Path) -> IoResult<Mesh> {
+    load_with_weld_epsilon(path, DEFAULT_WELD_EPSILON)
+}
+
+/// Parses a stl file, merging vertices closer than `weld_epsilon`.
+///
+/// If `weld_epsilon` is zero, only vertices with exactly the same coordinates are merged.
+pub fn load_with_weld_epsilon(path: &Path, weld_epsilon: f32) -> IoResult<Mesh> {
+    let mut file = File::open(path)?;
+    let mut data = Vec::new();
+    let _ = file.read_to_end(&mut data)?;
+
+    parse(&data[..], weld_epsilon)
+}
+
+/// Parses the content of a stl file, merging vertices closer than `weld_epsilon`.
+pub fn parse(data: &[u8], weld_epsilon: f32) -> IoResult<Mesh> {
+    let triangles = if is_binary(data) {
+        parse_binary(data)?
+    } else {
+        match std::str::from_utf8(data) {
+            Ok(string) if string.trim_start().starts_with("solid") => parse_ascii(string)?,
+            _ => {
+                // This is neither a valid ascii file, nor a binary file of the right size.
+                if data.len() < 84 {
+                    return error(format!(
+                        "Truncated binary stl file: {} bytes found, the header alone needs 84 bytes.",
+                        data.len()
+                    ));
+                }
+
+                return error(format!(
+                    "Truncated binary stl file: {} bytes expected for {} triangles, {} bytes found.",
+                    binary_len(data),
+                    triangle_count(data),
+                    data.len()
+                ));
+            }
+        }
+    };
+
+    weld(triangles, weld_epsilon)
+}
+
+fn triangle_count(data: &[u8]) -> usize {
+    u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize
+}
+
+fn binary_len(data: &[u8]) -> usize {
+    84 + triangle_count(data) * 50
+}
+
+// NOTE: we don't trust the `solid` keyword since many exporters write it on binary files too.
+fn is_binary(data: &[u8]) -> bool {
+    data.len() >= 84 && binary_len(data) == data.len()
+}
+
+fn parse_binary(data: &[u8]) -> IoResult<Vec<[Point3<f32>; 3]>> {
+    let read_f32 = |i: usize| f32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    let read_point = |i: usize| Point3::new(read_f32(i), read_f32(i + 4), read_f32(i + 8));
+    let mut res = Vec::with_capacity(triangle_count(data));
+
+    for t in 0..triangle_count(data) {
+        // Skip the normal (12 bytes) and ignore the attribute byte count (2 bytes).
+        let start = 84 + t * 50 + 12;
+        res.push([
+            read_point(start),
+            read_point(start + 12),
+            read_point(start + 24),
+        ]);
+    }
+
+    Ok(res)
+}
+
+fn parse_ascii(string: &str) -> IoResult<Vec<[Point3<f32>; 3]>> {
+    let mut res = Vec::new();
+    let mut curr_triangle = Vec::new();
+    let mut in_facet = false;
+    let mut ended = false;
+
+    for (l, line) in string.lines().enumerate() {
+        let mut words = obj::split_words(line);
+
+        match words.next() {
+            Some("facet") => {
+                if in_facet {
+                    return error(format!("At line {}: nested facet.", l + 1));
+                }
+                in_facet = true;
+                curr_triangle.clear();
+            }
+            Some("vertex") => {
+                if !in_facet {
+                    return error(format!("At line {}: vertex outside of a facet.", l + 1));
+                }
+
+                let mut coords = [0.0f32; 3];
+
+                for coord in coords.iter_mut() {
+                    *coord = match words.next().map(f32::from_str) {
+                        Some(Ok(c)) => c,
+                        _ => {
+                            return error(format!("At line {}: invalid vertex coordinates.", l + 1))
+                        }
+                    };
+                }
+
+                curr_triangle.push(Point3::new(coords[0], coords[1], coords[2]));
+            }
+            Some("endfacet") => {
+                if !in_facet || curr_triangle.len() != 3 {
+                    return error(format!(
+                        "At line {}: facets must have exactly three vertices.",
+                        l + 1
+                    ));
+                }
+                in_facet = false;
+                res.push([curr_triangle[0], curr_triangle[1], curr_triangle[2]]);
+            }
+            Some("endsolid") => {
+                ended = true;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if in_facet || !ended {
+        return error("Truncated ascii stl file: missing `endsolid`.".to_string());
+    }
+
+    Ok(res)
+}
+
+fn weld(triangles: Vec<[Point3<f32>; 3]>, weld_epsilon: f32) -> IoResult<Mesh> {
+    let mut coords: Vec<Point3<f32>> = Vec::new();
+    let mut faces = Vec::with_capacity(triangles.len());
+    let mut grid: HashMap<[i64; 3], Vec<u16>> = HashMap::new();
+    let epsilon = weld_epsilon.max(0.0);
+    let cell_size = if epsilon > 0.0 { epsilon } else { 1.0 };
+
+    let cell = |pt: &Point3<f32>| {
+        [
+            (pt.x / cell_size).floor() as i64,
+            (pt.y / cell_size).floor() as i64,
+            (pt.z / cell_size).floor() as i64,
+        ]
+    };
+
+    for triangle in triangles.iter() {
+        let mut face = [0u16; 3];
+
+        for (id, pt) in face.iter_mut().zip(triangle.iter()) {
+            let key = cell(pt);
+            let mut found = None;
+
+            // Look for an existing vertex on the neighboring cells.
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let neighbor = [key[0] + dx, key[1] + dy, key[2] + dz];
+
+                        for i in grid.get(&neighbor).into_iter().flatten() {
+                            if na::distance(&coords[*i as usize], pt) <= epsilon {
+                                found = Some(*i);
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+
+            *id = match found {
+                Some(i) => i,
+                None => {
+                    if coords.len() > u16::MAX as usize {
+                        return error(format!(
+                            "Too many distinct vertices: at most {} are supported.",
+                            u16::MAX as usize + 1
+                        ));
+                    }
+
+                    let i = coords.len() as u16;
+                    coords.push(*pt);
+
+                    grid.entry(key).or_default().push(i);
+
+                    i
+                }
+            };
+        }
+
+        faces.push(Point3::new(face[0], face[1], face[2]));
+    }
+
+    // Normals are computed from the faces: vertices that were not merged get the normal of their
+    // only face, merged ones get the average of their adjacent faces normals.
+    Ok(Mesh::new(coords, faces, None, None, false))
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+    use na::Point3;
+
+    fn binary_facet() -> Vec<u8> {
+        let mut data = vec![0u8; 80];
+        data.extend_from_slice(&1u32.to_le_bytes());
+
+        for coord in &[
+            0.0f32, 0.0, 1.0, // normal
+            0.0, 0.0, 0.0, // first vertex
+            1.0, 0.0, 0.0, // second vertex
+            0.0, 1.0, 0.0, // third vertex
+        ] {
+            data.extend_from_slice(&coord.to_le_bytes());
+        }
+
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
+    #[test]
+    fn parse_ascii_facet() {
+        let data = "solid triangle
+            facet normal 0 0 1
+              outer loop
+                vertex 0 0 0
+                vertex 1 0 0
+                vertex 0 1 0
+              endloop
+            endfacet
+            endsolid triangle";
+        let mesh = parse(data.as_bytes(), 0.0).unwrap();
+        let coords = mesh.coords().read().unwrap();
+        let faces = mesh.faces().read().unwrap();
+
+        assert_eq!(
+            coords.data().as_ref().unwrap(),
+            &vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ]
+        );
+        assert_eq!(faces.data().as_ref().unwrap(), &vec![Point3::new(0, 1, 2)]);
+    }
+
+    #[test]
+    fn parse_binary_facet() {
+        let mesh = parse(&binary_facet(), 0.0).unwrap();
+        let coords = mesh.coords().read().unwrap();
+        let faces = mesh.faces().read().unwrap();
+
+        assert_eq!(
+            coords.data().as_ref().unwrap(),
+            &vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ]
+        );
+        assert_eq!(faces.data().as_ref().unwrap(), &vec![Point3::new(0, 1, 2)]);
+    }
+
+    #[test]
+    fn truncated_binary_is_an_error() {
+        let data = binary_facet();
+
+        assert!(parse(&data[..data.len() - 10], 0.0).is_err());
+        assert!(parse(&data[..40], 0.0).is_err());
+    }
+}
@@ -17,6 +17,8 @@ pub struct PlanarLineRenderer {
     proj: ShaderUniform<Matrix3<f32>>,
     colors: GPUVec<Point3<f32>>,
     lines: GPUVec<Point2<f32>>,
+    polylines: Vec<(usize, Vec<Point2<f32>>, Point3<f32>)>,
+    next_polyline_id: usize,
 }
 
 impl PlanarLineRenderer {
@@ -29,6 +31,8 @@ impl PlanarLineRenderer {
         PlanarLineRenderer {
             lines: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
             colors: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
+            polylines: Vec::new(),
+            next_polyline_id: 0,
             pos: shader
                 .get_attrib::<Point2<f32>>("position")
                 .expect("Failed to get shader attribute."),
@@ -47,7 +51,7 @@ impl PlanarLineRenderer {
 
     /// Indicates whether some lines have to be drawn.
     pub fn needs_rendering(&self) -> bool {
-        self.lines.len() != 0
+        self.lines.len() != 0 || !self.polylines.is_empty()
     }
 
     /// Adds a line to be drawn during the next frame. Lines are not persistent between frames.
@@ -63,8 +67,49 @@ impl PlanarLineRenderer {
         }
     }
 
+    /// Adds a polyline drawn at each frame until it is removed.
+    ///
+    /// Each point of `points` is linked to the next one. Returns the identifier of the polyline,
+    /// to be given to `remove_polyline`.
+    pub fn add_polyline(&mut self, points: Vec<Point2<f32>>, color: Point3<f32>) -> usize {
+        let id = self.next_polyline_id;
+        self.next_polyline_id += 1;
+        self.polylines.push((id, points, color));
+
+        id
+    }
+
+    /// Removes the polyline with the identifier `id`.
+    ///
+    /// Returns `false` if there was no such polyline.
+    pub fn remove_polyline(&mut self, id: usize) -> bool {
+        let len = self.polylines.len();
+        self.polylines.retain(|p| p.0 != id);
+
+        self.polylines.len() != len
+    }
+
+    /// Removes all the polylines.
+    pub fn clear_polylines(&mut self) {
+        self.polylines.clear()
+    }
+
     /// Actually draws the lines.
     pub fn render(&mut self, camera: &mut dyn PlanarCamera) {
+        // The polylines are batched with the lines of the current frame.
+        for (_, points, color) in self.polylines.iter() {
+            for segment in points.windows(2) {
+                for lines in self.lines.data_mut().iter_mut() {
+                    lines.push(segment[0]);
+                    lines.push(segment[1]);
+                }
+                for colors in self.colors.data_mut().iter_mut() {
+                    colors.push(*color);
+                    colors.push(*color);
+                }
+            }
+        }
+
         if self.lines.len() == 0 {
             return;
         }
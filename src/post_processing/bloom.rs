@@ -0,0 +1,330 @@
+//! Post-processing effect to make the bright parts of the scene glow.
+
+use na::Vector2;
+
+use crate::context::{Context, Texture};
+use crate::post_processing::post_processing_effect::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, FramebufferManager, GPUVec, RenderTarget, ShaderAttribute,
+    ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Post processing effect which adds a glow around the brightest parts of the scene.
+///
+/// The pixels brighter than a threshold are extracted, blurred in framebuffers at half the window
+/// resolution, and added back on top of the scene.
+pub struct Bloom {
+    threshold: f32,
+    intensity: f32,
+    passes: usize,
+    size: Vector2<f32>,
+    bloom_ready: bool,
+    bright_pass: Pass,
+    blur_pass: Pass,
+    composite_pass: Pass,
+    gl_threshold: ShaderUniform<f32>,
+    gl_direction: ShaderUniform<Vector2<f32>>,
+    gl_intensity: ShaderUniform<f32>,
+    gl_bloom_texture: ShaderUniform<i32>,
+    targets: [RenderTarget; 2],
+    fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl Bloom {
+    /// Creates a new `Bloom` post processing effect.
+    ///
+    /// By default, the luminance threshold is 0.8, the intensity 1.0, and 4 blur passes are
+    /// performed.
+    pub fn new() -> Bloom {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let bright_pass = Pass::new(BRIGHT_PASS_FRAGMENT_SHADER);
+        let gl_threshold = bright_pass.shader.get_uniform("threshold").unwrap();
+        let blur_pass = Pass::new(BLUR_FRAGMENT_SHADER);
+        let gl_direction = blur_pass.shader.get_uniform("direction").unwrap();
+        let composite_pass = Pass::new(COMPOSITE_FRAGMENT_SHADER);
+        let gl_intensity = composite_pass.shader.get_uniform("intensity").unwrap();
+        let gl_bloom_texture = composite_pass.shader.get_uniform("bloom_texture").unwrap();
+
+        // The actual size of the targets is set at the first update.
+        let targets = [
+            FramebufferManager::new_scaled_render_target(1, 1, 0.5, false),
+            FramebufferManager::new_scaled_render_target(1, 1, 0.5, false),
+        ];
+
+        Bloom {
+            threshold: 0.8,
+            intensity: 1.0,
+            passes: 4,
+            size: na::zero(),
+            bloom_ready: false,
+            bright_pass,
+            blur_pass,
+            composite_pass,
+            gl_threshold,
+            gl_direction,
+            gl_intensity,
+            gl_bloom_texture,
+            targets,
+            fbo_vertices,
+        }
+    }
+
+    /// The luminance above which a pixel starts glowing.
+    #[inline]
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Sets the luminance above which a pixel starts glowing.
+    #[inline]
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// The factor applied to the glow before adding it to the scene.
+    #[inline]
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Sets the factor applied to the glow before adding it to the scene.
+    #[inline]
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+
+    /// The number of blur passes.
+    #[inline]
+    pub fn passes(&self) -> usize {
+        self.passes
+    }
+
+    /// Sets the number of blur passes.
+    ///
+    /// Each pass blurs the glow horizontally then vertically, making it wider.
+    #[inline]
+    pub fn set_passes(&mut self, passes: usize) {
+        self.passes = passes;
+    }
+
+    fn compute_bloom(
+        &mut self,
+        framebuffer_manager: &mut FramebufferManager,
+        scene: &RenderTarget,
+    ) {
+        let ctxt = Context::get();
+        let (w, h) = self.targets[0].size().unwrap();
+
+        verify!(ctxt.viewport(0, 0, w as i32, h as i32));
+
+        // Extract the bright pixels.
+        framebuffer_manager.select(&self.targets[0]);
+        self.bright_pass.shader.use_program();
+        self.gl_threshold.upload(&self.threshold);
+        self.bright_pass
+            .draw(&mut self.fbo_vertices, scene.texture_id());
+
+        // Blur them.
+        for _ in 0..self.passes {
+            framebuffer_manager.select(&self.targets[1]);
+            self.blur_pass.shader.use_program();
+            self.gl_direction.upload(&Vector2::new(1.0 / w as f32, 0.0));
+            self.blur_pass
+                .draw(&mut self.fbo_vertices, self.targets[0].texture_id());
+
+            framebuffer_manager.select(&self.targets[0]);
+            self.blur_pass.shader.use_program();
+            self.gl_direction.upload(&Vector2::new(0.0, 1.0 / h as f32));
+            self.blur_pass
+                .draw(&mut self.fbo_vertices, self.targets[1].texture_id());
+        }
+
+        self.bloom_ready = true;
+    }
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom::new()
+    }
+}
+
+impl PostProcessingEffect for Bloom {
+    fn update(&mut self, _: f32, w: f32, h: f32, _: f32, _: f32) {
+        let size = Vector2::new(w, h);
+
+        if size != self.size {
+            self.size = size;
+            self.bloom_ready = false;
+
+            for target in self.targets.iter_mut() {
+                target.resize(w, h);
+            }
+        }
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+
+        // Adds the glow computed by the last `draw_to`, if any.
+        let intensity = if self.bloom_ready {
+            self.intensity
+        } else {
+            0.0
+        };
+
+        verify!(ctxt.viewport(0, 0, self.size.x as i32, self.size.y as i32));
+
+        self.composite_pass.shader.use_program();
+        self.gl_intensity.upload(&intensity);
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, self.targets[0].texture_id()));
+        self.gl_bloom_texture.upload(&1);
+
+        self.composite_pass
+            .draw(&mut self.fbo_vertices, target.texture_id());
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+    }
+
+    fn draw_to(
+        &mut self,
+        framebuffer_manager: &mut FramebufferManager,
+        target: &RenderTarget,
+        output: &RenderTarget,
+    ) {
+        if self.size.x > 0.0 && self.size.y > 0.0 {
+            self.compute_bloom(framebuffer_manager, target);
+        }
+
+        framebuffer_manager.select(output);
+        self.draw(target)
+    }
+}
+
+// One of the full-screen passes of the bloom effect.
+struct Pass {
+    shader: Effect,
+    fbo_texture: ShaderUniform<i32>,
+    v_coord: ShaderAttribute<Vector2<f32>>,
+}
+
+impl Pass {
+    fn new(fragment_shader: &str) -> Pass {
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, fragment_shader);
+
+        shader.use_program();
+
+        Pass {
+            fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            shader,
+        }
+    }
+
+    // The shader must already be in use.
+    fn draw(&mut self, fbo_vertices: &mut GPUVec<Vector2<f32>>, texture: Option<&Texture>) {
+        let ctxt = Context::get();
+        self.v_coord.enable();
+
+        verify!(ctxt.clear_color(0.0, 0.0, 0.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, texture));
+
+        self.fbo_texture.upload(&0);
+        self.v_coord.bind(fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.v_coord.disable();
+    }
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec2    v_coord;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+static BRIGHT_PASS_FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform float     threshold;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      vec3  color = texture2D(fbo_texture, f_texcoord).rgb;
+      float luma  = dot(color, vec3(0.2126, 0.7152, 0.0722));
+      // Keep only the part of the color above the threshold.
+      float ratio = max(luma - threshold, 0.0) / max(luma, 0.0001);
+      gl_FragColor = vec4(color * ratio, 1.0);
+    }";
+
+// A 9-tap gaussian blur along `direction`, which is the size of a texel along x or y.
+static BLUR_FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform vec2      direction;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      vec3 color = texture2D(fbo_texture, f_texcoord).rgb * 0.227027;
+      color += texture2D(fbo_texture, f_texcoord + direction * 1.0).rgb * 0.1945946;
+      color += texture2D(fbo_texture, f_texcoord - direction * 1.0).rgb * 0.1945946;
+      color += texture2D(fbo_texture, f_texcoord + direction * 2.0).rgb * 0.1216216;
+      color += texture2D(fbo_texture, f_texcoord - direction * 2.0).rgb * 0.1216216;
+      color += texture2D(fbo_texture, f_texcoord + direction * 3.0).rgb * 0.054054;
+      color += texture2D(fbo_texture, f_texcoord - direction * 3.0).rgb * 0.054054;
+      color += texture2D(fbo_texture, f_texcoord + direction * 4.0).rgb * 0.016216;
+      color += texture2D(fbo_texture, f_texcoord - direction * 4.0).rgb * 0.016216;
+      gl_FragColor = vec4(color, 1.0);
+    }";
+
+static COMPOSITE_FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform sampler2D bloom_texture;
+    uniform float     intensity;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      vec4 color = texture2D(fbo_texture, f_texcoord);
+      vec3 bloom = texture2D(bloom_texture, f_texcoord).rgb;
+      gl_FragColor = vec4(color.rgb + bloom * intensity, color.a);
+    }";
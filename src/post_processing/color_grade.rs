@@ -0,0 +1,216 @@
+//! Post-processing effect to darken the borders of the screen and remap its colors.
+
+use na::Vector2;
+use std::rc::Rc;
+
+use crate::context::{Context, Texture};
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
+    TextureFilter, TextureWrapping,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Post processing effect which remaps the colors of the scene with a lookup table then darkens
+/// its borders.
+///
+/// Without vignette nor lookup table, which is the default, the scene is drawn unchanged.
+pub struct ColorGrade {
+    vignette_intensity: f32,
+    vignette_radius: f32,
+    lut: Option<(Rc<Texture>, u32)>,
+    shader: Effect,
+    fbo_texture: ShaderUniform<i32>,
+    lut_texture: ShaderUniform<i32>,
+    lut_size: ShaderUniform<f32>,
+    gl_vignette_intensity: ShaderUniform<f32>,
+    gl_vignette_radius: ShaderUniform<f32>,
+    v_coord: ShaderAttribute<Vector2<f32>>,
+    fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl ColorGrade {
+    /// Creates a new `ColorGrade` post processing effect.
+    pub fn new() -> ColorGrade {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        ColorGrade {
+            vignette_intensity: 0.0,
+            vignette_radius: 0.5,
+            lut: None,
+            fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            lut_texture: shader.get_uniform("lut_texture").unwrap(),
+            lut_size: shader.get_uniform("lut_size").unwrap(),
+            gl_vignette_intensity: shader.get_uniform("vignette_intensity").unwrap(),
+            gl_vignette_radius: shader.get_uniform("vignette_radius").unwrap(),
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            fbo_vertices,
+            shader,
+        }
+    }
+
+    /// The intensity and radius of the vignette.
+    #[inline]
+    pub fn vignette(&self) -> (f32, f32) {
+        (self.vignette_intensity, self.vignette_radius)
+    }
+
+    /// Sets the intensity and radius of the vignette.
+    ///
+    /// The screen is darkened from `radius` to its corners, where the colors are multiplied by
+    /// `1.0 - intensity`. The radius is relative to the distance from the center of the screen to
+    /// its corners, so `0.0` is the center and `1.0` the corners, and must be smaller than `1.0`.
+    /// The vignette is disabled with an intensity of `0.0`, which is the default.
+    #[inline]
+    pub fn set_vignette(&mut self, intensity: f32, radius: f32) {
+        self.vignette_intensity = intensity;
+        self.vignette_radius = radius;
+    }
+
+    /// The lookup table used to remap the colors of the scene, and its size.
+    #[inline]
+    pub fn lut(&self) -> Option<(&Rc<Texture>, u32)> {
+        self.lut.as_ref().map(|(texture, size)| (texture, *size))
+    }
+
+    /// Sets the lookup table used to remap the colors of the scene.
+    ///
+    /// The table is a 3D texture flattened into a 2D texture of `size * size` by `size` texels,
+    /// made of `size` squares ordered by increasing blue from left to right. In each square, the
+    /// red increases from left to right and the green from top to bottom, i.e., the texture of a
+    /// lookup table leaving the colors unchanged is made of increasingly blue gradients. The
+    /// colors are interpolated between the entries of the table, so the texture is set to use
+    /// linear filtering without mipmaps.
+    ///
+    /// # Failure
+    /// Fails if `size` is smaller than 2.
+    pub fn set_lut(&mut self, lut: Option<Rc<Texture>>, size: u32) {
+        assert!(size >= 2, "A lookup table must have at least 2 entries.");
+
+        if let Some(ref texture) = lut {
+            texture.set_filtering(TextureFilter::Linear, TextureFilter::Linear);
+            texture.set_wrap(TextureWrapping::ClampToEdge, TextureWrapping::ClampToEdge);
+        }
+
+        self.lut = lut.map(|texture| (texture, size));
+    }
+}
+
+impl Default for ColorGrade {
+    fn default() -> Self {
+        ColorGrade::new()
+    }
+}
+
+impl PostProcessingEffect for ColorGrade {
+    fn update(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.v_coord.enable();
+
+        self.shader.use_program();
+        verify!(ctxt.clear_color(0.0, 0.0, 0.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+
+        self.gl_vignette_intensity.upload(&self.vignette_intensity);
+        self.gl_vignette_radius.upload(&self.vignette_radius);
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+
+        // A size of zero disables the lookup table.
+        match self.lut {
+            Some((ref texture, size)) => {
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&**texture)));
+                self.lut_size.upload(&(size as f32));
+            }
+            None => {
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+                self.lut_size.upload(&0.0);
+            }
+        }
+
+        self.lut_texture.upload(&1);
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+        self.fbo_texture.upload(&0);
+        self.v_coord.bind(&mut self.fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+
+        self.v_coord.disable();
+    }
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec2    v_coord;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+static FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform sampler2D lut_texture;
+    uniform float     lut_size;
+    uniform float     vignette_intensity;
+    uniform float     vignette_radius;
+    varying vec2      f_texcoord;
+
+    vec3 lookup(vec3 color) {
+      float n     = lut_size;
+      float blue  = color.b * (n - 1.0);
+      float slice = floor(blue);
+      // The centers of the texels of the red and green entries in the first square.
+      vec2 uv = vec2((color.r * (n - 1.0) + 0.5) / (n * n), (color.g * (n - 1.0) + 0.5) / n);
+      vec3 lower = texture2D(lut_texture, uv + vec2(slice / n, 0.0)).rgb;
+      vec3 upper = texture2D(lut_texture, uv + vec2(min(slice + 1.0, n - 1.0) / n, 0.0)).rgb;
+      return mix(lower, upper, blue - slice);
+    }
+
+    void main(void) {
+      vec4 color = texture2D(fbo_texture, f_texcoord);
+      vec3 rgb   = color.rgb;
+
+      if (lut_size > 0.0) {
+        rgb = lookup(clamp(rgb, 0.0, 1.0));
+      }
+
+      if (vignette_intensity > 0.0) {
+        // The distance to the center, from 0.0 at the center to 1.0 at the corners.
+        float dist = length(f_texcoord - 0.5) * 1.41421356;
+        rgb *= 1.0 - vignette_intensity * smoothstep(vignette_radius, 1.0, dist);
+      }
+
+      gl_FragColor = vec4(rgb, color.a);
+    }";
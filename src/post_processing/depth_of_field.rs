@@ -0,0 +1,322 @@
+//! Post-processing effect to blur the parts of the scene out of focus.
+
+use na::Vector2;
+
+use crate::camera;
+use crate::context::{Context, Texture};
+use crate::post_processing::post_processing_effect::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, FramebufferManager, GPUVec, RenderTarget, ShaderAttribute,
+    ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Post processing effect which blurs the parts of the scene far from the focus distance.
+///
+/// The blur of each pixel, or circle of confusion, is computed from the depth of the scene and
+/// increases linearly from zero at the focus distance to its maximum at `focal_range` in front of
+/// or behind it. The scene is then blurred horizontally and vertically, each sample being weighted
+/// by its own blur so the objects in focus do not bleed into their blurred surroundings.
+///
+/// This needs the depth of the scene, which is not available with WebGL.
+pub struct DepthOfField {
+    focus_distance: f32,
+    focal_range: f32,
+    max_blur: f32,
+    znear: f32,
+    zfar: f32,
+    size: Vector2<f32>,
+    blur_ready: bool,
+    shader: Effect,
+    gl_fbo_texture: ShaderUniform<i32>,
+    gl_fbo_depth: ShaderUniform<i32>,
+    gl_use_depth: ShaderUniform<f32>,
+    gl_direction: ShaderUniform<Vector2<f32>>,
+    gl_znear: ShaderUniform<f32>,
+    gl_zfar: ShaderUniform<f32>,
+    gl_reversed_z: ShaderUniform<f32>,
+    gl_focus_distance: ShaderUniform<f32>,
+    gl_focal_range: ShaderUniform<f32>,
+    gl_max_blur: ShaderUniform<f32>,
+    gl_v_coord: ShaderAttribute<Vector2<f32>>,
+    // The scene blurred horizontally, with the circle of confusion of each pixel as its alpha.
+    target: RenderTarget,
+    fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl DepthOfField {
+    /// Creates a new `DepthOfField` post processing effect.
+    ///
+    /// By default, the focus distance is 10.0, the focal range 10.0, and the maximum blur 8
+    /// pixels.
+    pub fn new() -> DepthOfField {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        DepthOfField {
+            focus_distance: 10.0,
+            focal_range: 10.0,
+            max_blur: 8.0,
+            znear: 0.1,
+            zfar: 1024.0,
+            size: na::zero(),
+            blur_ready: false,
+            gl_fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            gl_fbo_depth: shader.get_uniform("fbo_depth").unwrap(),
+            gl_use_depth: shader.get_uniform("use_depth").unwrap(),
+            gl_direction: shader.get_uniform("direction").unwrap(),
+            gl_znear: shader.get_uniform("znear").unwrap(),
+            gl_zfar: shader.get_uniform("zfar").unwrap(),
+            gl_reversed_z: shader.get_uniform("reversed_z").unwrap(),
+            gl_focus_distance: shader.get_uniform("focus_distance").unwrap(),
+            gl_focal_range: shader.get_uniform("focal_range").unwrap(),
+            gl_max_blur: shader.get_uniform("max_blur").unwrap(),
+            gl_v_coord: shader.get_attrib("v_coord").unwrap(),
+            // The actual size of the target is set at the first update.
+            target: FramebufferManager::new_render_target(1, 1, false),
+            fbo_vertices,
+            shader,
+        }
+    }
+
+    /// The distance from the camera at which the scene is sharp.
+    #[inline]
+    pub fn focus_distance(&self) -> f32 {
+        self.focus_distance
+    }
+
+    /// Sets the distance from the camera at which the scene is sharp.
+    #[inline]
+    pub fn set_focus_distance(&mut self, distance: f32) {
+        self.focus_distance = distance;
+    }
+
+    /// The distance from the focus distance at which the blur reaches its maximum.
+    #[inline]
+    pub fn focal_range(&self) -> f32 {
+        self.focal_range
+    }
+
+    /// Sets the distance from the focus distance at which the blur reaches its maximum.
+    ///
+    /// Smaller ranges simulate larger apertures.
+    #[inline]
+    pub fn set_focal_range(&mut self, range: f32) {
+        self.focal_range = range.max(1.0e-6);
+    }
+
+    /// The radius of the blur of the parts of the scene the most out of focus, in pixels.
+    #[inline]
+    pub fn max_blur(&self) -> f32 {
+        self.max_blur
+    }
+
+    /// Sets the radius of the blur of the parts of the scene the most out of focus, in pixels.
+    #[inline]
+    pub fn set_max_blur(&mut self, radius: f32) {
+        self.max_blur = radius.max(0.0);
+    }
+
+    // Blurs `input`, or the horizontally blurred scene if `None`, along `direction`, a texel
+    // along x or y. The circles of confusion are computed from `depth` if given, and read from
+    // the alpha of the input otherwise.
+    fn blur(
+        &mut self,
+        input: Option<&RenderTarget>,
+        depth: Option<&Texture>,
+        direction: Vector2<f32>,
+        max_blur: f32,
+    ) {
+        let ctxt = Context::get();
+        let texture = match input {
+            Some(input) => input.texture_id(),
+            None => self.target.texture_id(),
+        };
+        self.gl_v_coord.enable();
+        self.shader.use_program();
+
+        verify!(ctxt.clear_color(0.0, 0.0, 0.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+
+        self.gl_use_depth
+            .upload(&if depth.is_some() { 1.0 } else { 0.0 });
+        self.gl_direction.upload(&direction);
+        self.gl_znear.upload(&self.znear);
+        // The far plane may be at infinity, which the shader handles as the largest float.
+        self.gl_zfar.upload(&self.zfar.min(f32::MAX));
+        self.gl_reversed_z
+            .upload(&(camera::reversed_z() as i32 as f32));
+        self.gl_focus_distance.upload(&self.focus_distance);
+        self.gl_focal_range.upload(&self.focal_range);
+        self.gl_max_blur.upload(&max_blur);
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, depth));
+        self.gl_fbo_depth.upload(&1);
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, texture));
+        self.gl_fbo_texture.upload(&0);
+
+        self.gl_v_coord.bind(&mut self.fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+
+        self.gl_v_coord.disable();
+    }
+}
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        DepthOfField::new()
+    }
+}
+
+impl PostProcessingEffect for DepthOfField {
+    fn update(&mut self, _: f32, w: f32, h: f32, znear: f32, zfar: f32) {
+        let size = Vector2::new(w, h);
+
+        self.znear = znear;
+        self.zfar = zfar;
+
+        if size != self.size {
+            self.size = size;
+            self.blur_ready = false;
+            self.target.resize(w, h);
+        }
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+
+        verify!(ctxt.viewport(0, 0, self.size.x as i32, self.size.y as i32));
+
+        // Blurs vertically the result of the last `draw_to_with_depth`, if any.
+        if self.blur_ready {
+            let direction = Vector2::new(0.0, 1.0 / self.size.y);
+            let max_blur = self.max_blur;
+            self.blur(None, None, direction, max_blur);
+        } else {
+            self.blur(Some(target), None, Vector2::zeros(), 0.0);
+        }
+    }
+
+    fn draw_to(
+        &mut self,
+        framebuffer_manager: &mut FramebufferManager,
+        target: &RenderTarget,
+        output: &RenderTarget,
+    ) {
+        self.draw_to_with_depth(framebuffer_manager, target, target, output)
+    }
+
+    fn draw_to_with_depth(
+        &mut self,
+        framebuffer_manager: &mut FramebufferManager,
+        target: &RenderTarget,
+        scene: &RenderTarget,
+        output: &RenderTarget,
+    ) {
+        let depth = scene.depth_id().and_then(|id| id.as_ref().left());
+        self.blur_ready = false;
+
+        if self.size.x > 0.0 && self.size.y > 0.0 && depth.is_some() {
+            let ctxt = Context::get();
+            verify!(ctxt.viewport(0, 0, self.size.x as i32, self.size.y as i32));
+
+            framebuffer_manager.select(&self.target);
+            let direction = Vector2::new(1.0 / self.size.x, 0.0);
+            let max_blur = self.max_blur;
+            self.blur(Some(target), depth, direction, max_blur);
+            self.blur_ready = true;
+        }
+
+        framebuffer_manager.select(output);
+        self.draw(target)
+    }
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec2    v_coord;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+static FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform sampler2D fbo_depth;
+    uniform float     use_depth;
+    uniform vec2      direction;
+    uniform float     znear;
+    uniform float     zfar;
+    uniform float     reversed_z;
+    uniform float     focus_distance;
+    uniform float     focal_range;
+    uniform float     max_blur;
+    varying vec2      f_texcoord;
+
+    // The number of samples on each side of a pixel.
+    const int TAPS = 8;
+
+    // The circle of confusion at `uv`, from 0.0 if in focus to 1.0 for the maximum blur.
+    float coc(vec2 uv) {
+      if (use_depth > 0.5) {
+        float depth = texture2D(fbo_depth, uv).x;
+        depth = reversed_z > 0.5 ? 1.0 - depth : depth;
+        float dist  = znear / max(1.0 - depth * (1.0 - znear / zfar), 1.0e-6);
+        return clamp(abs(dist - focus_distance) / focal_range, 0.0, 1.0);
+      } else {
+        return texture2D(fbo_texture, uv).a;
+      }
+    }
+
+    void main(void) {
+      float center = coc(f_texcoord);
+      float radius = center * max_blur;
+      vec3  sum    = texture2D(fbo_texture, f_texcoord).rgb;
+      float total  = 1.0;
+
+      for (int i = 1; i <= TAPS; ++i) {
+        float t = float(i) / float(TAPS);
+        vec2  offset = direction * t * radius;
+
+        for (int side = 0; side < 2; ++side) {
+          vec2 uv = side == 0 ? f_texcoord + offset : f_texcoord - offset;
+          // The samples in focus are ignored so they do not bleed into the blurred pixels.
+          float weight = exp(-2.0 * t * t) * coc(uv);
+          sum   += texture2D(fbo_texture, uv).rgb * weight;
+          total += weight;
+        }
+      }
+
+      // The horizontal pass keeps the circles of confusion for the vertical one.
+      gl_FragColor = vec4(sum / total, use_depth > 0.5 ? center : 1.0);
+    }";
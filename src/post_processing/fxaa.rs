@@ -0,0 +1,195 @@
+//! Post-processing effect to smooth aliased edges with Fast Approximate Anti-Aliasing.
+
+use na::Vector2;
+
+use crate::context::Context;
+use crate::post_processing::post_processing_effect::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Post processing effect which smooths aliased edges using FXAA.
+///
+/// This is much cheaper than oversampling: edges are detected from the luminance of the rendered
+/// scene, and blurred along their direction.
+#[allow(clippy::upper_case_acronyms)]
+pub struct FXAA {
+    shader: Effect,
+    subpixel_quality: f32,
+    screen_size: Vector2<f32>,
+    gl_subpixel_quality: ShaderUniform<f32>,
+    gl_screen_size: ShaderUniform<Vector2<f32>>,
+    fbo_texture: ShaderUniform<i32>,
+    v_coord: ShaderAttribute<Vector2<f32>>,
+    fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl FXAA {
+    /// Creates a new `FXAA` post processing effect with a subpixel quality of 0.75.
+    pub fn new() -> FXAA {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        FXAA {
+            subpixel_quality: 0.75,
+            screen_size: na::zero(),
+            gl_subpixel_quality: shader.get_uniform("subpixel_quality").unwrap(),
+            gl_screen_size: shader.get_uniform("screen_size").unwrap(),
+            fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            fbo_vertices,
+            shader,
+        }
+    }
+
+    /// The amount of smoothing applied to the detected edges.
+    #[inline]
+    pub fn subpixel_quality(&self) -> f32 {
+        self.subpixel_quality
+    }
+
+    /// Sets the amount of smoothing applied to the detected edges.
+    ///
+    /// This is clamped to `[0.0, 1.0]`, where `0.0` disables the anti-aliasing and `1.0` gives
+    /// the smoothest, but blurriest, result.
+    #[inline]
+    pub fn set_subpixel_quality(&mut self, quality: f32) {
+        self.subpixel_quality = na::clamp(quality, 0.0, 1.0);
+    }
+}
+
+impl Default for FXAA {
+    fn default() -> Self {
+        FXAA::new()
+    }
+}
+
+impl PostProcessingEffect for FXAA {
+    fn update(&mut self, _: f32, w: f32, h: f32, _: f32, _: f32) {
+        self.screen_size = Vector2::new(w, h);
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.v_coord.enable();
+
+        /*
+         * Finalize draw
+         */
+        self.shader.use_program();
+        verify!(ctxt.clear_color(0.0, 0.0, 0.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+        self.fbo_texture.upload(&0);
+        self.gl_screen_size.upload(&self.screen_size);
+        self.gl_subpixel_quality.upload(&self.subpixel_quality);
+        self.v_coord.bind(&mut self.fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.v_coord.disable();
+    }
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec2    v_coord;
+    uniform sampler2D fbo_texture;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+// NOTE: the color is passed through unchanged as long as the screen size is unknown (zero), e.g.,
+// during the first frame.
+static FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+#define EDGE_THRESHOLD     0.125
+#define EDGE_THRESHOLD_MIN 0.0312
+#define REDUCE_MUL         0.125
+#define REDUCE_MIN         0.0078125
+#define SPAN_MAX           8.0
+
+    uniform sampler2D fbo_texture;
+    uniform vec2      screen_size;
+    uniform float     subpixel_quality;
+    varying vec2      f_texcoord;
+
+    float luma(vec3 color) {
+      return dot(color, vec3(0.299, 0.587, 0.114));
+    }
+
+    vec3 fetch(vec2 texcoord) {
+      return texture2D(fbo_texture, texcoord).rgb;
+    }
+
+    void main(void) {
+      vec4 color = texture2D(fbo_texture, f_texcoord);
+
+      if (screen_size.x <= 0.0 || screen_size.y <= 0.0) {
+        gl_FragColor = color;
+        return;
+      }
+
+      vec2  texel   = 1.0 / screen_size;
+      float luma_m  = luma(color.rgb);
+      float luma_nw = luma(fetch(f_texcoord + vec2(-1.0,  1.0) * texel));
+      float luma_ne = luma(fetch(f_texcoord + vec2( 1.0,  1.0) * texel));
+      float luma_sw = luma(fetch(f_texcoord + vec2(-1.0, -1.0) * texel));
+      float luma_se = luma(fetch(f_texcoord + vec2( 1.0, -1.0) * texel));
+
+      float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+      float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+      // Not an edge: keep the original color.
+      if (luma_max - luma_min < max(EDGE_THRESHOLD_MIN, luma_max * EDGE_THRESHOLD)) {
+        gl_FragColor = color;
+        return;
+      }
+
+      // The blur direction is orthogonal to the luminance gradient.
+      vec2 dir = vec2(
+        (luma_sw + luma_se) - (luma_nw + luma_ne),
+        (luma_nw + luma_sw) - (luma_ne + luma_se));
+
+      float dir_reduce  = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+      float rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+      dir = clamp(dir * rcp_dir_min, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texel;
+
+      vec3 rgb_a = 0.5 * (
+        fetch(f_texcoord + dir * (1.0 / 3.0 - 0.5)) +
+        fetch(f_texcoord + dir * (2.0 / 3.0 - 0.5)));
+      vec3 rgb_b = rgb_a * 0.5 + 0.25 * (
+        fetch(f_texcoord + dir * -0.5) +
+        fetch(f_texcoord + dir * 0.5));
+
+      // Fall back to the narrower blur if the wider one sampled outside of the edge.
+      float luma_b = luma(rgb_b);
+      vec3  smoothed = (luma_b < luma_min || luma_b > luma_max) ? rgb_a : rgb_b;
+
+      gl_FragColor = vec4(mix(color.rgb, smoothed, subpixel_quality), color.a);
+    }";
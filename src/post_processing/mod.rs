@@ -1,15 +1,27 @@
 //! Post-processing effects.
 
+pub use crate::post_processing::bloom::Bloom;
+pub use crate::post_processing::color_grade::ColorGrade;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::post_processing::depth_of_field::DepthOfField;
+pub use crate::post_processing::fxaa::FXAA;
 pub use crate::post_processing::grayscales::Grayscales;
 pub use crate::post_processing::oculus_stereo::OculusStereo;
 pub use crate::post_processing::post_processing_effect::PostProcessingEffect;
+pub(crate) use crate::post_processing::screen_copy::ScreenCopy;
 #[cfg(not(target_arch = "wasm32"))]
 pub use crate::post_processing::sobel_edge_highlight::SobelEdgeHighlight;
 pub use crate::post_processing::waves::Waves;
 
+mod bloom;
+mod color_grade;
+#[cfg(not(target_arch = "wasm32"))]
+mod depth_of_field;
+mod fxaa;
 mod grayscales;
 mod oculus_stereo;
 pub mod post_processing_effect;
+mod screen_copy;
 #[cfg(not(target_arch = "wasm32"))]
 mod sobel_edge_highlight;
 mod waves;
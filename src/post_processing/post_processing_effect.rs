@@ -1,11 +1,11 @@
 //! Trait implemented by every post-processing effect.
 
-use crate::resource::RenderTarget;
+use crate::resource::{FramebufferManager, RenderTarget};
 
 /// Trait of post processing effect.
 ///
-/// One post-processing effect can be used at a time. It is executed once the scene has been
-/// rendered on a texture.
+/// Effects can be chained with `Window::add_post_processing_effect`. Each effect is executed once
+/// the scene, or the output of the previous effect, has been rendered on a texture.
 pub trait PostProcessingEffect {
     /// Updates the post processing effect.
     fn update(&mut self, dt: f32, w: f32, h: f32, znear: f32, zfar: f32);
@@ -16,4 +16,33 @@ pub trait PostProcessingEffect {
     /// * `fbo_texture` - id to the texture containing the last scene drawn.
     /// * `fbo_depth` - the depth buffer as a texture.
     fn draw(&mut self, target: &RenderTarget);
+    /// Render the effect to the `output` render target.
+    ///
+    /// The default implementation selects `output` before calling `draw`. Effects made of
+    /// several passes may override it to render their intermediate results to their own render
+    /// targets first.
+    fn draw_to(
+        &mut self,
+        framebuffer_manager: &mut FramebufferManager,
+        target: &RenderTarget,
+        output: &RenderTarget,
+    ) {
+        framebuffer_manager.select(output);
+        self.draw(target)
+    }
+    /// Render the effect to the `output` render target, with access to the depth of the scene.
+    ///
+    /// `scene` is the render target the scene was rendered into. Its depth texture contains the
+    /// depth of the scene even when `target` is the output of a previous effect, which has no
+    /// depth. Depth textures are not available with WebGL. This is called by the window instead
+    /// of `draw_to`, which the default implementation calls.
+    fn draw_to_with_depth(
+        &mut self,
+        framebuffer_manager: &mut FramebufferManager,
+        target: &RenderTarget,
+        _scene: &RenderTarget,
+        output: &RenderTarget,
+    ) {
+        self.draw_to(framebuffer_manager, target, output)
+    }
 }
@@ -0,0 +1,93 @@
+//! Post-processing effect copying the scene unchanged.
+
+use na::Vector2;
+
+use crate::context::Context;
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+// Post processing effect which copies the scene to the screen as is, when it is rendered
+// off-screen without any other effect.
+pub(crate) struct ScreenCopy {
+    shader: Effect,
+    fbo_texture: ShaderUniform<i32>,
+    v_coord: ShaderAttribute<Vector2<f32>>,
+    fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl ScreenCopy {
+    // Creates a new `ScreenCopy` post processing effect.
+    pub fn new() -> ScreenCopy {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        ScreenCopy {
+            fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            fbo_vertices,
+            shader,
+        }
+    }
+}
+
+impl PostProcessingEffect for ScreenCopy {
+    fn update(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.v_coord.enable();
+
+        self.shader.use_program();
+        verify!(ctxt.clear_color(0.0, 0.0, 0.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+        self.fbo_texture.upload(&0);
+        self.v_coord.bind(&mut self.fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.v_coord.disable();
+    }
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec2    v_coord;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+static FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_FragColor = texture2D(fbo_texture, f_texcoord);
+    }";
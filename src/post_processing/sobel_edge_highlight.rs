@@ -2,6 +2,7 @@
 
 use na::Vector2;
 
+use crate::camera;
 use crate::context::Context;
 use crate::post_processing::post_processing_effect::PostProcessingEffect;
 use crate::resource::{
@@ -25,6 +26,7 @@ pub struct SobelEdgeHighlight {
     gl_fbo_texture: ShaderUniform<i32>,
     gl_znear: ShaderUniform<f32>,
     gl_zfar: ShaderUniform<f32>,
+    gl_reversed_z: ShaderUniform<f32>,
     gl_threshold: ShaderUniform<f32>,
     gl_v_coord: ShaderAttribute<Vector2<f32>>,
     gl_fbo_vertices: GPUVec<Vector2<f32>>,
@@ -61,6 +63,7 @@ impl SobelEdgeHighlight {
             gl_fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
             gl_znear: shader.get_uniform("znear").unwrap(),
             gl_zfar: shader.get_uniform("zfar").unwrap(),
+            gl_reversed_z: shader.get_uniform("reversed_z").unwrap(),
             gl_threshold: shader.get_uniform("threshold").unwrap(),
             gl_v_coord: shader.get_attrib("v_coord").unwrap(),
             gl_fbo_vertices: fbo_vertices,
@@ -92,7 +95,10 @@ impl PostProcessingEffect for SobelEdgeHighlight {
         self.gl_nx.upload(&self.shiftx);
         self.gl_ny.upload(&self.shifty);
         self.gl_znear.upload(&self.zn);
-        self.gl_zfar.upload(&self.zf);
+        // The far plane may be at infinity, which the shader handles as the largest float.
+        self.gl_zfar.upload(&self.zf.min(f32::MAX));
+        self.gl_reversed_z
+            .upload(&(camera::reversed_z() as i32 as f32));
 
         verify!(ctxt.active_texture(Context::TEXTURE0));
         verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
@@ -144,13 +150,19 @@ static FRAGMENT_SHADER: &'static str = "#version 100
     uniform float     ny;
     uniform float     znear;
     uniform float     zfar;
+    uniform float     reversed_z;
     uniform float     threshold;
     varying vec2      f_texcoord;
 
     float lin_depth(vec2 uv) {
         float nlin_depth = texture2D(fbo_depth, uv).x;
 
-        return znear * zfar / ((nlin_depth * (zfar - znear)) - zfar);
+        if (reversed_z > 0.5) {
+            nlin_depth = 1.0 - nlin_depth;
+        }
+
+        // Bounded so the background stays finite if the far plane is at infinity.
+        return znear / min(nlin_depth * (1.0 - znear / zfar) - 1.0, -1.0e-6);
     }
 
     void main(void) {
@@ -0,0 +1,113 @@
+use na::{Point3, Vector3};
+use ncollide3d::procedural::TriMesh;
+use ncollide3d::transformation;
+
+// The tolerance, relative to the extents of the points, below which they are considered
+// coplanar, collinear, or identical.
+const DEGENERACY_TOLERANCE: f32 = 1.0e-5;
+
+/// Computes the convex hull of a set of points with the quickhull algorithm.
+///
+/// The hull has one normal per face, pointing outward, and no texture coordinates. Its vertices
+/// are duplicated for each face so the faces are shaded flat.
+///
+/// Returns an error if there are less than 4 points, if they are all coplanar, or if the hull
+/// has too many faces to fit in a single mesh.
+pub fn convex_hull(points: &[Point3<f32>]) -> Result<TriMesh<f32>, &'static str> {
+    if points.len() < 4 {
+        return Err("The convex hull of less than 4 points is not a solid.");
+    }
+
+    if is_degenerate(points) {
+        return Err("The convex hull of coplanar points is not a solid.");
+    }
+
+    let mut hull = transformation::convex_hull(points);
+
+    // Three vertices are needed per face once they are duplicated.
+    if hull.num_triangles() * 3 > u16::MAX as usize + 1 {
+        return Err("The convex hull has too many faces to fit in a single mesh.");
+    }
+
+    hull.replicate_vertices();
+    hull.recompute_normals();
+
+    Ok(hull)
+}
+
+// Tests whether the points do not span the three dimensions.
+fn is_degenerate(points: &[Point3<f32>]) -> bool {
+    let p0 = points[0];
+    let farthest = |dist: &dyn Fn(&Point3<f32>) -> f32| {
+        points
+            .iter()
+            .map(|p| (*p, dist(p)))
+            .fold((p0, 0.0), |a, b| if b.1 > a.1 { b } else { a })
+    };
+
+    // The farthest point from the first one.
+    let (p1, extent) = farthest(&|p| (p - p0).norm());
+    let tolerance = extent * DEGENERACY_TOLERANCE;
+
+    if extent == 0.0 {
+        return true;
+    }
+
+    // The farthest point from the line going through the first two points.
+    let dir = (p1 - p0) / extent;
+    let (p2, dist) = farthest(&|p| (p - p0).cross(&dir).norm());
+
+    if dist <= tolerance {
+        return true;
+    }
+
+    // The farthest point from the plane going through the first three points.
+    let normal: Vector3<f32> = (p1 - p0).cross(&(p2 - p0)).normalize();
+    let (_, dist) = farthest(&|p| (p - p0).dot(&normal).abs());
+
+    dist <= tolerance
+}
+
+#[cfg(test)]
+mod test {
+    use super::convex_hull;
+    use na::Point3;
+
+    #[test]
+    fn tetrahedron_with_an_interior_point() {
+        let points = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.1, 0.1, 0.1),
+        ];
+        let hull = convex_hull(&points).unwrap();
+        let center = Point3::new(0.25, 0.25, 0.25);
+
+        assert_eq!(hull.num_triangles(), 4);
+        assert_eq!(hull.coords.len(), 12);
+        assert!(!hull.coords.contains(&points[4]));
+
+        let normals = hull.normals.as_ref().unwrap();
+
+        for (p, n) in hull.coords.iter().zip(normals.iter()) {
+            assert!((p - center).dot(n) > 0.0);
+        }
+    }
+
+    #[test]
+    fn coplanar_points_are_an_error() {
+        let points = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(0.5, 0.0, 0.5),
+        ];
+
+        assert!(convex_hull(&points).is_err());
+        assert!(convex_hull(&[points[0]; 4]).is_err());
+        assert!(convex_hull(&points[..3]).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use na::{Point2, Point3, Vector3};
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use std::ops::Range;
+
+/// The maximum number of vertices along each axis of a mesh generated by `heightmap_tiles`.
+pub const MAX_HEIGHTMAP_TILE_SIZE: usize = 256;
+
+/// Generates a terrain from a grid of heights.
+///
+/// The grid is centered at the origin, lies on the `xz` plane and spans `scale.x` along the `x`
+/// axis and `scale.z` along the `z` axis. The height of each vertex is its value in `heights`
+/// multiplied by `scale.y`.
+///
+/// The vertices are stored in the same order as `heights`: row by row along the `z` axis, the
+/// vertex at column `ix` and row `iz` having the index `iz * nx + ix` and the `x` and `z`
+/// coordinates increasing with `ix` and `iz`. The texture coordinates map the whole texture to
+/// the grid, with `u` along the `x` axis and `v` along the `z` axis. The normals are computed
+/// from the neighboring heights, using one-sided differences on the borders of the grid.
+///
+/// # Arguments
+/// * `heights` - the heights of the vertices, of length `nx * nz`
+/// * `nx` - the number of vertices along the `x` axis
+/// * `nz` - the number of vertices along the `z` axis
+/// * `scale` - the extents of the terrain, and the factor applied to the heights
+///
+/// A single mesh cannot have more than 65536 vertices, e.g., more than 256 by 256 heights. Use
+/// `heightmap_tiles` to split larger grids into several meshes.
+///
+/// # Failure
+/// Fails if `nx` or `nz` is smaller than 2, if `heights` does not contain exactly `nx * nz`
+/// values, or if there are more than 65536 vertices.
+pub fn heightmap(heights: &[f32], nx: usize, nz: usize, scale: Vector3<f32>) -> TriMesh<f32> {
+    check_grid(heights, nx, nz);
+    assert!(
+        nx * nz <= u16::MAX as usize + 1,
+        "A heightmap cannot have more than 65536 vertices."
+    );
+
+    tile(heights, nx, nz, scale, 0..nx, 0..nz)
+}
+
+/// Generates a terrain from a grid of heights of any size, split into several meshes.
+///
+/// The terrain is the same as the one given by `heightmap`, but cut into tiles of at most
+/// `MAX_HEIGHTMAP_TILE_SIZE` vertices along each axis. Adjacent tiles share the vertices of their
+/// common border, and their normals and texture coordinates are computed from the whole grid so
+/// there is no visible seam between them. The tiles are ordered row by row along the `z` axis.
+///
+/// # Failure
+/// Fails if `nx` or `nz` is smaller than 2, or if `heights` does not contain exactly `nx * nz`
+/// values.
+pub fn heightmap_tiles(
+    heights: &[f32],
+    nx: usize,
+    nz: usize,
+    scale: Vector3<f32>,
+) -> Vec<TriMesh<f32>> {
+    check_grid(heights, nx, nz);
+
+    let mut tiles = Vec::new();
+
+    for zs in tile_ranges(nz) {
+        for xs in tile_ranges(nx) {
+            tiles.push(tile(heights, nx, nz, scale, xs, zs.clone()));
+        }
+    }
+
+    tiles
+}
+
+fn check_grid(heights: &[f32], nx: usize, nz: usize) {
+    assert!(
+        nx >= 2 && nz >= 2,
+        "A heightmap must have at least 2 vertices along each direction."
+    );
+    assert!(
+        heights.len() == nx * nz,
+        "The heightmap has {} heights instead of {}.",
+        heights.len(),
+        nx * nz
+    );
+}
+
+// The ranges of vertices of the tiles along an axis of `n` vertices, overlapping by one vertex.
+fn tile_ranges(n: usize) -> impl Iterator<Item = Range<usize>> {
+    let step = MAX_HEIGHTMAP_TILE_SIZE - 1;
+
+    (0..n - 1)
+        .step_by(step)
+        .map(move |start| start..(start + MAX_HEIGHTMAP_TILE_SIZE).min(n))
+}
+
+// The part of the terrain made of the vertices of the columns `xs` and the rows `zs`.
+fn tile(
+    heights: &[f32],
+    nx: usize,
+    nz: usize,
+    scale: Vector3<f32>,
+    xs: Range<usize>,
+    zs: Range<usize>,
+) -> TriMesh<f32> {
+    let dx = scale.x / (nx - 1) as f32;
+    let dz = scale.z / (nz - 1) as f32;
+    let height = |ix: usize, iz: usize| heights[iz * nx + ix] * scale.y;
+    let (tile_nx, tile_nz) = (xs.len(), zs.len());
+
+    let mut coords = Vec::with_capacity(tile_nx * tile_nz);
+    let mut normals = Vec::with_capacity(tile_nx * tile_nz);
+    let mut uvs = Vec::with_capacity(tile_nx * tile_nz);
+
+    for iz in zs {
+        for ix in xs.clone() {
+            let u = ix as f32 / (nx - 1) as f32;
+            let v = iz as f32 / (nz - 1) as f32;
+
+            coords.push(Point3::new(
+                (u - 0.5) * scale.x,
+                height(ix, iz),
+                (v - 0.5) * scale.z,
+            ));
+            uvs.push(Point2::new(u, v));
+
+            // Central differences, or one-sided ones on the borders.
+            let (x0, x1) = (ix.saturating_sub(1), (ix + 1).min(nx - 1));
+            let (z0, z1) = (iz.saturating_sub(1), (iz + 1).min(nz - 1));
+            let slope_x = (height(x1, iz) - height(x0, iz)) / ((x1 - x0) as f32 * dx);
+            let slope_z = (height(ix, z1) - height(ix, z0)) / ((z1 - z0) as f32 * dz);
+            let normal = Vector3::new(-slope_x, 1.0, -slope_z);
+
+            normals.push(normal.try_normalize(1.0e-8).unwrap_or_else(Vector3::y));
+        }
+    }
+
+    let mut faces = Vec::with_capacity((tile_nx - 1) * (tile_nz - 1) * 2);
+    let id = |ix: usize, iz: usize| (iz * tile_nx + ix) as u32;
+
+    for iz in 0..tile_nz - 1 {
+        for ix in 0..tile_nx - 1 {
+            faces.push(Point3::new(id(ix, iz), id(ix, iz + 1), id(ix + 1, iz)));
+            faces.push(Point3::new(
+                id(ix + 1, iz),
+                id(ix, iz + 1),
+                id(ix + 1, iz + 1),
+            ));
+        }
+    }
+
+    TriMesh::new(
+        coords,
+        Some(normals),
+        Some(uvs),
+        Some(IndexBuffer::Unified(faces)),
+    )
+}
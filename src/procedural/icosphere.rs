@@ -0,0 +1,145 @@
+use na::{Point2, Point3, Vector2, Vector3};
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+/// The maximum number of subdivisions of an icosphere.
+///
+/// Beyond that, the number of vertices would overflow the 16-bits indices of the meshes.
+pub const MAX_ICOSPHERE_SUBDIVISIONS: u32 = 6;
+
+/// Generates a sphere by subdividing the faces of an icosahedron.
+///
+/// Contrary to `ncollide3d::procedural::sphere`, the triangles have almost equal sizes and there
+/// is no pinching at the poles. The sphere has `20 * 4^subdivisions` triangles: 20 without
+/// subdivision, 1280 for 3 subdivisions, and 81920 for the maximum, `MAX_ICOSPHERE_SUBDIVISIONS`,
+/// which `subdivisions` is clamped to.
+///
+/// The texture coordinates are spherical, with `u` going around the `y` axis.
+pub fn icosphere(radius: f32, subdivisions: u32) -> TriMesh<f32> {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut coords: Vec<Point3<f32>> = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Point3::from(Vector3::new(x, y, z).normalize()))
+    .collect();
+    let mut faces: Vec<Point3<u32>> = [
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ]
+    .iter()
+    .map(|f| Point3::new(f[0], f[1], f[2]))
+    .collect();
+
+    /*
+     * Split each triangle into four, sharing the midpoints between adjacent triangles.
+     */
+    for _ in 0..subdivisions.min(MAX_ICOSPHERE_SUBDIVISIONS) {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, coords: &mut Vec<Point3<f32>>| {
+            let key = (a.min(b), a.max(b));
+            *midpoints.entry(key).or_insert_with(|| {
+                let mid = na::center(&coords[a as usize], &coords[b as usize]);
+                coords.push(Point3::from(mid.coords.normalize()));
+                coords.len() as u32 - 1
+            })
+        };
+
+        let mut new_faces = Vec::with_capacity(faces.len() * 4);
+
+        for f in faces.iter() {
+            let ab = midpoint(f.x, f.y, &mut coords);
+            let bc = midpoint(f.y, f.z, &mut coords);
+            let ca = midpoint(f.z, f.x, &mut coords);
+
+            new_faces.push(Point3::new(f.x, ab, ca));
+            new_faces.push(Point3::new(f.y, bc, ab));
+            new_faces.push(Point3::new(f.z, ca, bc));
+            new_faces.push(Point3::new(ab, bc, ca));
+        }
+
+        faces = new_faces;
+    }
+
+    /*
+     * Texture coordinates.
+     */
+    let mut uvs: Vec<Point2<f32>> = coords
+        .iter()
+        .map(|p| {
+            Point2::new(
+                0.5 + p.x.atan2(p.z) / (2.0 * PI),
+                0.5 - p.y.clamp(-1.0, 1.0).asin() / PI,
+            )
+        })
+        .collect();
+
+    // The triangles crossing the seam where `u` wraps around must use copies of their vertices
+    // with `u > 1`, otherwise the whole texture would be squeezed into them.
+    let mut wrapped: HashMap<u32, u32> = HashMap::new();
+
+    for f in faces.iter_mut() {
+        let us = [
+            uvs[f.x as usize].x,
+            uvs[f.y as usize].x,
+            uvs[f.z as usize].x,
+        ];
+        let max = us.iter().cloned().fold(f32::MIN, f32::max);
+        let min = us.iter().cloned().fold(f32::MAX, f32::min);
+
+        if max - min > 0.5 {
+            for i in f.iter_mut() {
+                if uvs[*i as usize].x < 0.5 {
+                    *i = *wrapped.entry(*i).or_insert_with(|| {
+                        coords.push(coords[*i as usize]);
+                        uvs.push(uvs[*i as usize] + Vector2::x());
+                        coords.len() as u32 - 1
+                    });
+                }
+            }
+        }
+    }
+
+    let normals = coords.iter().map(|p| p.coords).collect();
+
+    for p in coords.iter_mut() {
+        *p *= radius;
+    }
+
+    TriMesh::new(
+        coords,
+        Some(normals),
+        Some(uvs),
+        Some(IndexBuffer::Unified(faces)),
+    )
+}
@@ -0,0 +1,13 @@
+//! Procedural generation of meshes complementing the ones of `ncollide3d::procedural`.
+
+pub use self::convex_hull::convex_hull;
+pub use self::heightmap::{heightmap, heightmap_tiles, MAX_HEIGHTMAP_TILE_SIZE};
+pub use self::icosphere::{icosphere, MAX_ICOSPHERE_SUBDIVISIONS};
+pub use self::revolution::{capsule, cone, cylinder};
+pub use self::torus::torus;
+
+mod convex_hull;
+mod heightmap;
+mod icosphere;
+mod revolution;
+mod torus;
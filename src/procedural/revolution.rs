@@ -0,0 +1,313 @@
+use na::{Point2, Point3, Vector3};
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use std::f32::consts::PI;
+
+/// Generates a cylinder centered at the origin, with its axis aligned with the `y` axis.
+///
+/// The `u` texture coordinate of the side goes around the `y` axis and `v` goes upward, while
+/// the caps are mapped to the disk inscribed in the texture. Each cap has a single center
+/// vertex, and its border vertices are separate from the ones of the side so the edges of the
+/// cylinder are sharp. The cylinder has `(radial_segments + 1) * (height_segments + 3) + 2`
+/// vertices, which must not exceed 65536.
+///
+/// # Arguments
+/// * `radius` - the radius of the cylinder
+/// * `height` - the height of the cylinder
+/// * `radial_segments` - the number of subdivisions around the `y` axis
+/// * `height_segments` - the number of subdivisions along the `y` axis
+///
+/// # Failure
+/// Fails if `radial_segments` is smaller than 3 or `height_segments` is 0.
+pub fn cylinder(
+    radius: f32,
+    height: f32,
+    radial_segments: u32,
+    height_segments: u32,
+) -> TriMesh<f32> {
+    assert!(
+        radial_segments >= 3 && height_segments >= 1,
+        "A cylinder must have at least 3 radial segments and 1 height segment."
+    );
+
+    let mut mesh = Revolution::new(radial_segments);
+    let half_height = height / 2.0;
+
+    let bottom = mesh.push_cap_center(-half_height, -1.0);
+    let bottom_ring = mesh.push_cap_ring(-half_height, radius, -1.0);
+    mesh.push_fan(bottom, bottom_ring, false);
+
+    let mut lower = 0;
+
+    for j in 0..=height_segments {
+        let v = j as f32 / height_segments as f32;
+        let ring = mesh.push_ring(-half_height + height * v, radius, &Vector3::x(), v);
+
+        if j != 0 {
+            mesh.connect(lower, ring, false);
+        }
+
+        lower = ring;
+    }
+
+    let top = mesh.push_cap_center(half_height, 1.0);
+    let top_ring = mesh.push_cap_ring(half_height, radius, 1.0);
+    mesh.push_fan(top, top_ring, true);
+
+    mesh.into_trimesh()
+}
+
+/// Generates a cone centered at the origin, pointing toward the positive `y` axis.
+///
+/// The `u` texture coordinate of the side goes around the `y` axis and `v` goes upward, while
+/// the base is mapped to the disk inscribed in the texture. The base has a single center
+/// vertex, while the apex is duplicated for each radial segment so the side is shaded smoothly
+/// around it. The cone has `(radial_segments + 1) * (height_segments + 2) + 1` vertices, which
+/// must not exceed 65536.
+///
+/// # Arguments
+/// * `radius` - the radius of the base of the cone
+/// * `height` - the height of the cone
+/// * `radial_segments` - the number of subdivisions around the `y` axis
+/// * `height_segments` - the number of subdivisions along the `y` axis
+///
+/// # Failure
+/// Fails if `radial_segments` is smaller than 3 or `height_segments` is 0.
+pub fn cone(radius: f32, height: f32, radial_segments: u32, height_segments: u32) -> TriMesh<f32> {
+    assert!(
+        radial_segments >= 3 && height_segments >= 1,
+        "A cone must have at least 3 radial segments and 1 height segment."
+    );
+
+    let mut mesh = Revolution::new(radial_segments);
+    let half_height = height / 2.0;
+    // The normal of the side in the plane containing the `y` axis.
+    let normal = Vector3::new(height, radius, 0.0).normalize();
+
+    let bottom = mesh.push_cap_center(-half_height, -1.0);
+    let bottom_ring = mesh.push_cap_ring(-half_height, radius, -1.0);
+    mesh.push_fan(bottom, bottom_ring, false);
+
+    let mut lower = 0;
+
+    for j in 0..=height_segments {
+        let v = j as f32 / height_segments as f32;
+        let ring = mesh.push_ring(-half_height + height * v, radius * (1.0 - v), &normal, v);
+
+        if j != 0 {
+            mesh.connect(lower, ring, j == height_segments);
+        }
+
+        lower = ring;
+    }
+
+    mesh.into_trimesh()
+}
+
+/// Generates a capsule centered at the origin, with its axis aligned with the `y` axis.
+///
+/// The capsule is made of a cylinder of the given height closed by two half-spheres, so its
+/// total height is `height + 2.0 * radius`. The `u` texture coordinate goes around the `y` axis
+/// and `v` goes upward. Each pole has a single vertex. The capsule has
+/// `(radial_segments + 1) * (2 * cap_segments + height_segments - 1) + 2` vertices, which must
+/// not exceed 65536.
+///
+/// # Arguments
+/// * `radius` - the radius of the cylinder and of the half-spheres
+/// * `height` - the height of the cylinder between the two half-spheres
+/// * `radial_segments` - the number of subdivisions around the `y` axis
+/// * `height_segments` - the number of subdivisions of the cylinder along the `y` axis
+/// * `cap_segments` - the number of subdivisions of each half-sphere from its pole to the
+///   cylinder
+///
+/// # Failure
+/// Fails if `radial_segments` is smaller than 3, or `height_segments` or `cap_segments` is 0.
+pub fn capsule(
+    radius: f32,
+    height: f32,
+    radial_segments: u32,
+    height_segments: u32,
+    cap_segments: u32,
+) -> TriMesh<f32> {
+    assert!(
+        radial_segments >= 3 && height_segments >= 1 && cap_segments >= 1,
+        "A capsule must have at least 3 radial segments, 1 height segment, and 1 cap segment."
+    );
+
+    let mut mesh = Revolution::new(radial_segments);
+    let half_height = height / 2.0;
+    let total_height = height + 2.0 * radius;
+    let v = |y: f32| (y + half_height + radius) / total_height;
+
+    // The rings are listed from the bottom pole to the top pole, by their height, radius, and
+    // the normal of the surface in the plane containing the `y` axis.
+    let mut rings = Vec::new();
+
+    for k in 1..=cap_segments {
+        let (sin, cos) = (PI / 2.0 * (k as f32 / cap_segments as f32 - 1.0)).sin_cos();
+        rings.push((
+            -half_height + radius * sin,
+            radius * cos,
+            Vector3::new(cos, sin, 0.0),
+        ));
+    }
+
+    for j in 1..height_segments {
+        let y = -half_height + height * j as f32 / height_segments as f32;
+        rings.push((y, radius, Vector3::x()));
+    }
+
+    for k in 0..cap_segments {
+        let (sin, cos) = (PI / 2.0 * (k as f32 / cap_segments as f32)).sin_cos();
+        rings.push((
+            half_height + radius * sin,
+            radius * cos,
+            Vector3::new(cos, sin, 0.0),
+        ));
+    }
+
+    let bottom = mesh.push_pole(-half_height - radius, -1.0, 0.0);
+    let mut lower = 0;
+
+    for (i, (y, r, normal)) in rings.iter().enumerate() {
+        let ring = mesh.push_ring(*y, *r, normal, v(*y));
+
+        if i == 0 {
+            mesh.push_fan(bottom, ring, false);
+        } else {
+            mesh.connect(lower, ring, false);
+        }
+
+        lower = ring;
+    }
+
+    let top = mesh.push_pole(half_height + radius, 1.0, 1.0);
+    mesh.push_fan(top, lower, true);
+
+    mesh.into_trimesh()
+}
+
+// A surface of revolution around the `y` axis, made of rings of vertices.
+struct Revolution {
+    segments: u32,
+    coords: Vec<Point3<f32>>,
+    normals: Vec<Vector3<f32>>,
+    uvs: Vec<Point2<f32>>,
+    faces: Vec<Point3<u32>>,
+}
+
+impl Revolution {
+    fn new(segments: u32) -> Revolution {
+        Revolution {
+            segments,
+            coords: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            faces: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, coords: Point3<f32>, normal: Vector3<f32>, uv: Point2<f32>) -> u32 {
+        self.coords.push(coords);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+
+        self.coords.len() as u32 - 1
+    }
+
+    // The sine and cosine of the angle of the `i`-th vertex of a ring around the `y` axis.
+    fn angle(&self, i: u32) -> (f32, f32) {
+        (i as f32 / self.segments as f32 * 2.0 * PI).sin_cos()
+    }
+
+    // Pushes a ring of the lateral surface, whose normal is given in the `xy` plane, and returns
+    // the index of its first vertex. The first vertex is duplicated at the end of the ring so the
+    // texture wraps around.
+    fn push_ring(&mut self, y: f32, radius: f32, normal: &Vector3<f32>, v: f32) -> u32 {
+        let first = self.coords.len() as u32;
+
+        for i in 0..=self.segments {
+            let (sin, cos) = self.angle(i);
+            let u = i as f32 / self.segments as f32;
+
+            let _ = self.push(
+                Point3::new(radius * cos, y, radius * sin),
+                Vector3::new(normal.x * cos, normal.y, normal.x * sin),
+                Point2::new(u, v),
+            );
+        }
+
+        first
+    }
+
+    // Pushes the border of a flat cap facing the given direction along the `y` axis.
+    fn push_cap_ring(&mut self, y: f32, radius: f32, dir: f32) -> u32 {
+        let first = self.coords.len() as u32;
+
+        for i in 0..=self.segments {
+            let (sin, cos) = self.angle(i);
+
+            let _ = self.push(
+                Point3::new(radius * cos, y, radius * sin),
+                Vector3::y() * dir,
+                Point2::new(0.5 + cos / 2.0, 0.5 + sin / 2.0),
+            );
+        }
+
+        first
+    }
+
+    fn push_cap_center(&mut self, y: f32, dir: f32) -> u32 {
+        self.push(
+            Point3::new(0.0, y, 0.0),
+            Vector3::y() * dir,
+            Point2::new(0.5, 0.5),
+        )
+    }
+
+    fn push_pole(&mut self, y: f32, dir: f32, v: f32) -> u32 {
+        self.push(
+            Point3::new(0.0, y, 0.0),
+            Vector3::y() * dir,
+            Point2::new(0.5, v),
+        )
+    }
+
+    // Connects the vertex `center` to the ring starting at `ring`, with triangles
+    // facing upward if `up` is `true`.
+    fn push_fan(&mut self, center: u32, ring: u32, up: bool) {
+        for i in 0..self.segments {
+            let (a, b) = (ring + i, ring + i + 1);
+
+            if up {
+                self.faces.push(Point3::new(center, b, a));
+            } else {
+                self.faces.push(Point3::new(center, a, b));
+            }
+        }
+    }
+
+    // Connects two consecutive rings of the lateral surface. If `apex` is `true`, all the
+    // vertices of the upper ring are at the same position, so only one triangle is created per
+    // segment.
+    fn connect(&mut self, lower: u32, upper: u32, apex: bool) {
+        for i in 0..self.segments {
+            let (a, b) = (lower + i, lower + i + 1);
+            let (c, d) = (upper + i, upper + i + 1);
+
+            self.faces.push(Point3::new(a, c, b));
+
+            if !apex {
+                self.faces.push(Point3::new(b, c, d));
+            }
+        }
+    }
+
+    fn into_trimesh(self) -> TriMesh<f32> {
+        TriMesh::new(
+            self.coords,
+            Some(self.normals),
+            Some(self.uvs),
+            Some(IndexBuffer::Unified(self.faces)),
+        )
+    }
+}
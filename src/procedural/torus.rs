@@ -0,0 +1,66 @@
+use na::{Point2, Point3, Vector3};
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use std::f32::consts::PI;
+
+/// Generates a torus centered at the origin, with its axis aligned with the `y` axis.
+///
+/// The `u` texture coordinate goes around the `y` axis and `v` around the tube. The vertices
+/// along the seams where `u` and `v` wrap around are duplicated, so the texture has no
+/// discontinuity there, while the normals of the duplicated vertices are the same so the shading
+/// is smooth. The torus has `2 * major_segments * minor_segments` triangles and
+/// `(major_segments + 1) * (minor_segments + 1)` vertices, which must not exceed 65536.
+///
+/// # Arguments
+/// * `major_radius` - the distance from the center of the torus to the center of the tube
+/// * `minor_radius` - the radius of the tube
+/// * `major_segments` - the number of subdivisions around the `y` axis
+/// * `minor_segments` - the number of subdivisions around the tube
+///
+/// `major_segments` and `minor_segments` are clamped to at least 3, the smallest number of
+/// subdivisions that encloses a volume.
+pub fn torus(
+    major_radius: f32,
+    minor_radius: f32,
+    major_segments: u32,
+    minor_segments: u32,
+) -> TriMesh<f32> {
+    let major_segments = major_segments.max(3);
+    let minor_segments = minor_segments.max(3);
+
+    let mut coords = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+
+    for i in 0..=major_segments {
+        let u = i as f32 / major_segments as f32;
+        let (sin_theta, cos_theta) = (u * 2.0 * PI).sin_cos();
+        let center = Vector3::new(cos_theta, 0.0, sin_theta) * major_radius;
+
+        for j in 0..=minor_segments {
+            let v = j as f32 / minor_segments as f32;
+            let (sin_phi, cos_phi) = (v * 2.0 * PI).sin_cos();
+            let normal = Vector3::new(cos_phi * cos_theta, sin_phi, cos_phi * sin_theta);
+
+            coords.push(Point3::from(center + normal * minor_radius));
+            normals.push(normal);
+            uvs.push(Point2::new(u, v));
+        }
+    }
+
+    let mut faces = Vec::new();
+    let id = |i: u32, j: u32| i * (minor_segments + 1) + j;
+
+    for i in 0..major_segments {
+        for j in 0..minor_segments {
+            faces.push(Point3::new(id(i, j), id(i, j + 1), id(i + 1, j)));
+            faces.push(Point3::new(id(i + 1, j), id(i, j + 1), id(i + 1, j + 1)));
+        }
+    }
+
+    TriMesh::new(
+        coords,
+        Some(normals),
+        Some(uvs),
+        Some(IndexBuffer::Unified(faces)),
+    )
+}
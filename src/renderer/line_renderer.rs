@@ -2,9 +2,10 @@
 
 use crate::camera::Camera;
 use crate::context::Context;
+use crate::renderer::wide_line_renderer::WideLineRenderer;
 use crate::renderer::Renderer;
 use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
-use na::{Matrix4, Point3};
+use na::{Isometry3, Matrix4, Point3, Vector3};
 
 #[path = "../error.rs"]
 mod error;
@@ -18,6 +19,8 @@ pub struct LineRenderer {
     proj: ShaderUniform<Matrix4<f32>>,
     lines: GPUVec<Point3<f32>>,
     line_width: f32,
+    antialiasing: bool,
+    wide_lines: WideLineRenderer,
 }
 
 impl LineRenderer {
@@ -43,6 +46,8 @@ impl LineRenderer {
                 .expect("Failed to get shader uniform."),
             shader: shader,
             line_width: 1.0,
+            antialiasing: false,
+            wide_lines: WideLineRenderer::new(),
         }
     }
 
@@ -62,10 +67,34 @@ impl LineRenderer {
         }
     }
 
-    /// Sets the line width for the rendered lines.
+    /// Sets the line width for the rendered lines, in pixels.
+    ///
+    /// Lines wider than one pixel are expanded into quads facing the camera.
     pub fn set_line_width(&mut self, line_width: f32) {
         self.line_width = line_width;
     }
+
+    /// The line width of the rendered lines, in pixels.
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    /// Enables or disables the anti-aliasing of the rendered lines.
+    ///
+    /// Anti-aliased lines are expanded into quads facing the camera, with edges fading out.
+    pub fn set_antialiasing(&mut self, antialiasing: bool) {
+        self.antialiasing = antialiasing;
+    }
+
+    /// Whether the rendered lines are anti-aliased.
+    pub fn antialiasing(&self) -> bool {
+        self.antialiasing
+    }
+
+    // Thin aliased lines are drawn directly, which is faster.
+    fn uses_wide_lines(&self) -> bool {
+        self.line_width > 1.0 || self.antialiasing
+    }
 }
 
 impl Renderer for LineRenderer {
@@ -75,6 +104,25 @@ impl Renderer for LineRenderer {
             return;
         }
 
+        if self.uses_wide_lines() {
+            for lines in self.lines.data_mut().iter_mut() {
+                for line in lines.chunks_exact(4) {
+                    self.wide_lines.push(line[0], line[2], line[1]);
+                }
+
+                lines.clear()
+            }
+
+            return self.wide_lines.render(
+                pass,
+                camera,
+                &Isometry3::identity(),
+                &Vector3::repeat(1.0),
+                self.line_width,
+                self.antialiasing,
+            );
+        }
+
         self.shader.use_program();
         self.pos.enable();
         self.color.enable();
@@ -86,7 +134,6 @@ impl Renderer for LineRenderer {
 
         let ctxt = Context::get();
         verify!(ctxt.draw_arrays(Context::LINES, 0, (self.lines.len() / 2) as i32));
-        verify!(ctxt.line_width(self.line_width));
 
         self.pos.disable();
         self.color.disable();
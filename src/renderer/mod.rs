@@ -5,9 +5,14 @@ pub use self::conrod_renderer::ConrodRenderer;
 pub use self::line_renderer::LineRenderer;
 pub use self::point_renderer::PointRenderer;
 pub use self::renderer::Renderer;
+pub use self::shadow_map_renderer::{ShadowMap, ShadowMapRenderer};
+pub use self::skybox_renderer::SkyboxRenderer;
 
 #[cfg(feature = "conrod")]
 mod conrod_renderer;
 pub mod line_renderer;
 pub mod point_renderer;
 mod renderer;
+pub mod shadow_map_renderer;
+pub mod skybox_renderer;
+pub(crate) mod wide_line_renderer;
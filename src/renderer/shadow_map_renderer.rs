@@ -0,0 +1,357 @@
+//! A renderer for the shadow maps of directional lights and spotlights.
+
+use crate::context::{Context, Texture};
+use crate::light::{Light, Lights};
+use crate::resource::{Effect, FramebufferManager, RenderTarget, ShaderAttribute, ShaderUniform};
+use crate::scene::{Instances, SceneNodeData};
+use na::{Isometry3, Matrix3, Matrix4, Orthographic3, Perspective3, Point3, Vector3};
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+#[path = "../error.rs"]
+mod error;
+
+/// The depth of the scene as seen by a directional light or a spotlight, used by materials to
+/// draw shadows.
+///
+/// The depth is packed into the four color channels of the texture.
+#[derive(Clone)]
+pub struct ShadowMap {
+    target: Rc<RenderTarget>,
+    light_index: usize,
+    light_transform: Matrix4<f32>,
+    bias: f32,
+    softness: f32,
+    resolution: u32,
+}
+
+impl ShadowMap {
+    /// The texture containing the packed depth of the scene.
+    #[inline]
+    pub fn texture(&self) -> &Texture {
+        self.target.texture_id().unwrap()
+    }
+
+    /// The index of the light casting the shadows, in the order of `Lights::iter`.
+    #[inline]
+    pub fn light_index(&self) -> usize {
+        self.light_index
+    }
+
+    /// The transformation from world coordinates to the clip space of the light.
+    #[inline]
+    pub fn light_transform(&self) -> &Matrix4<f32> {
+        &self.light_transform
+    }
+
+    /// The offset subtracted from the depth of a fragment before comparing it to the shadow map.
+    #[inline]
+    pub fn bias(&self) -> f32 {
+        self.bias
+    }
+
+    /// The radius of the filter smoothing the borders of the shadows, in texels of the shadow
+    /// map.
+    #[inline]
+    pub fn softness(&self) -> f32 {
+        self.softness
+    }
+
+    /// The width and height of the shadow map, in pixels.
+    #[inline]
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+}
+
+/// Structure which renders the shadow maps of the directional lights and spotlights of a scene.
+pub struct ShadowMapRenderer {
+    shader: Effect,
+    pos: ShaderAttribute<Point3<f32>>,
+    light_transform: ShaderUniform<Matrix4<f32>>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    resolution: u32,
+    bias: f32,
+    softness: f32,
+    // One target per light casting shadows, created the first time they are needed.
+    targets: Vec<Rc<RenderTarget>>,
+}
+
+impl ShadowMapRenderer {
+    /// Creates a new shadow map renderer, with shadow maps of 1024x1024 pixels.
+    pub fn new() -> ShadowMapRenderer {
+        let mut shader = Effect::new_from_str(SHADOW_VERTEX_SRC, SHADOW_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        ShadowMapRenderer {
+            pos: shader
+                .get_attrib::<Point3<f32>>("position")
+                .expect("Failed to get shader attribute."),
+            light_transform: shader
+                .get_uniform::<Matrix4<f32>>("light_transform")
+                .expect("Failed to get shader uniform."),
+            transform: shader
+                .get_uniform::<Matrix4<f32>>("transform")
+                .expect("Failed to get shader uniform."),
+            scale: shader
+                .get_uniform::<Matrix3<f32>>("scale")
+                .expect("Failed to get shader uniform."),
+            shader,
+            resolution: 1024,
+            bias: 0.005,
+            softness: 1.0,
+            targets: Vec::new(),
+        }
+    }
+
+    /// The width and height of the shadow maps, in pixels.
+    #[inline]
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    /// Sets the width and height of the shadow maps, in pixels.
+    pub fn set_resolution(&mut self, resolution: u32) {
+        if resolution != self.resolution {
+            self.resolution = resolution;
+            // The shadow maps are created again at the next rendering.
+            self.targets.clear();
+        }
+    }
+
+    /// The offset subtracted from the depth of a fragment before comparing it to the shadow map,
+    /// for the lights which do not override it.
+    #[inline]
+    pub fn bias(&self) -> f32 {
+        self.bias
+    }
+
+    /// Sets the offset subtracted from the depth of a fragment before comparing it to the shadow
+    /// map, for the lights which do not override it with `Lights::set_shadow_bias`.
+    ///
+    /// Increase it if shadowed stripes appear on lit surfaces ("shadow acne").
+    #[inline]
+    pub fn set_bias(&mut self, bias: f32) {
+        self.bias = bias;
+    }
+
+    /// The radius of the filter smoothing the borders of the shadows, in texels of the shadow
+    /// maps.
+    #[inline]
+    pub fn softness(&self) -> f32 {
+        self.softness
+    }
+
+    /// Sets the radius of the filter smoothing the borders of the shadows, in texels of the
+    /// shadow maps.
+    ///
+    /// The shadow maps are sampled 3x3 times over this radius, so `0.0` gives hard shadows and
+    /// large radii give banded shadows. The default is 1.0.
+    #[inline]
+    pub fn set_softness(&mut self, softness: f32) {
+        self.softness = softness.max(0.0);
+    }
+
+    /// Renders the shadow maps of the lights of `lights` casting shadows.
+    ///
+    /// Up to `MAX_SHADOWS` directional lights and spotlights cast shadows, see
+    /// `Lights::set_casts_shadow`. The shadow map of a directional light covers the bounding box
+    /// of the whole scene, while the one of a spotlight covers its cone. Returns no shadow map if
+    /// there is no such light or if the scene is empty. The viewport and the depth test are
+    /// modified by this method.
+    pub fn render(
+        &mut self,
+        scene: &SceneNodeData,
+        lights: &Lights,
+        framebuffer_manager: &mut FramebufferManager,
+    ) -> Vec<ShadowMap> {
+        let (mins, maxs) = match scene.bounding_box() {
+            Some(bounds) => bounds,
+            None => return Vec::new(),
+        };
+        let mut shadow_maps = Vec::new();
+
+        for (k, (light_index, light, bias)) in lights.shadow_casters().enumerate() {
+            let light_transform = fit_light_transform(light, &mins, &maxs);
+            let resolution = self.resolution;
+
+            while self.targets.len() <= k {
+                self.targets.push(Rc::new(create_shadow_target(resolution)));
+            }
+
+            let target = self.targets[k].clone();
+            self.render_depth(scene, &target, &light_transform, framebuffer_manager);
+
+            shadow_maps.push(ShadowMap {
+                target,
+                light_index,
+                light_transform,
+                bias: bias.unwrap_or(self.bias),
+                softness: self.softness,
+                resolution,
+            });
+        }
+
+        shadow_maps
+    }
+
+    // Renders the depth of the shadow casters of `scene` seen through `light_transform`.
+    fn render_depth(
+        &mut self,
+        scene: &SceneNodeData,
+        target: &RenderTarget,
+        light_transform: &Matrix4<f32>,
+        framebuffer_manager: &mut FramebufferManager,
+    ) {
+        let ctxt = Context::get();
+        let resolution = self.resolution as i32;
+
+        framebuffer_manager.select(target);
+        verify!(ctxt.viewport(0, 0, resolution, resolution));
+        // The shadow maps do not use the reversed depth of the cameras.
+        verify!(ctxt.clear_depth(1.0));
+        verify!(ctxt.depth_func(Context::LEQUAL));
+        verify!(ctxt.clear_color(1.0, 1.0, 1.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+        verify!(ctxt.disable(Context::CULL_FACE));
+
+        self.shader.use_program();
+        self.pos.enable();
+        self.light_transform.upload(light_transform);
+
+        let pos = &mut self.pos;
+        let transform_uniform = &mut self.transform;
+        let scale_uniform = &mut self.scale;
+
+        scene.apply_to_shadow_casters(&mut |object, transform, scale| {
+            // Instances are drawn around the node, not at its position.
+            if object.data().user_data().is::<Rc<RefCell<Instances>>>()
+                || !object.data().surface_rendering_active()
+            {
+                return;
+            }
+
+            let formated_scale = Matrix3::from_diagonal(scale);
+
+            transform_uniform.upload(&transform.to_homogeneous());
+            scale_uniform.upload(&formated_scale);
+
+            let mut mesh = object.mesh().borrow_mut();
+
+            mesh.bind_coords(pos);
+            mesh.bind_faces();
+
+            verify!(ctxt.draw_elements(
+                Context::TRIANGLES,
+                mesh.num_pts() as i32,
+                Context::UNSIGNED_SHORT,
+                0
+            ));
+
+            mesh.unbind();
+        });
+
+        self.pos.disable();
+        verify!(ctxt.enable(Context::CULL_FACE));
+    }
+}
+
+// The transformation from world coordinates to the clip space of `light`, which is a directional
+// light or a spotlight, fitted around the scene bounded by `mins` and `maxs`.
+fn fit_light_transform(light: &Light, mins: &Point3<f32>, maxs: &Point3<f32>) -> Matrix4<f32> {
+    let dir = light.direction().unwrap().normalize();
+    let center = na::center(mins, maxs);
+    let radius = (na::distance(mins, maxs) / 2.0).max(1.0e-3);
+    let up = if dir.y.abs() > 0.99 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+
+    match *light {
+        Light::Spot {
+            ref position,
+            outer_angle,
+            range,
+            ..
+        } => {
+            /*
+             * Fit a perspective projection around the cone of the light, up to the end of
+             * the scene.
+             */
+            let dist = na::distance(position, &center);
+            let zfar = range.min(dist + radius).max(2.0e-3);
+            let znear = (dist - radius).max(zfar * 1.0e-3).min(zfar / 2.0);
+            let fov = (2.0 * outer_angle).clamp(1.0e-3, PI * 0.95);
+            let view = Isometry3::look_at_rh(position, &(position + dir), &up);
+            let proj = Perspective3::new(1.0, fov, znear, zfar);
+            proj.to_homogeneous() * view.to_homogeneous()
+        }
+        _ => {
+            /*
+             * Fit an orthographic projection around the bounding sphere of the scene.
+             */
+            let eye = center - dir * (2.0 * radius);
+            let view = Isometry3::look_at_rh(&eye, &center, &up);
+            let proj = Orthographic3::new(-radius, radius, -radius, radius, radius, 3.0 * radius);
+            proj.to_homogeneous() * view.to_homogeneous()
+        }
+    }
+}
+
+impl Default for ShadowMapRenderer {
+    fn default() -> Self {
+        ShadowMapRenderer::new()
+    }
+}
+
+// The packed depth must not be interpolated, so nearest filtering is used.
+fn create_shadow_target(resolution: u32) -> RenderTarget {
+    let ctxt = Context::get();
+    let target =
+        FramebufferManager::new_render_target(resolution as usize, resolution as usize, false);
+
+    verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+    for param in [Context::TEXTURE_MIN_FILTER, Context::TEXTURE_MAG_FILTER].iter() {
+        verify!(ctxt.tex_parameteri(Context::TEXTURE_2D, *param, Context::NEAREST as i32));
+    }
+
+    verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+
+    target
+}
+
+/// Vertex shader used to render the shadow map.
+pub static SHADOW_VERTEX_SRC: &str = A_VERY_LONG_STRING;
+/// Fragment shader used to render the shadow map.
+pub static SHADOW_FRAGMENT_SRC: &str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &str = "#version 100
+    attribute vec3 position;
+    uniform   mat3 scale;
+    uniform   mat4 light_transform, transform;
+    void main() {
+        gl_Position = light_transform * transform * vec4(scale * position, 1.0);
+    }";
+
+// NOTE: the depth is packed into 8-bits color channels since depth textures are not available
+// everywhere. The depth is clamped so that the far plane does not wrap around to zero.
+const ANOTHER_VERY_LONG_STRING: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    vec4 pack_depth(float depth) {
+        vec4 rgba = fract(depth * vec4(1.0, 255.0, 65025.0, 16581375.0));
+        return rgba - rgba.yzww * vec4(1.0 / 255.0, 1.0 / 255.0, 1.0 / 255.0, 0.0);
+    }
+
+    void main() {
+        gl_FragColor = pack_depth(min(gl_FragCoord.z, 0.99999));
+    }";
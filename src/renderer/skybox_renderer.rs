@@ -0,0 +1,165 @@
+//! A renderer for cubemap skyboxes.
+
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::renderer::Renderer;
+use crate::resource::{
+    AllocationType, BufferType, CubemapTexture, Effect, GPUVec, ShaderAttribute, ShaderUniform,
+};
+use na::{Matrix4, Point3};
+use std::rc::Rc;
+
+#[path = "../error.rs"]
+mod error;
+
+/// Structure which draws a cubemap as an infinitely far background.
+pub struct SkyboxRenderer {
+    shader: Effect,
+    pos: ShaderAttribute<Point3<f32>>,
+    skybox: ShaderUniform<i32>,
+    view: ShaderUniform<Matrix4<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    cube: GPUVec<Point3<f32>>,
+    cubemap: Option<Rc<CubemapTexture>>,
+}
+
+impl SkyboxRenderer {
+    /// Creates a new skybox renderer without any cubemap.
+    pub fn new() -> SkyboxRenderer {
+        let mut shader = Effect::new_from_str(SKYBOX_VERTEX_SRC, SKYBOX_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        SkyboxRenderer {
+            cube: GPUVec::new(
+                cube_triangles(),
+                BufferType::Array,
+                AllocationType::StaticDraw,
+            ),
+            pos: shader
+                .get_attrib::<Point3<f32>>("position")
+                .expect("Failed to get shader attribute."),
+            skybox: shader
+                .get_uniform::<i32>("skybox")
+                .expect("Failed to get shader uniform."),
+            view: shader
+                .get_uniform::<Matrix4<f32>>("view")
+                .expect("Failed to get shader uniform."),
+            proj: shader
+                .get_uniform::<Matrix4<f32>>("proj")
+                .expect("Failed to get shader uniform."),
+            shader,
+            cubemap: None,
+        }
+    }
+
+    /// Indicates whether a skybox has to be drawn.
+    pub fn needs_rendering(&self) -> bool {
+        self.cubemap.is_some()
+    }
+
+    /// Sets the cubemap drawn by this renderer, or disables it if `None`.
+    pub fn set_cubemap(&mut self, cubemap: Option<Rc<CubemapTexture>>) {
+        self.cubemap = cubemap;
+    }
+
+    /// The cubemap drawn by this renderer.
+    pub fn cubemap(&self) -> Option<&Rc<CubemapTexture>> {
+        self.cubemap.as_ref()
+    }
+}
+
+impl Default for SkyboxRenderer {
+    fn default() -> Self {
+        SkyboxRenderer::new()
+    }
+}
+
+impl Renderer for SkyboxRenderer {
+    /// Actually draws the skybox.
+    fn render(&mut self, pass: usize, camera: &mut dyn Camera) {
+        let cubemap = match self.cubemap {
+            Some(ref cubemap) => cubemap.clone(),
+            None => return,
+        };
+
+        let ctxt = Context::get();
+
+        self.shader.use_program();
+        self.pos.enable();
+
+        camera.upload(pass, &mut self.proj, &mut self.view);
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(cubemap.texture())));
+        self.skybox.upload(&0);
+
+        self.pos.bind(&mut self.cube);
+
+        // The skybox is drawn on the far plane, behind everything else.
+        verify!(ctxt.depth_mask(false));
+        verify!(ctxt.disable(Context::CULL_FACE));
+        verify!(ctxt.draw_arrays(Context::TRIANGLES, 0, self.cube.len() as i32));
+        verify!(ctxt.enable(Context::CULL_FACE));
+        verify!(ctxt.depth_mask(true));
+
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, None));
+        self.pos.disable();
+    }
+}
+
+// The 12 triangles of the cube [-1, 1]^3.
+fn cube_triangles() -> Vec<Point3<f32>> {
+    let corner = |i: usize| {
+        Point3::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+        )
+    };
+    let quads = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ];
+
+    quads
+        .iter()
+        .flat_map(|q| vec![q[0], q[1], q[2], q[0], q[2], q[3]])
+        .map(corner)
+        .collect()
+}
+
+/// Vertex shader used to display the skybox.
+pub static SKYBOX_VERTEX_SRC: &str = A_VERY_LONG_STRING;
+/// Fragment shader used to display the skybox.
+pub static SKYBOX_FRAGMENT_SRC: &str = ANOTHER_VERY_LONG_STRING;
+
+// NOTE: the translation of the view matrix is ignored by transforming a direction (w = 0), and
+// the depth is forced to 1.0 (the far plane) by setting z = w.
+const A_VERY_LONG_STRING: &str = "#version 100
+    attribute vec3 position;
+    varying   vec3 direction;
+    uniform   mat4 proj;
+    uniform   mat4 view;
+    void main() {
+        vec4 pos = proj * vec4((view * vec4(position, 0.0)).xyz, 1.0);
+        gl_Position = pos.xyww;
+        direction = position;
+    }";
+
+const ANOTHER_VERY_LONG_STRING: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    varying vec3 direction;
+    uniform samplerCube skybox;
+    void main() {
+        gl_FragColor = textureCube(skybox, direction);
+    }";
@@ -0,0 +1,384 @@
+//! A batched renderer of lines thicker than one pixel.
+
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, Mesh, ShaderAttribute, ShaderUniform,
+};
+use crate::scene::WireframeMode;
+use na::{Isometry3, Matrix3, Matrix4, Point3, Vector2, Vector3};
+
+#[path = "../error.rs"]
+mod error;
+
+// The width of the transparent border of anti-aliased lines, in pixels.
+const FEATHER: f32 = 1.0;
+
+/// Draws lines as quads facing the camera.
+///
+/// Wide lines are not supported by every context, so each segment is expanded into two triangles
+/// of constant width in screen-space instead.
+pub(crate) struct WideLineRenderer {
+    shader: Effect,
+    pos: ShaderAttribute<Point3<f32>>,
+    other: ShaderAttribute<Point3<f32>>,
+    side: ShaderAttribute<Point3<f32>>,
+    color: ShaderAttribute<Point3<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    view: ShaderUniform<Matrix4<f32>>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    viewport: ShaderUniform<Vector2<f32>>,
+    half_width: ShaderUniform<f32>,
+    feather: ShaderUniform<f32>,
+    // Each vertex is made of its position, the other end of its segment, its side and its color.
+    vertices: GPUVec<Point3<f32>>,
+}
+
+/// The vertices drawing the edges of a mesh, cached by the mesh until its coordinates or faces
+/// are modified.
+pub(crate) struct WideLineEdges {
+    mode: WireframeMode,
+    // The versions of the coordinates and faces the vertices were built from.
+    versions: (usize, usize),
+    // Each vertex is made of its position, the other end of its segment and its side.
+    vertices: GPUVec<Point3<f32>>,
+}
+
+// The vertices of the two triangles drawing the segment from `a` to `b`, made of their position,
+// the other end of the segment, and their side and whether they are at its start or at its end.
+fn segment_vertices(
+    a: Point3<f32>,
+    b: Point3<f32>,
+) -> [(Point3<f32>, Point3<f32>, Point3<f32>); 6] {
+    let a_left = (a, b, Point3::new(1.0, 1.0, 0.0));
+    let a_right = (a, b, Point3::new(-1.0, 1.0, 0.0));
+    let b_left = (b, a, Point3::new(1.0, -1.0, 0.0));
+    let b_right = (b, a, Point3::new(-1.0, -1.0, 0.0));
+
+    [a_left, a_right, b_left, a_right, b_right, b_left]
+}
+
+impl WideLineRenderer {
+    pub fn new() -> WideLineRenderer {
+        let mut shader = Effect::new_from_str(WIDE_LINES_VERTEX_SRC, WIDE_LINES_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        WideLineRenderer {
+            vertices: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
+            pos: shader
+                .get_attrib("position")
+                .expect("Failed to get shader attribute."),
+            other: shader
+                .get_attrib("other")
+                .expect("Failed to get shader attribute."),
+            side: shader
+                .get_attrib("side")
+                .expect("Failed to get shader attribute."),
+            color: shader
+                .get_attrib("color")
+                .expect("Failed to get shader attribute."),
+            proj: shader
+                .get_uniform("proj")
+                .expect("Failed to get shader uniform."),
+            view: shader
+                .get_uniform("view")
+                .expect("Failed to get shader uniform."),
+            transform: shader
+                .get_uniform("transform")
+                .expect("Failed to get shader uniform."),
+            scale: shader
+                .get_uniform("scale")
+                .expect("Failed to get shader uniform."),
+            viewport: shader
+                .get_uniform("viewport")
+                .expect("Failed to get shader uniform."),
+            half_width: shader
+                .get_uniform("half_width")
+                .expect("Failed to get shader uniform."),
+            feather: shader
+                .get_uniform("feather")
+                .expect("Failed to get shader uniform."),
+            shader,
+        }
+    }
+
+    /// Adds a segment to be drawn by the next call to `render`.
+    pub fn push(&mut self, a: Point3<f32>, b: Point3<f32>, color: Point3<f32>) {
+        for vertices in self.vertices.data_mut().iter_mut() {
+            for &(pos, other, side) in segment_vertices(a, b).iter() {
+                vertices.push(pos);
+                vertices.push(other);
+                vertices.push(side);
+                vertices.push(color);
+            }
+        }
+    }
+
+    /// Builds the vertices drawing the edges of `mesh` selected by `mode`, unless they are
+    /// already cached by `mesh`.
+    ///
+    /// The edges shared by several triangles are drawn once. The vertices are rebuilt only when
+    /// the coordinates or faces of `mesh` are modified. Returns `false` if they need to be built
+    /// but the vertices of `mesh` are not available in RAM.
+    pub fn prepare_edges(mesh: &mut Mesh, mode: WireframeMode) -> bool {
+        let versions = (
+            mesh.coords().read().unwrap().version(),
+            mesh.faces().read().unwrap().version(),
+        );
+
+        if let Some(i) = mesh
+            .wide_line_edges_mut()
+            .iter()
+            .position(|e| e.mode == mode)
+        {
+            if mesh.wide_line_edges_mut()[i].versions == versions {
+                return true;
+            }
+
+            let _ = mesh.wide_line_edges_mut().swap_remove(i);
+        }
+
+        let edges: Vec<(u16, u16)> = match mode {
+            WireframeMode::Triangles => {
+                let faces = mesh.faces().read().unwrap();
+                let faces = match faces.data() {
+                    Some(faces) => faces,
+                    None => return false,
+                };
+                let mut edges = Vec::with_capacity(faces.len() * 3);
+
+                for f in faces.iter() {
+                    for &(a, b) in &[(f.x, f.y), (f.y, f.z), (f.z, f.x)] {
+                        edges.push((a.min(b), a.max(b)));
+                    }
+                }
+
+                edges.sort_unstable();
+                edges.dedup();
+                edges
+            }
+            WireframeMode::Edges => {
+                let edges = mesh.wireframe_edges().clone();
+                let edges = edges.read().unwrap();
+
+                match edges.data() {
+                    Some(edges) => edges.iter().map(|e| (e.x, e.y)).collect(),
+                    None => return false,
+                }
+            }
+        };
+
+        let vertices = {
+            let coords = mesh.coords().read().unwrap();
+            let coords = match coords.data() {
+                Some(coords) => coords,
+                None => return false,
+            };
+            let mut vertices = Vec::with_capacity(edges.len() * 18);
+
+            for &(a, b) in edges.iter() {
+                let (a, b) = (coords[a as usize], coords[b as usize]);
+
+                for &(pos, other, side) in segment_vertices(a, b).iter() {
+                    vertices.push(pos);
+                    vertices.push(other);
+                    vertices.push(side);
+                }
+            }
+
+            vertices
+        };
+
+        mesh.wide_line_edges_mut().push(WideLineEdges {
+            mode,
+            versions,
+            vertices: GPUVec::new(vertices, BufferType::Array, AllocationType::StaticDraw),
+        });
+
+        true
+    }
+
+    /// Draws the lines added since the last call to this method.
+    ///
+    /// The lines are expressed in the local frame given by `transform` and `scale`. Anti-aliased
+    /// lines are blended with the scene behind them.
+    pub fn render(
+        &mut self,
+        pass: usize,
+        camera: &mut dyn Camera,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        width: f32,
+        antialiased: bool,
+    ) {
+        if self.vertices.len() == 0 {
+            return;
+        }
+
+        self.begin(pass, camera, transform, scale, width, antialiased);
+        self.color.enable();
+
+        self.pos.bind_sub_buffer(&mut self.vertices, 3, 0);
+        self.other.bind_sub_buffer(&mut self.vertices, 3, 1);
+        self.side.bind_sub_buffer(&mut self.vertices, 3, 2);
+        self.color.bind_sub_buffer(&mut self.vertices, 3, 3);
+
+        let ctxt = Context::get();
+        verify!(ctxt.draw_arrays(Context::TRIANGLES, 0, (self.vertices.len() / 4) as i32));
+
+        self.color.disable();
+        self.end(antialiased);
+
+        for vertices in self.vertices.data_mut().iter_mut() {
+            vertices.clear()
+        }
+    }
+
+    /// Draws the edges of `mesh` selected by `mode` with the given color.
+    ///
+    /// The vertices of the edges must have been built by `prepare_edges`, otherwise nothing is
+    /// drawn. Only the uniforms are uploaded, the vertices being cached by `mesh`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_edges(
+        &mut self,
+        pass: usize,
+        camera: &mut dyn Camera,
+        mesh: &mut Mesh,
+        mode: WireframeMode,
+        color: &Point3<f32>,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        width: f32,
+        antialiased: bool,
+    ) {
+        let edges = match mesh
+            .wide_line_edges_mut()
+            .iter_mut()
+            .find(|e| e.mode == mode)
+        {
+            Some(edges) if edges.vertices.len() != 0 => edges,
+            _ => return,
+        };
+
+        self.begin(pass, camera, transform, scale, width, antialiased);
+        self.color.set_constant(color);
+
+        self.pos.bind_sub_buffer(&mut edges.vertices, 2, 0);
+        self.other.bind_sub_buffer(&mut edges.vertices, 2, 1);
+        self.side.bind_sub_buffer(&mut edges.vertices, 2, 2);
+
+        let ctxt = Context::get();
+        verify!(ctxt.draw_arrays(Context::TRIANGLES, 0, (edges.vertices.len() / 3) as i32));
+
+        self.end(antialiased);
+    }
+
+    // Activates the shader and uploads the uniforms shared by all the lines.
+    fn begin(
+        &mut self,
+        pass: usize,
+        camera: &mut dyn Camera,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        width: f32,
+        antialiased: bool,
+    ) {
+        let ctxt = Context::get();
+        let (_, _, w, h) = ctxt.current_viewport();
+        let feather = if antialiased { FEATHER } else { 0.0 };
+
+        self.shader.use_program();
+        self.pos.enable();
+        self.other.enable();
+        self.side.enable();
+
+        camera.upload(pass, &mut self.proj, &mut self.view);
+        self.transform.upload(&transform.to_homogeneous());
+        self.scale.upload(&Matrix3::from_diagonal(scale));
+        self.viewport
+            .upload(&Vector2::new(w.max(1) as f32, h.max(1) as f32));
+        self.half_width.upload(&(width / 2.0 + feather));
+        self.feather.upload(&feather);
+
+        if antialiased {
+            verify!(ctxt.enable(Context::BLEND));
+            verify!(ctxt.blend_func_separate(
+                Context::SRC_ALPHA,
+                Context::ONE_MINUS_SRC_ALPHA,
+                Context::ONE,
+                Context::ONE_MINUS_SRC_ALPHA,
+            ));
+        }
+
+        verify!(ctxt.disable(Context::CULL_FACE));
+    }
+
+    fn end(&mut self, antialiased: bool) {
+        if antialiased {
+            verify!(Context::get().disable(Context::BLEND));
+        }
+
+        self.pos.disable();
+        self.other.disable();
+        self.side.disable();
+    }
+}
+
+/// Vertex shader used to display wide lines.
+static WIDE_LINES_VERTEX_SRC: &str = "#version 100
+    attribute vec3 position;
+    attribute vec3 other;
+    attribute vec3 side;
+    attribute vec3 color;
+    uniform   mat4 proj;
+    uniform   mat4 view;
+    uniform   mat4 transform;
+    uniform   mat3 scale;
+    uniform   vec2 viewport;
+    uniform   float half_width;
+    varying   vec3 vColor;
+    varying   float vDist;
+
+    void main() {
+        vec4 a = proj * view * transform * vec4(scale * position, 1.0);
+        vec4 b = proj * view * transform * vec4(scale * other, 1.0);
+
+        // The direction of the segment from its start to its end, in pixels.
+        vec2 dir = (b.xy / b.w - a.xy / a.w) * viewport * side.y;
+        float len = length(dir);
+        dir = len > 1.0e-6 ? dir / len : vec2(1.0, 0.0);
+
+        // Offset perpendicularly to the segment, and away from the other end so consecutive
+        // segments overlap at their joints.
+        vec2 offset = vec2(-dir.y, dir.x) * side.x - dir * side.y;
+        a.xy += offset * half_width * 2.0 / viewport * a.w;
+
+        gl_Position = a;
+        vColor = color;
+        vDist = side.x * half_width;
+    }";
+
+/// Fragment shader used to display wide lines.
+static WIDE_LINES_FRAGMENT_SRC: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform float half_width;
+    uniform float feather;
+    varying vec3  vColor;
+    varying float vDist;
+
+    void main() {
+        float alpha = 1.0;
+
+        if (feather > 0.0) {
+            alpha = clamp((half_width - abs(vDist)) / feather, 0.0, 1.0);
+        }
+
+        gl_FragColor = vec4(vColor, alpha);
+    }";
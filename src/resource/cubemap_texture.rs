@@ -0,0 +1,116 @@
+//! A texture made of six square faces.
+
+use image::{self, DynamicImage, GenericImageView, ImageError};
+use std::io::{Error, ErrorKind, Result as IoResult};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::context::{Context, Texture};
+
+#[path = "../error.rs"]
+mod error;
+
+/// A cubemap texture, sampled with a direction instead of texture coordinates.
+///
+/// The six faces are always given in the OpenGL order: `+x`, `-x`, `+y`, `-y`, `+z`, `-z`.
+pub struct CubemapTexture {
+    texture: Rc<Texture>,
+    size: u32,
+}
+
+impl CubemapTexture {
+    /// Loads a cubemap from six image files.
+    pub fn from_files(faces: [&Path; 6]) -> IoResult<CubemapTexture> {
+        let mut images = Vec::with_capacity(6);
+
+        for path in faces.iter() {
+            match image::open(path) {
+                Ok(image) => images.push(image),
+                Err(ImageError::IoError(e)) => return Err(e),
+                Err(e) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{}: {}", path.display(), e),
+                    ))
+                }
+            }
+        }
+
+        let mut images = images.into_iter();
+        let mut next = || images.next().unwrap();
+
+        CubemapTexture::from_images([next(), next(), next(), next(), next(), next()])
+    }
+
+    /// Creates a cubemap from six images.
+    ///
+    /// All the faces must be square and have the same dimensions.
+    pub fn from_images(faces: [DynamicImage; 6]) -> IoResult<CubemapTexture> {
+        let size = faces[0].width();
+
+        for (i, face) in faces.iter().enumerate() {
+            if face.dimensions() != (size, size) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Invalid cubemap face {}: all faces must be {}x{} but this one is {}x{}.",
+                        i,
+                        size,
+                        size,
+                        face.width(),
+                        face.height()
+                    ),
+                ));
+            }
+        }
+
+        let ctxt = Context::get();
+        let texture = Texture::new();
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(&*texture)));
+
+        for (i, face) in faces.iter().enumerate() {
+            let pixels = face.to_rgba8().into_raw();
+
+            verify!(ctxt.tex_image2d(
+                Context::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
+                0,
+                Context::RGBA as i32,
+                size as i32,
+                size as i32,
+                0,
+                Context::RGBA,
+                Some(&pixels[..])
+            ));
+        }
+
+        for param in [Context::TEXTURE_WRAP_S, Context::TEXTURE_WRAP_T].iter() {
+            verify!(ctxt.tex_parameteri(
+                Context::TEXTURE_CUBE_MAP,
+                *param,
+                Context::CLAMP_TO_EDGE as i32
+            ));
+        }
+
+        for param in [Context::TEXTURE_MIN_FILTER, Context::TEXTURE_MAG_FILTER].iter() {
+            verify!(ctxt.tex_parameteri(Context::TEXTURE_CUBE_MAP, *param, Context::LINEAR as i32));
+        }
+
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, None));
+
+        Ok(CubemapTexture { texture, size })
+    }
+
+    /// The underlying GPU texture, to be bound to `Context::TEXTURE_CUBE_MAP`.
+    #[inline]
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// The width and height of each face, in pixels.
+    #[inline]
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
@@ -1,3 +1,5 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::marker::PhantomData;
@@ -7,6 +9,7 @@ use std::str;
 
 use crate::context::{Context, GLintptr, Program, Shader, UniformLocation};
 use crate::resource::{GLPrimitive, GPUVec};
+use na::{Point3, Vector4};
 
 #[path = "../error.rs"]
 mod error;
@@ -16,6 +19,9 @@ pub struct Effect {
     program: Program,
     vshader: Shader,
     fshader: Shader,
+    // The locations found by `uniform` and `attribute`, or `None` if absent from the program.
+    uniforms: RefCell<HashMap<String, Option<UniformLocation>>>,
+    attributes: RefCell<HashMap<String, Option<u32>>>,
 }
 
 impl Effect {
@@ -42,14 +48,26 @@ impl Effect {
     }
 
     /// Creates a new shader program from strings of the vertex and fragment shader.
+    ///
+    /// # Failure
+    /// Panics if the shaders fail to compile or link.
     pub fn new_from_str(vshader: &str, fshader: &str) -> Effect {
-        let (program, vshader, fshader) = load_shader_program(vshader, fshader);
+        Effect::try_new_from_str(vshader, fshader).unwrap_or_else(|log| panic!("{}", log))
+    }
+
+    /// Creates a new shader program from strings of the vertex and fragment shader.
+    ///
+    /// Returns the information log of the context if the shaders fail to compile or link.
+    pub fn try_new_from_str(vshader: &str, fshader: &str) -> Result<Effect, String> {
+        let (program, vshader, fshader) = load_shader_program(vshader, fshader)?;
 
-        Effect {
+        Ok(Effect {
             program,
             vshader,
             fshader,
-        }
+            uniforms: RefCell::new(HashMap::new()),
+            attributes: RefCell::new(HashMap::new()),
+        })
     }
 
     /// Gets a uniform variable from the shader program.
@@ -60,7 +78,10 @@ impl Effect {
         if ctxt.get_error() == 0 {
             if let Some(id) = location {
                 let data_type = PhantomData;
-                return Some(ShaderUniform { id, data_type });
+                return Some(ShaderUniform {
+                    id: Some(id),
+                    data_type,
+                });
             }
         }
 
@@ -73,7 +94,7 @@ impl Effect {
         let location = ctxt.get_attrib_location(&self.program, name);
 
         if ctxt.get_error() == 0 && location != -1 {
-            let id = location as u32;
+            let id = Some(location as u32);
             let data_type = PhantomData;
             return Some(ShaderAttribute { id, data_type });
         }
@@ -81,6 +102,58 @@ impl Effect {
         return None;
     }
 
+    /// Gets a uniform variable from the shader program, caching its location.
+    ///
+    /// If the program has no active uniform with this name, e.g., if it has been optimized out
+    /// by the shader compiler, a warning is printed the first time, and the returned uniform
+    /// ignores the values uploaded to it.
+    pub fn uniform<T: GLPrimitive>(&self, name: &str) -> ShaderUniform<T> {
+        let id = self
+            .uniforms
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let id = self.get_uniform::<T>(name).and_then(|u| u.id);
+
+                if id.is_none() {
+                    println!("Warning: the shader uniform `{}` does not exist.", name);
+                }
+
+                id
+            })
+            .clone();
+
+        ShaderUniform {
+            id,
+            data_type: PhantomData,
+        }
+    }
+
+    /// Gets an attribute from the shader program, caching its location.
+    ///
+    /// If the program has no active attribute with this name, a warning is printed the first
+    /// time, and the returned attribute ignores every operation.
+    pub fn attribute<T: GLPrimitive>(&self, name: &str) -> ShaderAttribute<T> {
+        let id = *self
+            .attributes
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let id = self.get_attrib::<T>(name).and_then(|a| a.id);
+
+                if id.is_none() {
+                    println!("Warning: the shader attribute `{}` does not exist.", name);
+                }
+
+                id
+            });
+
+        ShaderAttribute {
+            id,
+            data_type: PhantomData,
+        }
+    }
+
     /// Make this program active.
     pub fn use_program(&mut self) {
         verify!(Context::get().use_program(Some(&self.program)));
@@ -104,46 +177,65 @@ impl Drop for Effect {
 
 /// Structure encapsulating an uniform variable.
 pub struct ShaderUniform<T> {
-    id: UniformLocation,
+    // `None` if the uniform does not exist.
+    id: Option<UniformLocation>,
     data_type: PhantomData<T>,
 }
 
 impl<T: GLPrimitive> ShaderUniform<T> {
     /// Upload a value to this variable.
     pub fn upload(&mut self, value: &T) {
-        value.upload(&self.id)
+        if let Some(ref id) = self.id {
+            value.upload(id)
+        }
     }
 }
 
 /// Structure encapsulating an attribute.
 pub struct ShaderAttribute<T> {
-    id: u32,
+    // `None` if the attribute does not exist.
+    id: Option<u32>,
     data_type: PhantomData<T>,
 }
 
 impl<T: GLPrimitive> ShaderAttribute<T> {
     /// Disable this attribute.
     pub fn disable(&mut self) {
-        verify!(Context::get().disable_vertex_attrib_array(self.id));
+        if let Some(id) = self.id {
+            verify!(Context::get().disable_vertex_attrib_array(id));
+        }
     }
 
     /// Enable this attribute.
     pub fn enable(&mut self) {
-        verify!(Context::get().enable_vertex_attrib_array(self.id));
+        if let Some(id) = self.id {
+            verify!(Context::get().enable_vertex_attrib_array(id));
+        }
     }
 
     /// Binds this attribute to a gpu vector.
     pub fn bind(&mut self, vector: &mut GPUVec<T>) {
         vector.bind();
 
-        verify!(Context::get().vertex_attrib_pointer(
-            self.id,
-            T::size() as i32,
-            T::gl_type(),
-            false,
-            0,
-            0
-        ));
+        if let Some(id) = self.id {
+            verify!(Context::get().vertex_attrib_pointer(
+                id,
+                T::size() as i32,
+                T::gl_type(),
+                false,
+                0,
+                0
+            ));
+        }
+    }
+
+    /// Sets the number of instances drawn before this attribute advances to its next element.
+    ///
+    /// A divisor of zero, the default, makes this attribute advance at each vertex.
+    pub fn set_divisor(&mut self, divisor: u32) {
+        if let Some(id) = self.id {
+            verify!(Context::get().vertex_attrib_divisor(id, divisor));
+        }
     }
 
     /// Binds this attribute to non contiguous parts of a gpu vector.
@@ -162,21 +254,44 @@ impl<T: GLPrimitive> ShaderAttribute<T> {
     ) {
         vector.bind();
 
-        verify!(Context::get().vertex_attrib_pointer(
-            self.id,
-            T::size() as i32,
-            T::gl_type(),
-            false,
-            ((strides + 1) * mem::size_of::<T2>()) as i32,
-            (start_index * mem::size_of::<T2>()) as GLintptr
-        ));
+        if let Some(id) = self.id {
+            verify!(Context::get().vertex_attrib_pointer(
+                id,
+                T::size() as i32,
+                T::gl_type(),
+                false,
+                ((strides + 1) * mem::size_of::<T2>()) as i32,
+                (start_index * mem::size_of::<T2>()) as GLintptr
+            ));
+        }
+    }
+}
+
+impl ShaderAttribute<Point3<f32>> {
+    /// Sets the value read by the shaders while this attribute is disabled.
+    pub fn set_constant(&mut self, value: &Point3<f32>) {
+        if let Some(id) = self.id {
+            verify!(Context::get().vertex_attrib3f(id, value.x, value.y, value.z));
+        }
+    }
+}
+
+impl ShaderAttribute<Vector4<f32>> {
+    /// Sets the value read by the shaders while this attribute is disabled.
+    pub fn set_constant(&mut self, value: &Vector4<f32>) {
+        if let Some(id) = self.id {
+            verify!(Context::get().vertex_attrib4f(id, value.x, value.y, value.z, value.w));
+        }
     }
 }
 
 /// Loads a shader program using the given source codes for the vertex and fragment shader.
 ///
-/// Fails after displaying opengl compilation errors if the shaders are invalid.
-fn load_shader_program(vertex_shader: &str, fragment_shader: &str) -> (Program, Shader, Shader) {
+/// Fails with the opengl compilation or link errors if the shaders are invalid.
+fn load_shader_program(
+    vertex_shader: &str,
+    fragment_shader: &str,
+) -> Result<(Program, Shader, Shader), String> {
     // Create and compile the vertex shader
     let ctxt = Context::get();
     let vshader = verify!(ctxt
@@ -185,7 +300,6 @@ fn load_shader_program(vertex_shader: &str, fragment_shader: &str) -> (Program,
 
     verify!(ctxt.shader_source(&vshader, vertex_shader));
     verify!(ctxt.compile_shader(&vshader));
-    check_shader_error(&vshader);
 
     // Create and compile the fragment shader
     let fshader = verify!(ctxt
@@ -193,28 +307,58 @@ fn load_shader_program(vertex_shader: &str, fragment_shader: &str) -> (Program,
         .expect("Could not create fragment shader."));
     verify!(ctxt.shader_source(&fshader, fragment_shader));
     verify!(ctxt.compile_shader(&fshader));
-    check_shader_error(&fshader);
 
     // Link the vertex and fragment shader into a shader program
     let program = verify!(ctxt.create_program().expect("Could not create program."));
     verify!(ctxt.attach_shader(&program, &vshader));
     verify!(ctxt.attach_shader(&program, &fshader));
     verify!(ctxt.link_program(&program));
-    (program, vshader, fshader)
+
+    let error = check_shader_error(&vshader, "Vertex shader compilation failed")
+        .or_else(|| check_shader_error(&fshader, "Fragment shader compilation failed"))
+        .or_else(|| check_program_error(&program));
+
+    match error {
+        Some(log) => {
+            verify!(ctxt.delete_program(Some(&program)));
+            verify!(ctxt.delete_shader(Some(&fshader)));
+            verify!(ctxt.delete_shader(Some(&vshader)));
+            Err(log)
+        }
+        None => Ok((program, vshader, fshader)),
+    }
 }
 
 /// Checks if a shader handle is valid.
 ///
-/// If it is not valid, it fails with a descriptive error message.
-fn check_shader_error(shader: &Shader) {
+/// If it is not valid, it returns a descriptive error message.
+fn check_shader_error(shader: &Shader, message: &str) -> Option<String> {
     let ctxt = Context::get();
     let compiles = ctxt.get_shader_parameter_int(shader, Context::COMPILE_STATUS);
 
     if compiles == Some(0) {
-        if let Some(log) = ctxt.get_shader_info_log(shader) {
-            panic!("Shader compilation failed: {}", log);
-        } else {
-            println!("Shader compilation failed.");
+        match ctxt.get_shader_info_log(shader) {
+            Some(log) => Some(format!("{}: {}", message, log)),
+            None => Some(format!("{}.", message)),
+        }
+    } else {
+        None
+    }
+}
+
+/// Checks if a program has been linked successfully.
+///
+/// If it has not, it returns a descriptive error message.
+fn check_program_error(program: &Program) -> Option<String> {
+    let ctxt = Context::get();
+    let links = ctxt.get_program_parameter_int(program, Context::LINK_STATUS);
+
+    if links == Some(0) {
+        match ctxt.get_program_info_log(program) {
+            Some(log) => Some(format!("Shader program linking failed: {}", log)),
+            None => Some("Shader program linking failed.".to_string()),
         }
+    } else {
+        None
     }
 }
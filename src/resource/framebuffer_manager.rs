@@ -18,6 +18,12 @@ pub enum RenderTarget {
 pub struct OffscreenBuffers {
     texture: Texture,
     depth: Either<Texture, Renderbuffer>,
+    // Whether the depth buffer is combined with a stencil buffer.
+    stencil: bool,
+    // Whether the depth buffer stores 32-bits floating-point values.
+    float_depth: bool,
+    scale: f32,
+    size: (usize, usize),
 }
 
 impl RenderTarget {
@@ -42,7 +48,20 @@ impl RenderTarget {
         }
     }
 
+    /// The width and height of the off-screen buffers, in pixels.
+    ///
+    /// Returns `None` if the texture is on-screen.
+    pub fn size(&self) -> Option<(usize, usize)> {
+        match *self {
+            RenderTarget::Screen => None,
+            RenderTarget::Offscreen(ref o) => Some(o.size),
+        }
+    }
+
     /// Resizes this render target.
+    ///
+    /// The size of off-screen buffers created with `FramebufferManager::new_scaled_render_target`
+    /// is multiplied by their scale factor.
     pub fn resize(&mut self, w: f32, h: f32) {
         let ctxt = Context::get();
 
@@ -50,7 +69,10 @@ impl RenderTarget {
             RenderTarget::Screen => {
                 verify!(ctxt.viewport(0, 0, w as i32, h as i32));
             }
-            RenderTarget::Offscreen(ref o) => {
+            RenderTarget::Offscreen(ref mut o) => {
+                o.size = scaled_size(w, h, o.scale);
+                let (w, h) = (o.size.0 as f32, o.size.1 as f32);
+
                 // Update the fbo
                 verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&o.texture)));
                 verify!(ctxt.tex_image2d(
@@ -65,40 +87,88 @@ impl RenderTarget {
                 ));
                 verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
 
-                match &o.depth {
-                    Either::Left(texture) => {
-                        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(texture)));
-                        verify!(ctxt.tex_image2d(
-                            Context::TEXTURE_2D,
-                            0,
-                            Context::DEPTH_COMPONENT as i32,
-                            w as i32,
-                            h as i32,
-                            0,
-                            Context::DEPTH_COMPONENT,
-                            None
-                        ));
-                        verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
-                    }
-                    Either::Right(renderbuffer) => {
-                        verify!(ctxt.bind_renderbuffer(Some(renderbuffer)));
-                        verify!(ctxt.renderbuffer_storage(
-                            Context::DEPTH_COMPONENT16,
-                            w as i32,
-                            h as i32
-                        ));
-                        verify!(ctxt.bind_renderbuffer(None));
-                    }
-                }
+                allocate_depth(o);
             }
         }
     }
+
+    /// Sets whether the depth buffer of this render target stores 32-bits floating-point values
+    /// instead of fixed-point ones, e.g., for `Window::set_reversed_z`.
+    ///
+    /// Its content is lost if this changes. This does nothing for the screen, and is not
+    /// supported by OpenGL ES 2 nor WebGL.
+    pub fn set_float_depth(&mut self, enabled: bool) {
+        if let RenderTarget::Offscreen(ref mut o) = *self {
+            if o.float_depth != enabled {
+                o.float_depth = enabled;
+                allocate_depth(o);
+            }
+        }
+    }
+}
+
+/// A multisampled off-screen buffer.
+///
+/// Multisampled buffers cannot be sampled by shaders, so their content has to be resolved into
+/// a regular render target with `FramebufferManager::resolve` before being used. Their depth
+/// buffer is combined with a stencil buffer.
+pub struct MultisampledRenderTarget {
+    fbo: Framebuffer,
+    color: Renderbuffer,
+    depth: Renderbuffer,
+    // Whether the depth buffer stores 32-bits floating-point values.
+    float_depth: bool,
+    samples: i32,
+    size: (usize, usize),
+}
+
+impl MultisampledRenderTarget {
+    /// The number of samples per pixel of this render target.
+    #[inline]
+    pub fn samples(&self) -> u32 {
+        self.samples as u32
+    }
+
+    /// The width and height of this render target, in pixels.
+    #[inline]
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Resizes this render target.
+    pub fn resize(&mut self, w: f32, h: f32) {
+        let ctxt = Context::get();
+        self.size = scaled_size(w, h, 1.0);
+        let (w, h) = (self.size.0 as i32, self.size.1 as i32);
+
+        verify!(ctxt.bind_renderbuffer(Some(&self.color)));
+        verify!(ctxt.renderbuffer_storage_multisample(self.samples, Context::RGBA8, w, h));
+        verify!(ctxt.bind_renderbuffer(Some(&self.depth)));
+        verify!(ctxt.renderbuffer_storage_multisample(
+            self.samples,
+            depth_renderbuffer_format(true, self.float_depth),
+            w,
+            h
+        ));
+        verify!(ctxt.bind_renderbuffer(None));
+    }
+
+    /// Sets whether the depth buffer of this render target stores 32-bits floating-point values,
+    /// like `RenderTarget::set_float_depth`.
+    pub fn set_float_depth(&mut self, enabled: bool) {
+        if self.float_depth != enabled {
+            self.float_depth = enabled;
+            self.resize(self.size.0 as f32, self.size.1 as f32);
+        }
+    }
 }
 
 /// A framebuffer manager. It is a simple to to switch between an off-screen framebuffer and the
 /// default (window) framebuffer.
 pub struct FramebufferManager {
     fbo_onscreen: bool,
+    // Whether a multisampled render target is bound instead of the one above.
+    multisampled_selected: bool,
     fbo: Framebuffer,
 }
 
@@ -117,6 +187,7 @@ impl FramebufferManager {
 
         FramebufferManager {
             fbo_onscreen: true,
+            multisampled_selected: false,
             fbo: fbo,
         }
     }
@@ -127,8 +198,58 @@ impl FramebufferManager {
         width: usize,
         height: usize,
         create_depth_texture: bool,
+    ) -> RenderTarget {
+        FramebufferManager::new_scaled_render_target(width, height, 1.0, create_depth_texture)
+    }
+
+    /// Creates a new render target with a size equal to a fraction of `width` and `height`.
+    ///
+    /// The `scale` factor is kept when the render target is resized, e.g., a scale of 0.5 gives
+    /// a render target with half the window resolution.
+    pub fn new_scaled_render_target(
+        width: usize,
+        height: usize,
+        scale: f32,
+        create_depth_texture: bool,
+    ) -> RenderTarget {
+        FramebufferManager::new_render_target_impl(
+            width,
+            height,
+            scale,
+            create_depth_texture,
+            false,
+        )
+    }
+
+    /// Creates a new render target whose depth buffer is combined with a stencil buffer.
+    ///
+    /// The depth texture, if any, can still be sampled as a regular depth texture. The stencil
+    /// buffer is not supported by WebGL, which creates a render target without it instead.
+    pub fn new_stencil_render_target(
+        width: usize,
+        height: usize,
+        create_depth_texture: bool,
+    ) -> RenderTarget {
+        let stencil = cfg!(not(target_arch = "wasm32"));
+        FramebufferManager::new_render_target_impl(
+            width,
+            height,
+            1.0,
+            create_depth_texture,
+            stencil,
+        )
+    }
+
+    fn new_render_target_impl(
+        width: usize,
+        height: usize,
+        scale: f32,
+        create_depth_texture: bool,
+        stencil: bool,
     ) -> RenderTarget {
         let ctxt = Context::get();
+        let size = scaled_size(width as f32, height as f32, scale);
+        let (width, height) = size;
 
         /* Texture */
         verify!(ctxt.active_texture(Context::TEXTURE0));
@@ -169,7 +290,7 @@ impl FramebufferManager {
         verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
 
         /* Depth buffer */
-        if create_depth_texture && cfg!(not(any(target_arch = "wasm32", target_arch = "asmjs"))) {
+        if create_depth_texture && cfg!(not(target_arch = "wasm32")) {
             verify!(ctxt.active_texture(Context::TEXTURE1));
             let fbo_depth = verify!(ctxt.create_texture().expect("Failed to create a texture."));
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&fbo_depth)));
@@ -193,21 +314,35 @@ impl FramebufferManager {
                 Context::TEXTURE_WRAP_T,
                 Context::CLAMP_TO_EDGE as i32
             ));
-            verify!(ctxt.tex_image2di(
-                Context::TEXTURE_2D,
-                0,
-                Context::DEPTH_COMPONENT as i32,
-                width as i32,
-                height as i32,
-                0,
-                Context::DEPTH_COMPONENT,
-                None
-            ));
+
+            if stencil {
+                verify!(ctxt.tex_image2d_depth_stencil(
+                    Context::TEXTURE_2D,
+                    width as i32,
+                    height as i32
+                ));
+            } else {
+                verify!(ctxt.tex_image2di(
+                    Context::TEXTURE_2D,
+                    0,
+                    Context::DEPTH_COMPONENT as i32,
+                    width as i32,
+                    height as i32,
+                    0,
+                    Context::DEPTH_COMPONENT,
+                    None
+                ));
+            }
+
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
 
             RenderTarget::Offscreen(OffscreenBuffers {
                 texture: fbo_texture,
                 depth: Either::Left(fbo_depth),
+                stencil,
+                float_depth: false,
+                scale,
+                size,
             })
         } else {
             // Create a renderbuffer instead of the texture for the depth.
@@ -215,7 +350,7 @@ impl FramebufferManager {
                 verify!(ctxt.create_renderbuffer()).expect("Failed to create a renderbuffer.");
             verify!(ctxt.bind_renderbuffer(Some(&renderbuffer)));
             verify!(ctxt.renderbuffer_storage(
-                Context::DEPTH_COMPONENT16,
+                depth_renderbuffer_format(stencil, false),
                 width as i32,
                 height as i32
             ));
@@ -224,10 +359,52 @@ impl FramebufferManager {
             RenderTarget::Offscreen(OffscreenBuffers {
                 texture: fbo_texture,
                 depth: Either::Right(renderbuffer),
+                stencil,
+                float_depth: false,
+                scale,
+                size,
             })
         }
     }
 
+    /// Creates a new multisampled render target with `samples` samples per pixel.
+    ///
+    /// Returns `None` if multisampled off-screen buffers are not supported by the context, or if
+    /// `samples` is larger than the number of samples it supports.
+    pub fn new_multisampled_render_target(
+        width: usize,
+        height: usize,
+        samples: u32,
+    ) -> Option<MultisampledRenderTarget> {
+        let ctxt = Context::get();
+
+        if samples < 2 || samples as i32 > ctxt.max_samples() {
+            return None;
+        }
+
+        let fbo = ctxt.create_framebuffer()?;
+        let color = verify!(ctxt.create_renderbuffer())?;
+        let depth = verify!(ctxt.create_renderbuffer())?;
+        let mut target = MultisampledRenderTarget {
+            fbo,
+            color,
+            depth,
+            float_depth: false,
+            samples: samples as i32,
+            size: (width, height),
+        };
+        target.resize(width as f32, height as f32);
+
+        verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, Some(&target.fbo)));
+        verify!(ctxt.framebuffer_renderbuffer(Context::COLOR_ATTACHMENT0, Some(&target.color)));
+        verify!(
+            ctxt.framebuffer_renderbuffer(Context::DEPTH_STENCIL_ATTACHMENT, Some(&target.depth))
+        );
+        verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, None));
+
+        Some(target)
+    }
+
     /// Returns the render target associated with the screen.
     pub fn screen() -> RenderTarget {
         RenderTarget::Screen
@@ -253,38 +430,201 @@ impl FramebufferManager {
                     0
                 ));
 
-                match &o.depth {
-                    Either::Left(texture) => {
-                        verify!(ctxt.framebuffer_texture2d(
-                            Context::FRAMEBUFFER,
-                            Context::DEPTH_ATTACHMENT,
-                            Context::TEXTURE_2D,
-                            Some(texture),
-                            0
-                        ));
-                    }
-                    Either::Right(renderbuffer) => verify!(ctxt
-                        .framebuffer_renderbuffer(Context::DEPTH_ATTACHMENT, Some(renderbuffer))),
-                }
+                attach_depth(o);
             }
         }
     }
 
+    /// Selects an off-screen render target, with `texture` as its color buffer instead of its
+    /// own texture.
+    ///
+    /// `texture` must have the same size as `target`. Selecting the screen ignores `texture`.
+    pub fn select_with_texture(&mut self, target: &RenderTarget, texture: &Texture) {
+        self.select(target);
+
+        if let RenderTarget::Offscreen(_) = *target {
+            verify!(Context::get().framebuffer_texture2d(
+                Context::FRAMEBUFFER,
+                Context::COLOR_ATTACHMENT0,
+                Context::TEXTURE_2D,
+                Some(texture),
+                0
+            ));
+        }
+    }
+
+    /// Selects a multisampled render target.
+    pub fn select_multisampled(&mut self, target: &MultisampledRenderTarget) {
+        verify!(Context::get().bind_framebuffer(Context::FRAMEBUFFER, Some(&target.fbo)));
+        self.multisampled_selected = true;
+    }
+
+    /// Resolves the samples of `source` into the color and depth buffers of `target`, which is
+    /// selected afterwards.
+    ///
+    /// The depth is only resolved if the depth buffer of `target` has the same format as the
+    /// one of `source`, which is the case with most desktop drivers.
+    pub fn resolve(&mut self, source: &MultisampledRenderTarget, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.select(target);
+
+        let (w, h) = source.size;
+        let (tw, th) = target.size().unwrap_or((w, h));
+        let src = (0, 0, w as i32, h as i32);
+        let dst = (0, 0, tw as i32, th as i32);
+
+        verify!(ctxt.bind_framebuffer(Context::READ_FRAMEBUFFER, Some(&source.fbo)));
+        verify!(ctxt.blit_framebuffer(src, dst, Context::COLOR_BUFFER_BIT, Context::NEAREST));
+        ignore!(ctxt.blit_framebuffer(src, dst, Context::DEPTH_BUFFER_BIT, Context::NEAREST));
+
+        // Read from `target` again too.
+        if self.fbo_onscreen {
+            verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, None));
+        } else {
+            verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, Some(&self.fbo)));
+        }
+    }
+
+    /// Reads the depth buffer of a render target, as stored in the depth buffer, i.e., on the
+    /// range `[0.0, 1.0]`.
+    ///
+    /// The screen framebuffer is selected again afterwards if it was selected. Otherwise, the
+    /// off-screen target must be selected again before rendering to it. This is not supported by
+    /// WebGL.
+    pub fn read_depth(
+        &self,
+        target: &RenderTarget,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        out: &mut [f32],
+    ) {
+        let ctxt = Context::get();
+
+        if let RenderTarget::Offscreen(ref o) = *target {
+            verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, Some(&self.fbo)));
+            verify!(ctxt.framebuffer_texture2d(
+                Context::FRAMEBUFFER,
+                Context::COLOR_ATTACHMENT0,
+                Context::TEXTURE_2D,
+                Some(&o.texture),
+                0
+            ));
+
+            attach_depth(o);
+        } else {
+            verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, None));
+        }
+
+        verify!(ctxt.read_depth_pixels(x as i32, y as i32, width as i32, height as i32, out));
+
+        if self.fbo_onscreen {
+            verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, None));
+        } else {
+            verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, Some(&self.fbo)));
+        }
+    }
+
     fn select_onscreen(&mut self) {
-        if !self.fbo_onscreen {
+        if !self.fbo_onscreen || self.multisampled_selected {
             verify!(Context::get().bind_framebuffer(Context::FRAMEBUFFER, None));
             self.fbo_onscreen = true;
+            self.multisampled_selected = false;
         }
     }
 
     fn select_fbo(&mut self) {
-        if self.fbo_onscreen {
+        if self.fbo_onscreen || self.multisampled_selected {
             verify!(Context::get().bind_framebuffer(Context::FRAMEBUFFER, Some(&self.fbo)));
             self.fbo_onscreen = false;
+            self.multisampled_selected = false;
         }
     }
 }
 
+// The format of a depth renderbuffer, combined with a stencil buffer if `stencil` is `true`.
+fn depth_renderbuffer_format(stencil: bool, float_depth: bool) -> u32 {
+    match (stencil, float_depth) {
+        (true, false) => Context::DEPTH24_STENCIL8,
+        (false, false) => Context::DEPTH_COMPONENT16,
+        (true, true) => Context::DEPTH32F_STENCIL8,
+        (false, true) => Context::DEPTH_COMPONENT32F,
+    }
+}
+
+// Allocates the depth buffer of `buffers` with their size and format.
+fn allocate_depth(buffers: &OffscreenBuffers) {
+    let ctxt = Context::get();
+    let (w, h) = (buffers.size.0 as i32, buffers.size.1 as i32);
+
+    match &buffers.depth {
+        Either::Left(texture) => {
+            verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(texture)));
+
+            if buffers.float_depth {
+                verify!(ctxt.tex_image2d_float_depth(Context::TEXTURE_2D, w, h, buffers.stencil));
+            } else if buffers.stencil {
+                verify!(ctxt.tex_image2d_depth_stencil(Context::TEXTURE_2D, w, h));
+            } else {
+                verify!(ctxt.tex_image2d(
+                    Context::TEXTURE_2D,
+                    0,
+                    Context::DEPTH_COMPONENT as i32,
+                    w,
+                    h,
+                    0,
+                    Context::DEPTH_COMPONENT,
+                    None
+                ));
+            }
+
+            verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+        }
+        Either::Right(renderbuffer) => {
+            verify!(ctxt.bind_renderbuffer(Some(renderbuffer)));
+            verify!(ctxt.renderbuffer_storage(
+                depth_renderbuffer_format(buffers.stencil, buffers.float_depth),
+                w,
+                h
+            ));
+            verify!(ctxt.bind_renderbuffer(None));
+        }
+    }
+}
+
+// Attaches the depth buffer of `buffers` to the bound framebuffer, along with its stencil buffer
+// if any. Otherwise, the stencil buffer of a previously attached target is detached.
+fn attach_depth(buffers: &OffscreenBuffers) {
+    let ctxt = Context::get();
+    let attachment = if buffers.stencil {
+        Context::DEPTH_STENCIL_ATTACHMENT
+    } else {
+        verify!(ctxt.framebuffer_renderbuffer(Context::STENCIL_ATTACHMENT, None));
+        Context::DEPTH_ATTACHMENT
+    };
+
+    match &buffers.depth {
+        Either::Left(texture) => {
+            verify!(ctxt.framebuffer_texture2d(
+                Context::FRAMEBUFFER,
+                attachment,
+                Context::TEXTURE_2D,
+                Some(texture),
+                0
+            ));
+        }
+        Either::Right(renderbuffer) => {
+            verify!(ctxt.framebuffer_renderbuffer(attachment, Some(renderbuffer)))
+        }
+    }
+}
+
+// The size of a scaled render target, which is never empty.
+fn scaled_size(w: f32, h: f32, scale: f32) -> (usize, usize) {
+    (((w * scale) as usize).max(1), ((h * scale) as usize).max(1))
+}
+
 impl Drop for FramebufferManager {
     fn drop(&mut self) {
         let ctxt = Context::get();
@@ -295,6 +635,21 @@ impl Drop for FramebufferManager {
     }
 }
 
+impl Drop for MultisampledRenderTarget {
+    fn drop(&mut self) {
+        let ctxt = Context::get();
+        if verify!(ctxt.is_framebuffer(Some(&self.fbo))) {
+            verify!(ctxt.delete_framebuffer(Some(&self.fbo)));
+        }
+
+        for renderbuffer in [&self.color, &self.depth].iter() {
+            if verify!(ctxt.is_renderbuffer(Some(renderbuffer))) {
+                verify!(ctxt.delete_renderbuffer(Some(renderbuffer)));
+            }
+        }
+    }
+}
+
 impl Drop for OffscreenBuffers {
     fn drop(&mut self) {
         let ctxt = Context::get();
@@ -2,6 +2,7 @@
 
 use crate::context::{Buffer, Context};
 use crate::resource::gl_primitive::GLPrimitive;
+use std::mem::size_of;
 
 #[path = "../error.rs"]
 mod error;
@@ -15,6 +16,10 @@ pub struct GPUVec<T> {
     alloc_type: AllocationType,
     buffer: Option<(usize, Buffer)>,
     data: Option<Vec<T>>,
+    // The range of elements modified by `update_range` since the last upload.
+    dirty: Option<(usize, usize)>,
+    // Incremented each time the elements may have been modified.
+    version: usize,
 }
 
 // FIXME: implement Clone
@@ -28,6 +33,8 @@ impl<T: GLPrimitive> GPUVec<T> {
             alloc_type: alloc_type,
             buffer: None,
             data: Some(data),
+            dirty: None,
+            version: 0,
         }
     }
 
@@ -50,6 +57,7 @@ impl<T: GLPrimitive> GPUVec<T> {
     #[inline]
     pub fn data_mut(&mut self) -> &mut Option<Vec<T>> {
         self.trash = true;
+        self.version += 1;
 
         &mut self.data
     }
@@ -60,6 +68,15 @@ impl<T: GLPrimitive> GPUVec<T> {
         &self.data
     }
 
+    /// A number incremented each time the elements of this vector may have been modified, by
+    /// `data_mut` or `update_range`.
+    ///
+    /// This allows data derived from the elements to be cached until they change.
+    #[inline]
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
     /// Returns `true` if this vector is already uploaded to the GPU.
     #[inline]
     pub fn is_on_gpu(&self) -> bool {
@@ -67,6 +84,8 @@ impl<T: GLPrimitive> GPUVec<T> {
     }
 
     /// Returns `true` if the cpu data and gpu data are out of sync.
+    ///
+    /// This is `false` if only a range modified by `update_range` has not yet been uploaded.
     #[inline]
     pub fn trash(&self) -> bool {
         self.trash
@@ -102,9 +121,54 @@ impl<T: GLPrimitive> GPUVec<T> {
                     *len = update_buffer(&d[..], *len, buffer, self.buf_type, self.alloc_type)
                 }
             }
+        } else if let Some((start, end)) = self.dirty {
+            if let (Some((_, ref buffer)), Some(ref d)) = (&self.buffer, &self.data) {
+                let ctxt = Context::get();
+                let offset = start * size_of::<T>();
+
+                verify!(ctxt.bind_buffer(self.buf_type.to_gl(), Some(buffer)));
+                verify!(ctxt.buffer_sub_data(self.buf_type.to_gl(), offset as u32, &d[start..end]));
+            }
         }
 
         self.trash = false;
+        self.dirty = None;
+    }
+
+    /// Replaces the elements starting at `start` by `values`.
+    ///
+    /// Contrary to the modifications made through `data_mut`, this does not mark the whole
+    /// vector as `trash`: if it is already on the GPU, only the span of elements modified since
+    /// the last upload is uploaded again at the next bind. The elements are always uploaded
+    /// whole, at offsets multiple of the size of `T`. The length of the vector cannot be changed
+    /// this way: use `data_mut` to resize it, which uploads the whole vector again.
+    ///
+    /// # Failure
+    /// Fails if this vector is not available on RAM, or if `start + values.len()` is larger than
+    /// its length.
+    pub fn update_range(&mut self, start: usize, values: &[T]) {
+        let data = self
+            .data
+            .as_mut()
+            .expect("The vector must be available on RAM to be updated.");
+        let end = start + values.len();
+        assert!(
+            end <= data.len(),
+            "The updated range {}..{} is out of bounds of a vector of length {}.",
+            start,
+            end,
+            data.len()
+        );
+        data[start..end].copy_from_slice(values);
+        self.version += 1;
+
+        // If the vector is trash, the whole vector will be uploaded at the next bind anyway.
+        if !self.trash && self.buffer.is_some() && !values.is_empty() {
+            self.dirty = Some(match self.dirty {
+                Some((s, e)) => (s.min(start), e.max(end)),
+                None => (start, end),
+            });
+        }
     }
 
     /// Binds this vector to the appropriate gpu array.
@@ -153,6 +217,7 @@ impl<T: GLPrimitive> GPUVec<T> {
         self.len = self.len();
         self.buffer = None;
         self.trash = false;
+        self.dirty = None;
     }
 
     /// Removes this resource from the RAM.
@@ -160,7 +225,7 @@ impl<T: GLPrimitive> GPUVec<T> {
     /// This is useful to save memory for vectors required on the GPU only.
     #[inline]
     pub fn unload_from_ram(&mut self) {
-        if self.trash && self.is_on_gpu() {
+        if (self.trash || self.dirty.is_some()) && self.is_on_gpu() {
             self.load_to_gpu();
         }
 
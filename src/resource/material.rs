@@ -1,7 +1,7 @@
 //! Trait implemented by materials.
 
 use crate::camera::Camera;
-use crate::light::Light;
+use crate::light::Lights;
 use crate::planar_camera::PlanarCamera;
 use crate::resource::{Mesh, PlanarMesh};
 use crate::scene::{ObjectData, PlanarObjectData};
@@ -17,7 +17,7 @@ pub trait Material {
         transform: &Isometry3<f32>,
         scale: &Vector3<f32>,
         camera: &mut dyn Camera, // FIXME: replace those two arguments by
-        light: &Light,           // a structure with all environment datas
+        lights: &Lights,         // a structure with all environment datas
         data: &ObjectData,
         mesh: &mut Mesh,
     );
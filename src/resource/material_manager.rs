@@ -1,6 +1,9 @@
 //! A resource manager to load materials.
 
-use crate::builtin::{NormalsMaterial, ObjectMaterial, UvsMaterial};
+use crate::builtin::{
+    InstancedMaterial, NormalsMaterial, ObjectMaterial, PbrMaterial, PointCloudMaterial,
+    UvsMaterial, VertexColorMaterial,
+};
 use crate::resource::Material;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -13,6 +16,12 @@ thread_local!(static KEY_MATERIAL_MANAGER: RefCell<MaterialManager> = RefCell::n
 /// Upon construction, it contains:
 /// * the `object` material, used as the default to render objects.
 /// * the `normals` material, used do display an object normals.
+/// * the `uvs` material, used do display an object texture coordinates.
+/// * the `vertex_color` material, used do display an object per-vertex colors.
+/// * the `instanced` material, used to draw the instances of an `InstancedNode`.
+/// * the `point_cloud` material, used to draw the points of a `PointCloudNode`.
+/// * the `pbr` material, a physically-based material with the default parameters of a
+///   `PbrMaterial`.
 ///
 /// It keeps a cache of already-loaded materials. Note that this is only a cache, nothing more.
 /// Thus, its usage is not required to load materials.
@@ -42,6 +51,26 @@ impl MaterialManager {
         ));
         let _ = materials.insert("uvs".to_string(), um.clone());
 
+        let vcm = Rc::new(RefCell::new(
+            Box::new(VertexColorMaterial::new()) as Box<dyn Material + 'static>
+        ));
+        let _ = materials.insert("vertex_color".to_string(), vcm.clone());
+
+        let im = Rc::new(RefCell::new(
+            Box::new(InstancedMaterial::new()) as Box<dyn Material + 'static>
+        ));
+        let _ = materials.insert("instanced".to_string(), im.clone());
+
+        let pcm = Rc::new(RefCell::new(
+            Box::new(PointCloudMaterial::new()) as Box<dyn Material + 'static>
+        ));
+        let _ = materials.insert("point_cloud".to_string(), pcm.clone());
+
+        let pbrm = Rc::new(RefCell::new(
+            Box::new(PbrMaterial::new()) as Box<dyn Material + 'static>
+        ));
+        let _ = materials.insert("pbr".to_string(), pbrm.clone());
+
         MaterialManager {
             default_material: om,
             materials: materials,
@@ -1,10 +1,14 @@
 //! Data structure of a scene node geometry.
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::iter;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Once, RwLock};
 
+use crate::renderer::wide_line_renderer::WideLineEdges;
 use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
+use crate::resource::mesh_simplification;
 use crate::resource::ShaderAttribute;
-use na::{self, Point2, Point3, Vector3};
+use na::{self, Point2, Point3, Vector3, Vector4};
 use ncollide3d::procedural::{IndexBuffer, TriMesh};
 use num::Zero;
 
@@ -19,7 +23,22 @@ pub struct Mesh {
     faces: Arc<RwLock<GPUVec<Point3<u16>>>>,
     normals: Arc<RwLock<GPUVec<Vector3<f32>>>>,
     uvs: Arc<RwLock<GPUVec<Point2<f32>>>>,
+    colors: Option<Arc<RwLock<GPUVec<Point3<f32>>>>>,
+    tangents: Option<Arc<RwLock<GPUVec<Vector4<f32>>>>>,
     edges: Option<Arc<RwLock<GPUVec<Point2<u16>>>>>,
+    // The edges drawn by `WireframeMode::Edges`.
+    wireframe_edges: Option<Arc<RwLock<GPUVec<Point2<u16>>>>>,
+    // The edges drawn as lines wider than one pixel, for each wireframe mode.
+    wide_line_edges: Vec<WideLineEdges>,
+    aabb: RwLock<Option<CachedAabb>>,
+}
+
+// The local AABB of the vertices of a mesh, and the version of the vertices it was computed from.
+#[derive(Clone, Copy)]
+struct CachedAabb {
+    version: usize,
+    mins: Point3<f32>,
+    maxs: Point3<f32>,
 }
 
 impl Mesh {
@@ -186,7 +205,12 @@ impl Mesh {
             faces: faces,
             normals: normals,
             uvs: uvs,
+            colors: None,
+            tangents: None,
             edges: None,
+            wireframe_edges: None,
+            wide_line_edges: Vec::new(),
+            aabb: RwLock::new(None),
         }
     }
 
@@ -205,6 +229,32 @@ impl Mesh {
         uvs.bind(&mut *self.uvs.write().unwrap());
     }
 
+    /// Binds this mesh vertex colors buffer to a vertex attribute.
+    ///
+    /// Returns `false` if this mesh does not have any vertex colors.
+    pub fn bind_colors(&mut self, colors: &mut ShaderAttribute<Point3<f32>>) -> bool {
+        match self.colors {
+            Some(ref cs) => {
+                colors.bind(&mut *cs.write().unwrap());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Binds this mesh vertex tangents buffer to a vertex attribute.
+    ///
+    /// Returns `false` if this mesh does not have any tangents.
+    pub fn bind_tangents(&mut self, tangents: &mut ShaderAttribute<Vector4<f32>>) -> bool {
+        match self.tangents {
+            Some(ref ts) => {
+                tangents.bind(&mut *ts.write().unwrap());
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Binds this mesh index buffer to a vertex attribute.
     pub fn bind_faces(&mut self) {
         self.faces.write().unwrap().bind();
@@ -240,12 +290,58 @@ impl Mesh {
         self.edges.as_mut().unwrap().write().unwrap().bind();
     }
 
+    /// The edges of this mesh without the diagonals of its flat polygons.
+    ///
+    /// Each edge shared by several triangles is listed once. The edges shared by exactly two
+    /// coplanar triangles are not listed, so, e.g., the quads of a mesh loaded from an obj file
+    /// are outlined without the diagonal they were split along. The edges are compared by the
+    /// positions of their vertices, so this also works for the meshes whose vertices are
+    /// duplicated along the seams of their texture coordinates or normals.
+    ///
+    /// The edges are computed from the faces and vertices in RAM the first time they are needed,
+    /// and are not updated if those are modified afterwards.
+    pub fn wireframe_edges(&mut self) -> &Arc<RwLock<GPUVec<Point2<u16>>>> {
+        if self.wireframe_edges.is_none() {
+            let coords = self.coords.read().unwrap();
+            let faces = self.faces.read().unwrap();
+            let edges = match (coords.data(), faces.data()) {
+                (Some(coords), Some(faces)) => compute_wireframe_edges(coords, faces),
+                _ => Vec::new(),
+            };
+            let gpu_edges =
+                GPUVec::new(edges, BufferType::ElementArray, AllocationType::StaticDraw);
+            self.wireframe_edges = Some(Arc::new(RwLock::new(gpu_edges)));
+        }
+
+        self.wireframe_edges.as_ref().unwrap()
+    }
+
+    // The vertices drawing the edges of this mesh as wide lines, managed by `WideLineRenderer`.
+    pub(crate) fn wide_line_edges_mut(&mut self) -> &mut Vec<WideLineEdges> {
+        &mut self.wide_line_edges
+    }
+
+    /// Binds the buffer of the edges returned by `wireframe_edges`, and returns their number.
+    pub fn bind_wireframe_edges(&mut self) -> usize {
+        let mut edges = self.wireframe_edges().write().unwrap();
+        edges.bind();
+        edges.len()
+    }
+
     /// Unbind this mesh buffers to vertex attributes.
     pub fn unbind(&self) {
         self.coords.write().unwrap().unbind();
         self.normals.write().unwrap().unbind();
         self.uvs.write().unwrap().unbind();
         self.faces.write().unwrap().unbind();
+
+        if let Some(ref cs) = self.colors {
+            cs.write().unwrap().unbind();
+        }
+
+        if let Some(ref ts) = self.tangents {
+            ts.write().unwrap().unbind();
+        }
     }
 
     /// Number of points needed to draw this mesh.
@@ -253,13 +349,287 @@ impl Mesh {
         self.faces.read().unwrap().len() * 3
     }
 
-    /// Recompute this mesh normals.
+    /// Recompute this mesh normals from its vertex coordinates.
+    ///
+    /// The normals are smooth: the normal of each vertex is the area-weighted average of the
+    /// normals of its adjacent faces. The normals are uploaded to the GPU the next time this mesh
+    /// is drawn, and the tangents are recomputed if this mesh has any. Nothing is done, and a
+    /// warning is printed, if the data of this mesh is not available on the CPU.
     pub fn recompute_normals(&mut self) {
-        Mesh::compute_normals(
-            &self.coords.read().unwrap().data().as_ref().unwrap()[..],
-            &self.faces.read().unwrap().data().as_ref().unwrap()[..],
-            self.normals.write().unwrap().data_mut().as_mut().unwrap(),
-        );
+        {
+            let coords = self.coords.read().unwrap();
+            let faces = self.faces.read().unwrap();
+            let mut normals = self.normals.write().unwrap();
+
+            match (coords.data(), faces.data(), normals.data_mut()) {
+                (Some(coords), Some(faces), Some(normals)) => {
+                    Mesh::compute_normals(coords, faces, normals)
+                }
+                _ => {
+                    println!("Warning: the normals of a mesh not available on the CPU cannot be recomputed.");
+                    return;
+                }
+            }
+        }
+
+        if self.tangents.is_some() {
+            self.recompute_tangents()
+        }
+    }
+
+    /// Recompute this mesh normals so each face is shaded flat.
+    ///
+    /// The vertices shared by several faces are duplicated so each face has its own normal,
+    /// which modifies the vertex and index buffers of this mesh. The texture coordinates and
+    /// vertex colors are duplicated as well, and the tangents are recomputed if this mesh has any.
+    /// Nothing is done, and a warning is printed, if the data of this mesh is not available on
+    /// the CPU or if there would be more than 65536 vertices.
+    pub fn recompute_normals_flat(&mut self) {
+        {
+            let mut coords = self.coords.write().unwrap();
+            let mut faces = self.faces.write().unwrap();
+            let mut normals = self.normals.write().unwrap();
+            let mut uvs = self.uvs.write().unwrap();
+            let mut colors = self.colors.as_ref().map(|cs| cs.write().unwrap());
+
+            if !coords.is_on_ram()
+                || !faces.is_on_ram()
+                || !uvs.is_on_ram()
+                || colors.as_ref().is_some_and(|cs| !cs.is_on_ram())
+            {
+                println!(
+                    "Warning: the normals of a mesh not available on the CPU cannot be recomputed."
+                );
+                return;
+            }
+
+            if faces.len() * 3 > u16::MAX as usize + 1 {
+                println!("Warning: a mesh with more than 21845 faces cannot be shaded flat.");
+                return;
+            }
+
+            let old_faces = faces.data_mut().take().unwrap();
+            let old_coords = coords.data_mut().take().unwrap();
+            let old_uvs = uvs.data_mut().take().unwrap();
+            let duplicate = |f: &Point3<u16>| [f.x as usize, f.y as usize, f.z as usize];
+
+            let new_coords: Vec<_> = old_faces
+                .iter()
+                .flat_map(duplicate)
+                .map(|i| old_coords[i])
+                .collect();
+            let new_normals = new_coords
+                .chunks(3)
+                .flat_map(|tri| {
+                    let normal = (tri[1] - tri[0])
+                        .cross(&(tri[2] - tri[0]))
+                        .try_normalize(1.0e-12)
+                        .unwrap_or_else(Vector3::zero);
+                    [normal; 3]
+                })
+                .collect();
+
+            *uvs.data_mut() = Some(
+                old_faces
+                    .iter()
+                    .flat_map(duplicate)
+                    .map(|i| old_uvs[i])
+                    .collect(),
+            );
+
+            if let Some(ref mut colors) = colors {
+                let old_colors = colors.data_mut().take().unwrap();
+                *colors.data_mut() = Some(
+                    old_faces
+                        .iter()
+                        .flat_map(duplicate)
+                        .map(|i| old_colors[i])
+                        .collect(),
+                );
+            }
+
+            *faces.data_mut() = Some(
+                (0..old_faces.len() as u16)
+                    .map(|i| Point3::new(i * 3, i * 3 + 1, i * 3 + 2))
+                    .collect(),
+            );
+            *coords.data_mut() = Some(new_coords);
+            *normals.data_mut() = Some(new_normals);
+        }
+
+        // The edges are recomputed from the new faces the next time they are needed.
+        self.edges = None;
+        self.wireframe_edges = None;
+
+        if self.tangents.is_some() {
+            self.recompute_tangents()
+        }
+    }
+
+    /// Recompute this mesh tangents from its vertex coordinates, normals and texture coordinates.
+    ///
+    /// The tangents are required for normal mapping. They are not computed, and a warning is
+    /// printed, if this mesh has no texture coordinates or if its data is not available on the
+    /// CPU.
+    pub fn recompute_tangents(&mut self) {
+        let tangents = {
+            let coords = self.coords.read().unwrap();
+            let faces = self.faces.read().unwrap();
+            let normals = self.normals.read().unwrap();
+            let uvs = self.uvs.read().unwrap();
+
+            match (coords.data(), faces.data(), normals.data(), uvs.data()) {
+                (Some(coords), Some(faces), Some(normals), Some(uvs)) => {
+                    Mesh::compute_tangents(coords, faces, normals, uvs)
+                }
+                _ => None,
+            }
+        };
+
+        match tangents {
+            Some(ts) => {
+                self.tangents = Some(Arc::new(RwLock::new(GPUVec::new(
+                    ts,
+                    BufferType::Array,
+                    AllocationType::StaticDraw,
+                ))))
+            }
+            None => {
+                static WARNING: Once = Once::new();
+
+                self.tangents = None;
+                WARNING.call_once(|| {
+                    println!("Warning: tangents cannot be computed for a mesh without texture coordinates. Normal mapping is disabled for this mesh.")
+                });
+            }
+        }
+    }
+
+    /// Creates a denser and smoother mesh by applying `iterations` steps of Loop subdivision to
+    /// this mesh.
+    ///
+    /// Each step splits every triangle into four. The texture coordinates and vertex colors of
+    /// the new vertices are interpolated linearly, and the normals of the new mesh are recomputed.
+    /// The edges belonging to a single face use the boundary rules, so open meshes do not shrink.
+    /// Since the faces refer to vertices by index, the seams where vertices are duplicated, e.g.,
+    /// to have discontinuous texture coordinates, are treated as boundaries as well.
+    ///
+    /// The number of triangles grows exponentially with `iterations`. Fewer steps are applied,
+    /// and a warning is printed, if the new mesh would have more than 65536 vertices.
+    ///
+    /// # Failure
+    /// Fails if the coordinates, faces, texture coordinates, or vertex colors of this mesh are not
+    /// available on the CPU.
+    pub fn subdivide(&self, iterations: u32) -> Mesh {
+        let msg = "The mesh data must be available on the CPU to be subdivided.";
+        let mut coords = self.coords.read().unwrap().to_owned().expect(msg);
+        let mut faces = self.faces.read().unwrap().to_owned().expect(msg);
+        let mut uvs = self.uvs.read().unwrap().to_owned().expect(msg);
+        let mut colors = self
+            .colors
+            .as_ref()
+            .map(|cs| cs.read().unwrap().to_owned().expect(msg));
+
+        for i in 0..iterations {
+            let edges = loop_edges(&faces);
+
+            if coords.len() + edges.list.len() > u16::MAX as usize + 1 {
+                println!(
+                    "Warning: only {} of the {} subdivision steps were applied so the mesh has at most 65536 vertices.",
+                    i, iterations
+                );
+                break;
+            }
+
+            let (new_coords, new_faces) = loop_subdivision_step(&coords, &faces, &edges);
+            uvs = loop_interpolate(&uvs, &edges, na::center);
+            colors = colors.map(|cs| loop_interpolate(&cs, &edges, na::center));
+            coords = new_coords;
+            faces = new_faces;
+        }
+
+        let mut res = Mesh::new(coords, faces, None, Some(uvs), false);
+        res.set_colors(colors);
+
+        if self.tangents.is_some() {
+            res.recompute_tangents();
+        }
+
+        res
+    }
+
+    /// Creates a simplified version of this mesh with at most `target_triangle_count` triangles.
+    ///
+    /// This is the same as `self.simplify_with_boundary(target_triangle_count, true)`.
+    pub fn simplify(&self, target_triangle_count: usize) -> Mesh {
+        self.simplify_with_boundary(target_triangle_count, true)
+    }
+
+    /// Creates a simplified version of this mesh with at most `target_triangle_count` triangles.
+    ///
+    /// The edges are collapsed in the order minimizing the quadric error metric, so the overall
+    /// shape of the mesh is preserved. The collapses that would flip a face or make the mesh
+    /// non-manifold are skipped, so the result may have more triangles than requested. The
+    /// vertices with the same position, texture coordinates, and color are merged first. The
+    /// texture coordinates and vertex colors are interpolated along the collapsed edges, and the
+    /// normals of the new mesh are recomputed.
+    ///
+    /// If `preserve_boundary` is `true`, the vertices on the edges belonging to a single face are
+    /// not moved. Since the seams where vertices are duplicated, e.g., to have discontinuous
+    /// texture coordinates, are boundaries as well, set it to `false` only for meshes without
+    /// such seams or if cracks along them are acceptable.
+    ///
+    /// A copy of this mesh is returned if it already has at most `target_triangle_count`
+    /// triangles.
+    ///
+    /// # Failure
+    /// Fails if the coordinates, faces, texture coordinates, or vertex colors of this mesh are not
+    /// available on the CPU.
+    pub fn simplify_with_boundary(
+        &self,
+        target_triangle_count: usize,
+        preserve_boundary: bool,
+    ) -> Mesh {
+        let msg = "The mesh data must be available on the CPU to be simplified.";
+        let coords = self.coords.read().unwrap().to_owned().expect(msg);
+        let faces = self.faces.read().unwrap().to_owned().expect(msg);
+        let uvs = self.uvs.read().unwrap().to_owned().expect(msg);
+        let colors = self
+            .colors
+            .as_ref()
+            .map(|cs| cs.read().unwrap().to_owned().expect(msg));
+
+        let mut res = if faces.len() <= target_triangle_count {
+            let normals = self.normals.read().unwrap().to_owned();
+            let mut res = Mesh::new(coords, faces, normals, Some(uvs), false);
+            res.set_colors(colors);
+            res
+        } else {
+            let simplified = mesh_simplification::simplify(
+                &coords,
+                &faces,
+                &uvs,
+                colors.as_ref().map(|cs| &cs[..]),
+                target_triangle_count,
+                preserve_boundary,
+            );
+
+            let mut res = Mesh::new(
+                simplified.coords,
+                simplified.faces,
+                None,
+                Some(simplified.uvs),
+                false,
+            );
+            res.set_colors(simplified.colors);
+            res
+        };
+
+        if self.tangents.is_some() {
+            res.recompute_tangents();
+        }
+
+        res
     }
 
     /// This mesh faces.
@@ -277,11 +647,91 @@ impl Mesh {
         &self.coords
     }
 
+    /// The smallest and largest coordinates of the vertices of this mesh, in its local space.
+    ///
+    /// The bounding box is cached until the vertices are modified, and is still available after
+    /// they are unloaded from the RAM if it was computed before. Returns `None` if the mesh has
+    /// no vertex, or if its vertices are not available in RAM and the bounding box was not
+    /// computed yet.
+    pub fn local_aabb(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        let coords = self.coords.read().unwrap();
+
+        if let Some(aabb) = *self.aabb.read().unwrap() {
+            if aabb.version == coords.version() {
+                return Some((aabb.mins, aabb.maxs));
+            }
+        }
+
+        let pts = match coords.data() {
+            Some(pts) if !pts.is_empty() => pts,
+            _ => return None,
+        };
+
+        let mut mins = pts[0];
+        let mut maxs = pts[0];
+
+        for pt in pts.iter() {
+            mins = mins.inf(pt);
+            maxs = maxs.sup(pt);
+        }
+
+        *self.aabb.write().unwrap() = Some(CachedAabb {
+            version: coords.version(),
+            mins,
+            maxs,
+        });
+
+        Some((mins, maxs))
+    }
+
     /// This mesh texture coordinates.
     pub fn uvs(&self) -> &Arc<RwLock<GPUVec<Point2<f32>>>> {
         &self.uvs
     }
 
+    /// This mesh vertex colors, if any.
+    pub fn colors(&self) -> Option<&Arc<RwLock<GPUVec<Point3<f32>>>>> {
+        self.colors.as_ref()
+    }
+
+    /// This mesh vertex tangents, if any.
+    ///
+    /// The `w` component of each tangent is the handedness of the tangent space.
+    pub fn tangents(&self) -> Option<&Arc<RwLock<GPUVec<Vector4<f32>>>>> {
+        self.tangents.as_ref()
+    }
+
+    // A mesh sharing the vertices, faces, normals, texture coordinates, and tangents of this one,
+    // but not its vertex colors, so the colors of either can be set independently.
+    pub(crate) fn clone_sharing_geometry(&self) -> Mesh {
+        Mesh {
+            coords: self.coords.clone(),
+            faces: self.faces.clone(),
+            normals: self.normals.clone(),
+            uvs: self.uvs.clone(),
+            colors: None,
+            tangents: self.tangents.clone(),
+            edges: self.edges.clone(),
+            wireframe_edges: self.wireframe_edges.clone(),
+            wide_line_edges: Vec::new(),
+            aabb: RwLock::new(*self.aabb.read().unwrap()),
+        }
+    }
+
+    /// Sets the per-vertex colors of this mesh.
+    ///
+    /// Colors components must be on the range `[0.0, 1.0]`. Use `None` to remove the vertex
+    /// colors.
+    pub fn set_colors(&mut self, colors: Option<Vec<Point3<f32>>>) {
+        self.colors = colors.map(|cs| {
+            Arc::new(RwLock::new(GPUVec::new(
+                cs,
+                BufferType::Array,
+                AllocationType::StaticDraw,
+            )))
+        });
+    }
+
     /// Computes normals from a set of faces.
     pub fn compute_normals_array(
         coordinates: &[Point3<f32>],
@@ -295,41 +745,331 @@ impl Mesh {
     }
 
     /// Computes normals from a set of faces.
+    ///
+    /// The normal of each vertex is the average of the normals of its adjacent faces, weighted
+    /// by their area.
     pub fn compute_normals(
         coordinates: &[Point3<f32>],
         faces: &[Point3<u16>],
         normals: &mut Vec<Vector3<f32>>,
     ) {
-        let mut divisor: Vec<f32> = iter::repeat(0f32).take(coordinates.len()).collect();
-
         normals.clear();
         normals.extend(iter::repeat(Vector3::<f32>::zero()).take(coordinates.len()));
 
-        // Accumulate normals ...
+        // Accumulate normals, the norm of the cross product being twice the face area ...
         for f in faces.iter() {
             let edge1 = coordinates[f.y as usize] - coordinates[f.x as usize];
             let edge2 = coordinates[f.z as usize] - coordinates[f.x as usize];
             let cross = edge1.cross(&edge2);
-            let normal;
 
-            if !cross.is_zero() {
-                normal = cross.normalize()
-            } else {
-                normal = cross
+            normals[f.x as usize] += cross;
+            normals[f.y as usize] += cross;
+            normals[f.z as usize] += cross;
+        }
+
+        // ... and normalize them.
+        for n in normals.iter_mut() {
+            *n = n.try_normalize(1.0e-12).unwrap_or_else(Vector3::zero)
+        }
+    }
+
+    /// Computes tangents from a set of faces and texture coordinates.
+    ///
+    /// Returns `None` if the texture coordinates of every face are degenerate, e.g., if the mesh
+    /// has no texture coordinates.
+    pub fn compute_tangents(
+        coordinates: &[Point3<f32>],
+        faces: &[Point3<u16>],
+        normals: &[Vector3<f32>],
+        uvs: &[Point2<f32>],
+    ) -> Option<Vec<Vector4<f32>>> {
+        let mut tangents = vec![Vector3::<f32>::zero(); coordinates.len()];
+        let mut bitangents = vec![Vector3::<f32>::zero(); coordinates.len()];
+        let mut has_uvs = false;
+
+        // Accumulate the tangents of the faces ...
+        for f in faces.iter() {
+            let (a, b, c) = (f.x as usize, f.y as usize, f.z as usize);
+            let edge1 = coordinates[b] - coordinates[a];
+            let edge2 = coordinates[c] - coordinates[a];
+            let duv1 = uvs[b] - uvs[a];
+            let duv2 = uvs[c] - uvs[a];
+            let det = duv1.x * duv2.y - duv2.x * duv1.y;
+
+            if det.abs() <= 1.0e-12 {
+                continue;
+            }
+
+            has_uvs = true;
+
+            let tangent = (edge1 * duv2.y - edge2 * duv1.y) / det;
+            let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / det;
+
+            for i in [a, b, c].iter() {
+                tangents[*i] += tangent;
+                bitangents[*i] += bitangent;
+            }
+        }
+
+        if !has_uvs {
+            return None;
+        }
+
+        // ... and make them orthogonal to the normals.
+        let res = tangents
+            .iter()
+            .zip(bitangents.iter())
+            .zip(normals.iter())
+            .map(|((t, b), n)| {
+                let t = (t - n * n.dot(t))
+                    .try_normalize(1.0e-12)
+                    .unwrap_or_else(|| any_orthogonal(n));
+                let handedness = if n.cross(&t).dot(b) < 0.0 { -1.0 } else { 1.0 };
+
+                Vector4::new(t.x, t.y, t.z, handedness)
+            })
+            .collect();
+
+        Some(res)
+    }
+}
+
+// A unit vector orthogonal to `n`, used where the texture coordinates do not define a tangent.
+fn any_orthogonal(n: &Vector3<f32>) -> Vector3<f32> {
+    let axis = if n.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+
+    n.cross(&axis)
+        .try_normalize(1.0e-12)
+        .unwrap_or_else(Vector3::x)
+}
+
+// An edge of a mesh being subdivided.
+struct LoopEdge {
+    // The endpoints of this edge, the smallest index first.
+    ends: (u16, u16),
+    // The vertices opposite to this edge in its adjacent faces.
+    opposite: Vec<u16>,
+}
+
+impl LoopEdge {
+    // Whether this edge belongs to a single face, or to more than two, in which case it is
+    // handled as a boundary too.
+    fn is_boundary(&self) -> bool {
+        self.opposite.len() != 2
+    }
+}
+
+// An edge of a mesh, with the normal of the first face containing it.
+struct WireframeEdge {
+    ids: Point2<u16>,
+    normal: Option<Vector3<f32>>,
+    num_faces: usize,
+    coplanar: bool,
+}
+
+// The edges of `faces` not shared by exactly two coplanar faces, each listed once.
+fn compute_wireframe_edges(coords: &[Point3<f32>], faces: &[Point3<u16>]) -> Vec<Point2<u16>> {
+    let mut edges: Vec<WireframeEdge> = Vec::new();
+    let mut ids = HashMap::new();
+    let key = |i: u16| {
+        let p = coords[i as usize];
+        [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+    };
+
+    for face in faces {
+        let (a, b, c) = (
+            coords[face.x as usize],
+            coords[face.y as usize],
+            coords[face.z as usize],
+        );
+        let normal = (b - a).cross(&(c - a)).try_normalize(1.0e-12);
+
+        for &(i, j) in &[(face.x, face.y), (face.y, face.z), (face.z, face.x)] {
+            let (ki, kj) = (key(i), key(j));
+            let k = if ki < kj { (ki, kj) } else { (kj, ki) };
+
+            match ids.entry(k) {
+                Entry::Vacant(entry) => {
+                    let _ = entry.insert(edges.len());
+                    edges.push(WireframeEdge {
+                        ids: Point2::new(i, j),
+                        normal,
+                        num_faces: 1,
+                        coplanar: false,
+                    });
+                }
+                Entry::Occupied(entry) => {
+                    let edge = &mut edges[*entry.get()];
+                    edge.num_faces += 1;
+                    edge.coplanar = match (edge.normal, normal) {
+                        (Some(n1), Some(n2)) => n1.dot(&n2) > 1.0 - 1.0e-4,
+                        _ => false,
+                    };
+                }
             }
+        }
+    }
+
+    edges
+        .into_iter()
+        .filter(|e| e.num_faces != 2 || !e.coplanar)
+        .map(|e| e.ids)
+        .collect()
+}
+
+// The edges of a mesh being subdivided.
+struct LoopEdges {
+    list: Vec<LoopEdge>,
+    // The index in `list` of the edge with the given endpoints, the smallest index first.
+    ids: HashMap<(u16, u16), usize>,
+}
+
+// Collects the edges of `faces`.
+fn loop_edges(faces: &[Point3<u16>]) -> LoopEdges {
+    let mut edges: Vec<LoopEdge> = Vec::new();
+    let mut ids = HashMap::new();
+
+    for f in faces.iter() {
+        for (a, b, c) in [(f.x, f.y, f.z), (f.y, f.z, f.x), (f.z, f.x, f.y)].iter() {
+            let ends = (*a.min(b), *a.max(b));
+            let id = *ids.entry(ends).or_insert_with(|| {
+                edges.push(LoopEdge {
+                    ends,
+                    opposite: Vec::new(),
+                });
+                edges.len() - 1
+            });
 
-            normals[f.x as usize] = normals[f.x as usize] + normal;
-            normals[f.y as usize] = normals[f.y as usize] + normal;
-            normals[f.z as usize] = normals[f.z as usize] + normal;
+            edges[id].opposite.push(*c);
+        }
+    }
+
+    LoopEdges { list: edges, ids }
+}
+
+// Applies one step of Loop subdivision. The new vertex of the `i`-th edge has the index
+// `coords.len() + i`.
+fn loop_subdivision_step(
+    coords: &[Point3<f32>],
+    faces: &[Point3<u16>],
+    edges: &LoopEdges,
+) -> (Vec<Point3<f32>>, Vec<Point3<u16>>) {
+    let LoopEdges { list: edges, ids } = edges;
+    let mut neighbors = vec![Vector3::zeros(); coords.len()];
+    let mut valences = vec![0usize; coords.len()];
+    let mut boundary_neighbors = vec![Vector3::zeros(); coords.len()];
+    let mut boundary_valences = vec![0usize; coords.len()];
+
+    for e in edges.iter() {
+        let (a, b) = (e.ends.0 as usize, e.ends.1 as usize);
+
+        neighbors[a] += coords[b].coords;
+        neighbors[b] += coords[a].coords;
+        valences[a] += 1;
+        valences[b] += 1;
 
-            divisor[f.x as usize] = divisor[f.x as usize] + 1.0;
-            divisor[f.y as usize] = divisor[f.y as usize] + 1.0;
-            divisor[f.z as usize] = divisor[f.z as usize] + 1.0;
+        if e.is_boundary() {
+            boundary_neighbors[a] += coords[b].coords;
+            boundary_neighbors[b] += coords[a].coords;
+            boundary_valences[a] += 1;
+            boundary_valences[b] += 1;
         }
+    }
+
+    // Move the existing vertices ...
+    let mut res: Vec<Point3<f32>> = coords
+        .iter()
+        .enumerate()
+        .map(|(i, p)| match boundary_valences[i] {
+            // Vertices not referenced by any face stay in place.
+            _ if valences[i] == 0 => *p,
+            0 => {
+                let n = valences[i] as f32;
+                let beta = if valences[i] == 3 {
+                    3.0 / 16.0
+                } else {
+                    3.0 / (8.0 * n)
+                };
+
+                p * (1.0 - n * beta) + neighbors[i] * beta
+            }
+            2 => p * 0.75 + boundary_neighbors[i] * 0.125,
+            // Corners and non-manifold vertices stay in place.
+            _ => *p,
+        })
+        .collect();
+
+    // ... add the vertices of the edges ...
+    res.extend(edges.iter().map(|e| {
+        let (a, b) = (coords[e.ends.0 as usize], coords[e.ends.1 as usize]);
 
-        // ... and compute the mean
-        for (n, divisor) in normals.iter_mut().zip(divisor.iter()) {
-            *n = *n / *divisor
+        if e.is_boundary() {
+            na::center(&a, &b)
+        } else {
+            let (c, d) = (
+                coords[e.opposite[0] as usize],
+                coords[e.opposite[1] as usize],
+            );
+            Point3::from((a.coords + b.coords) * 0.375 + (c.coords + d.coords) * 0.125)
         }
+    }));
+
+    // ... and split the faces.
+    let edge_vertex = |a: u16, b: u16| (coords.len() + ids[&(a.min(b), a.max(b))]) as u16;
+    let mut new_faces = Vec::with_capacity(faces.len() * 4);
+
+    for f in faces.iter() {
+        let ab = edge_vertex(f.x, f.y);
+        let bc = edge_vertex(f.y, f.z);
+        let ca = edge_vertex(f.z, f.x);
+
+        new_faces.push(Point3::new(f.x, ab, ca));
+        new_faces.push(Point3::new(f.y, bc, ab));
+        new_faces.push(Point3::new(f.z, ca, bc));
+        new_faces.push(Point3::new(ab, bc, ca));
+    }
+
+    (res, new_faces)
+}
+
+// Appends the values of the vertices of the edges, interpolated linearly, to the values of the
+// existing vertices.
+fn loop_interpolate<T: Copy>(values: &[T], edges: &LoopEdges, mid: impl Fn(&T, &T) -> T) -> Vec<T> {
+    let mut res = values.to_vec();
+    res.extend(
+        edges
+            .list
+            .iter()
+            .map(|e| mid(&values[e.ends.0 as usize], &values[e.ends.1 as usize])),
+    );
+
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::{loop_edges, loop_subdivision_step};
+    use na::Point3;
+
+    #[test]
+    fn subdivision_keeps_unused_vertices() {
+        let coords = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(5.0, 5.0, 5.0),
+        ];
+        let faces = [Point3::new(0, 1, 2)];
+        let edges = loop_edges(&faces);
+        let (new_coords, new_faces) = loop_subdivision_step(&coords, &faces, &edges);
+
+        assert_eq!(new_coords.len(), 7);
+        assert_eq!(new_faces.len(), 4);
+        assert_eq!(new_coords[3], coords[3]);
+        assert!(new_coords.iter().all(|p| p.iter().all(|x| x.is_finite())));
     }
 }
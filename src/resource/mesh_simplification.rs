@@ -0,0 +1,475 @@
+//! Mesh simplification by quadric error metric edge collapses.
+
+use na::{Matrix4, Point2, Point3, Vector3, Vector4};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+// The weight of the quadrics keeping the boundaries in place when they are not locked, relative
+// to the quadrics of the faces.
+const BOUNDARY_WEIGHT: f64 = 1000.0;
+
+// The geometry resulting from a simplification.
+pub(crate) struct SimplifiedMesh {
+    pub coords: Vec<Point3<f32>>,
+    pub faces: Vec<Point3<u16>>,
+    pub uvs: Vec<Point2<f32>>,
+    pub colors: Option<Vec<Point3<f32>>>,
+}
+
+// A candidate edge collapse, moving `v1` to `target` and removing `v2`.
+struct Collapse {
+    cost: f64,
+    v1: usize,
+    v2: usize,
+    target: Vector3<f64>,
+    // The position of `target` along the edge, used to interpolate the vertex attributes.
+    t: f32,
+    // The versions of `v1` and `v2` when this collapse was computed.
+    versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Collapse) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Collapse) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    // Reversed so the heap pops the cheapest collapse first.
+    fn cmp(&self, other: &Collapse) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+struct Simplifier {
+    pos: Vec<Vector3<f64>>,
+    uvs: Vec<Point2<f32>>,
+    colors: Option<Vec<Point3<f32>>>,
+    quadrics: Vec<Matrix4<f64>>,
+    boundary: Vec<bool>,
+    versions: Vec<u32>,
+    alive: Vec<bool>,
+    faces: Vec<[usize; 3]>,
+    face_alive: Vec<bool>,
+    vertex_faces: Vec<Vec<usize>>,
+    preserve_boundary: bool,
+}
+
+// Simplifies a mesh until it has at most `target` faces, or until no more edge can be collapsed.
+//
+// The vertices with the same position, texture coordinates, and color are merged first, so
+// meshes without shared vertices, e.g., loaded from stl files, can be simplified.
+pub(crate) fn simplify(
+    coords: &[Point3<f32>],
+    faces: &[Point3<u16>],
+    uvs: &[Point2<f32>],
+    colors: Option<&[Point3<f32>]>,
+    target: usize,
+    preserve_boundary: bool,
+) -> SimplifiedMesh {
+    let mut simplifier = Simplifier::new(coords, faces, uvs, colors, preserve_boundary);
+    let mut heap = BinaryHeap::new();
+    let mut num_faces = simplifier.faces.len();
+
+    for v1 in 0..simplifier.pos.len() {
+        for v2 in simplifier.neighbors(v1) {
+            if v1 < v2 {
+                heap.extend(simplifier.collapse(v1, v2));
+            }
+        }
+    }
+
+    while num_faces > target {
+        let c = match heap.pop() {
+            Some(c) => c,
+            None => break,
+        };
+
+        if !simplifier.alive[c.v1]
+            || !simplifier.alive[c.v2]
+            || simplifier.versions[c.v1] != c.versions.0
+            || simplifier.versions[c.v2] != c.versions.1
+            || !simplifier.can_collapse(&c)
+        {
+            continue;
+        }
+
+        num_faces -= simplifier.apply(&c);
+
+        for v2 in simplifier.neighbors(c.v1) {
+            heap.extend(simplifier.collapse(c.v1, v2));
+        }
+    }
+
+    simplifier.into_mesh()
+}
+
+impl Simplifier {
+    fn new(
+        coords: &[Point3<f32>],
+        faces: &[Point3<u16>],
+        uvs: &[Point2<f32>],
+        colors: Option<&[Point3<f32>]>,
+        preserve_boundary: bool,
+    ) -> Simplifier {
+        /*
+         * Merge the duplicated vertices.
+         */
+        let mut ids = HashMap::new();
+        let mut remap = Vec::with_capacity(coords.len());
+        let mut pos = Vec::new();
+        let mut new_uvs = Vec::new();
+        let mut new_colors = colors.map(|_| Vec::new());
+
+        for (i, p) in coords.iter().enumerate() {
+            let color = colors.map(|cs| cs[i]);
+            let mut key = vec![p.x, p.y, p.z, uvs[i].x, uvs[i].y];
+            key.extend(color.iter().flat_map(|c| c.coords.iter().cloned()));
+            let key: Vec<u32> = key.iter().map(|e| e.to_bits()).collect();
+
+            let id = *ids.entry(key).or_insert_with(|| {
+                pos.push(Vector3::new(p.x as f64, p.y as f64, p.z as f64));
+                new_uvs.push(uvs[i]);
+
+                if let (Some(new_colors), Some(color)) = (new_colors.as_mut(), color) {
+                    new_colors.push(color);
+                }
+
+                pos.len() - 1
+            });
+
+            remap.push(id);
+        }
+
+        let faces: Vec<[usize; 3]> = faces
+            .iter()
+            .map(|f| {
+                [
+                    remap[f.x as usize],
+                    remap[f.y as usize],
+                    remap[f.z as usize],
+                ]
+            })
+            .filter(|f| f[0] != f[1] && f[1] != f[2] && f[2] != f[0])
+            .collect();
+
+        /*
+         * Compute the adjacency, boundaries, and quadrics.
+         */
+        let mut vertex_faces = vec![Vec::new(); pos.len()];
+        let mut quadrics = vec![Matrix4::zeros(); pos.len()];
+        let mut edges = HashMap::new();
+
+        for (i, f) in faces.iter().enumerate() {
+            let normal = (pos[f[1]] - pos[f[0]]).cross(&(pos[f[2]] - pos[f[0]]));
+            let area = normal.norm() / 2.0;
+
+            if let Some(n) = normal.try_normalize(1.0e-20) {
+                let plane = Vector4::new(n.x, n.y, n.z, -n.dot(&pos[f[0]]));
+                let quadric = plane * plane.transpose() * area;
+
+                for v in f.iter() {
+                    quadrics[*v] += quadric;
+                }
+            }
+
+            for j in 0..3 {
+                let (a, b) = (f[j], f[(j + 1) % 3]);
+                vertex_faces[a].push(i);
+                edges
+                    .entry((a.min(b), a.max(b)))
+                    .or_insert_with(Vec::new)
+                    .push(i);
+            }
+        }
+
+        let mut boundary = vec![false; pos.len()];
+
+        for ((a, b), adjacent) in edges.iter() {
+            if adjacent.len() != 1 {
+                continue;
+            }
+
+            boundary[*a] = true;
+            boundary[*b] = true;
+
+            if !preserve_boundary {
+                // A plane containing the edge, orthogonal to its face.
+                let f = &faces[adjacent[0]];
+                let face_normal = (pos[f[1]] - pos[f[0]]).cross(&(pos[f[2]] - pos[f[0]]));
+                let edge = pos[*b] - pos[*a];
+
+                if let Some(n) = edge.cross(&face_normal).try_normalize(1.0e-20) {
+                    let plane = Vector4::new(n.x, n.y, n.z, -n.dot(&pos[*a]));
+                    let quadric = plane * plane.transpose() * edge.norm_squared() * BOUNDARY_WEIGHT;
+
+                    quadrics[*a] += quadric;
+                    quadrics[*b] += quadric;
+                }
+            }
+        }
+
+        Simplifier {
+            versions: vec![0; pos.len()],
+            alive: vec![true; pos.len()],
+            face_alive: vec![true; faces.len()],
+            pos,
+            uvs: new_uvs,
+            colors: new_colors,
+            quadrics,
+            boundary,
+            faces,
+            vertex_faces,
+            preserve_boundary,
+        }
+    }
+
+    // The vertices sharing an edge with `v`.
+    fn neighbors(&self, v: usize) -> Vec<usize> {
+        let mut res: Vec<usize> = self.vertex_faces[v]
+            .iter()
+            .flat_map(|f| self.faces[*f].iter().cloned())
+            .filter(|n| *n != v)
+            .collect();
+        res.sort_unstable();
+        res.dedup();
+
+        res
+    }
+
+    // The error of moving a vertex with the quadric `q` to `p`.
+    fn error(q: &Matrix4<f64>, p: &Vector3<f64>) -> f64 {
+        let p = Vector4::new(p.x, p.y, p.z, 1.0);
+        (p.transpose() * q * p)[0]
+    }
+
+    // Computes the best collapse of the edge between `v1` and `v2`.
+    fn collapse(&self, v1: usize, v2: usize) -> Option<Collapse> {
+        let (p1, p2) = (self.pos[v1], self.pos[v2]);
+        let q = self.quadrics[v1] + self.quadrics[v2];
+
+        let target = if self.preserve_boundary && self.boundary[v1] {
+            if self.boundary[v2] {
+                return None;
+            }
+
+            p1
+        } else if self.preserve_boundary && self.boundary[v2] {
+            p2
+        } else {
+            let mut candidates = vec![p1, p2, (p1 + p2) / 2.0];
+
+            if let Some(inv) = q.fixed_slice::<3, 3>(0, 0).try_inverse() {
+                candidates.push(-inv * q.fixed_slice::<3, 1>(0, 3));
+            }
+
+            candidates
+                .into_iter()
+                .map(|p| (Simplifier::error(&q, &p), p))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .unwrap()
+                .1
+        };
+
+        let edge = p2 - p1;
+        let t = if edge.norm_squared() > 0.0 {
+            ((target - p1).dot(&edge) / edge.norm_squared()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        Some(Collapse {
+            cost: Simplifier::error(&q, &target).max(0.0),
+            v1,
+            v2,
+            target,
+            t: t as f32,
+            versions: (self.versions[v1], self.versions[v2]),
+        })
+    }
+
+    // Tests whether the collapse keeps the mesh manifold and does not flip any face.
+    fn can_collapse(&self, c: &Collapse) -> bool {
+        let n1 = self.neighbors(c.v1);
+        let n2 = self.neighbors(c.v2);
+        let num_common = n1.iter().filter(|n| n2.contains(n)).count();
+        let num_shared = self.vertex_faces[c.v1]
+            .iter()
+            .filter(|f| self.faces[**f].contains(&c.v2))
+            .count();
+
+        // The link condition.
+        if num_common != num_shared {
+            return false;
+        }
+
+        for v in [c.v1, c.v2].iter() {
+            for f in self.vertex_faces[*v].iter() {
+                let face = &self.faces[*f];
+
+                if face.contains(&c.v1) && face.contains(&c.v2) {
+                    continue;
+                }
+
+                let old = face.map(|i| self.pos[i]);
+                let new = face.map(|i| if i == *v { c.target } else { self.pos[i] });
+                let old_normal = (old[1] - old[0]).cross(&(old[2] - old[0]));
+                let new_normal = (new[1] - new[0]).cross(&(new[2] - new[0]));
+
+                if old_normal.dot(&new_normal) <= 0.0 {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    // Applies the collapse, and returns the number of faces removed.
+    fn apply(&mut self, c: &Collapse) -> usize {
+        let (v1, v2) = (c.v1, c.v2);
+        let mut removed = 0;
+
+        self.pos[v1] = c.target;
+        self.uvs[v1] = self.uvs[v1] + (self.uvs[v2] - self.uvs[v1]) * c.t;
+
+        if let Some(colors) = self.colors.as_mut() {
+            colors[v1] = colors[v1] + (colors[v2] - colors[v1]) * c.t;
+        }
+
+        self.quadrics[v1] = self.quadrics[v1] + self.quadrics[v2];
+        self.boundary[v1] = self.boundary[v1] || self.boundary[v2];
+
+        for f in std::mem::take(&mut self.vertex_faces[v2]) {
+            if self.faces[f].contains(&v1) {
+                self.face_alive[f] = false;
+                removed += 1;
+            } else {
+                for i in self.faces[f].iter_mut() {
+                    if *i == v2 {
+                        *i = v1;
+                    }
+                }
+
+                self.vertex_faces[v1].push(f);
+            }
+        }
+
+        let face_alive = &self.face_alive;
+
+        for v in self.neighbors(v1).into_iter().chain(Some(v1)) {
+            self.vertex_faces[v].retain(|f| face_alive[*f]);
+        }
+
+        self.alive[v2] = false;
+        self.versions[v1] += 1;
+
+        removed
+    }
+
+    fn into_mesh(self) -> SimplifiedMesh {
+        let mut remap = vec![None; self.pos.len()];
+        let mut res = SimplifiedMesh {
+            coords: Vec::new(),
+            faces: Vec::new(),
+            uvs: Vec::new(),
+            colors: self.colors.as_ref().map(|_| Vec::new()),
+        };
+
+        for (f, alive) in self.faces.iter().zip(self.face_alive.iter()) {
+            if !*alive {
+                continue;
+            }
+
+            let mut ids = [0u16; 3];
+
+            for (id, v) in ids.iter_mut().zip(f.iter()) {
+                *id = *remap[*v].get_or_insert_with(|| {
+                    let p = self.pos[*v];
+                    res.coords
+                        .push(Point3::new(p.x as f32, p.y as f32, p.z as f32));
+                    res.uvs.push(self.uvs[*v]);
+
+                    if let (Some(colors), Some(new_colors)) =
+                        (self.colors.as_ref(), res.colors.as_mut())
+                    {
+                        new_colors.push(colors[*v]);
+                    }
+
+                    (res.coords.len() - 1) as u16
+                });
+            }
+
+            res.faces.push(Point3::new(ids[0], ids[1], ids[2]));
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{simplify, SimplifiedMesh};
+    use na::{Point3, Vector3};
+    use ncollide3d::procedural::{IndexBuffer, TriMesh};
+
+    fn simplify_trimesh(
+        mut mesh: TriMesh<f32>,
+        target: usize,
+        preserve_boundary: bool,
+    ) -> SimplifiedMesh {
+        mesh.unify_index_buffer();
+
+        let faces: Vec<Point3<u16>> = match mesh.indices {
+            IndexBuffer::Unified(ref fs) => fs.iter().map(|f| na::convert(*f)).collect(),
+            IndexBuffer::Split(_) => unreachable!(),
+        };
+        let res = simplify(
+            &mesh.coords,
+            &faces,
+            mesh.uvs.as_ref().unwrap(),
+            None,
+            target,
+            preserve_boundary,
+        );
+
+        assert_eq!(res.uvs.len(), res.coords.len());
+        assert!(res
+            .faces
+            .iter()
+            .all(|f| f.iter().all(|i| (*i as usize) < res.coords.len())));
+
+        res
+    }
+
+    #[test]
+    fn simplify_closed_mesh() {
+        let sphere = crate::procedural::icosphere(1.0, 3);
+        assert_eq!(sphere.num_triangles(), 1280);
+
+        let res = simplify_trimesh(sphere, 200, true);
+        assert!((190..=200).contains(&res.faces.len()));
+    }
+
+    #[test]
+    fn simplify_open_mesh() {
+        let heights: Vec<f32> = (0..17 * 17)
+            .map(|i| ((i % 17) as f32 * 0.7).sin() * ((i / 17) as f32 * 0.4).cos())
+            .collect();
+        let terrain = crate::procedural::heightmap(&heights, 17, 17, Vector3::new(10.0, 1.0, 10.0));
+        assert_eq!(terrain.num_triangles(), 512);
+
+        for &preserve_boundary in &[true, false] {
+            let res = simplify_trimesh(terrain.clone(), 100, preserve_boundary);
+            assert!((90..=100).contains(&res.faces.len()));
+        }
+    }
+}
@@ -1,9 +1,10 @@
 //! GPU resource managers
 
 pub use crate::context::Texture;
+pub use crate::resource::cubemap_texture::CubemapTexture;
 pub use crate::resource::effect::{Effect, ShaderAttribute, ShaderUniform};
 pub use crate::resource::framebuffer_manager::{
-    FramebufferManager, OffscreenBuffers, RenderTarget,
+    FramebufferManager, MultisampledRenderTarget, OffscreenBuffers, RenderTarget,
 };
 pub use crate::resource::gl_primitive::{GLPrimitive, PrimitiveArray};
 pub use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
@@ -14,8 +15,13 @@ pub use crate::resource::mesh_manager::MeshManager;
 pub use crate::resource::planar_material_manager::PlanarMaterialManager;
 pub use crate::resource::planar_mesh::PlanarMesh;
 pub use crate::resource::planar_mesh_manager::PlanarMeshManager;
-pub use crate::resource::texture_manager::{TextureManager, TextureWrapping};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::resource::texture_manager::PendingTexture;
+pub use crate::resource::texture_manager::{
+    PixelFormat, TextureFilter, TextureManager, TextureWrapping,
+};
 
+mod cubemap_texture;
 mod effect;
 mod framebuffer_manager;
 mod gl_primitive;
@@ -24,6 +30,7 @@ pub mod material;
 mod material_manager;
 mod mesh;
 mod mesh_manager;
+mod mesh_simplification;
 mod planar_material_manager;
 mod planar_mesh;
 mod planar_mesh_manager;
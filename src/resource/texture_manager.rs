@@ -1,11 +1,15 @@
 //! A resource manager to load textures.
 
-use image::{self, DynamicImage};
-use std::cell::RefCell;
+use image::{self, DynamicImage, ImageBuffer, ImageError, ImageResult};
+use std::cell::{Cell, RefCell};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
 
 use crate::context::{Context, Texture};
 
@@ -34,6 +38,46 @@ impl Into<u32> for TextureWrapping {
     }
 }
 
+/// Filtering parameters for a texture.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Uses the color of the texel nearest to the texture coordinate.
+    Nearest,
+    /// Interpolates linearly the colors of the four texels nearest to the texture coordinate.
+    Linear,
+    /// Uses the nearest texel of the mipmap level with the nearest size.
+    NearestMipmapNearest,
+    /// Interpolates linearly the texels of the mipmap level with the nearest size.
+    LinearMipmapNearest,
+    /// Interpolates linearly between the nearest texels of the two mipmap levels with the
+    /// nearest sizes.
+    NearestMipmapLinear,
+    /// Interpolates linearly the texels of the two mipmap levels with the nearest sizes, and
+    /// between those levels.
+    LinearMipmapLinear,
+}
+
+impl TextureFilter {
+    /// Whether this filter samples the mipmaps of the texture.
+    pub fn uses_mipmaps(self) -> bool {
+        !matches!(self, TextureFilter::Nearest | TextureFilter::Linear)
+    }
+}
+
+impl From<TextureFilter> for u32 {
+    #[inline]
+    fn from(filter: TextureFilter) -> u32 {
+        match filter {
+            TextureFilter::Nearest => Context::NEAREST,
+            TextureFilter::Linear => Context::LINEAR,
+            TextureFilter::NearestMipmapNearest => Context::NEAREST_MIPMAP_NEAREST,
+            TextureFilter::LinearMipmapNearest => Context::LINEAR_MIPMAP_NEAREST,
+            TextureFilter::NearestMipmapLinear => Context::NEAREST_MIPMAP_LINEAR,
+            TextureFilter::LinearMipmapLinear => Context::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
+
 impl Texture {
     /// Allocates a new texture on the gpu. The texture is not configured.
     pub fn new() -> Rc<Texture> {
@@ -58,6 +102,143 @@ impl Texture {
         let wrap: u32 = wrapping.into();
         verify!(ctxt.tex_parameteri(Context::TEXTURE_2D, Context::TEXTURE_WRAP_T, wrap as i32));
     }
+
+    /// Sets the wrapping of this texture along the `s` and `t` texture coordinates.
+    ///
+    /// This affects every object using this texture.
+    pub fn set_wrap(&self, s: TextureWrapping, t: TextureWrapping) {
+        let ctxt = Context::get();
+        let (s, t): (u32, u32) = (s.into(), t.into());
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(self)));
+        verify!(ctxt.tex_parameteri(Context::TEXTURE_2D, Context::TEXTURE_WRAP_S, s as i32));
+        verify!(ctxt.tex_parameteri(Context::TEXTURE_2D, Context::TEXTURE_WRAP_T, t as i32));
+    }
+
+    /// Sets the filters used when this texture is minified and magnified.
+    ///
+    /// The mipmaps of the texture are generated if `min` uses them, and regenerated each time the
+    /// texture is updated. This affects every object using this texture.
+    ///
+    /// # Failure
+    /// Panics if `mag` uses mipmaps.
+    pub fn set_filtering(&self, min: TextureFilter, mag: TextureFilter) {
+        assert!(
+            !mag.uses_mipmaps(),
+            "The magnification filter cannot use mipmaps."
+        );
+
+        let ctxt = Context::get();
+        let (min_filter, mag_filter): (u32, u32) = (min.into(), mag.into());
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(self)));
+        verify!(ctxt.tex_parameteri(
+            Context::TEXTURE_2D,
+            Context::TEXTURE_MIN_FILTER,
+            min_filter as i32
+        ));
+        verify!(ctxt.tex_parameteri(
+            Context::TEXTURE_2D,
+            Context::TEXTURE_MAG_FILTER,
+            mag_filter as i32
+        ));
+
+        if min.uses_mipmaps() && !self.uses_mipmaps() {
+            verify!(ctxt.generate_mipmap(Context::TEXTURE_2D));
+        }
+
+        self.set_uses_mipmaps(min.uses_mipmaps());
+    }
+
+    /// Sets the maximum anisotropy used to filter this texture.
+    ///
+    /// Anisotropic filtering keeps textures seen at grazing angles sharp. It is best combined
+    /// with mipmaps and the `TextureFilter::LinearMipmapLinear` filter. The anisotropy is clamped
+    /// between 1, which disables it, and the maximum supported by the context. This does nothing,
+    /// except printing a warning the first time, if anisotropic filtering is not supported.
+    pub fn set_anisotropy(&self, anisotropy: f32) {
+        let ctxt = Context::get();
+
+        match ctxt.max_anisotropy() {
+            Some(max) => {
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(self)));
+                verify!(ctxt.tex_parameterf(
+                    Context::TEXTURE_2D,
+                    Context::TEXTURE_MAX_ANISOTROPY,
+                    anisotropy.max(1.0).min(max.max(1.0))
+                ));
+            }
+            None => {
+                if !ANISOTROPY_WARNING.with(|warned| warned.replace(true)) {
+                    println!("Warning: anisotropic filtering is not supported by this context.");
+                }
+            }
+        }
+    }
+
+    /// Replaces the image of this texture with raw RGBA pixels.
+    ///
+    /// The pixels are given row by row starting with the top row, without padding between the
+    /// rows. The objects using this texture display the new image from the next frame on, so this
+    /// can be called at each frame, e.g., to play a video.
+    ///
+    /// The whole image is uploaded to the GPU at each call, which costs about as much as copying
+    /// it. If the image has the same size as the previous one and both are RGBA, the memory of the
+    /// texture is reused. Otherwise, the texture is reallocated, which is slower.
+    ///
+    /// # Failure
+    /// Panics if `data` does not contain exactly `width * height * 4` bytes.
+    pub fn update_from_rgba(&self, data: &[u8], width: u32, height: u32) {
+        let len = width as usize * height as usize * 4;
+        assert!(
+            data.len() == len,
+            "Invalid pixel data: expected {} bytes, got {}.",
+            len,
+            data.len()
+        );
+
+        let ctxt = Context::get();
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(self)));
+        verify!(ctxt.pixel_storei(Context::UNPACK_ALIGNMENT, 1));
+
+        if self.image_layout() == (width, height, Context::RGBA) {
+            verify!(ctxt.tex_sub_image2d(
+                Context::TEXTURE_2D,
+                0,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                Context::RGBA,
+                Some(data)
+            ));
+        } else {
+            verify!(ctxt.tex_image2d(
+                Context::TEXTURE_2D,
+                0,
+                Context::RGBA as i32,
+                width as i32,
+                height as i32,
+                0,
+                Context::RGBA,
+                Some(data)
+            ));
+            // Textures created by `Texture::new` are not configured, and need a filter that does
+            // not use mipmaps until they are generated.
+            if self.image_layout().2 == 0 && !self.uses_mipmaps() {
+                verify!(ctxt.tex_parameteri(
+                    Context::TEXTURE_2D,
+                    Context::TEXTURE_MIN_FILTER,
+                    Context::LINEAR as i32
+                ));
+            }
+
+            self.set_image_layout(width, height, Context::RGBA);
+        }
+
+        if self.uses_mipmaps() {
+            verify!(ctxt.generate_mipmap(Context::TEXTURE_2D));
+        }
+    }
 }
 
 impl Drop for Texture {
@@ -71,14 +252,40 @@ impl Drop for Texture {
     }
 }
 
+thread_local!(static ANISOTROPY_WARNING: Cell<bool> = const { Cell::new(false) });
 thread_local!(static KEY_TEXTURE_MANAGER: RefCell<TextureManager> = RefCell::new(TextureManager::new()));
 
+/// The layout of the pixels of a raw image.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// One byte per pixel, for its luminance.
+    Gray,
+    /// Three bytes per pixel, for its red, green and blue components.
+    Rgb,
+    /// Four bytes per pixel, for its red, green, blue and alpha components.
+    Rgba,
+}
+
+impl PixelFormat {
+    /// The number of bytes of each pixel.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Gray => 1,
+            PixelFormat::Rgb => 3,
+            PixelFormat::Rgba => 4,
+        }
+    }
+}
+
 /// The texture manager.
 ///
 /// It keeps a cache of already-loaded textures, and can load new textures.
 pub struct TextureManager {
     default_texture: Rc<Texture>,
     textures: HashMap<String, (Rc<Texture>, (u32, u32))>,
+    // The files the textures added with `add` were loaded from.
+    paths: HashMap<String, PathBuf>,
+    mipmaps: bool,
 }
 
 impl TextureManager {
@@ -125,6 +332,8 @@ impl TextureManager {
 
         TextureManager {
             textures: HashMap::new(),
+            paths: HashMap::new(),
+            mipmaps: false,
             default_texture: default_tex,
         }
     }
@@ -151,6 +360,29 @@ impl TextureManager {
             .map(|t| (t.0.clone(), t.1))
     }
 
+    /// The file a texture was loaded from with `add`, if any.
+    pub fn file_path(&self, texture: &Rc<Texture>) -> Option<&Path> {
+        self.textures
+            .iter()
+            .find(|(_, t)| Rc::ptr_eq(&t.0, texture))
+            .and_then(|(name, _)| self.paths.get(name))
+            .map(|path| path.as_path())
+    }
+
+    /// Sets whether mipmaps are generated for the textures loaded from now on.
+    ///
+    /// Textures with mipmaps are filtered with `TextureFilter::LinearMipmapLinear` when they are
+    /// minified, which prevents the shimmering of detailed textures seen from afar or at grazing
+    /// angles. They use a third more memory. Mipmaps are not generated by default.
+    pub fn set_mipmap_generation(&mut self, enabled: bool) {
+        self.mipmaps = enabled
+    }
+
+    /// Whether mipmaps are generated for the textures loaded from now on.
+    pub fn mipmap_generation(&self) -> bool {
+        self.mipmaps
+    }
+
     /// Allocates a new texture that is not yet configured.
     ///
     /// If a texture with same name exists, nothing is created and the old texture is returned.
@@ -165,9 +397,13 @@ impl TextureManager {
     ///
     /// If a texture with same name exists, nothing is created and the old texture is returned.
     pub fn add_image(&mut self, dynamic_image: DynamicImage, name: &str) -> Rc<Texture> {
+        let mipmaps = self.mipmaps;
+
         self.textures
             .entry(name.to_string())
-            .or_insert_with(|| TextureManager::load_texture_into_context(dynamic_image).unwrap())
+            .or_insert_with(|| {
+                TextureManager::load_texture_into_context(dynamic_image, mipmaps).unwrap()
+            })
             .0
             .clone()
     }
@@ -182,23 +418,74 @@ impl TextureManager {
         )
     }
 
+    /// Allocates a new texture from raw pixels.
+    ///
+    /// The pixels are given row by row starting with the top row, without padding between the
+    /// rows. The texture is uploaded directly, without being encoded.
+    /// If a texture with same name exists, nothing is created and the old texture is returned.
+    ///
+    /// # Failure
+    /// Panics if `data` does not contain exactly `width * height` pixels of the given format.
+    pub fn add_image_from_pixels(
+        &mut self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        name: &str,
+    ) -> Rc<Texture> {
+        match self.textures.entry(name.to_string()) {
+            Entry::Occupied(entry) => entry.into_mut().0.clone(),
+            Entry::Vacant(entry) => {
+                let image = image_from_pixels(data, width, height, format);
+                let texture =
+                    TextureManager::load_texture_into_context(image, self.mipmaps).unwrap();
+                entry.insert(texture).0.clone()
+            }
+        }
+    }
+
+    // Allocates a new texture from raw pixels, without registering it.
+    pub(crate) fn load_texture_from_pixels(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+    ) -> Rc<Texture> {
+        let image = image_from_pixels(data, width, height, format);
+        TextureManager::load_texture_into_context(image, self.mipmaps)
+            .unwrap()
+            .0
+    }
+
     /// Allocates a new texture read from a file.
-    fn load_texture_from_file(path: &Path) -> (Rc<Texture>, (u32, u32)) {
-        TextureManager::load_texture_into_context(image::open(path).unwrap())
+    fn load_texture_from_file(path: &Path, mipmaps: bool) -> (Rc<Texture>, (u32, u32)) {
+        TextureManager::load_texture_into_context(image::open(path).unwrap(), mipmaps)
             .expect(path.to_str().unwrap())
     }
 
     fn load_texture_into_context(
         dynamic_image: DynamicImage,
+        mipmaps: bool,
     ) -> Result<(Rc<Texture>, (u32, u32)), &'static str> {
         let ctxt = Context::get();
         let tex = Texture::new();
         let width;
         let height;
 
+        // Luminance textures are not supported by every context.
+        let dynamic_image = match dynamic_image {
+            DynamicImage::ImageLuma8(_) => DynamicImage::ImageRgb8(dynamic_image.to_rgb8()),
+            DynamicImage::ImageLumaA8(_) => DynamicImage::ImageRgba8(dynamic_image.to_rgba8()),
+            _ => dynamic_image,
+        };
+
         unsafe {
             verify!(ctxt.active_texture(Context::TEXTURE0));
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*tex)));
+            // The rows of the images are not padded.
+            verify!(ctxt.pixel_storei(Context::UNPACK_ALIGNMENT, 1));
 
             match dynamic_image {
                 DynamicImage::ImageRgb8(image) => {
@@ -215,6 +502,7 @@ impl TextureManager {
                         Context::RGB,
                         Some(&image.into_raw()[..])
                     ));
+                    tex.set_image_layout(width, height, Context::RGB);
                 }
                 DynamicImage::ImageRgba8(image) => {
                     width = image.width();
@@ -230,6 +518,7 @@ impl TextureManager {
                         Context::RGBA,
                         Some(&image.into_raw()[..])
                     ));
+                    tex.set_image_layout(width, height, Context::RGBA);
                 }
                 _ => {
                     return Err("Failed to load texture, unsuported pixel format.");
@@ -246,27 +535,175 @@ impl TextureManager {
                 Context::TEXTURE_WRAP_T,
                 Context::CLAMP_TO_EDGE as i32
             ));
-            verify!(ctxt.tex_parameteri(
-                Context::TEXTURE_2D,
-                Context::TEXTURE_MIN_FILTER,
-                Context::LINEAR as i32
-            ));
             verify!(ctxt.tex_parameteri(
                 Context::TEXTURE_2D,
                 Context::TEXTURE_MAG_FILTER,
                 Context::LINEAR as i32
             ));
         }
+
+        if mipmaps {
+            tex.set_filtering(TextureFilter::LinearMipmapLinear, TextureFilter::Linear);
+        } else {
+            tex.set_filtering(TextureFilter::Linear, TextureFilter::Linear);
+        }
+
         Ok((tex, (width, height)))
     }
 
     /// Allocates a new texture read from a file. If a texture with same name exists, nothing is
     /// created and the old texture is returned.
     pub fn add(&mut self, path: &Path, name: &str) -> Rc<Texture> {
+        let paths = &mut self.paths;
+        let mipmaps = self.mipmaps;
+
+        self.textures
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let _ = paths.insert(name.to_string(), path.to_path_buf());
+                TextureManager::load_texture_from_file(path, mipmaps)
+            })
+            .0
+            .clone()
+    }
+
+    // Allocates a new texture from an image decoded from the file at `path`. If a texture with same
+    // name exists, nothing is created and the old texture is returned.
+    pub(crate) fn add_decoded(
+        &mut self,
+        dynamic_image: DynamicImage,
+        path: &Path,
+        name: &str,
+    ) -> Rc<Texture> {
+        let paths = &mut self.paths;
+        let mipmaps = self.mipmaps;
+
         self.textures
             .entry(name.to_string())
-            .or_insert_with(|| TextureManager::load_texture_from_file(path))
+            .or_insert_with(|| {
+                let _ = paths.insert(name.to_string(), path.to_path_buf());
+                TextureManager::load_texture_into_context(dynamic_image, mipmaps).unwrap()
+            })
             .0
             .clone()
     }
+
+    /// Allocates a new texture read from a file and containing linear data, e.g., a roughness
+    /// map, which is not decoded from sRGB when gamma correction is enabled.
+    ///
+    /// If a texture with same name exists, nothing is created and the old texture is returned.
+    pub fn add_linear(&mut self, path: &Path, name: &str) -> Rc<Texture> {
+        let texture = self.add(path, name);
+        texture.set_srgb(false);
+        texture
+    }
+}
+
+fn image_from_pixels(data: &[u8], width: u32, height: u32, format: PixelFormat) -> DynamicImage {
+    let len = width as usize * height as usize * format.bytes_per_pixel();
+    assert!(
+        data.len() == len,
+        "Invalid pixel data: expected {} bytes, got {}.",
+        len,
+        data.len()
+    );
+
+    let data = data.to_vec();
+    let image = match format {
+        PixelFormat::Gray => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma8)
+        }
+        PixelFormat::Rgb => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb8),
+        PixelFormat::Rgba => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+        }
+    };
+
+    image.unwrap()
+}
+
+/// A texture decoded from a file by a worker thread.
+///
+/// The file is read and decoded in the background, while the texture is uploaded when `poll` is
+/// called on the thread of the window. It is then registered by the global `TextureManager`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct PendingTexture {
+    path: PathBuf,
+    name: String,
+    receiver: Option<Receiver<ImageResult<DynamicImage>>>,
+    texture: Option<Rc<Texture>>,
+    error: Option<ImageError>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PendingTexture {
+    /// Starts loading the texture named `name` from a file.
+    ///
+    /// If a texture with same name is already registered by the global `TextureManager`, nothing
+    /// is loaded and the old texture is used.
+    pub fn new(path: &Path, name: &str) -> PendingTexture {
+        let texture = TextureManager::get_global_manager(|tm| tm.get(name));
+        let receiver = if texture.is_none() {
+            let (sender, receiver) = mpsc::channel();
+            let file = path.to_path_buf();
+            let _ = thread::spawn(move || {
+                let _ = sender.send(image::open(&file));
+            });
+
+            Some(receiver)
+        } else {
+            None
+        };
+
+        PendingTexture {
+            path: path.to_path_buf(),
+            name: name.to_string(),
+            receiver,
+            texture,
+            error: None,
+        }
+    }
+
+    /// Uploads the texture if it has been decoded, and returns `true` if its loading is complete.
+    ///
+    /// The loading is complete when the texture is available, or when it failed.
+    pub fn poll(&mut self) -> bool {
+        let result = match self.receiver {
+            Some(ref receiver) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => Err(ImageError::IoError(std::io::Error::other(
+                    "the worker thread decoding the texture panicked",
+                ))),
+            },
+            None => return true,
+        };
+
+        self.receiver = None;
+
+        match result {
+            Ok(image) => {
+                let (path, name) = (&self.path, &self.name);
+                let mut image = Some(image);
+                self.texture = Some(TextureManager::get_global_manager(|tm| {
+                    tm.add_decoded(image.take().unwrap(), path, name)
+                }));
+            }
+            Err(e) => self.error = Some(e),
+        }
+
+        true
+    }
+
+    /// The texture, if it has been loaded by a call to `poll`.
+    #[inline]
+    pub fn texture(&self) -> Option<&Rc<Texture>> {
+        self.texture.as_ref()
+    }
+
+    /// The error that made the loading fail, if any.
+    #[inline]
+    pub fn error(&self) -> Option<&ImageError> {
+        self.error.as_ref()
+    }
 }
@@ -0,0 +1,146 @@
+//! A scene node drawing many instances of the same mesh.
+
+use crate::resource::{AllocationType, BufferType, GPUVec};
+use crate::scene::SceneNode;
+use na::{Isometry3, Point3, Vector4};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The per-instance data of an `InstancedNode`.
+///
+/// Those are uploaded to the GPU independently from the instanced mesh.
+pub struct Instances {
+    translations: GPUVec<Point3<f32>>,
+    rotations: GPUVec<Vector4<f32>>,
+    colors: GPUVec<Point3<f32>>,
+}
+
+impl Instances {
+    /// Creates a new set of instances.
+    ///
+    /// If `colors` is empty, all the instances are white. Otherwise, it must have the same
+    /// length as `transforms`.
+    pub fn new(transforms: &[Isometry3<f32>], colors: &[Point3<f32>]) -> Instances {
+        let mut res = Instances {
+            translations: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw),
+            rotations: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw),
+            colors: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw),
+        };
+
+        res.set(transforms, colors);
+
+        res
+    }
+
+    /// Replaces all the instances.
+    ///
+    /// The same constraints as for `Instances::new` apply to `colors`.
+    pub fn set(&mut self, transforms: &[Isometry3<f32>], colors: &[Point3<f32>]) {
+        assert!(
+            colors.is_empty() || colors.len() == transforms.len(),
+            "There must be as many instance colors as instance transforms."
+        );
+
+        let translations = transforms
+            .iter()
+            .map(|t| Point3::from(t.translation.vector))
+            .collect();
+        let rotations = transforms.iter().map(|t| t.rotation.coords).collect();
+        let colors = if colors.is_empty() {
+            vec![Point3::new(1.0, 1.0, 1.0); transforms.len()]
+        } else {
+            colors.to_vec()
+        };
+
+        *self.translations.data_mut() = Some(translations);
+        *self.rotations.data_mut() = Some(rotations);
+        *self.colors.data_mut() = Some(colors);
+    }
+
+    /// The number of instances.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.translations.len()
+    }
+
+    /// Whether there is no instance.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The translation, rotation (as a quaternion with the real part last), and color of the
+    /// `i`-th instance.
+    #[inline]
+    pub fn get(&self, i: usize) -> (Point3<f32>, Vector4<f32>, Point3<f32>) {
+        fn get<T: Copy>(v: &Option<Vec<T>>, i: usize) -> T {
+            v.as_ref().expect("Instances must be on RAM.")[i]
+        }
+
+        (
+            get(self.translations.data(), i),
+            get(self.rotations.data(), i),
+            get(self.colors.data(), i),
+        )
+    }
+
+    /// The buffer of instance translations.
+    #[inline]
+    pub fn translations_mut(&mut self) -> &mut GPUVec<Point3<f32>> {
+        &mut self.translations
+    }
+
+    /// The buffer of instance rotations, as quaternions with the real part last.
+    #[inline]
+    pub fn rotations_mut(&mut self) -> &mut GPUVec<Vector4<f32>> {
+        &mut self.rotations
+    }
+
+    /// The buffer of instance colors.
+    #[inline]
+    pub fn colors_mut(&mut self) -> &mut GPUVec<Point3<f32>> {
+        &mut self.colors
+    }
+}
+
+/// A scene node drawing many instances of the same mesh with a single draw call.
+///
+/// Each instance has its own transformation, relative to the node, and its own color, multiplied
+/// by the node color. The node itself can be manipulated as any other `SceneNode`.
+#[derive(Clone)]
+pub struct InstancedNode {
+    node: SceneNode,
+    instances: Rc<RefCell<Instances>>,
+}
+
+impl InstancedNode {
+    #[doc(hidden)]
+    pub fn new(node: SceneNode, instances: Rc<RefCell<Instances>>) -> InstancedNode {
+        InstancedNode { node, instances }
+    }
+
+    /// The scene node drawing the instances.
+    #[inline]
+    pub fn node(&self) -> &SceneNode {
+        &self.node
+    }
+
+    /// The scene node drawing the instances.
+    #[inline]
+    pub fn node_mut(&mut self) -> &mut SceneNode {
+        &mut self.node
+    }
+
+    /// The instances drawn by this node.
+    #[inline]
+    pub fn instances(&self) -> &Rc<RefCell<Instances>> {
+        &self.instances
+    }
+
+    /// Replaces the instances drawn by this node.
+    ///
+    /// Only the instance buffers are uploaded again, not the mesh.
+    pub fn set_instances(&mut self, transforms: &[Isometry3<f32>], colors: &[Point3<f32>]) {
+        self.instances.borrow_mut().set(transforms, colors)
+    }
+}
@@ -1,11 +1,24 @@
 //! Everything related to the scene graph.
 
-pub use self::object::{Object, ObjectData};
+pub use self::instanced_node::{InstancedNode, Instances};
+pub(crate) use self::object::{
+    num_outlined_objects, restore_default_depth_state, set_default_backface_culling,
+    set_polygon_offset,
+};
+pub use self::object::{DepthFunc, Object, ObjectData, WireframeMode};
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::pending_node::PendingNode;
 pub use self::planar_object::{PlanarObject, PlanarObjectData};
 pub use self::planar_scene_node::{PlanarSceneNode, PlanarSceneNodeData};
-pub use self::scene_node::{SceneNode, SceneNodeData};
+pub use self::point_cloud_node::{PointCloud, PointCloudNode};
+pub(crate) use self::scene_node::set_visible_layers;
+pub use self::scene_node::{BillboardMode, Descendants, SceneNode, SceneNodeData};
 
+mod instanced_node;
 mod object;
+#[cfg(not(target_arch = "wasm32"))]
+mod pending_node;
 mod planar_object;
 mod planar_scene_node;
+mod point_cloud_node;
 mod scene_node;
@@ -1,30 +1,154 @@
 //! Data structure of a scene node.
 
-use crate::camera::Camera;
-use crate::light::Light;
-use crate::resource::{Material, Mesh, Texture, TextureManager};
+use crate::camera::{self, Camera};
+use crate::context::Context;
+use crate::light::Lights;
+use crate::resource::{CubemapTexture, Material, Mesh, Texture, TextureManager};
+use crate::scene::{Instances, PointCloud};
 use na::{Isometry3, Point2, Point3, Vector3};
 use std::any::Any;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::path::Path;
 use std::rc::Rc;
 
 #[path = "../error.rs"]
 mod error;
 
+thread_local! {
+    // Whether backface culling is used by objects which do not override it.
+    static DEFAULT_BACKFACE_CULLING: Cell<bool> = const { Cell::new(true) };
+    // The number of objects with an outline, so the outlines are not searched for if there is
+    // none.
+    static NUM_OUTLINED_OBJECTS: Cell<usize> = const { Cell::new(0) };
+}
+
+// The number of existing objects with an outline.
+pub(crate) fn num_outlined_objects() -> usize {
+    NUM_OUTLINED_OBJECTS.with(|n| n.get())
+}
+
+fn count_outlined_object(added: bool) {
+    NUM_OUTLINED_OBJECTS.with(|n| {
+        if added {
+            n.set(n.get() + 1)
+        } else {
+            n.set(n.get() - 1)
+        }
+    })
+}
+
+// Sets whether backface culling is used by objects which do not override it.
+pub(crate) fn set_default_backface_culling(active: bool) {
+    DEFAULT_BACKFACE_CULLING.with(|cull| cull.set(active))
+}
+
+/// Which edges of an object are drawn as lines.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WireframeMode {
+    /// All the edges of the triangles of the mesh are drawn.
+    Triangles,
+    /// Each edge is drawn once, without the diagonals of the flat polygons of the mesh. See
+    /// `Mesh::wireframe_edges` for details.
+    Edges,
+}
+
+/// The comparison deciding whether a fragment passes the depth test.
+///
+/// The depth of the fragment is compared to the one stored in the depth buffer, as if smaller
+/// depths were closer to the camera. For example, `Less` only draws the fragments in front of
+/// the ones already drawn, even if the depth is reversed with `Window::set_reversed_z`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DepthFunc {
+    /// The fragments are never drawn.
+    Never,
+    /// The fragments are drawn if they are closer than the stored depth.
+    Less,
+    /// The fragments are drawn if they are at the stored depth.
+    Equal,
+    /// The fragments are drawn if they are closer than or at the stored depth. This is the
+    /// default.
+    LessEqual,
+    /// The fragments are drawn if they are farther than the stored depth.
+    Greater,
+    /// The fragments are drawn if they are not at the stored depth.
+    NotEqual,
+    /// The fragments are drawn if they are farther than or at the stored depth.
+    GreaterEqual,
+    /// The fragments are always drawn.
+    Always,
+}
+
+impl DepthFunc {
+    // The OpenGL comparison implementing this function, for a reversed depth buffer if
+    // `reversed_z` is `true`.
+    fn gl_func(self, reversed_z: bool) -> u32 {
+        match (self, reversed_z) {
+            (DepthFunc::Never, _) => Context::NEVER,
+            (DepthFunc::Equal, _) => Context::EQUAL,
+            (DepthFunc::NotEqual, _) => Context::NOTEQUAL,
+            (DepthFunc::Always, _) => Context::ALWAYS,
+            (DepthFunc::Less, false) | (DepthFunc::Greater, true) => Context::LESS,
+            (DepthFunc::LessEqual, false) | (DepthFunc::GreaterEqual, true) => Context::LEQUAL,
+            (DepthFunc::Greater, false) | (DepthFunc::Less, true) => Context::GREATER,
+            (DepthFunc::GreaterEqual, false) | (DepthFunc::LessEqual, true) => Context::GEQUAL,
+        }
+    }
+}
+
+// Restores the depth state used by the objects with the default one, after drawing an object
+// which overrides it. The transparent objects do not write to the depth buffer.
+pub(crate) fn restore_default_depth_state(transparent: bool) {
+    let ctxt = Context::get();
+    verify!(ctxt.enable(Context::DEPTH_TEST));
+    verify!(ctxt.depth_mask(!transparent));
+    verify!(ctxt.depth_func(DepthFunc::LessEqual.gl_func(camera::reversed_z())));
+    verify!(ctxt.disable(Context::POLYGON_OFFSET_FILL));
+}
+
+// Sets the polygon offset, with positive values pushing the polygons away from the camera even
+// if the depth is reversed. `POLYGON_OFFSET_FILL` must be enabled separately.
+pub(crate) fn set_polygon_offset(factor: f32, units: f32) {
+    let sign = if camera::reversed_z() { -1.0 } else { 1.0 };
+    verify!(Context::get().polygon_offset(sign * factor, sign * units));
+}
+
 /// Set of data identifying a scene node.
 pub struct ObjectData {
     material: Rc<RefCell<Box<dyn Material + 'static>>>,
     texture: Rc<Texture>,
+    normal_map: Option<Rc<Texture>>,
+    emissive_color: Point3<f32>,
+    emissive_texture: Option<Rc<Texture>>,
+    reflection_map: Option<Rc<CubemapTexture>>,
+    reflectivity: f32,
     color: Point3<f32>,
+    alpha: f32,
+    transparent: bool,
     lines_color: Option<Point3<f32>>,
+    wireframe_overlay: Option<Point3<f32>>,
+    wireframe_mode: WireframeMode,
     wlines: f32,
     wpoints: f32,
     draw_surface: bool,
-    cull: bool,
+    // `None` to use the default of the window.
+    cull: Option<bool>,
+    depth_test: bool,
+    // `None` to write the depth of opaque objects only.
+    depth_write: Option<bool>,
+    depth_func: DepthFunc,
+    polygon_offset: Option<(f32, f32)>,
+    outline: Option<(Point3<f32>, f32)>,
     user_data: Box<dyn Any + 'static>,
 }
 
+impl Drop for ObjectData {
+    fn drop(&mut self) {
+        if self.outline.is_some() {
+            count_outlined_object(false);
+        }
+    }
+}
+
 impl ObjectData {
     /// The texture of this object.
     #[inline]
@@ -32,12 +156,54 @@ impl ObjectData {
         &self.texture
     }
 
+    /// The tangent-space normal map of this object, if any.
+    #[inline]
+    pub fn normal_map(&self) -> Option<&Rc<Texture>> {
+        self.normal_map.as_ref()
+    }
+
+    /// The color emitted by this object, regardless of the lighting.
+    #[inline]
+    pub fn emissive_color(&self) -> &Point3<f32> {
+        &self.emissive_color
+    }
+
+    /// The texture multiplied by the emissive color of this object, if any.
+    #[inline]
+    pub fn emissive_texture(&self) -> Option<&Rc<Texture>> {
+        self.emissive_texture.as_ref()
+    }
+
+    /// The environment reflected by this object, if any.
+    #[inline]
+    pub fn reflection_map(&self) -> Option<&Rc<CubemapTexture>> {
+        self.reflection_map.as_ref()
+    }
+
+    /// The fraction of the environment reflected by this object when looking at it head-on.
+    #[inline]
+    pub fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+
     /// The color of this object.
     #[inline]
     pub fn color(&self) -> &Point3<f32> {
         &self.color
     }
 
+    /// The opacity of this object, multiplied by the alpha channel of its texture.
+    #[inline]
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Whether this object is drawn with alpha blending, after the opaque objects.
+    #[inline]
+    pub fn transparent(&self) -> bool {
+        self.transparent
+    }
+
     /// The width of the lines draw for this object.
     #[inline]
     pub fn lines_width(&self) -> f32 {
@@ -50,6 +216,18 @@ impl ObjectData {
         self.lines_color.as_ref()
     }
 
+    /// The color of the wireframe drawn on top of the surface of this object, if any.
+    #[inline]
+    pub fn wireframe_overlay(&self) -> Option<&Point3<f32>> {
+        self.wireframe_overlay.as_ref()
+    }
+
+    /// Which edges of this object are drawn as lines.
+    #[inline]
+    pub fn wireframe_mode(&self) -> WireframeMode {
+        self.wireframe_mode
+    }
+
     /// The size of the points draw for this object.
     #[inline]
     pub fn points_size(&self) -> f32 {
@@ -63,9 +241,72 @@ impl ObjectData {
     }
 
     /// Whether this object uses backface culling or not.
+    ///
+    /// Unless it has been set for this object, this is the default set by
+    /// `Window::set_backface_culling`.
     #[inline]
     pub fn backface_culling_enabled(&self) -> bool {
         self.cull
+            .unwrap_or_else(|| DEFAULT_BACKFACE_CULLING.with(|cull| cull.get()))
+    }
+
+    /// Whether the fragments of this object are subject to the depth test.
+    #[inline]
+    pub fn depth_test_enabled(&self) -> bool {
+        self.depth_test
+    }
+
+    /// Whether this object writes its depth to the depth buffer.
+    ///
+    /// Unless it has been set for this object, only opaque objects write their depth.
+    #[inline]
+    pub fn depth_write_enabled(&self) -> bool {
+        self.depth_write.unwrap_or(!self.transparent)
+    }
+
+    /// The comparison used by the depth test of this object.
+    #[inline]
+    pub fn depth_func(&self) -> DepthFunc {
+        self.depth_func
+    }
+
+    /// The factor and units of the polygon offset applied to the depth of this object, if any.
+    #[inline]
+    pub fn polygon_offset(&self) -> Option<(f32, f32)> {
+        self.polygon_offset
+    }
+
+    /// The color and width in pixels of the outline drawn around this object, if any.
+    #[inline]
+    pub fn outline(&self) -> Option<(Point3<f32>, f32)> {
+        self.outline
+    }
+
+    // Whether this object uses the depth state shared by all the objects by default.
+    pub(crate) fn has_default_depth_state(&self) -> bool {
+        self.depth_test
+            && self.depth_write.is_none()
+            && self.depth_func == DepthFunc::LessEqual
+            && self.polygon_offset.is_none()
+    }
+
+    // Sets the depth state of this object, to be restored with `restore_default_depth_state`.
+    pub(crate) fn apply_depth_state(&self) {
+        let ctxt = Context::get();
+
+        if self.depth_test {
+            verify!(ctxt.enable(Context::DEPTH_TEST));
+        } else {
+            verify!(ctxt.disable(Context::DEPTH_TEST));
+        }
+
+        verify!(ctxt.depth_mask(self.depth_write_enabled()));
+        verify!(ctxt.depth_func(self.depth_func.gl_func(camera::reversed_z())));
+
+        if let Some((factor, units)) = self.polygon_offset {
+            verify!(ctxt.enable(Context::POLYGON_OFFSET_FILL));
+            set_polygon_offset(factor, units);
+        }
     }
 
     /// An user-defined data.
@@ -85,6 +326,8 @@ pub struct Object {
     // (thus removing the need of ObjectData at all.)
     data: ObjectData,
     mesh: Rc<RefCell<Mesh>>,
+    // The simpler meshes used from the given distances to the camera, sorted by distance.
+    lods: Vec<(f32, Rc<RefCell<Mesh>>)>,
 }
 
 impl Object {
@@ -100,17 +343,35 @@ impl Object {
         let user_data = ();
         let data = ObjectData {
             color: Point3::new(r, g, b),
+            alpha: 1.0,
+            transparent: false,
             lines_color: None,
+            wireframe_overlay: None,
+            wireframe_mode: WireframeMode::Triangles,
             texture,
+            normal_map: None,
+            emissive_color: Point3::origin(),
+            emissive_texture: None,
+            reflection_map: None,
+            reflectivity: 0.5,
             wlines: 0.0,
             wpoints: 0.0,
             draw_surface: true,
-            cull: true,
+            cull: None,
+            depth_test: true,
+            depth_write: None,
+            depth_func: DepthFunc::LessEqual,
+            polygon_offset: None,
+            outline: None,
             material,
             user_data: Box::new(user_data),
         };
 
-        Object { data, mesh }
+        Object {
+            data,
+            mesh,
+            lods: Vec::new(),
+        }
     }
 
     #[doc(hidden)]
@@ -120,19 +381,41 @@ impl Object {
         scale: &Vector3<f32>,
         pass: usize,
         camera: &mut dyn Camera,
-        light: &Light,
+        lights: &Lights,
     ) {
         self.data.material.borrow_mut().render(
             pass,
             transform,
             scale,
             camera,
-            light,
+            lights,
             &self.data,
             &mut *self.mesh.borrow_mut(),
         );
     }
 
+    // Renders this object with the mesh of the level of detail used at `distance` from the
+    // camera.
+    pub(crate) fn render_at_distance(
+        &self,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        pass: usize,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        distance: f32,
+    ) {
+        self.data.material.borrow_mut().render(
+            pass,
+            transform,
+            scale,
+            camera,
+            lights,
+            &self.data,
+            &mut self.lod_mesh(distance).borrow_mut(),
+        );
+    }
+
     /// Gets the data of this object.
     #[inline]
     pub fn data(&self) -> &ObjectData {
@@ -145,10 +428,128 @@ impl Object {
         &mut self.data
     }
 
-    /// Enables or disables backface culling for this object.
+    /// Enables or disables backface culling for this object, regardless of the default set by
+    /// `Window::set_backface_culling`.
     #[inline]
     pub fn enable_backface_culling(&mut self, active: bool) {
-        self.data.cull = active;
+        self.data.cull = Some(active);
+    }
+
+    /// Makes this object use the backface culling default set by `Window::set_backface_culling`.
+    #[inline]
+    pub fn reset_backface_culling(&mut self) {
+        self.data.cull = None;
+    }
+
+    /// Enables or disables the depth test for this object (default at `true`).
+    ///
+    /// Without depth test, the object is drawn over the objects drawn before it, e.g., for
+    /// overlays, and does not write its depth either.
+    #[inline]
+    pub fn set_depth_test(&mut self, active: bool) {
+        self.data.depth_test = active;
+    }
+
+    /// Sets whether this object writes its depth to the depth buffer.
+    ///
+    /// By default, only opaque objects write their depth, so the transparent ones do not hide
+    /// each other.
+    #[inline]
+    pub fn set_depth_write(&mut self, active: bool) {
+        self.data.depth_write = Some(active);
+    }
+
+    /// Makes this object write its depth only if it is opaque, which is the default.
+    #[inline]
+    pub fn reset_depth_write(&mut self) {
+        self.data.depth_write = None;
+    }
+
+    /// Sets the comparison used by the depth test of this object (default at
+    /// `DepthFunc::LessEqual`).
+    #[inline]
+    pub fn set_depth_func(&mut self, func: DepthFunc) {
+        self.data.depth_func = func;
+    }
+
+    /// Offsets the depth of the surface of this object by `factor` times its depth slope plus
+    /// `units` times the smallest resolvable depth difference.
+    ///
+    /// Negative values move the surface toward the camera, e.g., so coplanar decals are drawn over
+    /// the surface they lie on without z-fighting. The offset is applied with
+    /// `GL_POLYGON_OFFSET_FILL`, so it does not affect the lines and points of the object.
+    #[inline]
+    pub fn set_polygon_offset(&mut self, factor: f32, units: f32) {
+        self.data.polygon_offset = Some((factor, units));
+    }
+
+    /// Removes the polygon offset of this object.
+    #[inline]
+    pub fn unset_polygon_offset(&mut self) {
+        self.data.polygon_offset = None;
+    }
+
+    /// Sets the color and width in pixels of the outline drawn around this object, or removes it
+    /// if `None`.
+    ///
+    /// The outline surrounds the silhouette of the object on the screen and is drawn over the
+    /// rest of the scene. It is made of the edges of the triangles of the mesh, so it requires its
+    /// vertices and faces to be kept in RAM.
+    #[inline]
+    pub fn set_outline(&mut self, outline: Option<(Point3<f32>, f32)>) {
+        if self.data.outline.is_some() != outline.is_some() {
+            count_outlined_object(outline.is_some());
+        }
+
+        self.data.outline = outline;
+    }
+
+    // A copy of this object sharing its mesh, material, textures, and the instances or points of
+    // instanced nodes and point clouds. Other user-defined data are not copied.
+    pub(crate) fn clone_sharing_resources(&self) -> Object {
+        let data = &self.data;
+        let user_data: Box<dyn Any + 'static> =
+            if let Some(instances) = data.user_data.downcast_ref::<Rc<RefCell<Instances>>>() {
+                Box::new(instances.clone())
+            } else if let Some(cloud) = data.user_data.downcast_ref::<Rc<RefCell<PointCloud>>>() {
+                Box::new(cloud.clone())
+            } else {
+                Box::new(())
+            };
+
+        if data.outline.is_some() {
+            count_outlined_object(true);
+        }
+
+        Object {
+            data: ObjectData {
+                material: data.material.clone(),
+                texture: data.texture.clone(),
+                normal_map: data.normal_map.clone(),
+                emissive_color: data.emissive_color,
+                emissive_texture: data.emissive_texture.clone(),
+                reflection_map: data.reflection_map.clone(),
+                reflectivity: data.reflectivity,
+                color: data.color,
+                alpha: data.alpha,
+                transparent: data.transparent,
+                lines_color: data.lines_color,
+                wireframe_overlay: data.wireframe_overlay,
+                wireframe_mode: data.wireframe_mode,
+                wlines: data.wlines,
+                wpoints: data.wpoints,
+                draw_surface: data.draw_surface,
+                cull: data.cull,
+                depth_test: data.depth_test,
+                depth_write: data.depth_write,
+                depth_func: data.depth_func,
+                polygon_offset: data.polygon_offset,
+                outline: data.outline,
+                user_data,
+            },
+            mesh: self.mesh.clone(),
+            lods: self.lods.clone(),
+        }
     }
 
     /// Attaches user-defined data to this object.
@@ -193,6 +594,35 @@ impl Object {
         self.data.lines_color
     }
 
+    /// Enables or disables the drawing of the edges of this object with the given color, on top
+    /// of its surface.
+    ///
+    /// This is independent from `set_lines_width`. The wireframe is drawn by the default
+    /// material only.
+    #[inline]
+    pub fn set_wireframe_overlay(&mut self, color: Point3<f32>, enabled: bool) {
+        self.data.wireframe_overlay = if enabled { Some(color) } else { None }
+    }
+
+    /// The color of the wireframe drawn on top of the surface of this object, if any.
+    #[inline]
+    pub fn wireframe_overlay(&self) -> Option<Point3<f32>> {
+        self.data.wireframe_overlay
+    }
+
+    /// Sets which edges of this object are drawn by `set_lines_width` and
+    /// `set_wireframe_overlay`.
+    #[inline]
+    pub fn set_wireframe_mode(&mut self, mode: WireframeMode) {
+        self.data.wireframe_mode = mode
+    }
+
+    /// Which edges of this object are drawn as lines.
+    #[inline]
+    pub fn wireframe_mode(&self) -> WireframeMode {
+        self.data.wireframe_mode
+    }
+
     /// Sets the size of the points drawn for this object.
     #[inline]
     pub fn set_points_size(&mut self, size: f32) {
@@ -223,6 +653,67 @@ impl Object {
         &self.mesh
     }
 
+    /// The simpler meshes of this object and the distances to the camera they are used from,
+    /// sorted by increasing distance.
+    #[inline]
+    pub fn lods(&self) -> &[(f32, Rc<RefCell<Mesh>>)] {
+        &self.lods
+    }
+
+    /// Sets the levels of detail of this object.
+    ///
+    /// Each mesh of `lods` is drawn instead of the mesh of this object when the distance between
+    /// the camera and the center of the object is at least the distance associated to it, and
+    /// smaller than the next one. The mesh of the object is still used for frustum culling,
+    /// shadows, and picking. An empty slice removes the levels of detail.
+    pub fn set_lods(&mut self, lods: &[(f32, Rc<RefCell<Mesh>>)]) {
+        self.lods = lods.to_vec();
+        self.lods
+            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        if self.data.normal_map.is_some() {
+            for (_, mesh) in &self.lods {
+                let mut bmesh = mesh.borrow_mut();
+
+                if bmesh.tangents().is_none() {
+                    bmesh.recompute_tangents();
+                }
+            }
+        }
+    }
+
+    /// The mesh drawn when the center of this object is at the given distance from the camera.
+    pub fn lod_mesh(&self, distance: f32) -> &Rc<RefCell<Mesh>> {
+        self.lods
+            .iter()
+            .rev()
+            .find(|lod| distance >= lod.0)
+            .map_or(&self.mesh, |lod| &lod.1)
+    }
+
+    /// Replaces this object's mesh.
+    pub fn set_mesh(&mut self, mesh: Rc<RefCell<Mesh>>) {
+        if self.data.normal_map.is_some() {
+            let mut bmesh = mesh.borrow_mut();
+
+            if bmesh.tangents().is_none() {
+                bmesh.recompute_tangents();
+            }
+        }
+
+        self.mesh = mesh
+    }
+
+    /// Replaces this object's mesh by a smoother one obtained with `iterations` steps of Loop
+    /// subdivision.
+    ///
+    /// See `Mesh::subdivide` for details. The other objects sharing the previous mesh are not
+    /// affected.
+    pub fn subdivide(&mut self, iterations: u32) {
+        let mesh = self.mesh.borrow().subdivide(iterations);
+        self.set_mesh(Rc::new(RefCell::new(mesh)))
+    }
+
     /// Mutably access the object's vertices.
     #[inline(always)]
     pub fn modify_vertices<F: FnMut(&mut Vec<Point3<f32>>)>(&mut self, f: &mut F) {
@@ -255,6 +746,14 @@ impl Object {
         self.mesh.borrow_mut().recompute_normals();
     }
 
+    /// Recomputes the normals of this object's mesh so each face is shaded flat.
+    ///
+    /// See `Mesh::recompute_normals_flat` for details.
+    #[inline]
+    pub fn recompute_normals_flat(&mut self) {
+        self.mesh.borrow_mut().recompute_normals_flat();
+    }
+
     /// Mutably access the object's normals.
     #[inline(always)]
     pub fn modify_normals<F: FnMut(&mut Vec<Vector3<f32>>)>(&mut self, f: &mut F) {
@@ -343,6 +842,88 @@ impl Object {
         self.data.color.z = b;
     }
 
+    /// Sets the opacity of the object, on the range `[0.0, 1.0]`.
+    ///
+    /// This has no effect unless the object is transparent.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.data.alpha = alpha;
+    }
+
+    /// Sets whether this object is drawn with alpha blending, after the opaque objects.
+    #[inline]
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.data.transparent = transparent;
+    }
+
+    /// Sets the color emitted by the object.
+    ///
+    /// This color is added to the lit color of the object, so it is visible even in the dark.
+    /// Colors components must be on the range `[0.0, 1.0]`. Defaults to black.
+    #[inline]
+    pub fn set_emissive_color(&mut self, color: Point3<f32>) {
+        self.data.emissive_color = color
+    }
+
+    /// Sets the texture multiplied by the emissive color of the object.
+    ///
+    /// Use `None` to emit the emissive color uniformly.
+    #[inline]
+    pub fn set_emissive_texture(&mut self, texture: Option<Rc<Texture>>) {
+        self.data.emissive_texture = texture
+    }
+
+    /// Sets the environment reflected by the object.
+    ///
+    /// The cube map is sampled in the direction of the view vector reflected by the surface, and
+    /// mixed with the lit color of the object depending on the reflectivity and the angle between
+    /// the surface and the view vector. The cube map of the skybox can be reused here so the
+    /// object reflects the sky. Only the default `ObjectMaterial` uses it.
+    #[inline]
+    pub fn set_reflection_map(&mut self, map: Rc<CubemapTexture>) {
+        self.data.reflection_map = Some(map)
+    }
+
+    /// Removes the environment reflected by the object.
+    #[inline]
+    pub fn unset_reflection_map(&mut self) {
+        self.data.reflection_map = None
+    }
+
+    /// Sets the fraction of the environment reflected by the object when looking at it head-on.
+    ///
+    /// The reflection increases toward `1.0` at grazing angles, following Schlick's approximation
+    /// of the Fresnel equations. Must be on the range `[0.0, 1.0]`. Defaults to `0.5`. This has
+    /// no effect without a reflection map.
+    #[inline]
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.data.reflectivity = reflectivity
+    }
+
+    /// Sets the per-vertex colors of the object.
+    ///
+    /// Those colors are used by the `VertexColorMaterial`. Colors components must be on the range
+    /// `[0.0, 1.0]`.
+    ///
+    /// If the mesh of this object is shared, e.g., by all the objects created by
+    /// `SceneNode::add_cube`, this object is first given a mesh of its own, sharing the vertices
+    /// and faces of the original one but not its colors. `mesh()` then returns that new mesh.
+    ///
+    /// Panics if the number of colors does not match the number of vertices.
+    #[inline]
+    pub fn set_vertex_colors(&mut self, colors: Vec<Point3<f32>>) {
+        let nvertices = self.mesh.borrow().coords().read().unwrap().len();
+
+        assert!(
+            colors.len() == nvertices,
+            "Invalid number of vertex colors: found {}, expected {}.",
+            colors.len(),
+            nvertices
+        );
+
+        set_unshared_colors(&mut self.mesh, colors);
+    }
+
     /// Sets the texture of the object.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
@@ -375,4 +956,81 @@ impl Object {
     pub fn set_texture(&mut self, texture: Rc<Texture>) {
         self.data.texture = texture
     }
+
+    /// Sets the tangent-space normal map of the object.
+    ///
+    /// The tangents of the object's mesh are computed if they are not already available.
+    #[inline]
+    pub fn set_normal_map(&mut self, texture: Rc<Texture>) {
+        for mesh in Some(&self.mesh)
+            .into_iter()
+            .chain(self.lods.iter().map(|l| &l.1))
+        {
+            let mut mesh = mesh.borrow_mut();
+
+            if mesh.tangents().is_none() {
+                mesh.recompute_tangents();
+            }
+        }
+
+        self.data.normal_map = Some(texture)
+    }
+
+    /// Removes the normal map of the object.
+    #[inline]
+    pub fn unset_normal_map(&mut self) {
+        self.data.normal_map = None
+    }
+}
+
+// Sets the vertex colors of `mesh`, replacing it first by a copy sharing its geometry if it is
+// shared, so the other users of the mesh are not recolored.
+fn set_unshared_colors(mesh: &mut Rc<RefCell<Mesh>>, colors: Vec<Point3<f32>>) {
+    if Rc::strong_count(mesh) > 1 {
+        let copy = mesh.borrow().clone_sharing_geometry();
+        *mesh = Rc::new(RefCell::new(copy));
+    }
+
+    mesh.borrow_mut().set_colors(Some(colors));
+}
+
+#[cfg(test)]
+mod test {
+    use super::set_unshared_colors;
+    use crate::resource::MeshManager;
+    use na::Point3;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn vertex_colors_of_shared_meshes_are_not_shared() {
+        // The meshes of two nodes added by `add_cube`.
+        let cube = MeshManager::get_global_manager(|mm| mm.get("cube")).unwrap();
+        let mut first = cube.clone();
+        let second = cube.clone();
+        let n = cube.borrow().coords().read().unwrap().len();
+        let red = vec![Point3::new(1.0, 0.0, 0.0); n];
+
+        set_unshared_colors(&mut first, red.clone());
+
+        assert!(!Rc::ptr_eq(&first, &second));
+        assert!(second.borrow().colors().is_none());
+        assert!(MeshManager::get_global_manager(|mm| mm.get("cube"))
+            .unwrap()
+            .borrow()
+            .colors()
+            .is_none());
+        assert!(Arc::ptr_eq(
+            first.borrow().coords(),
+            second.borrow().coords()
+        ));
+
+        // A mesh used by a single object is modified in place.
+        let ptr = Rc::as_ptr(&first);
+        set_unshared_colors(&mut first, red);
+        assert_eq!(Rc::as_ptr(&first), ptr);
+
+        let colors = first.borrow().colors().unwrap().clone();
+        assert_eq!(colors.read().unwrap().len(), n);
+    }
 }
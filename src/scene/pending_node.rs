@@ -0,0 +1,157 @@
+//! Scene nodes loaded in the background.
+
+use crate::loader::mtl::MtlMaterial;
+use crate::loader::obj;
+use crate::resource::Mesh;
+use crate::scene::scene_node::obj_object;
+use crate::scene::SceneNode;
+use image::DynamicImage;
+use na::{self, Vector3};
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::{self, Result as IoResult};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+// The meshes of an obj file with their materials, and the decoded images of their textures.
+type ObjContent = (
+    Vec<(String, Mesh, Option<MtlMaterial>)>,
+    HashMap<PathBuf, DynamicImage>,
+);
+
+/// A scene node whose objects are loaded from an obj file by a worker thread.
+///
+/// The file is parsed in the background, while the meshes and textures are uploaded when `poll`
+/// is called on the thread of the window:
+///
+/// ```no_run
+/// # use kiss3d::window::Window;
+/// # use nalgebra::{Point2, Point3, Vector3};
+/// # use std::path::Path;
+/// # let mut window = Window::new("Loading");
+/// # let font = kiss3d::text::Font::default();
+/// let path = Path::new("model.obj");
+/// let mut pending = window.add_obj_async(path, Path::new("."), Vector3::from_element(1.0));
+///
+/// while window.render() {
+///     if !pending.poll() {
+///         window.draw_text("Loading...", &Point2::origin(), 60.0, &font, &Point3::new(1.0, 1.0, 1.0));
+///     }
+/// }
+/// ```
+pub struct PendingNode {
+    node: SceneNode,
+    mtl_dir: PathBuf,
+    receiver: Option<Receiver<IoResult<ObjContent>>>,
+    // The bits of the fraction of the file parsed by the worker thread.
+    progress: Arc<AtomicU32>,
+    error: Option<io::Error>,
+}
+
+impl PendingNode {
+    // Starts parsing the obj file at `path`, whose objects are added to `node` once parsed.
+    pub(crate) fn new(node: SceneNode, path: &Path, mtl_dir: &Path) -> PendingNode {
+        let (sender, receiver) = mpsc::channel();
+        let (file, dir) = (path.to_path_buf(), mtl_dir.to_path_buf());
+        let progress = Arc::new(AtomicU32::new(0.0f32.to_bits()));
+        let worker_progress = progress.clone();
+
+        let _ = thread::spawn(move || {
+            let name = file.to_string_lossy().into_owned();
+            let report =
+                |fraction: f32| worker_progress.store(fraction.to_bits(), Ordering::Relaxed);
+            let result = obj::parse_file_with_progress(&file, &dir, &name, report).map(|objs| {
+                let mut images = HashMap::new();
+
+                for mtl in objs.iter().filter_map(|obj| obj.2.as_ref()) {
+                    for t in mtl.diffuse_texture.iter().chain(mtl.ambiant_texture.iter()) {
+                        let mut tpath = PathBuf::new();
+                        tpath.push(&dir);
+                        tpath.push(&t[..]);
+
+                        if let Entry::Vacant(entry) = images.entry(tpath) {
+                            // The texture is loaded from the main thread if it cannot be decoded.
+                            if let Ok(image) = image::open(entry.key()) {
+                                let _ = entry.insert(image);
+                            }
+                        }
+                    }
+                }
+
+                (objs, images)
+            });
+
+            let _ = sender.send(result);
+        });
+
+        PendingNode {
+            node,
+            mtl_dir: mtl_dir.to_path_buf(),
+            receiver: Some(receiver),
+            progress,
+            error: None,
+        }
+    }
+
+    /// Adds the objects to the node if the file has been parsed, and returns `true` if its
+    /// loading is complete.
+    ///
+    /// The loading is complete when the objects have been added, or when it failed.
+    pub fn poll(&mut self) -> bool {
+        let result = match self.receiver {
+            Some(ref receiver) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the worker thread parsing the obj file panicked",
+                )),
+            },
+            None => return true,
+        };
+
+        self.receiver = None;
+
+        match result {
+            Ok((objs, mut images)) => {
+                for (_, mesh, mtl) in objs.into_iter() {
+                    let mesh = Rc::new(RefCell::new(mesh));
+                    let object = obj_object(mesh, mtl, &self.mtl_dir, &mut images);
+                    let _ = self
+                        .node
+                        .add_object(Vector3::from_element(1.0), na::one(), object);
+                }
+            }
+            Err(e) => self.error = Some(e),
+        }
+
+        true
+    }
+
+    /// The fraction of the obj file parsed so far by the worker thread, between 0.0 and 1.0.
+    ///
+    /// This can be used to display a loading bar until `poll` returns `true`.
+    #[inline]
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.progress.load(Ordering::Relaxed))
+    }
+
+    /// The node the objects are added to.
+    ///
+    /// It has no children until the loading is complete.
+    #[inline]
+    pub fn node(&self) -> &SceneNode {
+        &self.node
+    }
+
+    /// The error that made the loading fail, if any.
+    #[inline]
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+}
@@ -296,6 +296,48 @@ impl PlanarObject {
             .map(|uvs| f(&uvs[..]));
     }
 
+    /// Maps the texture subrectangle starting at `origin` with the extents `size` to the object.
+    ///
+    /// Both are expressed in normalized texture coordinates, where `(0, 0)` is the top-left
+    /// corner of the texture and `(1, 1)` its bottom-right corner. The texture coordinates of each
+    /// vertex are recomputed from its position relative to the bounding box of the object's
+    /// vertices, so the top-left corner of the subrectangle is mapped to the top-left corner of
+    /// the object.
+    pub fn set_texture_subrect(&mut self, origin: Point2<f32>, size: Vector2<f32>) {
+        let bmesh = self.mesh.borrow_mut();
+        let coords = bmesh.coords().read().unwrap();
+        let mut uvs = bmesh.uvs().write().unwrap();
+
+        if let (Some(coords), Some(uvs)) = (coords.data().as_ref(), uvs.data_mut().as_mut()) {
+            let mut mins = Point2::new(f32::MAX, f32::MAX);
+            let mut maxs = Point2::new(f32::MIN, f32::MIN);
+
+            for pt in coords.iter() {
+                mins = mins.inf(pt);
+                maxs = maxs.sup(pt);
+            }
+
+            let extents = maxs - mins;
+            uvs.resize(coords.len(), Point2::origin());
+
+            for (uv, pt) in uvs.iter_mut().zip(coords.iter()) {
+                // The texture coordinates go downward while the planar y axis goes upward.
+                let u = if extents.x > 0.0 {
+                    (pt.x - mins.x) / extents.x
+                } else {
+                    0.0
+                };
+                let v = if extents.y > 0.0 {
+                    (maxs.y - pt.y) / extents.y
+                } else {
+                    0.0
+                };
+
+                *uv = origin + Vector2::new(u * size.x, v * size.y);
+            }
+        }
+    }
+
     /// Sets the color of the object.
     ///
     /// Colors components must be on the range `[0.0, 1.0]`.
@@ -328,6 +328,14 @@ impl PlanarSceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.set_texture(texture.clone()))
     }
 
+    /// Maps a subrectangle of their texture to the objects contained by this node and its
+    /// children.
+    ///
+    /// See `PlanarObject::set_texture_subrect` for details.
+    pub fn set_texture_subrect(&mut self, origin: Point2<f32>, size: Vector2<f32>) {
+        self.apply_to_objects_mut(&mut |o| o.set_texture_subrect(origin, size))
+    }
+
     /// Applies a closure to each object contained by this node and its children.
     #[inline]
     pub fn apply_to_objects_mut<F: FnMut(&mut PlanarObject)>(&mut self, f: &mut F) {
@@ -643,6 +651,39 @@ impl PlanarSceneNode {
         res.expect("Unable to load the default rectangle geometry.")
     }
 
+    /// Adds a textured rectangle as a children of this node. The sprite is initially axis-aligned
+    /// and centered at (0, 0).
+    ///
+    /// The whole texture is mapped to the sprite, with its top row at the top of the sprite. Unlike
+    /// the rectangles added by `add_rectangle`, each sprite has its own mesh so its texture
+    /// coordinates can be changed with `set_texture_subrect` without affecting other objects.
+    ///
+    /// # Arguments
+    /// * `width` - the sprite extent along the x axis
+    /// * `height` - the sprite extent along the y axis
+    /// * `texture` - the texture displayed by the sprite
+    pub fn add_sprite(&mut self, width: f32, height: f32, texture: Rc<Texture>) -> PlanarSceneNode {
+        let vtx = vec![
+            Point2::new(0.5, 0.5),
+            Point2::new(-0.5, -0.5),
+            Point2::new(-0.5, 0.5),
+            Point2::new(0.5, -0.5),
+        ];
+        let uvs = vec![
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 1.0),
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 1.0),
+        ];
+        let ids = vec![Point3::new(0, 1, 2), Point3::new(1, 0, 3)];
+
+        let mesh = PlanarMesh::new(vtx, ids, Some(uvs), false);
+        let mat = PlanarMaterialManager::get_global_manager(|mm| mm.get_default());
+        let object = PlanarObject::new(Rc::new(RefCell::new(mesh)), 1.0, 1.0, 1.0, texture, mat);
+
+        self.add_object(Vector2::new(width, height), na::one(), object)
+    }
+
     /// Adds a circle as a children of this node. The circle is initially centered at (0, 0, 0).
     ///
     /// # Arguments
@@ -927,6 +968,16 @@ impl PlanarSceneNode {
         self.data_mut().set_texture(texture)
     }
 
+    /// Maps a subrectangle of their texture to the objects contained by this node and its
+    /// children.
+    ///
+    /// The subrectangle starts at `origin` and has the extents `size`, both expressed in
+    /// normalized texture coordinates where `(0, 0)` is the top-left corner of the texture. This
+    /// is typically used to display a single image from a texture atlas.
+    pub fn set_texture_subrect(&mut self, origin: Point2<f32>, size: Vector2<f32>) {
+        self.data_mut().set_texture_subrect(origin, size)
+    }
+
     /// Sets the local scaling factors of the object.
     #[inline]
     pub fn set_local_scale(&mut self, sx: f32, sy: f32) {
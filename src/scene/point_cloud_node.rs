@@ -0,0 +1,140 @@
+//! A scene node drawing a large set of colored points.
+
+use crate::resource::{AllocationType, BufferType, GPUVec};
+use crate::scene::SceneNode;
+use na::Point3;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The points of a `PointCloudNode`.
+///
+/// Those are uploaded to the GPU once, and only the modified parts are uploaded again afterwards.
+pub struct PointCloud {
+    points: GPUVec<Point3<f32>>,
+    colors: GPUVec<Point3<f32>>,
+    point_size: f32,
+}
+
+impl PointCloud {
+    /// Creates a new point cloud with points of size 1.
+    ///
+    /// If `colors` is empty, all the points are white. Otherwise, it must have the same length as
+    /// `points`.
+    pub fn new(points: Vec<Point3<f32>>, colors: Vec<Point3<f32>>) -> PointCloud {
+        assert!(
+            colors.is_empty() || colors.len() == points.len(),
+            "There must be as many point colors as points."
+        );
+
+        let colors = if colors.is_empty() {
+            vec![Point3::new(1.0, 1.0, 1.0); points.len()]
+        } else {
+            colors
+        };
+
+        PointCloud {
+            points: GPUVec::new(points, BufferType::Array, AllocationType::StaticDraw),
+            colors: GPUVec::new(colors, BufferType::Array, AllocationType::StaticDraw),
+            point_size: 1.0,
+        }
+    }
+
+    /// The number of points.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Whether this point cloud is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The size of the points, in pixels.
+    #[inline]
+    pub fn point_size(&self) -> f32 {
+        self.point_size
+    }
+
+    /// Sets the size of the points, in pixels.
+    #[inline]
+    pub fn set_point_size(&mut self, point_size: f32) {
+        self.point_size = point_size
+    }
+
+    /// Replaces the points starting at the index `start`.
+    pub fn update_points(&mut self, start: usize, points: &[Point3<f32>]) {
+        self.points.update_range(start, points)
+    }
+
+    /// Replaces the colors of the points starting at the index `start`.
+    pub fn update_colors(&mut self, start: usize, colors: &[Point3<f32>]) {
+        self.colors.update_range(start, colors)
+    }
+
+    /// The buffer of points.
+    #[inline]
+    pub fn points_mut(&mut self) -> &mut GPUVec<Point3<f32>> {
+        &mut self.points
+    }
+
+    /// The buffer of point colors.
+    #[inline]
+    pub fn colors_mut(&mut self) -> &mut GPUVec<Point3<f32>> {
+        &mut self.colors
+    }
+}
+
+/// A scene node drawing a point cloud.
+///
+/// The points are relative to the node, which can be manipulated as any other `SceneNode`.
+#[derive(Clone)]
+pub struct PointCloudNode {
+    node: SceneNode,
+    cloud: Rc<RefCell<PointCloud>>,
+}
+
+impl PointCloudNode {
+    #[doc(hidden)]
+    pub fn new(node: SceneNode, cloud: Rc<RefCell<PointCloud>>) -> PointCloudNode {
+        PointCloudNode { node, cloud }
+    }
+
+    /// The scene node drawing the point cloud.
+    #[inline]
+    pub fn node(&self) -> &SceneNode {
+        &self.node
+    }
+
+    /// The scene node drawing the point cloud.
+    #[inline]
+    pub fn node_mut(&mut self) -> &mut SceneNode {
+        &mut self.node
+    }
+
+    /// The point cloud drawn by this node.
+    #[inline]
+    pub fn cloud(&self) -> &Rc<RefCell<PointCloud>> {
+        &self.cloud
+    }
+
+    /// Sets the size of the points, in pixels.
+    pub fn set_point_size(&mut self, point_size: f32) {
+        self.cloud.borrow_mut().set_point_size(point_size)
+    }
+
+    /// Replaces the points starting at the index `start`.
+    ///
+    /// Only the modified points are uploaded again to the GPU.
+    pub fn update_points(&mut self, start: usize, points: &[Point3<f32>]) {
+        self.cloud.borrow_mut().update_points(start, points)
+    }
+
+    /// Replaces the colors of the points starting at the index `start`.
+    ///
+    /// Only the modified colors are uploaded again to the GPU.
+    pub fn update_colors(&mut self, start: usize, colors: &[Point3<f32>]) {
+        self.cloud.borrow_mut().update_colors(start, colors)
+    }
+}
@@ -1,16 +1,48 @@
 use crate::camera::Camera;
-use crate::light::Light;
-use crate::resource::{Material, MaterialManager, Mesh, MeshManager, Texture, TextureManager};
-use crate::scene::Object;
+use crate::context::{render_stats, Context};
+use crate::exporter::obj;
+use crate::light::Lights;
+use crate::loader::gltf::{self, GltfMaterial, GltfNode, GltfPrimitive};
+use crate::loader::mtl::MtlMaterial;
+use crate::loader::obj as obj_loader;
+use crate::loader::stl;
+use crate::renderer::wide_line_renderer::WideLineRenderer;
+use crate::resource::{
+    CubemapTexture, Material, MaterialManager, Mesh, MeshManager, PixelFormat, Texture,
+    TextureFilter, TextureManager, TextureWrapping,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::scene::PendingNode;
+use crate::scene::{
+    num_outlined_objects, restore_default_depth_state, DepthFunc, InstancedNode, Instances, Object,
+    PointCloud, PointCloudNode, WireframeMode,
+};
+use image::DynamicImage;
 use na;
-use na::{Isometry3, Point2, Point3, Translation3, UnitQuaternion, Vector3};
+use na::{Isometry3, Point2, Point3, Translation3, UnitQuaternion, Vector3, Vector4};
 use ncollide3d::procedural;
 use ncollide3d::procedural::TriMesh;
-use std::cell::{Ref, RefCell, RefMut};
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::Result as IoResult;
 use std::mem;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
+#[path = "../error.rs"]
+mod error;
+
+thread_local! {
+    // The layers whose objects are rendered.
+    static VISIBLE_LAYERS: Cell<u32> = const { Cell::new(u32::MAX) };
+}
+
+// Sets the layers whose objects are rendered.
+pub(crate) fn set_visible_layers(mask: u32) {
+    VISIBLE_LAYERS.with(|layers| layers.set(mask))
+}
+
 // XXX: once something like `fn foo(self: Rc<RefCell<SceneNode>>)` is allowed, this extra struct
 // will not be needed any more.
 /// The datas contained by a `SceneNode`.
@@ -20,6 +52,12 @@ pub struct SceneNodeData {
     world_scale: Vector3<f32>,
     world_transform: Isometry3<f32>,
     visible: bool,
+    layer_mask: u32,
+    casts_shadows: bool,
+    billboard: BillboardMode,
+    look_at_target: Option<Point3<f32>>,
+    look_at_axis: Vector3<f32>,
+    look_at_up: Vector3<f32>,
     up_to_date: bool,
     children: Vec<SceneNode>,
     object: Option<Object>,
@@ -27,6 +65,19 @@ pub struct SceneNodeData {
     parent: Option<*const RefCell<SceneNodeData>>,
 }
 
+/// How a scene node is rotated to face the camera.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BillboardMode {
+    /// The node keeps its rotation.
+    None,
+    /// The node is rotated to face the camera eye, with its `y` axis aligned with the up
+    /// direction of the camera.
+    Spherical,
+    /// The node is only rotated around the world `y` axis to face the camera eye, so it stays
+    /// upright.
+    Cylindrical,
+}
+
 /// A node of the scene graph.
 ///
 /// This may represent a group of other nodes, and/or contain an object that can be rendered.
@@ -69,6 +120,11 @@ impl SceneNodeData {
         }
     }
 
+    // The children of this node.
+    pub(crate) fn children(&self) -> &[SceneNode] {
+        &self.children
+    }
+
     /// Whether this node contains an `Object`.
     #[inline]
     pub fn has_object(&self) -> bool {
@@ -82,51 +138,323 @@ impl SceneNodeData {
     }
 
     /// Render the scene graph rooted by this node.
-    pub fn render(&mut self, pass: usize, camera: &mut dyn Camera, light: &Light) {
+    ///
+    /// The transparent objects are drawn after the opaque ones, from the furthest to the closest
+    /// to the camera. They are sorted per object, by the center of their bounding box, so the
+    /// triangles of a single object (or of intersecting objects) may still be drawn in the wrong
+    /// order.
+    pub fn render(&mut self, pass: usize, camera: &mut dyn Camera, lights: &Lights) {
+        if self.visible {
+            self.render_sorted(pass, camera, lights, None)
+        }
+    }
+
+    /// Render the scene graph rooted by this node, skipping the objects lying entirely outside of
+    /// the given frustum planes.
+    ///
+    /// The planes follow the conventions of `Camera::frustum_planes`.
+    pub fn render_with_frustum_culling(
+        &mut self,
+        pass: usize,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        frustum: &[Vector4<f32>; 6],
+    ) {
         if self.visible {
-            self.do_render(&na::one(), &Vector3::from_element(1.0), pass, camera, light)
+            self.render_sorted(pass, camera, lights, Some(frustum))
+        }
+    }
+
+    fn render_sorted(
+        &mut self,
+        pass: usize,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        frustum: Option<&[Vector4<f32>; 6]>,
+    ) {
+        let mut transparent = Vec::new();
+
+        self.do_render(
+            &na::one(),
+            &Vector3::from_element(1.0),
+            pass,
+            camera,
+            lights,
+            frustum,
+            &mut transparent,
+        );
+
+        // The root is not part of `transparent` since we don't have a `SceneNode` for it.
+        let root_is_transparent = self.object.as_ref().is_some_and(|o| o.data().transparent());
+
+        if transparent.is_empty() && !root_is_transparent {
+            return;
+        }
+
+        let eye = camera.eye();
+        let mut sorted: Vec<_> = transparent
+            .into_iter()
+            .map(|node| {
+                let distance = node.data().distance_to(&eye);
+                (distance, Some(node))
+            })
+            .collect();
+
+        if root_is_transparent {
+            sorted.push((self.distance_to(&eye), None));
+        }
+
+        // Back to front.
+        sorted.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+
+        let ctxt = Context::get();
+        verify!(ctxt.enable(Context::BLEND));
+        verify!(ctxt.blend_func_separate(
+            Context::SRC_ALPHA,
+            Context::ONE_MINUS_SRC_ALPHA,
+            Context::ONE,
+            Context::ONE_MINUS_SRC_ALPHA,
+        ));
+        verify!(ctxt.depth_mask(false));
+
+        for (_, node) in sorted {
+            match node {
+                Some(node) => node.data().render_object(pass, camera, lights, frustum),
+                None => self.render_object(pass, camera, lights, frustum),
+            }
         }
+
+        verify!(ctxt.depth_mask(true));
+        verify!(ctxt.disable(Context::BLEND));
     }
 
+    // The transparent objects are not drawn but added to `transparent` instead.
+    #[allow(clippy::too_many_arguments)]
     fn do_render(
         &mut self,
         transform: &Isometry3<f32>,
         scale: &Vector3<f32>,
         pass: usize,
         camera: &mut dyn Camera,
-        light: &Light,
+        lights: &Lights,
+        frustum: Option<&[Vector4<f32>; 6]>,
+        transparent: &mut Vec<SceneNode>,
     ) {
-        if !self.up_to_date {
+        // The rotation of billboards depends on the camera, so it is recomputed at each frame.
+        if !self.up_to_date || self.billboard != BillboardMode::None {
+            let mut world_transform = *transform * self.local_transform;
+
+            if let Some(rotation) = billboard_rotation(
+                self.billboard,
+                &world_transform.translation.vector,
+                &*camera,
+            ) {
+                world_transform.rotation = rotation;
+
+                if world_transform != self.world_transform {
+                    for c in self.children.iter_mut() {
+                        c.data_mut().invalidate()
+                    }
+                }
+            }
+
             self.up_to_date = true;
-            self.world_transform = *transform * self.local_transform;
+            self.world_transform = world_transform;
             self.world_scale = scale.component_mul(&self.local_scale);
         }
 
-        match self.object {
-            Some(ref o) => o.render(
-                &self.world_transform,
-                &self.world_scale,
-                pass,
-                camera,
-                light,
-            ),
-            None => {}
+        if self
+            .object
+            .as_ref()
+            .is_some_and(|o| !o.data().transparent())
+        {
+            self.render_object(pass, camera, lights, frustum)
         }
 
         for c in self.children.iter_mut() {
-            let mut bc = c.data_mut();
-            if bc.visible {
+            let is_transparent = {
+                let mut bc = c.data_mut();
+                if !bc.visible {
+                    continue;
+                }
+
                 bc.do_render(
                     &self.world_transform,
                     &self.world_scale,
                     pass,
                     camera,
-                    light,
+                    lights,
+                    frustum,
+                    transparent,
+                );
+
+                bc.object.as_ref().is_some_and(|o| o.data().transparent())
+            };
+
+            if is_transparent {
+                transparent.push(c.clone())
+            }
+        }
+    }
+
+    // Draws the object of this node, if it is not culled. The world transform must be up to date.
+    fn render_object(
+        &self,
+        pass: usize,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        frustum: Option<&[Vector4<f32>; 6]>,
+    ) {
+        if let Some(ref o) = self.object {
+            if !self.in_visible_layers() {
+                return;
+            }
+
+            let culled = frustum.is_some_and(|planes| {
+                is_object_outside_frustum(o, &self.world_transform, &self.world_scale, planes)
+            });
+
+            render_stats::update(|stats| {
+                if culled {
+                    stats.culled_nodes += 1
+                } else {
+                    stats.visible_nodes += 1
+                }
+            });
+
+            if !culled {
+                // The depth state of the object is restored so it does not leak to the next ones.
+                let default_depth_state = o.data().has_default_depth_state();
+
+                if !default_depth_state {
+                    o.data().apply_depth_state();
+                }
+
+                if o.lods().is_empty() {
+                    o.render(
+                        &self.world_transform,
+                        &self.world_scale,
+                        pass,
+                        camera,
+                        lights,
+                    )
+                } else {
+                    let distance = self.distance_to(&camera.eye());
+                    o.render_at_distance(
+                        &self.world_transform,
+                        &self.world_scale,
+                        pass,
+                        camera,
+                        lights,
+                        distance,
+                    )
+                }
+
+                if !default_depth_state {
+                    restore_default_depth_state(o.data().transparent());
+                }
+            }
+        }
+    }
+
+    // Draws the outlines of the visible objects of the scene graph rooted by this node. This must
+    // be called after `render`, so their world transforms are up to date.
+    pub(crate) fn render_outlines(
+        &self,
+        pass: usize,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        lines: &mut WideLineRenderer,
+    ) {
+        // NOTE: WebGL render targets have no stencil buffer.
+        if num_outlined_objects() == 0 || cfg!(target_arch = "wasm32") {
+            return;
+        }
+
+        let ctxt = Context::get();
+        verify!(ctxt.stencil_mask(0xFF));
+        verify!(ctxt.clear_stencil(0));
+        verify!(ctxt.clear(Context::STENCIL_BUFFER_BIT));
+        verify!(ctxt.enable(Context::STENCIL_TEST));
+
+        // Marks the pixels covered by the outlined objects, including their occluded parts. The
+        // stencil test always fails, so the colors and depths are left untouched. These draws
+        // are not counted in the statistics of the frame, since they draw nothing visible.
+        verify!(ctxt.stencil_func(Context::NEVER, 1, 0xFF));
+        verify!(ctxt.stencil_op(Context::REPLACE, Context::KEEP, Context::KEEP));
+
+        render_stats::uncounted(|| {
+            self.apply_to_outlined(&mut |node, o, _| {
+                o.render(
+                    &node.world_transform,
+                    &node.world_scale,
+                    pass,
+                    camera,
+                    lights,
+                )
+            })
+        });
+
+        // The edges of the triangles are thickened by the width of the outline, their parts
+        // outside of the marked pixels forming the outline around the silhouette.
+        verify!(ctxt.stencil_func(Context::NOTEQUAL, 1, 0xFF));
+        verify!(ctxt.stencil_op(Context::KEEP, Context::KEEP, Context::KEEP));
+        verify!(ctxt.disable(Context::DEPTH_TEST));
+
+        self.apply_to_outlined(&mut |node, o, (color, width)| {
+            let mut mesh = o.mesh().borrow_mut();
+
+            if WideLineRenderer::prepare_edges(&mut mesh, WireframeMode::Triangles) {
+                lines.render_edges(
+                    pass,
+                    camera,
+                    &mut mesh,
+                    WireframeMode::Triangles,
+                    &color,
+                    &node.world_transform,
+                    &node.world_scale,
+                    width * 2.0,
+                    true,
                 )
             }
+        });
+
+        verify!(ctxt.enable(Context::DEPTH_TEST));
+        verify!(ctxt.disable(Context::STENCIL_TEST));
+    }
+
+    // Calls `f` with the visible outlined objects of the scene graph rooted by this node.
+    fn apply_to_outlined<F: FnMut(&SceneNodeData, &Object, (Point3<f32>, f32))>(&self, f: &mut F) {
+        if !self.visible {
+            return;
+        }
+
+        if let Some(ref o) = self.object {
+            if let Some(outline) = o.data().outline() {
+                if self.in_visible_layers() {
+                    f(self, o, outline)
+                }
+            }
+        }
+
+        for c in self.children.iter() {
+            c.data().apply_to_outlined(f)
         }
     }
 
+    // The distance between `point` and the center of the bounding box of the object of this node.
+    // The world transform must be up to date.
+    fn distance_to(&self, point: &Point3<f32>) -> f32 {
+        let center = self
+            .object
+            .as_ref()
+            .and_then(|o| object_world_aabb(o, &self.world_transform, &self.world_scale))
+            .map(|(mins, maxs)| na::center(&mins, &maxs))
+            .unwrap_or_else(|| Point3::from(self.world_transform.translation.vector));
+
+        na::distance(&center, point)
+    }
+
     /// A reference to the object possibly contained by this node.
     #[inline]
     pub fn object(&self) -> Option<&Object> {
@@ -202,6 +530,20 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.set_lines_color(color))
     }
 
+    /// Enables or disables the drawing of the edges of the objects contained by this node and its
+    /// children with the given color, on top of their surfaces.
+    #[inline]
+    pub fn set_wireframe_overlay(&mut self, color: Point3<f32>, enabled: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_wireframe_overlay(color, enabled))
+    }
+
+    /// Sets which edges of the objects contained by this node and its children are drawn as
+    /// lines.
+    #[inline]
+    pub fn set_wireframe_mode(&mut self, mode: WireframeMode) {
+        self.apply_to_objects_mut(&mut |o| o.set_wireframe_mode(mode))
+    }
+
     /// Sets the size of the points drawn for the objects contained by this node and its children.
     #[inline]
     pub fn set_points_size(&mut self, size: f32) {
@@ -222,6 +564,59 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.enable_backface_culling(active))
     }
 
+    /// Makes the objects contained by this node and its children use the backface culling
+    /// default set by `Window::set_backface_culling`.
+    #[inline]
+    pub fn reset_backface_culling(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.reset_backface_culling())
+    }
+
+    /// Enables or disables the depth test for the objects contained by this node and its
+    /// children.
+    #[inline]
+    pub fn set_depth_test(&mut self, active: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_depth_test(active))
+    }
+
+    /// Sets whether the objects contained by this node and its children write their depth.
+    #[inline]
+    pub fn set_depth_write(&mut self, active: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_depth_write(active))
+    }
+
+    /// Makes the objects contained by this node and its children write their depth only if they
+    /// are opaque.
+    #[inline]
+    pub fn reset_depth_write(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.reset_depth_write())
+    }
+
+    /// Sets the comparison used by the depth test of the objects contained by this node and its
+    /// children.
+    #[inline]
+    pub fn set_depth_func(&mut self, func: DepthFunc) {
+        self.apply_to_objects_mut(&mut |o| o.set_depth_func(func))
+    }
+
+    /// Offsets the depth of the surface of the objects contained by this node and its children.
+    #[inline]
+    pub fn set_polygon_offset(&mut self, factor: f32, units: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_polygon_offset(factor, units))
+    }
+
+    /// Removes the polygon offset of the objects contained by this node and its children.
+    #[inline]
+    pub fn unset_polygon_offset(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.unset_polygon_offset())
+    }
+
+    /// Sets the color and width in pixels of the outline drawn around the objects contained by
+    /// this node and its children, or removes it if `None`.
+    #[inline]
+    pub fn set_outline(&mut self, outline: Option<(Point3<f32>, f32)>) {
+        self.apply_to_objects_mut(&mut |o| o.set_outline(outline))
+    }
+
     /// Mutably accesses the vertices of the objects contained by this node and its children.
     ///
     /// The provided closure is called once per object.
@@ -245,6 +640,13 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.recompute_normals())
     }
 
+    /// Recomputes the normals of the meshes of the objects contained by this node and its
+    /// children so each face is shaded flat.
+    #[inline]
+    pub fn recompute_normals_flat(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.recompute_normals_flat())
+    }
+
     /// Mutably accesses the normals of the objects contained by this node and its children.
     ///
     /// The provided closure is called once per object.
@@ -308,6 +710,124 @@ impl SceneNodeData {
         self.visible = visible;
     }
 
+    /// The layers this node belongs to, as a bit mask.
+    #[inline]
+    pub fn layer_mask(&self) -> u32 {
+        self.layer_mask
+    }
+
+    /// Sets the layers this node belongs to, as a bit mask.
+    ///
+    /// The object of this node is rendered only if this mask intersects the visible layers of the
+    /// window, set by `Window::set_visible_layers`. The children of this node are not affected.
+    /// Every node belongs to the first layer, i.e., has a mask of `1`, by default.
+    #[inline]
+    pub fn set_layer_mask(&mut self, mask: u32) {
+        self.layer_mask = mask;
+    }
+
+    // Whether the object of this node belongs to one of the visible layers.
+    fn in_visible_layers(&self) -> bool {
+        VISIBLE_LAYERS.with(|layers| layers.get() & self.layer_mask != 0)
+    }
+
+    /// Whether the object of this node casts shadows.
+    #[inline]
+    pub fn casts_shadows(&self) -> bool {
+        self.casts_shadows
+    }
+
+    /// Sets whether the object of this node casts shadows.
+    ///
+    /// This does not affect the children of this node, and the object still receives shadows
+    /// from other objects.
+    #[inline]
+    pub fn set_casts_shadows(&mut self, casts_shadows: bool) {
+        self.casts_shadows = casts_shadows;
+    }
+
+    /// How this node is rotated to face the camera.
+    #[inline]
+    pub fn billboard(&self) -> BillboardMode {
+        self.billboard
+    }
+
+    /// Sets how this node is rotated to face the camera.
+    ///
+    /// While this is not `BillboardMode::None`, the rotation of the world transformation of this
+    /// node is replaced at each frame so its local `z` axis points toward the camera. Its world
+    /// translation and scale are unaffected, and its children inherit the new rotation.
+    #[inline]
+    pub fn set_billboard(&mut self, mode: BillboardMode) {
+        self.invalidate();
+        self.billboard = mode;
+    }
+
+    /// The world-space point this node is aimed at, if any.
+    #[inline]
+    pub fn look_at_target(&self) -> Option<Point3<f32>> {
+        self.look_at_target
+    }
+
+    /// Sets the world-space point this node is aimed at.
+    ///
+    /// While this is not `None`, the local rotation of this node is recomputed at each frame,
+    /// before the scene is rendered, so its aimed axis points toward `target` in world-space
+    /// whatever the transformations of its parents. Setting it to `None` keeps the last computed
+    /// rotation and gives back control of the local rotation.
+    #[inline]
+    pub fn set_look_at_target(&mut self, target: Option<Point3<f32>>) {
+        self.look_at_target = target;
+    }
+
+    /// The local axis pointed toward the look-at target, and the local axis kept as close as
+    /// possible to the world `up` vector.
+    #[inline]
+    pub fn look_at_axes(&self) -> (Vector3<f32>, Vector3<f32>) {
+        (self.look_at_axis, self.look_at_up)
+    }
+
+    /// Sets the local axis pointed toward the look-at target, and the world up vector.
+    ///
+    /// The node is rotated so that `axis` points toward the target, then around it so that its
+    /// local `y` axis is as close as possible to `up`, in world-space. These default to the `z` and
+    /// `y` axes.
+    #[inline]
+    pub fn set_look_at_axes(&mut self, axis: Vector3<f32>, up: Vector3<f32>) {
+        self.look_at_axis = axis;
+        self.look_at_up = up;
+    }
+
+    /// Recomputes the local rotations of the nodes of this scene graph that have a look-at target.
+    pub(crate) fn update_look_at_targets(&mut self) {
+        let (transform, _) = self.parent_world_transformation_and_scale();
+        self.do_update_look_at_targets(&transform)
+    }
+
+    fn do_update_look_at_targets(&mut self, transform: &Isometry3<f32>) {
+        if let Some(target) = self.look_at_target {
+            let position = transform * Point3::from(self.local_transform.translation.vector);
+
+            if let Some(rotation) =
+                look_at_rotation(&position, &target, &self.look_at_axis, &self.look_at_up)
+            {
+                let rotation = transform.rotation.inverse() * rotation;
+
+                if rotation != self.local_transform.rotation {
+                    self.invalidate();
+                    self.local_transform.rotation = rotation;
+                }
+            }
+        }
+
+        // NOTE: this must match the transformations computed by `do_render`.
+        let world_transform = *transform * self.local_transform;
+
+        for c in self.children.iter_mut() {
+            c.data_mut().do_update_look_at_targets(&world_transform)
+        }
+    }
+
     /// Sets the color of the objects contained by this node and its children.
     ///
     /// Colors components must be on the range `[0.0, 1.0]`.
@@ -316,6 +836,79 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.set_color(r, g, b))
     }
 
+    /// Sets the opacity of the objects contained by this node and its children.
+    ///
+    /// This has no effect on the objects which are not transparent.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_alpha(alpha))
+    }
+
+    /// Sets whether the objects contained by this node and its children are drawn with alpha
+    /// blending, after the opaque objects.
+    #[inline]
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_transparent(transparent))
+    }
+
+    /// Sets the per-vertex colors of the objects contained by this node and its children.
+    ///
+    /// Those colors are used by the `VertexColorMaterial`. The objects whose mesh is shared with
+    /// other objects are given their own mesh first, see `Object::set_vertex_colors`. Panics if
+    /// the number of colors does not match the number of vertices of one of the objects.
+    #[inline]
+    pub fn set_vertex_colors(&mut self, colors: Vec<Point3<f32>>) {
+        self.apply_to_objects_mut(&mut |o| o.set_vertex_colors(colors.clone()))
+    }
+
+    /// Sets the color emitted by the objects contained by this node and its children.
+    ///
+    /// This color is added to the lit color of the objects, so it is visible even in the dark.
+    /// Colors components must be on the range `[0.0, 1.0]`. Defaults to black.
+    #[inline]
+    pub fn set_emissive_color(&mut self, color: Point3<f32>) {
+        self.apply_to_objects_mut(&mut |o| o.set_emissive_color(color))
+    }
+
+    /// Sets the texture multiplied by the emissive color of the objects contained by this node
+    /// and its children.
+    #[inline]
+    pub fn set_emissive_texture(&mut self, texture: Rc<Texture>) {
+        self.apply_to_objects_mut(&mut |o| o.set_emissive_texture(Some(texture.clone())))
+    }
+
+    /// Removes the emissive texture of the objects contained by this node and its children.
+    #[inline]
+    pub fn unset_emissive_texture(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.set_emissive_texture(None))
+    }
+
+    /// Sets the environment reflected by the objects contained by this node and its children.
+    ///
+    /// The cube map is mixed with the lit color of the objects depending on their reflectivity
+    /// and the viewing angle. The cube map of the skybox can be reused so the objects reflect the
+    /// sky.
+    #[inline]
+    pub fn set_reflection_map(&mut self, map: Rc<CubemapTexture>) {
+        self.apply_to_objects_mut(&mut |o| o.set_reflection_map(map.clone()))
+    }
+
+    /// Removes the environment reflected by the objects contained by this node and its children.
+    #[inline]
+    pub fn unset_reflection_map(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.unset_reflection_map())
+    }
+
+    /// Sets the fraction of the environment reflected by the objects contained by this node and
+    /// its children when looking at them head-on.
+    ///
+    /// The reflection increases toward `1.0` at grazing angles. Must be on the range
+    /// `[0.0, 1.0]`. Defaults to `0.5`.
+    #[inline]
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_reflectivity(reflectivity))
+    }
+
     /// Sets the texture of the objects contained by this node and its children.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
@@ -346,6 +939,36 @@ impl SceneNodeData {
         self.set_texture(texture)
     }
 
+    /// Sets the texture of the objects contained by this node and its children.
+    ///
+    /// The texture is uploaded from raw RGBA pixels, given row by row starting with the top row.
+    /// It is not registered by the global `TextureManager`, so each call creates a new texture.
+    ///
+    /// # Failure
+    /// Panics if `data` does not contain exactly `width * height * 4` bytes.
+    pub fn set_texture_from_rgba(&mut self, data: &[u8], width: u32, height: u32) {
+        let texture = TextureManager::get_global_manager(|tm| {
+            tm.load_texture_from_pixels(data, width, height, PixelFormat::Rgba)
+        });
+
+        self.set_texture(texture)
+    }
+
+    /// Sets the filters of the textures of the objects contained by this node and its children.
+    ///
+    /// See `Texture::set_filtering` for details. The textures are modified, so this also affects
+    /// the other objects sharing them.
+    pub fn set_texture_filtering(&self, min: TextureFilter, mag: TextureFilter) {
+        self.apply_to_objects(&mut |o| o.data().texture().set_filtering(min, mag))
+    }
+
+    /// Sets the wrapping of the textures of the objects contained by this node and its children.
+    ///
+    /// The textures are modified, so this also affects the other objects sharing them.
+    pub fn set_texture_wrap(&self, s: TextureWrapping, t: TextureWrapping) {
+        self.apply_to_objects(&mut |o| o.data().texture().set_wrap(s, t))
+    }
+
     /// Sets the texture of the objects contained by this node and its children.
     ///
     /// The texture must already have been registered as `name`.
@@ -365,28 +988,72 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.set_texture(texture.clone()))
     }
 
-    /// Applies a closure to each object contained by this node and its children.
-    #[inline]
-    pub fn apply_to_objects_mut<F: FnMut(&mut Object)>(&mut self, f: &mut F) {
-        match self.object {
-            Some(ref mut o) => f(o),
-            None => {}
-        }
+    /// Replaces the mesh of the objects contained by this node and its children.
+    pub fn set_mesh(&mut self, mesh: Rc<RefCell<Mesh>>) {
+        self.apply_to_objects_mut(&mut |o| o.set_mesh(mesh.clone()))
+    }
 
-        for c in self.children.iter_mut() {
-            c.data_mut().apply_to_objects_mut(f)
-        }
+    /// Sets the levels of detail of the objects contained by this node and its children.
+    ///
+    /// See `Object::set_lods` for details.
+    pub fn set_lods(&mut self, lods: &[(f32, Rc<RefCell<Mesh>>)]) {
+        self.apply_to_objects_mut(&mut |o| o.set_lods(lods))
     }
 
-    /// Applies a closure to each object contained by this node and its children.
-    #[inline]
-    pub fn apply_to_objects<F: FnMut(&Object)>(&self, f: &mut F) {
-        match self.object {
-            Some(ref o) => f(o),
-            None => {}
-        }
+    /// Replaces the mesh of each object contained by this node and its children by a smoother
+    /// one obtained with `iterations` steps of Loop subdivision.
+    pub fn subdivide(&mut self, iterations: u32) {
+        self.apply_to_objects_mut(&mut |o| o.subdivide(iterations))
+    }
 
-        for c in self.children.iter() {
+    /// Sets the tangent-space normal map of the objects contained by this node and its children.
+    ///
+    /// The tangents of the meshes are computed if they are not already available.
+    pub fn set_normal_map(&mut self, texture: Rc<Texture>) {
+        self.apply_to_objects_mut(&mut |o| o.set_normal_map(texture.clone()))
+    }
+
+    /// Sets the normal map of the objects contained by this node and its children.
+    ///
+    /// The texture is loaded from a file and registered by the global `TextureManager`.
+    ///
+    /// # Arguments
+    ///   * `path` - relative path of the texture on the disk
+    ///   * `name` - &str identifier to store this texture under
+    #[inline]
+    pub fn set_normal_map_from_file(&mut self, path: &Path, name: &str) {
+        let texture = TextureManager::get_global_manager(|tm| tm.add(path, name));
+
+        self.set_normal_map(texture)
+    }
+
+    /// Removes the normal map of the objects contained by this node and its children.
+    pub fn unset_normal_map(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.unset_normal_map())
+    }
+
+    /// Applies a closure to each object contained by this node and its children.
+    #[inline]
+    pub fn apply_to_objects_mut<F: FnMut(&mut Object)>(&mut self, f: &mut F) {
+        match self.object {
+            Some(ref mut o) => f(o),
+            None => {}
+        }
+
+        for c in self.children.iter_mut() {
+            c.data_mut().apply_to_objects_mut(f)
+        }
+    }
+
+    /// Applies a closure to each object contained by this node and its children.
+    #[inline]
+    pub fn apply_to_objects<F: FnMut(&Object)>(&self, f: &mut F) {
+        match self.object {
+            Some(ref o) => f(o),
+            None => {}
+        }
+
+        for c in self.children.iter() {
             c.data().apply_to_objects(f)
         }
     }
@@ -457,6 +1124,124 @@ impl SceneNodeData {
         self.local_transform.inverse()
     }
 
+    /// The world-space axis-aligned bounding box of this node and all its descendants.
+    ///
+    /// Returns the `(mins, maxs)` corners, or `None` if the subtree contains no geometry. This is
+    /// recomputed from the mesh vertices at each call.
+    pub fn bounding_box(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        let (transform, scale) = self.parent_world_transformation_and_scale();
+        let mut aabb = None;
+
+        self.do_bounding_box(&transform, &scale, &mut aabb);
+
+        aabb
+    }
+
+    /// The axis-aligned bounding box of this node and all its descendants, ignoring the
+    /// transformations of its parents.
+    ///
+    /// Returns the `(mins, maxs)` corners, or `None` if the subtree contains no geometry. This is
+    /// recomputed from the mesh vertices at each call.
+    pub fn local_bounding_box(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        let mut aabb = None;
+
+        self.do_bounding_box(&na::one(), &Vector3::from_element(1.0), &mut aabb);
+
+        aabb
+    }
+
+    fn do_bounding_box(
+        &self,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        aabb: &mut Option<(Point3<f32>, Point3<f32>)>,
+    ) {
+        // NOTE: this must match the transformations computed by `do_render`.
+        let world_transform = *transform * self.local_transform;
+        let world_scale = scale.component_mul(&self.local_scale);
+
+        if let Some(ref object) = self.object {
+            let mesh = object.mesh().borrow();
+            let coords = mesh.coords().read().unwrap();
+
+            for pt in coords.data().iter().flat_map(|c| c.iter()) {
+                let pt = world_transform * Point3::from(pt.coords.component_mul(&world_scale));
+
+                *aabb = match *aabb {
+                    Some((mins, maxs)) => Some((mins.inf(&pt), maxs.sup(&pt))),
+                    None => Some((pt, pt)),
+                };
+            }
+        }
+
+        for c in self.children.iter() {
+            c.data()
+                .do_bounding_box(&world_transform, &world_scale, aabb)
+        }
+    }
+
+    /// Calls `f` with each visible object casting shadows of the scene graph rooted by this node,
+    /// together with its world transformation and scale.
+    pub fn apply_to_shadow_casters<F: FnMut(&Object, &Isometry3<f32>, &Vector3<f32>)>(
+        &self,
+        f: &mut F,
+    ) {
+        if self.visible {
+            self.do_apply_to_shadow_casters(&na::one(), &Vector3::from_element(1.0), f)
+        }
+    }
+
+    fn do_apply_to_shadow_casters<F: FnMut(&Object, &Isometry3<f32>, &Vector3<f32>)>(
+        &self,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        f: &mut F,
+    ) {
+        // NOTE: this must match the transformations computed by `do_render`.
+        let world_transform = *transform * self.local_transform;
+        let world_scale = scale.component_mul(&self.local_scale);
+
+        if let Some(ref object) = self.object {
+            if self.casts_shadows && self.in_visible_layers() {
+                f(object, &world_transform, &world_scale)
+            }
+        }
+
+        for c in self.children.iter() {
+            let bc = c.data();
+
+            if bc.visible {
+                bc.do_apply_to_shadow_casters(&world_transform, &world_scale, f)
+            }
+        }
+    }
+
+    // Accumulates the transformations of the parents of this node, the same way `do_render` does.
+    fn parent_world_transformation_and_scale(&self) -> (Isometry3<f32>, Vector3<f32>) {
+        let mut transform: Isometry3<f32> = na::one();
+        let mut scale = Vector3::from_element(1.0);
+        let mut parent = self.parent;
+
+        while let Some(p) = parent {
+            let dp = unsafe { (*p).borrow() };
+
+            transform = dp.local_transform * transform;
+            scale.component_mul_assign(&dp.local_scale);
+            parent = dp.parent;
+        }
+
+        (transform, scale)
+    }
+
+    // Unlike `world_transformation`, this does not rely on the cached world transformation.
+    pub(crate) fn world_transformation_and_scale(&self) -> (Isometry3<f32>, Vector3<f32>) {
+        let (transform, scale) = self.parent_world_transformation_and_scale();
+        (
+            transform * self.local_transform,
+            scale.component_mul(&self.local_scale),
+        )
+    }
+
     /// Appends a transformation to this node local transformation.
     #[inline]
     pub fn append_transformation(&mut self, t: &Isometry3<f32>) {
@@ -601,6 +1386,12 @@ impl SceneNode {
             world_transform: local_transform,
             world_scale: local_scale,
             visible: true,
+            layer_mask: 1,
+            casts_shadows: true,
+            billboard: BillboardMode::None,
+            look_at_target: None,
+            look_at_axis: Vector3::z(),
+            look_at_up: Vector3::y(),
             up_to_date: false,
             children: Vec::new(),
             object: object,
@@ -624,6 +1415,118 @@ impl SceneNode {
         self.data_mut().parent = None
     }
 
+    /// Iterates through the descendants of this node, excluding itself, in depth-first order.
+    ///
+    /// The nodes are yielded as new handles, so they can be modified during the iteration with
+    /// `data_mut`. Adding or removing children to a node already yielded does not affect the
+    /// iteration. No recursion is involved, so this works on arbitrarily deep trees.
+    pub fn descendants(&self) -> Descendants {
+        let mut stack: Vec<SceneNode> = self.data().children.clone();
+        stack.reverse();
+
+        Descendants { stack }
+    }
+
+    /// Calls `f` on this node and all its descendants, in depth-first order, together with their
+    /// world transformation.
+    ///
+    /// No recursion is involved, so this works on arbitrarily deep trees.
+    pub fn visit<F: FnMut(&SceneNode, &Isometry3<f32>)>(&self, mut f: F) {
+        let (root_transform, _) = self.data().world_transformation_and_scale();
+        let mut stack = vec![(self.clone(), root_transform)];
+
+        while let Some((node, transform)) = stack.pop() {
+            f(&node, &transform);
+
+            let data = node.data();
+            for c in data.children.iter().rev() {
+                let local_transform = c.data().local_transform;
+                stack.push((c.clone(), transform * local_transform));
+            }
+        }
+    }
+
+    /// Writes the visible objects of the subtree rooted by this node to an obj file and its mtl
+    /// file.
+    ///
+    /// See `exporter::obj::write` for details.
+    pub fn write_obj(&self, obj_path: &Path, mtl_path: &Path) -> IoResult<()> {
+        obj::write(self, obj_path, mtl_path)
+    }
+
+    /// Creates a copy of the subtree rooted by this node.
+    ///
+    /// The copy is not attached to any parent. Its nodes have the same local transformations,
+    /// colors, and rendering options as the original, but can be modified independently. The
+    /// meshes, materials, and textures are shared with the original instead of being copied:
+    /// modifying the vertices of an object of the copy also modifies the original, unless its mesh
+    /// is replaced first with `set_mesh`. Likewise, the instances of instanced nodes and the points
+    /// of point clouds are shared. Other user-defined data are not copied.
+    pub fn deep_clone(&self) -> SceneNode {
+        let data = self.data();
+        let mut node = SceneNode::new(
+            data.local_scale,
+            data.local_transform,
+            data.object.as_ref().map(|o| o.clone_sharing_resources()),
+        );
+
+        {
+            let mut node_data = node.data_mut();
+            node_data.visible = data.visible;
+            node_data.layer_mask = data.layer_mask;
+            node_data.casts_shadows = data.casts_shadows;
+            node_data.billboard = data.billboard;
+            node_data.look_at_target = data.look_at_target;
+            node_data.look_at_axis = data.look_at_axis;
+            node_data.look_at_up = data.look_at_up;
+        }
+
+        for c in data.children.iter() {
+            node.add_child(c.deep_clone())
+        }
+
+        node
+    }
+
+    /// Moves this node from its current parent, if any, to the children of `new_parent`.
+    ///
+    /// The local transformation and scale of this node are modified so that its world
+    /// transformation and scale are unchanged. Use `window.scene_mut()` as `new_parent` to move
+    /// this node to the root of the scene.
+    ///
+    /// Returns an error, and leaves the scene graph unchanged, if `new_parent` is this node or one
+    /// of its descendants.
+    pub fn reparent(&mut self, new_parent: &mut SceneNode) -> Result<(), &'static str> {
+        let this = &*self.data as *const RefCell<SceneNodeData>;
+        let mut ancestor = Some(&*new_parent.data as *const RefCell<SceneNodeData>);
+
+        while let Some(a) = ancestor {
+            if a == this {
+                return Err("A node cannot be moved under itself or one of its descendants.");
+            }
+
+            ancestor = unsafe { (*a).borrow().parent };
+        }
+
+        let (transform, scale) = self.data().world_transformation_and_scale();
+        let (parent_transform, parent_scale) = new_parent.data().world_transformation_and_scale();
+
+        self.unlink();
+
+        {
+            let mut data = self.data_mut();
+            data.invalidate();
+            data.local_transform = parent_transform.inverse() * transform;
+            // A null parent scale cannot be compensated.
+            data.local_scale =
+                scale.zip_map(&parent_scale, |s, p| if p != 0.0 { s / p } else { s });
+        }
+
+        new_parent.add_child(self.clone());
+
+        Ok(())
+    }
+
     /// The data of this scene node.
     pub fn data(&self) -> Ref<SceneNodeData> {
         self.data.borrow()
@@ -700,6 +1603,50 @@ impl SceneNode {
         res.expect("Unable to load the default sphere geometry.")
     }
 
+    /// Adds a sphere made of almost equal triangles to this node children. The sphere is
+    /// initially centered at (0, 0, 0).
+    ///
+    /// See `procedural::icosphere` for the number of triangles.
+    ///
+    /// # Arguments
+    /// * `radius` - the sphere radius
+    /// * `subdivisions` - the number of times the faces of the initial icosahedron are split. It
+    ///   is clamped to `procedural::MAX_ICOSPHERE_SUBDIVISIONS`.
+    pub fn add_icosphere(&mut self, radius: f32, subdivisions: u32) -> SceneNode {
+        self.add_trimesh(
+            crate::procedural::icosphere(radius, subdivisions),
+            Vector3::from_element(1.0),
+        )
+    }
+
+    /// Adds a torus to this node children. The torus is initially centered at (0, 0, 0) and has
+    /// its axis aligned with the `y` axis.
+    ///
+    /// See `procedural::torus` for details.
+    ///
+    /// `major_segments` and `minor_segments` are clamped to at least 3.
+    pub fn add_torus(
+        &mut self,
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    ) -> SceneNode {
+        self.add_trimesh(
+            crate::procedural::torus(major_radius, minor_radius, major_segments, minor_segments),
+            Vector3::from_element(1.0),
+        )
+    }
+
+    /// Adds the convex hull of a set of points to this node children.
+    ///
+    /// See `procedural::convex_hull` for details.
+    pub fn add_convex_hull(&mut self, points: &[Point3<f32>]) -> Result<SceneNode, &'static str> {
+        let hull = crate::procedural::convex_hull(points)?;
+
+        Ok(self.add_trimesh(hull, Vector3::from_element(1.0)))
+    }
+
     /// Adds a cone to the scene. The cone is initially centered at (0, 0, 0) and points toward the
     /// positive `y` axis.
     ///
@@ -712,6 +1659,27 @@ impl SceneNode {
         res.expect("Unable to load the default cone geometry.")
     }
 
+    /// Adds a cone with the given tessellation to this node children. The cone is initially
+    /// centered at (0, 0, 0) and points toward the positive `y` axis.
+    ///
+    /// `add_cone` uses 50 radial segments and 1 height segment. See `procedural::cone` for
+    /// details.
+    ///
+    /// # Failure
+    /// Fails if `radial_segments` is smaller than 3 or `height_segments` is 0.
+    pub fn add_cone_with_subdivisions(
+        &mut self,
+        r: f32,
+        h: f32,
+        radial_segments: u32,
+        height_segments: u32,
+    ) -> SceneNode {
+        self.add_trimesh(
+            crate::procedural::cone(r, h, radial_segments, height_segments),
+            Vector3::from_element(1.0),
+        )
+    }
+
     /// Adds a cylinder to this node children. The cylinder is initially centered at (0, 0, 0)
     /// and has its principal axis aligned with the `y` axis.
     ///
@@ -724,6 +1692,27 @@ impl SceneNode {
         res.expect("Unable to load the default cylinder geometry.")
     }
 
+    /// Adds a cylinder with the given tessellation to this node children. The cylinder is
+    /// initially centered at (0, 0, 0) and has its principal axis aligned with the `y` axis.
+    ///
+    /// `add_cylinder` uses 50 radial segments and 1 height segment. See `procedural::cylinder`
+    /// for details.
+    ///
+    /// # Failure
+    /// Fails if `radial_segments` is smaller than 3 or `height_segments` is 0.
+    pub fn add_cylinder_with_subdivisions(
+        &mut self,
+        r: f32,
+        h: f32,
+        radial_segments: u32,
+        height_segments: u32,
+    ) -> SceneNode {
+        self.add_trimesh(
+            crate::procedural::cylinder(r, h, radial_segments, height_segments),
+            Vector3::from_element(1.0),
+        )
+    }
+
     /// Adds a capsule to this node children. The capsule is initially centered at (0, 0, 0) and
     /// has its principal axis aligned with the `y` axis.
     ///
@@ -737,6 +1726,34 @@ impl SceneNode {
         )
     }
 
+    /// Adds a capsule with the given tessellation to this node children. The capsule is
+    /// initially centered at (0, 0, 0) and has its principal axis aligned with the `y` axis.
+    ///
+    /// See `procedural::capsule` for details.
+    ///
+    /// # Arguments
+    /// * `h` - the height of the cylinder between the caps
+    /// * `r` - the capsule caps radius
+    /// * `radial_segments` - the number of subdivisions around the `y` axis
+    /// * `height_segments` - the number of subdivisions of the cylinder along the `y` axis
+    /// * `cap_segments` - the number of subdivisions of each cap from its pole to the cylinder
+    ///
+    /// # Failure
+    /// Fails if `radial_segments` is smaller than 3, or `height_segments` or `cap_segments` is 0.
+    pub fn add_capsule_with_subdivisions(
+        &mut self,
+        r: f32,
+        h: f32,
+        radial_segments: u32,
+        height_segments: u32,
+        cap_segments: u32,
+    ) -> SceneNode {
+        self.add_trimesh(
+            crate::procedural::capsule(r, h, radial_segments, height_segments, cap_segments),
+            Vector3::from_element(1.0),
+        )
+    }
+
     /// Adds a double-sided quad to this node children. The quad is initially centered at (0, 0,
     /// 0). The quad itself is composed of a user-defined number of triangles regularly spaced on a
     /// grid. This is the main way to draw height maps.
@@ -774,6 +1791,132 @@ impl SceneNode {
         node
     }
 
+    /// Adds a terrain built from a grid of `nx` by `nz` heights to this node children.
+    ///
+    /// See `procedural::heightmap` for details, including the order of the vertices. A mesh
+    /// cannot have more than 65536 vertices so, past that, the terrain is split by
+    /// `procedural::heightmap_tiles` and the returned node is a group with one child per tile.
+    pub fn add_heightmap(
+        &mut self,
+        heights: &[f32],
+        nx: usize,
+        nz: usize,
+        scale: Vector3<f32>,
+    ) -> SceneNode {
+        if nx * nz <= u16::MAX as usize + 1 {
+            return self.add_trimesh(
+                crate::procedural::heightmap(heights, nx, nz, scale),
+                Vector3::from_element(1.0),
+            );
+        }
+
+        let mut group = self.add_group();
+
+        for tile in crate::procedural::heightmap_tiles(heights, nx, nz, scale) {
+            let _ = group.add_trimesh(tile, Vector3::from_element(1.0));
+        }
+
+        group
+    }
+
+    /// Adds a terrain built from a grayscale image to this node children.
+    ///
+    /// Each pixel gives the height of one vertex, black being 0 and white being 1 before
+    /// `scale.y` is applied. The rows of the image are laid out along the `z` axis. See
+    /// `procedural::heightmap` for details.
+    ///
+    /// Images of more than 65536 pixels, e.g., larger than 256 by 256, are split into several
+    /// meshes as described by `add_heightmap`.
+    pub fn add_heightmap_from_image(&mut self, path: &Path, scale: Vector3<f32>) -> SceneNode {
+        let image = image::open(path)
+            .unwrap_or_else(|e| panic!("Unable to load the image {}: {}", path.display(), e))
+            .into_luma16();
+        let (nx, nz) = image.dimensions();
+        let heights: Vec<f32> = image
+            .pixels()
+            .map(|p| p[0] as f32 / u16::MAX as f32)
+            .collect();
+
+        self.add_heightmap(&heights, nx as usize, nz as usize, scale)
+    }
+
+    /// Adds a grid of lines on the `xz` plane, centered at (0, 0, 0), to this node children.
+    ///
+    /// The grid is made of `subdivisions` squares along each axis, and its lines through the
+    /// origin along the `x` and `z` axis are drawn in red and blue. The lines are uploaded once
+    /// and drawn with the other objects of the scene, so the returned node can be transformed
+    /// and hidden like any other node. Grids with more than 16384 subdivisions are split into
+    /// several meshes, which are all children of the returned node.
+    ///
+    /// # Arguments
+    /// * `size` - the extent of the grid along the `x` and `z` axis.
+    /// * `subdivisions` - the number of squares along each axis. `0` is treated as `1`.
+    /// * `color` - the color of the lines not on an axis.
+    pub fn add_grid(&mut self, size: f32, subdivisions: u32, color: Point3<f32>) -> SceneNode {
+        let mut grid = self.add_group();
+        let n = subdivisions.max(1);
+        let half = size / 2.0;
+        let mut segments = Vec::new();
+
+        for i in 0..=n {
+            // The lines on the axis are drawn separately.
+            if 2 * i == n {
+                continue;
+            }
+
+            let t = -half + size * i as f32 / n as f32;
+            segments.push((Point3::new(-half, 0.0, t), Point3::new(half, 0.0, t)));
+            segments.push((Point3::new(t, 0.0, -half), Point3::new(t, 0.0, half)));
+        }
+
+        let x_axis = [(Point3::new(-half, 0.0, 0.0), Point3::new(half, 0.0, 0.0))];
+        let z_axis = [(Point3::new(0.0, 0.0, -half), Point3::new(0.0, 0.0, half))];
+
+        for (segments, color) in [
+            (&segments[..], color),
+            (&x_axis[..], Point3::new(1.0, 0.0, 0.0)),
+            (&z_axis[..], Point3::new(0.0, 0.0, 1.0)),
+        ] {
+            // A mesh cannot index more than 65536 vertices, so large grids use several meshes.
+            for segments in segments.chunks(MAX_LINES_MESH_SEGMENTS) {
+                let mesh = Rc::new(RefCell::new(lines_mesh(segments)));
+                let mut lines = grid.add_mesh(mesh, Vector3::from_element(1.0));
+                lines.set_color(color.x, color.y, color.z);
+                lines.set_surface_rendering_activation(false);
+                lines.set_lines_width(1.0);
+                lines.set_casts_shadows(false);
+            }
+        }
+
+        grid
+    }
+
+    /// Adds a grid of lines on the `xz` plane with a filled square underneath it to this node
+    /// children.
+    ///
+    /// The square has the color `floor_color` and receives the shadows of the scene, but does not
+    /// cast any. See `add_grid` for details.
+    pub fn add_grid_with_floor(
+        &mut self,
+        size: f32,
+        subdivisions: u32,
+        color: Point3<f32>,
+        floor_color: Point3<f32>,
+    ) -> SceneNode {
+        let mut grid = self.add_grid(size, subdivisions, color);
+        let mut floor = grid.add_quad(size, size, 1, 1);
+
+        // The floor is slightly below the lines so they do not z-fight.
+        floor.set_local_transformation(Isometry3::new(
+            Vector3::new(0.0, -size * 1.0e-4, 0.0),
+            Vector3::x() * -std::f32::consts::FRAC_PI_2,
+        ));
+        floor.set_color(floor_color.x, floor_color.y, floor_color.z);
+        floor.set_casts_shadows(false);
+
+        grid
+    }
+
     /// Creates and adds a new object using the geometry registered as `geometry_name`.
     pub fn add_geom_with_name(
         &mut self,
@@ -792,6 +1935,56 @@ impl SceneNode {
         self.add_object(scale, na::one(), object)
     }
 
+    /// Creates and adds a new node drawing many instances of the same mesh.
+    ///
+    /// Each instance is given a transformation relative to the new node, and a color. If `colors`
+    /// is empty, all the instances are white.
+    pub fn add_instanced(
+        &mut self,
+        mesh: Rc<RefCell<Mesh>>,
+        transforms: &[Isometry3<f32>],
+        colors: &[Point3<f32>],
+    ) -> InstancedNode {
+        let tex = TextureManager::get_global_manager(|tm| tm.get_default());
+        let mat = MaterialManager::get_global_manager(|mm| mm.get("instanced").unwrap());
+        let instances = Rc::new(RefCell::new(Instances::new(transforms, colors)));
+        let mut object = Object::new(mesh, 1.0, 1.0, 1.0, tex, mat);
+
+        object.set_user_data(Box::new(instances.clone()));
+
+        let node = self.add_object(Vector3::from_element(1.0), na::one(), object);
+
+        InstancedNode::new(node, instances)
+    }
+
+    /// Creates and adds a new node drawing a point cloud.
+    ///
+    /// The points are relative to the new node. If `colors` is empty, all the points are white.
+    /// Otherwise, it must have the same length as `points`.
+    pub fn add_point_cloud(
+        &mut self,
+        points: Vec<Point3<f32>>,
+        colors: Vec<Point3<f32>>,
+    ) -> PointCloudNode {
+        let tex = TextureManager::get_global_manager(|tm| tm.get_default());
+        let mat = MaterialManager::get_global_manager(|mm| mm.get("point_cloud").unwrap());
+        let cloud = Rc::new(RefCell::new(PointCloud::new(points, colors)));
+        let mesh = Rc::new(RefCell::new(Mesh::new(
+            Vec::new(),
+            Vec::new(),
+            None,
+            None,
+            false,
+        )));
+        let mut object = Object::new(mesh, 1.0, 1.0, 1.0, tex, mat);
+
+        object.set_user_data(Box::new(cloud.clone()));
+
+        let node = self.add_object(Vector3::from_element(1.0), na::one(), object);
+
+        PointCloudNode::new(node, cloud)
+    }
+
     /// Creates and adds a new object using a mesh descriptor.
     pub fn add_trimesh(&mut self, descr: TriMesh<f32>, scale: Vector3<f32>) -> SceneNode {
         self.add_mesh(
@@ -805,11 +1998,24 @@ impl SceneNode {
     /// This will create a new node serving as a root of the scene described by the obj file. This
     /// newly created node is added to this node's children.
     pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vector3<f32>) -> SceneNode {
-        let tex = TextureManager::get_global_manager(|tm| tm.get_default());
-        let mat = MaterialManager::get_global_manager(|mm| mm.get_default());
+        self.add_obj_with_progress(path, mtl_dir, scale, |_| {})
+    }
 
+    /// Creates and adds multiple nodes created from an obj file, calling `progress` with the
+    /// fraction of the file parsed so far.
+    ///
+    /// See `add_obj` for details, and `loader::obj::parse_file_with_progress` for the reported fractions.
+    pub fn add_obj_with_progress<F: FnMut(f32)>(
+        &mut self,
+        path: &Path,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+        progress: F,
+    ) -> SceneNode {
         // FIXME: is there some error-handling stuff to do here instead of the `let _`.
-        let result = MeshManager::load_obj(path, mtl_dir, path.to_str().unwrap()).map(|objs| {
+        let result =
+            obj_loader::parse_file_with_progress(path, mtl_dir, path.to_str().unwrap(), progress);
+        let result = result.map(|objs| {
             let mut root;
 
             let self_root = objs.len() == 1;
@@ -825,29 +2031,8 @@ impl SceneNode {
             }
 
             for (_, mesh, mtl) in objs.into_iter() {
-                let mut object = Object::new(mesh, 1.0, 1.0, 1.0, tex.clone(), mat.clone());
-
-                match mtl {
-                    None => {}
-                    Some(mtl) => {
-                        object.set_color(mtl.diffuse.x, mtl.diffuse.y, mtl.diffuse.z);
-
-                        for t in mtl.diffuse_texture.iter() {
-                            let mut tpath = PathBuf::new();
-                            tpath.push(mtl_dir);
-                            tpath.push(&t[..]);
-                            object.set_texture_from_file(&tpath, tpath.to_str().unwrap())
-                        }
-
-                        for t in mtl.ambiant_texture.iter() {
-                            let mut tpath = PathBuf::new();
-                            tpath.push(mtl_dir);
-                            tpath.push(&t[..]);
-                            object.set_texture_from_file(&tpath, tpath.to_str().unwrap())
-                        }
-                    }
-                }
-
+                let mesh = Rc::new(RefCell::new(mesh));
+                let object = obj_object(mesh, mtl, mtl_dir, &mut HashMap::new());
                 let _ = root.add_object(child_scale, na::one(), object);
             }
 
@@ -865,35 +2050,225 @@ impl SceneNode {
         result.unwrap()
     }
 
-    /// Applies a closure to each object contained by this node and its children.
-    #[inline]
-    pub fn apply_to_scene_nodes_mut<F: FnMut(&mut SceneNode)>(&mut self, f: &mut F) {
-        f(self);
+    /// Starts loading an obj file in the background, and adds a new node to this node's children
+    /// to hold its objects.
+    ///
+    /// The file, its materials, and their textures are read and parsed by a worker thread, so the
+    /// window stays responsive. The objects are created and added to the new node, which has the
+    /// given scale, by the first call to `PendingNode::poll` after the parsing is complete.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_obj_async(
+        &mut self,
+        path: &Path,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+    ) -> PendingNode {
+        let mut node = self.add_group();
+        node.set_local_scale(scale.x, scale.y, scale.z);
 
-        for c in self.data_mut().children.iter_mut() {
-            c.apply_to_scene_nodes_mut(f)
-        }
+        PendingNode::new(node, path, mtl_dir)
     }
 
-    /// Applies a closure to each object contained by this node and its children.
-    #[inline]
-    pub fn apply_to_scene_nodes<F: FnMut(&SceneNode)>(&self, f: &mut F) {
-        f(self);
+    /// Creates and adds a new object to this node children using a stl file.
+    ///
+    /// Vertices closer than `stl::DEFAULT_WELD_EPSILON` are merged together.
+    pub fn add_stl(&mut self, path: &Path, scale: Vector3<f32>) -> SceneNode {
+        let mesh = stl::load(path)
+            .unwrap_or_else(|e| panic!("Unable to load the stl file {}: {}", path.display(), e));
 
-        for c in self.data().children.iter() {
-            c.apply_to_scene_nodes(f)
-        }
+        self.add_mesh(Rc::new(RefCell::new(mesh)), scale)
+    }
+
+    /// Adds a glTF model as a children of this node.
+    ///
+    /// The glTF node hierarchy is reproduced as nested scene nodes under a new group node.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the `.gltf` or `.glb` file.
+    /// * `scale` - scale to apply to the model.
+    pub fn add_gltf(&mut self, path: &Path, scale: Vector3<f32>) -> SceneNode {
+        self.add_gltf_with_progress(path, scale, |_| {})
+    }
+
+    /// Adds a glTF model as a children of this node, calling `progress` with the fraction of the
+    /// file loaded so far.
+    ///
+    /// See `add_gltf` for details, and `loader::gltf::load_with_progress` for the reported fractions.
+    pub fn add_gltf_with_progress<F: FnMut(f32)>(
+        &mut self,
+        path: &Path,
+        scale: Vector3<f32>,
+        progress: F,
+    ) -> SceneNode {
+        let scene = gltf::load_with_progress(path, progress)
+            .unwrap_or_else(|e| panic!("Unable to load the glTF file: {}", e));
+        let tex = TextureManager::get_global_manager(|tm| tm.get_default());
+        let mat = MaterialManager::get_global_manager(|mm| mm.get_default());
+
+        let mut images = scene.images;
+        let textures: Vec<Rc<Texture>> = TextureManager::get_global_manager(|tm| {
+            images
+                .drain(..)
+                .enumerate()
+                .map(|(i, image)| tm.add_image(image, &format!("{}#{}", path.display(), i)))
+                .collect()
+        });
+
+        let mut root = SceneNode::new(scale, na::one(), None);
+        self.add_child(root.clone());
+
+        for node in scene.nodes.iter() {
+            root.add_gltf_node(node, &scene.meshes, &scene.materials, &textures, &tex, &mat);
+        }
+
+        root
+    }
+
+    fn add_gltf_node(
+        &mut self,
+        node: &GltfNode,
+        meshes: &[Vec<GltfPrimitive>],
+        materials: &[GltfMaterial],
+        textures: &[Rc<Texture>],
+        default_texture: &Rc<Texture>,
+        default_material: &Rc<RefCell<Box<dyn Material + 'static>>>,
+    ) {
+        let mut group = SceneNode::new(node.scale, node.transform, None);
+        self.add_child(group.clone());
+
+        for primitive in node.mesh.iter().flat_map(|m| meshes[*m].iter()) {
+            let mut object = Object::new(
+                primitive.mesh.clone(),
+                1.0,
+                1.0,
+                1.0,
+                default_texture.clone(),
+                default_material.clone(),
+            );
+
+            if let Some(material) = primitive.material.map(|m| &materials[m]) {
+                let color = material.base_color;
+                object.set_color(color.x, color.y, color.z);
+
+                if let Some(texture) = material.base_color_texture {
+                    object.set_texture(textures[texture].clone());
+                }
+            }
+
+            let _ = group.add_object(Vector3::from_element(1.0), na::one(), object);
+        }
+
+        for child in node.children.iter() {
+            group.add_gltf_node(
+                child,
+                meshes,
+                materials,
+                textures,
+                default_texture,
+                default_material,
+            );
+        }
+    }
+
+    /// Applies a closure to each object contained by this node and its children.
+    #[inline]
+    pub fn apply_to_scene_nodes_mut<F: FnMut(&mut SceneNode)>(&mut self, f: &mut F) {
+        f(self);
+
+        for c in self.data_mut().children.iter_mut() {
+            c.apply_to_scene_nodes_mut(f)
+        }
+    }
+
+    /// Applies a closure to each object contained by this node and its children.
+    #[inline]
+    pub fn apply_to_scene_nodes<F: FnMut(&SceneNode)>(&self, f: &mut F) {
+        f(self);
+
+        for c in self.data().children.iter() {
+            c.apply_to_scene_nodes(f)
+        }
+    }
+
+    /// Casts a ray on the visible objects of the scene graph rooted by this node.
+    ///
+    /// The ray is expressed in the same coordinate frame as this node. Returns the nearest node
+    /// hit by the ray, together with the hit point. Backfaces are ignored for objects using
+    /// backface culling, and so are the objects outside of the layers last made visible by the
+    /// window.
+    pub fn cast_ray(
+        &self,
+        origin: &Point3<f32>,
+        dir: &Vector3<f32>,
+    ) -> Option<(SceneNode, Point3<f32>)> {
+        let mut best = None;
+
+        if self.data().visible {
+            self.do_cast_ray(
+                origin,
+                dir,
+                &na::one(),
+                &Vector3::from_element(1.0),
+                &mut best,
+            );
+        }
+
+        best.map(|(node, toi)| (node, origin + dir * toi))
+    }
+
+    fn do_cast_ray(
+        &self,
+        origin: &Point3<f32>,
+        dir: &Vector3<f32>,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        best: &mut Option<(SceneNode, f32)>,
+    ) {
+        let data = self.data();
+        // NOTE: this must match the transformations computed by `SceneNodeData::do_render`.
+        let world_transform = *transform * data.local_transform;
+        let world_scale = scale.component_mul(&data.local_scale);
+
+        if let Some(object) = data.object.as_ref().filter(|_| data.in_visible_layers()) {
+            if let Some(toi) =
+                cast_ray_on_object(object, origin, dir, &world_transform, &world_scale)
+            {
+                if best.as_ref().map(|b| toi < b.1).unwrap_or(true) {
+                    *best = Some((self.clone(), toi));
+                }
+            }
+        }
+
+        for c in data.children.iter() {
+            if c.data().visible {
+                c.do_cast_ray(origin, dir, &world_transform, &world_scale, best)
+            }
+        }
     }
 
     //
-    //
+    //
     // fwd
     //
     //
 
     /// Render the scene graph rooted by this node.
-    pub fn render(&mut self, pass: usize, camera: &mut dyn Camera, light: &Light) {
-        self.data_mut().render(pass, camera, light)
+    pub fn render(&mut self, pass: usize, camera: &mut dyn Camera, lights: &Lights) {
+        self.data_mut().render(pass, camera, lights)
+    }
+
+    /// Render the scene graph rooted by this node, skipping the objects lying entirely outside of
+    /// the given frustum planes.
+    #[inline]
+    pub fn render_with_frustum_culling(
+        &mut self,
+        pass: usize,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        frustum: &[Vector4<f32>; 6],
+    ) {
+        self.data_mut()
+            .render_with_frustum_culling(pass, camera, lights, frustum)
     }
 
     /// Sets the material of the objects contained by this node and its children.
@@ -909,6 +2284,9 @@ impl SceneNode {
     }
 
     /// Sets the width of the lines drawn for the objects contained by this node and its children.
+    ///
+    /// The width is in pixels. With the default material, lines wider than one pixel are expanded
+    /// into quads facing the camera, like the lines drawn by `Window::draw_line`.
     #[inline]
     pub fn set_lines_width(&mut self, width: f32) {
         self.data_mut().set_lines_width(width)
@@ -920,6 +2298,27 @@ impl SceneNode {
         self.data_mut().set_lines_color(color)
     }
 
+    /// Enables or disables the drawing of the edges of the objects contained by this node and its
+    /// children with the given color, on top of their surfaces.
+    ///
+    /// The surfaces are pushed back slightly so the edges do not z-fight with them. The wireframe
+    /// is drawn by the default material only.
+    #[inline]
+    pub fn set_wireframe_overlay(&mut self, color: Point3<f32>, enabled: bool) {
+        self.data_mut().set_wireframe_overlay(color, enabled)
+    }
+
+    /// Sets which edges of the objects contained by this node and its children are drawn as
+    /// lines by `set_lines_width` and `set_wireframe_overlay`.
+    ///
+    /// With `WireframeMode::Edges`, the shared edges are drawn once and the diagonals of the flat
+    /// polygons are omitted, e.g., to see the quads of a mesh loaded from an obj file. The edges
+    /// are computed the first time they are drawn. The default is `WireframeMode::Triangles`.
+    #[inline]
+    pub fn set_wireframe_mode(&mut self, mode: WireframeMode) {
+        self.data_mut().set_wireframe_mode(mode)
+    }
+
     /// Sets the size of the points drawn for the objects contained by this node and its children.
     #[inline]
     pub fn set_points_size(&mut self, size: f32) {
@@ -935,11 +2334,91 @@ impl SceneNode {
 
     /// Activates or deactivates backface culling for the objects contained by this node and its
     /// children.
+    ///
+    /// This overrides the default set by `Window::set_backface_culling` for these objects only:
+    /// the culling state is set again before drawing each object.
     #[inline]
     pub fn enable_backface_culling(&mut self, active: bool) {
         self.data_mut().enable_backface_culling(active)
     }
 
+    /// Makes the objects contained by this node and its children use the backface culling
+    /// default set by `Window::set_backface_culling`.
+    #[inline]
+    pub fn reset_backface_culling(&mut self) {
+        self.data_mut().reset_backface_culling()
+    }
+
+    /// Enables or disables the depth test for the objects contained by this node and its
+    /// children (default at `true`).
+    ///
+    /// Without depth test, the objects are drawn over the ones drawn before them, e.g., for
+    /// overlays, and do not write their depth either. Like the other depth settings, this is set
+    /// before drawing each of these objects and restored afterward, so it does not affect the
+    /// other objects.
+    #[inline]
+    pub fn set_depth_test(&mut self, active: bool) {
+        self.data_mut().set_depth_test(active)
+    }
+
+    /// Sets whether the objects contained by this node and its children write their depth.
+    ///
+    /// By default, only opaque objects write their depth, so the transparent ones do not hide
+    /// each other.
+    #[inline]
+    pub fn set_depth_write(&mut self, active: bool) {
+        self.data_mut().set_depth_write(active)
+    }
+
+    /// Makes the objects contained by this node and its children write their depth only if they
+    /// are opaque, which is the default.
+    #[inline]
+    pub fn reset_depth_write(&mut self) {
+        self.data_mut().reset_depth_write()
+    }
+
+    /// Sets the comparison used by the depth test of the objects contained by this node and its
+    /// children (default at `DepthFunc::LessEqual`).
+    ///
+    /// E.g., `DepthFunc::Always` draws the objects regardless of the depth of the scene while
+    /// still writing their depth.
+    #[inline]
+    pub fn set_depth_func(&mut self, func: DepthFunc) {
+        self.data_mut().set_depth_func(func)
+    }
+
+    /// Offsets the depth of the surface of the objects contained by this node and its children
+    /// by `factor` times their depth slope plus `units` times the smallest resolvable depth
+    /// difference.
+    ///
+    /// Negative values move the surfaces toward the camera, e.g., `set_polygon_offset(-1.0, -1.0)`
+    /// draws floor markings over the coplanar floor without z-fighting. The offset is only
+    /// enabled while drawing these objects, and does not affect their lines and points.
+    #[inline]
+    pub fn set_polygon_offset(&mut self, factor: f32, units: f32) {
+        self.data_mut().set_polygon_offset(factor, units)
+    }
+
+    /// Removes the polygon offset of the objects contained by this node and its children.
+    #[inline]
+    pub fn unset_polygon_offset(&mut self) {
+        self.data_mut().unset_polygon_offset()
+    }
+
+    /// Sets the color and width in pixels of the outline drawn around the objects contained by
+    /// this node and its children, or removes it if `None`.
+    ///
+    /// Each object is outlined along its silhouette on the screen, whatever its mesh, e.g., to
+    /// highlight the selection of an editor. The outlines are drawn after the rest of the scene,
+    /// over the objects occluding the outlined ones, using the stencil buffer of the window. They
+    /// are made of the edges of the triangles of the meshes, so the vertices and faces of the
+    /// meshes must be kept in RAM, and only follow the mesh of instanced objects, not each of
+    /// their instances. Outlines are not drawn with WebGL nor by `Window::render_to_texture`.
+    #[inline]
+    pub fn set_outline(&mut self, outline: Option<(Point3<f32>, f32)>) {
+        self.data_mut().set_outline(outline)
+    }
+
     /// Mutably accesses the vertices of the objects contained by this node and its children.
     ///
     /// The provided closure is called once per object.
@@ -963,6 +2442,15 @@ impl SceneNode {
         self.data_mut().recompute_normals()
     }
 
+    /// Recomputes the normals of the meshes of the objects contained by this node and its
+    /// children so each face is shaded flat.
+    ///
+    /// See `Mesh::recompute_normals_flat` for details.
+    #[inline]
+    pub fn recompute_normals_flat(&mut self) {
+        self.data_mut().recompute_normals_flat()
+    }
+
     /// Mutably accesses the normals of the objects contained by this node and its children.
     ///
     /// The provided closure is called once per object.
@@ -1026,6 +2514,112 @@ impl SceneNode {
         self.data_mut().set_visible(visible)
     }
 
+    /// The layers this node belongs to, as a bit mask.
+    #[inline]
+    pub fn layer_mask(&self) -> u32 {
+        self.data().layer_mask()
+    }
+
+    /// Sets the layers this node belongs to, as a bit mask.
+    ///
+    /// The object of this node is rendered only if this mask intersects the visible layers of the
+    /// window, set by `Window::set_visible_layers`. The children of this node are not affected.
+    #[inline]
+    pub fn set_layer_mask(&mut self, mask: u32) {
+        self.data_mut().set_layer_mask(mask)
+    }
+
+    /// Whether the object of this node casts shadows.
+    #[inline]
+    pub fn casts_shadows(&self) -> bool {
+        self.data().casts_shadows()
+    }
+
+    /// Sets whether the object of this node casts shadows.
+    ///
+    /// This does not affect the children of this node, and the object still receives shadows
+    /// from other objects.
+    #[inline]
+    pub fn set_casts_shadows(&mut self, casts_shadows: bool) {
+        self.data_mut().set_casts_shadows(casts_shadows)
+    }
+
+    /// How this node is rotated to face the camera.
+    #[inline]
+    pub fn billboard(&self) -> BillboardMode {
+        self.data().billboard()
+    }
+
+    /// Sets how this node is rotated to face the camera.
+    ///
+    /// While this is not `BillboardMode::None`, the rotation of the world transformation of this
+    /// node is replaced at each frame so its local `z` axis points toward the camera, while its
+    /// local `y` axis points upward. Its local rotation is ignored, but its world translation and
+    /// scale are computed as usual, and its children inherit the new rotation. Since the quads
+    /// created by `add_quad` face the `z` axis, they can be used as sprites:
+    ///
+    /// ```no_run
+    /// # use kiss3d::window::Window;
+    /// # use kiss3d::scene::BillboardMode;
+    /// # let mut window = Window::new("Billboard");
+    /// let mut sprite = window.add_quad(1.0, 1.0, 1, 1);
+    /// sprite.set_texture_from_file(std::path::Path::new("sprite.png"), "sprite");
+    /// sprite.set_billboard(BillboardMode::Spherical);
+    /// ```
+    #[inline]
+    pub fn set_billboard(&mut self, mode: BillboardMode) {
+        self.data_mut().set_billboard(mode)
+    }
+
+    /// The world-space point this node is aimed at, if any.
+    #[inline]
+    pub fn look_at_target(&self) -> Option<Point3<f32>> {
+        self.data().look_at_target()
+    }
+
+    /// Sets the world-space point this node is aimed at.
+    ///
+    /// While this is not `None`, the local rotation of this node is recomputed at each frame,
+    /// before the scene is rendered, so its local `z` axis (see `set_look_at_axes`) points toward
+    /// `target` in world-space, even if its parents move. Setting it to `None` keeps the last
+    /// computed rotation and gives back control of the local rotation:
+    ///
+    /// ```no_run
+    /// # use kiss3d::window::Window;
+    /// # use nalgebra::Point3;
+    /// # let mut window = Window::new("Look-at");
+    /// let mut base = window.add_group();
+    /// let mut turret = base.add_cube(0.2, 0.2, 1.0);
+    /// let mut target = window.add_sphere(0.1);
+    ///
+    /// while window.render() {
+    ///     let t = window.elapsed_time().as_secs_f32();
+    ///     let position = Point3::new(t.cos() * 3.0, 0.0, t.sin() * 3.0);
+    ///     target.set_local_translation(position.coords.into());
+    ///     turret.set_look_at_target(Some(position));
+    /// }
+    /// ```
+    #[inline]
+    pub fn set_look_at_target(&mut self, target: Option<Point3<f32>>) {
+        self.data_mut().set_look_at_target(target)
+    }
+
+    /// The local axis pointed toward the look-at target, and the world up vector.
+    #[inline]
+    pub fn look_at_axes(&self) -> (Vector3<f32>, Vector3<f32>) {
+        self.data().look_at_axes()
+    }
+
+    /// Sets the local axis pointed toward the look-at target, and the world up vector.
+    ///
+    /// The node is rotated so that `axis` points toward the target, then around it so that its
+    /// local `y` axis is as close as possible to `up`, in world-space. These default to the `z` and
+    /// `y` axes.
+    #[inline]
+    pub fn set_look_at_axes(&mut self, axis: Vector3<f32>, up: Vector3<f32>) {
+        self.data_mut().set_look_at_axes(axis, up)
+    }
+
     /// Sets the color of the objects contained by this node and its children.
     ///
     /// Colors components must be on the range `[0.0, 1.0]`.
@@ -1034,6 +2628,86 @@ impl SceneNode {
         self.data_mut().set_color(r, g, b)
     }
 
+    /// Sets the opacity of the objects contained by this node and its children, on the range
+    /// `[0.0, 1.0]`.
+    ///
+    /// This has no effect on the objects which are not transparent. The default material
+    /// multiplies it by the alpha channel of the texture.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.data_mut().set_alpha(alpha)
+    }
+
+    /// Sets whether the objects contained by this node and its children are drawn with alpha
+    /// blending.
+    ///
+    /// Transparent objects are drawn after the opaque ones, without writing to the depth buffer,
+    /// and sorted from the furthest to the closest to the camera. The sorting is done per object,
+    /// not per triangle: the triangles of a concave object overlapping itself, or of intersecting
+    /// objects, may still be blended in the wrong order.
+    #[inline]
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.data_mut().set_transparent(transparent)
+    }
+
+    /// Sets the per-vertex colors of the objects contained by this node and its children.
+    ///
+    /// Those colors are used by the `VertexColorMaterial`. The objects whose mesh is shared with
+    /// other objects are given their own mesh first, see `Object::set_vertex_colors`. Panics if
+    /// the number of colors does not match the number of vertices of one of the objects.
+    #[inline]
+    pub fn set_vertex_colors(&mut self, colors: Vec<Point3<f32>>) {
+        self.data_mut().set_vertex_colors(colors)
+    }
+
+    /// Sets the color emitted by the objects contained by this node and its children.
+    ///
+    /// This color is added to the lit color of the objects, so it is visible even in the dark.
+    /// Colors components must be on the range `[0.0, 1.0]`. Defaults to black.
+    #[inline]
+    pub fn set_emissive_color(&mut self, color: Point3<f32>) {
+        self.data_mut().set_emissive_color(color)
+    }
+
+    /// Sets the texture multiplied by the emissive color of the objects contained by this node
+    /// and its children.
+    #[inline]
+    pub fn set_emissive_texture(&mut self, texture: Rc<Texture>) {
+        self.data_mut().set_emissive_texture(texture)
+    }
+
+    /// Removes the emissive texture of the objects contained by this node and its children.
+    #[inline]
+    pub fn unset_emissive_texture(&mut self) {
+        self.data_mut().unset_emissive_texture()
+    }
+
+    /// Sets the environment reflected by the objects contained by this node and its children.
+    ///
+    /// The cube map is mixed with the lit color of the objects depending on their reflectivity
+    /// and the viewing angle. The cube map of the skybox can be reused so the objects reflect the
+    /// sky.
+    #[inline]
+    pub fn set_reflection_map(&mut self, map: Rc<CubemapTexture>) {
+        self.data_mut().set_reflection_map(map)
+    }
+
+    /// Removes the environment reflected by the objects contained by this node and its children.
+    #[inline]
+    pub fn unset_reflection_map(&mut self) {
+        self.data_mut().unset_reflection_map()
+    }
+
+    /// Sets the fraction of the environment reflected by the objects contained by this node and
+    /// its children when looking at them head-on.
+    ///
+    /// The reflection increases toward `1.0` at grazing angles. Must be on the range
+    /// `[0.0, 1.0]`. Defaults to `0.5`.
+    #[inline]
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.data_mut().set_reflectivity(reflectivity)
+    }
+
     /// Sets the texture of the objects contained by this node and its children.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
@@ -1057,6 +2731,36 @@ impl SceneNode {
         self.data_mut().set_texture_from_memory(image_data, name)
     }
 
+    /// Sets the texture of the objects contained by this node and its children.
+    ///
+    /// The texture is uploaded from raw RGBA pixels, given row by row starting with the top row.
+    /// It is not registered by the global `TextureManager`, so each call creates a new texture. Use
+    /// `TextureManager::add_image_from_pixels` to share a texture between several nodes.
+    ///
+    /// # Failure
+    /// Panics if `data` does not contain exactly `width * height * 4` bytes.
+    pub fn set_texture_from_rgba(&mut self, data: &[u8], width: u32, height: u32) {
+        self.data_mut().set_texture_from_rgba(data, width, height)
+    }
+
+    /// Sets the filters of the textures of the objects contained by this node and its children.
+    ///
+    /// This is typically used with `TextureFilter::Nearest` to display pixel art without blurring
+    /// it. The textures are modified, so this also affects the other objects sharing them.
+    ///
+    /// # Failure
+    /// Panics if `mag` uses mipmaps.
+    pub fn set_texture_filtering(&mut self, min: TextureFilter, mag: TextureFilter) {
+        self.data().set_texture_filtering(min, mag)
+    }
+
+    /// Sets the wrapping of the textures of the objects contained by this node and its children.
+    ///
+    /// The textures are modified, so this also affects the other objects sharing them.
+    pub fn set_texture_wrap(&mut self, s: TextureWrapping, t: TextureWrapping) {
+        self.data().set_texture_wrap(s, t)
+    }
+
     /// Sets the texture of the objects contained by this node and its children.
     ///
     /// The texture must already have been registered as `name`.
@@ -1070,6 +2774,65 @@ impl SceneNode {
         self.data_mut().set_texture(texture)
     }
 
+    /// Replaces the mesh of the objects contained by this node and its children.
+    pub fn set_mesh(&mut self, mesh: Rc<RefCell<Mesh>>) {
+        self.data_mut().set_mesh(mesh)
+    }
+
+    /// Sets the levels of detail of the objects contained by this node and its children.
+    ///
+    /// Each mesh of `lods` is drawn instead of the mesh of an object when the distance between
+    /// the camera and the center of the object is at least the distance associated to it. The
+    /// meshes can be obtained with `Mesh::simplify`:
+    ///
+    /// ```no_run
+    /// # use kiss3d::window::Window;
+    /// # use std::cell::RefCell;
+    /// # use std::rc::Rc;
+    /// let mut window = Window::new("Levels of detail");
+    /// let mut sphere = window.add_sphere(1.0);
+    /// let mesh = sphere.data().object().unwrap().mesh().clone();
+    /// let low = Rc::new(RefCell::new(mesh.borrow().simplify(100)));
+    /// let lower = Rc::new(RefCell::new(mesh.borrow().simplify(20)));
+    /// sphere.set_lods(&[(10.0, low), (30.0, lower)]);
+    /// ```
+    pub fn set_lods(&mut self, lods: &[(f32, Rc<RefCell<Mesh>>)]) {
+        self.data_mut().set_lods(lods)
+    }
+
+    /// Replaces the mesh of each object contained by this node and its children by a smoother
+    /// one obtained with `iterations` steps of Loop subdivision.
+    ///
+    /// See `Mesh::subdivide` for details. The objects of other nodes sharing the previous meshes
+    /// are not affected.
+    pub fn subdivide(&mut self, iterations: u32) {
+        self.data_mut().subdivide(iterations)
+    }
+
+    /// Sets the tangent-space normal map of the objects contained by this node and its children.
+    ///
+    /// The tangents of the meshes are computed if they are not already available.
+    pub fn set_normal_map(&mut self, texture: Rc<Texture>) {
+        self.data_mut().set_normal_map(texture)
+    }
+
+    /// Sets the normal map of the objects contained by this node and its children.
+    ///
+    /// The texture is loaded from a file and registered by the global `TextureManager`.
+    ///
+    /// # Arguments
+    ///   * `path` - relative path of the texture on the disk
+    ///   * `name` - &str identifier to store this texture under
+    #[inline]
+    pub fn set_normal_map_from_file(&mut self, path: &Path, name: &str) {
+        self.data_mut().set_normal_map_from_file(path, name)
+    }
+
+    /// Removes the normal map of the objects contained by this node and its children.
+    pub fn unset_normal_map(&mut self) {
+        self.data_mut().unset_normal_map()
+    }
+
     /// Sets the local scaling factors of the object.
     #[inline]
     pub fn set_local_scale(&mut self, sx: f32, sy: f32, sz: f32) {
@@ -1083,6 +2846,23 @@ impl SceneNode {
         self.data_mut().reorient(eye, at, up)
     }
 
+    /// The world-space axis-aligned bounding box of this node and all its descendants.
+    ///
+    /// Returns the `(mins, maxs)` corners, or `None` if the subtree contains no geometry.
+    #[inline]
+    pub fn bounding_box(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        self.data().bounding_box()
+    }
+
+    /// The axis-aligned bounding box of this node and all its descendants, ignoring the
+    /// transformations of its parents.
+    ///
+    /// Returns the `(mins, maxs)` corners, or `None` if the subtree contains no geometry.
+    #[inline]
+    pub fn local_bounding_box(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        self.data().local_bounding_box()
+    }
+
     /// Appends a transformation to this node local transformation.
     #[inline]
     pub fn append_transformation(&mut self, t: &Isometry3<f32>) {
@@ -1143,3 +2923,395 @@ impl SceneNode {
         self.data_mut().set_local_rotation(r)
     }
 }
+
+// The maximum number of segments of a mesh built by `lines_mesh`, so it has at most 65536
+// vertices.
+const MAX_LINES_MESH_SEGMENTS: usize = 32768;
+
+// A mesh whose triangles are degenerate, so only their edges along the given segments are drawn.
+fn lines_mesh(segments: &[(Point3<f32>, Point3<f32>)]) -> Mesh {
+    assert!(segments.len() <= MAX_LINES_MESH_SEGMENTS);
+
+    let mut coords = Vec::with_capacity(segments.len() * 2);
+    let mut faces = Vec::with_capacity(segments.len());
+
+    for (a, b) in segments {
+        let i = coords.len() as u16;
+        coords.push(*a);
+        coords.push(*b);
+        faces.push(Point3::new(i, i + 1, i + 1));
+    }
+
+    let normals = vec![Vector3::y(); coords.len()];
+
+    Mesh::new(coords, faces, Some(normals), None, false)
+}
+
+// Creates an object from a mesh of an obj file and its material. The textures are taken from
+// `images` if they have already been decoded, and loaded from their files otherwise.
+pub(crate) fn obj_object(
+    mesh: Rc<RefCell<Mesh>>,
+    mtl: Option<MtlMaterial>,
+    mtl_dir: &Path,
+    images: &mut HashMap<PathBuf, DynamicImage>,
+) -> Object {
+    let tex = TextureManager::get_global_manager(|tm| tm.get_default());
+    let mat = MaterialManager::get_global_manager(|mm| mm.get_default());
+    let mut object = Object::new(mesh, 1.0, 1.0, 1.0, tex, mat);
+
+    if let Some(mtl) = mtl {
+        object.set_color(mtl.diffuse.x, mtl.diffuse.y, mtl.diffuse.z);
+
+        for t in mtl.diffuse_texture.iter().chain(mtl.ambiant_texture.iter()) {
+            let mut tpath = PathBuf::new();
+            tpath.push(mtl_dir);
+            tpath.push(&t[..]);
+
+            match images.remove(&tpath) {
+                Some(image) => {
+                    let mut image = Some(image);
+                    let texture = TextureManager::get_global_manager(|tm| {
+                        tm.add_decoded(image.take().unwrap(), &tpath, tpath.to_str().unwrap())
+                    });
+                    object.set_texture(texture)
+                }
+                None => object.set_texture_from_file(&tpath, tpath.to_str().unwrap()),
+            }
+        }
+    }
+
+    object
+}
+
+// The world rotation of a billboard at `position`, or `None` if `mode` is `BillboardMode::None`.
+fn billboard_rotation(
+    mode: BillboardMode,
+    position: &Vector3<f32>,
+    camera: &dyn Camera,
+) -> Option<UnitQuaternion<f32>> {
+    let camera_rotation = camera.view_transform().rotation.inverse();
+    // Looking toward the camera backward direction if the camera is on the billboard.
+    let to_eye = (camera.eye().coords - position)
+        .try_normalize(1.0e-6)
+        .unwrap_or_else(|| camera_rotation * Vector3::z());
+
+    match mode {
+        BillboardMode::None => None,
+        BillboardMode::Spherical => Some(UnitQuaternion::face_towards(
+            &to_eye,
+            &(camera_rotation * Vector3::y()),
+        )),
+        BillboardMode::Cylindrical => {
+            // Any direction will do if the camera is right above or below.
+            let dir = Vector3::new(to_eye.x, 0.0, to_eye.z)
+                .try_normalize(1.0e-6)
+                .unwrap_or_else(Vector3::z);
+
+            Some(UnitQuaternion::face_towards(&dir, &Vector3::y()))
+        }
+    }
+}
+
+// The world rotation pointing the local `axis` from `position` toward `target`, with the local `y`
+// axis as close as possible to `up`, or `None` if `target` is at `position`.
+fn look_at_rotation(
+    position: &Point3<f32>,
+    target: &Point3<f32>,
+    axis: &Vector3<f32>,
+    up: &Vector3<f32>,
+) -> Option<UnitQuaternion<f32>> {
+    let dir = (target - position).try_normalize(1.0e-6)?;
+
+    // Use another up vector when looking straight along it.
+    let up = if dir.cross(up).norm_squared() > 1.0e-12 * up.norm_squared() {
+        *up
+    } else if dir.cross(&Vector3::z()).norm_squared() > 1.0e-12 {
+        Vector3::z()
+    } else {
+        Vector3::x()
+    };
+
+    // Maps `axis` to the local `z` axis, which `face_towards` points toward `dir`.
+    let to_z = UnitQuaternion::rotation_between(axis, &Vector3::z()).unwrap_or_else(|| {
+        if axis.dot(&Vector3::z()) < 0.0 {
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::PI)
+        } else {
+            UnitQuaternion::identity()
+        }
+    });
+
+    Some(UnitQuaternion::face_towards(&dir, &up) * to_z)
+}
+
+// The world-space AABB of the local AABB of the mesh of an object, or `None` if the local AABB is
+// not known, see `Mesh::local_aabb`.
+fn object_world_aabb(
+    object: &Object,
+    transform: &Isometry3<f32>,
+    scale: &Vector3<f32>,
+) -> Option<(Point3<f32>, Point3<f32>)> {
+    let (local_mins, local_maxs) = object.mesh().borrow().local_aabb()?;
+
+    let mut mins = Point3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut maxs = Point3::new(-f32::MAX, -f32::MAX, -f32::MAX);
+
+    for i in 0..8 {
+        let corner = Vector3::new(
+            if i & 1 == 0 {
+                local_mins.x
+            } else {
+                local_maxs.x
+            },
+            if i & 2 == 0 {
+                local_mins.y
+            } else {
+                local_maxs.y
+            },
+            if i & 4 == 0 {
+                local_mins.z
+            } else {
+                local_maxs.z
+            },
+        );
+        let corner = transform * Point3::from(corner.component_mul(scale));
+
+        mins = mins.inf(&corner);
+        maxs = maxs.sup(&corner);
+    }
+
+    Some((mins, maxs))
+}
+
+// Tests if the mesh of an object, once transformed, lies entirely on the negative side of one of
+// the frustum planes. This is conservative: the world-space AABB of the mesh local AABB is tested.
+fn is_object_outside_frustum(
+    object: &Object,
+    transform: &Isometry3<f32>,
+    scale: &Vector3<f32>,
+    frustum: &[Vector4<f32>; 6],
+) -> bool {
+    // Instances may be anywhere around the node.
+    if object.data().user_data().is::<Rc<RefCell<Instances>>>() {
+        return false;
+    }
+
+    let (mins, maxs) = match object_world_aabb(object, transform, scale) {
+        Some(aabb) => aabb,
+        // We can't tell without the bounding box of the mesh.
+        None => return false,
+    };
+
+    frustum.iter().any(|plane| {
+        // The corner of the AABB the furthest along the plane normal.
+        let furthest = Point3::new(
+            if plane.x >= 0.0 { maxs.x } else { mins.x },
+            if plane.y >= 0.0 { maxs.y } else { mins.y },
+            if plane.z >= 0.0 { maxs.z } else { mins.z },
+        );
+
+        plane.xyz().dot(&furthest.coords) + plane.w < 0.0
+    })
+}
+
+// Computes the time of impact of a ray with an object mesh.
+fn cast_ray_on_object(
+    object: &Object,
+    origin: &Point3<f32>,
+    dir: &Vector3<f32>,
+    transform: &Isometry3<f32>,
+    scale: &Vector3<f32>,
+) -> Option<f32> {
+    if scale.iter().any(|s| *s == 0.0) {
+        return None;
+    }
+
+    // Express the ray in the mesh local space. The time of impact is preserved since we don't
+    // renormalize the direction.
+    let local_origin = transform.inverse_transform_point(origin);
+    let local_origin = Point3::from(local_origin.coords.component_div(scale));
+    let local_dir = transform.inverse_transform_vector(dir).component_div(scale);
+
+    let mesh = object.mesh().borrow();
+
+    // Coarse test with the mesh AABB first.
+    let (mins, maxs) = mesh.local_aabb()?;
+
+    if !ray_intersects_aabb(&local_origin, &local_dir, &mins, &maxs) {
+        return None;
+    }
+
+    let coords = mesh.coords().read().unwrap();
+    let faces = mesh.faces().read().unwrap();
+    let (coords, faces) = match (coords.data(), faces.data()) {
+        (Some(coords), Some(faces)) => (coords, faces),
+        _ => return None,
+    };
+
+    let cull = object.data().backface_culling_enabled();
+    let mut best: Option<f32> = None;
+
+    for face in faces.iter() {
+        let a = coords[face.x as usize];
+        let b = coords[face.y as usize];
+        let c = coords[face.z as usize];
+
+        if let Some(toi) = ray_triangle_toi(&local_origin, &local_dir, &a, &b, &c, cull) {
+            if best.map(|b| toi < b).unwrap_or(true) {
+                best = Some(toi);
+            }
+        }
+    }
+
+    best
+}
+
+fn ray_intersects_aabb(
+    origin: &Point3<f32>,
+    dir: &Vector3<f32>,
+    mins: &Point3<f32>,
+    maxs: &Point3<f32>,
+) -> bool {
+    let mut tmin = 0.0f32;
+    let mut tmax = f32::MAX;
+
+    for i in 0..3 {
+        if dir[i] == 0.0 {
+            if origin[i] < mins[i] || origin[i] > maxs[i] {
+                return false;
+            }
+        } else {
+            let t1 = (mins[i] - origin[i]) / dir[i];
+            let t2 = (maxs[i] - origin[i]) / dir[i];
+            tmin = tmin.max(t1.min(t2));
+            tmax = tmax.min(t1.max(t2));
+
+            if tmin > tmax {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+// Möller–Trumbore ray-triangle intersection.
+fn ray_triangle_toi(
+    origin: &Point3<f32>,
+    dir: &Vector3<f32>,
+    a: &Point3<f32>,
+    b: &Point3<f32>,
+    c: &Point3<f32>,
+    cull: bool,
+) -> Option<f32> {
+    let ab = b - a;
+    let ac = c - a;
+    let p = dir.cross(&ac);
+    let det = ab.dot(&p);
+    // The determinant scales with the lengths of the ray direction and of the edges.
+    let tolerance = f32::EPSILON * dir.norm() * ab.norm() * ac.norm();
+
+    // A negative determinant means the ray hits a counter-clockwise triangle from behind.
+    if det.abs() <= tolerance || (cull && det < 0.0) {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let ao = origin - a;
+    let u = ao.dot(&p) * inv_det;
+
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = ao.cross(&ab);
+    let v = dir.dot(&q) * inv_det;
+
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let toi = ac.dot(&q) * inv_det;
+
+    if toi >= 0.0 {
+        Some(toi)
+    } else {
+        None
+    }
+}
+
+/// A depth-first iterator through the descendants of a scene node.
+///
+/// This is created by `SceneNode::descendants`.
+pub struct Descendants {
+    stack: Vec<SceneNode>,
+}
+
+impl Iterator for Descendants {
+    type Item = SceneNode;
+
+    fn next(&mut self) -> Option<SceneNode> {
+        let node = self.stack.pop()?;
+        self.stack
+            .extend(node.data().children.iter().rev().cloned());
+
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ray_intersects_aabb, ray_triangle_toi};
+    use na::{Point3, Vector3};
+
+    fn triangle(size: f32) -> [Point3<f32>; 3] {
+        [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(size, 0.0, 0.0),
+            Point3::new(0.0, size, 0.0),
+        ]
+    }
+
+    #[test]
+    fn ray_hits_triangles_of_any_size() {
+        for &size in &[1.0e-5, 1.0, 1.0e3] {
+            let [a, b, c] = triangle(size);
+            let origin = Point3::new(size * 0.25, size * 0.25, 2.0);
+
+            for &dir in &[-Vector3::z(), -Vector3::z() * 1.0e-3, -Vector3::z() * 1.0e3] {
+                let toi = ray_triangle_toi(&origin, &dir, &a, &b, &c, true).unwrap();
+                assert!((toi * dir.norm() - 2.0).abs() < 1.0e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn ray_misses_triangles() {
+        let [a, b, c] = triangle(1.0);
+        let origin = Point3::new(0.25, 0.25, 2.0);
+
+        // Outside of the triangle, behind the origin, parallel, and from behind with culling.
+        let outside = Point3::new(0.75, 0.75, 2.0);
+        assert!(ray_triangle_toi(&outside, &-Vector3::z(), &a, &b, &c, false).is_none());
+        assert!(ray_triangle_toi(&origin, &Vector3::z(), &a, &b, &c, false).is_none());
+        assert!(ray_triangle_toi(&origin, &Vector3::x(), &a, &b, &c, false).is_none());
+
+        let below = Point3::new(0.25, 0.25, -2.0);
+        assert!(ray_triangle_toi(&below, &Vector3::z(), &a, &b, &c, true).is_none());
+        assert!(ray_triangle_toi(&below, &Vector3::z(), &a, &b, &c, false).is_some());
+
+        // Degenerate triangle.
+        assert!(ray_triangle_toi(&origin, &-Vector3::z(), &a, &b, &b, false).is_none());
+    }
+
+    #[test]
+    fn ray_aabb_intersection() {
+        let (mins, maxs) = (Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let origin = Point3::new(0.0, 0.0, 5.0);
+
+        assert!(ray_intersects_aabb(&origin, &-Vector3::z(), &mins, &maxs));
+        assert!(!ray_intersects_aabb(&origin, &Vector3::z(), &mins, &maxs));
+        assert!(!ray_intersects_aabb(&origin, &Vector3::x(), &mins, &maxs));
+        let inside = Point3::origin();
+        assert!(ray_intersects_aabb(&inside, &Vector3::x(), &mins, &maxs));
+    }
+}
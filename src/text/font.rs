@@ -5,14 +5,18 @@ use std::path::Path;
 use std::rc::Rc;
 use std::sync::Once;
 
+use na::Vector2;
 use rusttype;
 
 #[path = "../error.rs"]
 mod error;
 
 /// A ttf font.
+///
+/// A font can have fallback fonts, used for drawing the characters it does not have a glyph for.
 pub struct Font {
     font: rusttype::Font<'static>,
+    fallbacks: Vec<Rc<Font>>,
 }
 
 impl Font {
@@ -27,7 +31,29 @@ impl Font {
     /// Loads a new ttf font from the memory.
     pub fn from_bytes(memory: &[u8]) -> Option<Rc<Font>> {
         let font = rusttype::Font::from_bytes(memory.to_vec()).unwrap();
-        Some(Rc::new(Font { font }))
+        Some(Rc::new(Font {
+            font,
+            fallbacks: Vec::new(),
+        }))
+    }
+
+    /// Creates a font drawing the characters missing from `primary` with the first font of
+    /// `fallbacks` having a glyph for them.
+    ///
+    /// The characters missing from all those fonts are drawn with the missing glyph of `primary`.
+    /// The fallbacks of `primary` are replaced, and the fallbacks of the fonts of `fallbacks` are
+    /// not used.
+    pub fn with_fallbacks(primary: &Rc<Font>, fallbacks: &[Rc<Font>]) -> Rc<Font> {
+        Rc::new(Font {
+            font: primary.font.clone(),
+            fallbacks: fallbacks.to_vec(),
+        })
+    }
+
+    /// The fallback fonts of this font.
+    #[inline]
+    pub fn fallbacks(&self) -> &[Rc<Font>] {
+        &self.fallbacks
     }
 
     /// Instanciate a default font.
@@ -52,6 +78,99 @@ impl Font {
         &self.font
     }
 
+    /// The distance from the baseline to the top of the highest glyphs, at the given scale.
+    pub fn ascent(&self, scale: f32) -> f32 {
+        self.font.v_metrics(rusttype::Scale::uniform(scale)).ascent
+    }
+
+    /// The distance from the baseline to the bottom of the lowest glyphs, at the given scale.
+    ///
+    /// This is usually negative since the glyphs go below the baseline.
+    pub fn descent(&self, scale: f32) -> f32 {
+        self.font.v_metrics(rusttype::Scale::uniform(scale)).descent
+    }
+
+    /// The vertical distance between two consecutive lines of text, at the given scale.
+    pub fn line_height(&self, scale: f32) -> f32 {
+        let vmetrics = self.font.v_metrics(rusttype::Scale::uniform(scale));
+        vmetrics.ascent - vmetrics.descent
+    }
+
+    /// The size of `text` rendered with this font at the given scale.
+    ///
+    /// The width is the one of the longest line, including the kerning of the font if any, and
+    /// the height is the height of a line times the number of lines. This is the size of the area
+    /// covered by `draw_text`.
+    pub fn measure(&self, text: &str, scale: f32) -> Vector2<f32> {
+        let line_height = self.line_height(scale);
+        let scale = rusttype::Scale::uniform(scale);
+        let mut size = Vector2::<f32>::zeros();
+
+        for line in text.lines() {
+            size.x = size.x.max(self.line_width(line, scale));
+            size.y += line_height;
+        }
+
+        size
+    }
+
+    // The horizontal advance of a line of text.
+    pub(crate) fn line_width(&self, line: &str, scale: rusttype::Scale) -> f32 {
+        self.layout(line, scale, rusttype::point(0.0, 0.0))
+            .last()
+            .map(|(_, g)| g.position().x + g.unpositioned().h_metrics().advance_width)
+            .unwrap_or(0.0)
+    }
+
+    // Positions the glyphs of a line of text starting at `origin`, each with the unique
+    // identifier of the font it comes from.
+    pub(crate) fn layout(
+        &self,
+        line: &str,
+        scale: rusttype::Scale,
+        origin: rusttype::Point<f32>,
+    ) -> Vec<(usize, rusttype::PositionedGlyph<'static>)> {
+        let mut res = Vec::with_capacity(line.len());
+        let mut caret = 0.0;
+        let mut last: Option<(usize, rusttype::GlyphId)> = None;
+
+        for c in line.chars() {
+            let (uid, face) = self.face_for(c);
+            let glyph = face.glyph(c).scaled(scale);
+
+            // There is no kerning between glyphs of different fonts.
+            if let Some((last_uid, last_id)) = last {
+                if last_uid == uid {
+                    caret += face.pair_kerning(scale, last_id, glyph.id());
+                }
+            }
+
+            let advance = glyph.h_metrics().advance_width;
+            last = Some((uid, glyph.id()));
+            res.push((
+                uid,
+                glyph.positioned(rusttype::point(origin.x + caret, origin.y)),
+            ));
+            caret += advance;
+        }
+
+        res
+    }
+
+    // The font used to draw `c`, with its unique identifier.
+    fn face_for(&self, c: char) -> (usize, &rusttype::Font<'static>) {
+        // The glyph 0 is the missing glyph.
+        if self.font.glyph(c).id().0 == 0 {
+            for fallback in self.fallbacks.iter() {
+                if fallback.font.glyph(c).id().0 != 0 {
+                    return (Font::uid(fallback), &fallback.font);
+                }
+            }
+        }
+
+        (self as *const Font as usize, &self.font)
+    }
+
     /// The unique identifier of the specified font instance.
     #[inline]
     pub fn uid(font: &Rc<Font>) -> usize {
@@ -2,8 +2,9 @@
 
 pub use crate::text::font::Font;
 pub use crate::text::glyph::Glyph;
-pub use crate::text::renderer::TextRenderer;
+pub use crate::text::renderer::{HAlign, TextRenderer, VAlign};
 
 mod font;
 mod glyph;
 mod renderer;
+mod sdf;
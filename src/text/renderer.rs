@@ -9,16 +9,41 @@ use std::rc::Rc;
 
 use crate::context::{Context, Texture};
 use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
+use crate::text::sdf::{SdfAtlas, SDF_SCALE, SDF_SPREAD};
 use crate::text::Font;
 
 #[path = "../error.rs"]
 mod error;
 
+/// The horizontal alignment of the lines of a text relative to its position.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HAlign {
+    /// The lines start at the position.
+    Left,
+    /// The lines are centered on the position.
+    Center,
+    /// The lines end at the position.
+    Right,
+}
+
+/// The vertical alignment of a text relative to its position.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VAlign {
+    /// The top of the first line is at the position.
+    Top,
+    /// The text is centered on the position.
+    Middle,
+    /// The bottom of the last line is at the position.
+    Bottom,
+}
+
 struct TextRenderContext {
     len: usize,
     scale: f32,
+    align: HAlign,
     color: Point3<f32>,
     pos: Point2<f32>,
+    depth: f32,
     font: Rc<Font>,
 }
 
@@ -31,10 +56,20 @@ pub struct TextRenderer {
     invsz: ShaderUniform<Vector2<f32>>,
     tex: ShaderUniform<i32>,
     color: ShaderUniform<Point3<f32>>,
+    depth: ShaderUniform<f32>,
+    smoothing: ShaderUniform<f32>,
+    outline: ShaderUniform<Vector2<f32>>,
+    outline_color: ShaderUniform<Point3<f32>>,
     pos: ShaderAttribute<Point2<f32>>,
     uvs: ShaderAttribute<Point2<f32>>,
     contexts: Vec<TextRenderContext>,
     coords: GPUVec<Point2<f32>>,
+    depth_test: bool,
+    // The atlas the glyphs are drawn from if distance field rendering is enabled.
+    sdf: Option<SdfAtlas>,
+    // The width and softness of the outline of distance field glyphs, relative to their scale.
+    sdf_outline: Vector2<f32>,
+    sdf_outline_color: Point3<f32>,
 }
 
 impl TextRenderer {
@@ -109,17 +144,82 @@ impl TextRenderer {
             invsz: shader.get_uniform("invsz").expect("Could not find invsz"),
             tex: shader.get_uniform("tex0").expect("Could not find tex0"),
             color: shader.get_uniform("color").expect("Could not find color"),
+            depth: shader.get_uniform("depth").expect("Could not find depth"),
+            smoothing: shader
+                .get_uniform("smoothing")
+                .expect("Could not find smoothing"),
+            outline: shader
+                .get_uniform("outline")
+                .expect("Could not find outline"),
+            outline_color: shader
+                .get_uniform("outline_color")
+                .expect("Could not find outline_color"),
             pos: shader.get_attrib("pos").expect("Could not find pos"),
             uvs: shader.get_attrib("uvs").expect("Could not find uvs"),
             shader: shader,
             contexts: Vec::new(),
             coords: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
+            depth_test: false,
+            sdf: None,
+            sdf_outline: Vector2::zeros(),
+            sdf_outline_color: Point3::origin(),
         }
     }
 
+    /// Whether the glyphs are drawn from signed distance fields.
+    #[inline]
+    pub fn sdf(&self) -> bool {
+        self.sdf.is_some()
+    }
+
+    /// Sets whether the glyphs are drawn from signed distance fields instead of bitmaps.
+    ///
+    /// The distance to the outline of each glyph is baked once at a fixed scale, so the glyphs
+    /// stay sharp at any scale, at the cost of slightly rounded corners. They can also be
+    /// outlined with `set_sdf_outline`. This is disabled by default, since bitmaps look best for
+    /// small texts.
+    pub fn set_sdf(&mut self, enabled: bool) {
+        if enabled != self.sdf.is_some() {
+            self.sdf = if enabled { Some(SdfAtlas::new()) } else { None };
+        }
+    }
+
+    /// Sets the outline drawn around the glyphs when they are drawn from signed distance fields.
+    ///
+    /// The `width` of the outline is relative to the scale of the text, e.g., `0.05` for an
+    /// outline as wide as 5% of the height of a line, and the outline fades out over `softness`
+    /// beyond it, which gives a glow for large values. Their sum should not exceed `0.125`, the
+    /// largest distance to the glyphs stored by the distance fields. The outline is disabled by
+    /// setting both to `0.0`, which is the default.
+    pub fn set_sdf_outline(&mut self, width: f32, softness: f32, color: &Point3<f32>) {
+        self.sdf_outline = Vector2::new(width.max(0.0), softness.max(0.0));
+        self.sdf_outline_color = *color;
+    }
+
+    /// Whether the text is hidden by what has been drawn in front of it.
+    ///
+    /// This is `false` by default, so the text is drawn over everything else.
+    #[inline]
+    pub fn depth_test(&self) -> bool {
+        self.depth_test
+    }
+
+    /// Sets whether the text is hidden by what has been drawn in front of it.
+    ///
+    /// The depth of each text is given by `draw_text_at_depth`, and is compared to the depth
+    /// buffer of the framebuffer bound when `render` is called. The text itself does not write to
+    /// the depth buffer.
+    #[inline]
+    pub fn set_depth_test(&mut self, enabled: bool) {
+        self.depth_test = enabled
+    }
+
     /// Adds a piece of text to be drawn during the next frame. The text is not persistent between
     /// frames. This method must be called for each text to draw, and at each update loop
     /// iteration.
+    ///
+    /// The top-left corner of the text is at `pos`, and each line of the text is drawn below the
+    /// previous one.
     pub fn draw_text(
         &mut self,
         text: &str,
@@ -128,12 +228,59 @@ impl TextRenderer {
         font: &Rc<Font>,
         color: &Point3<f32>,
     ) {
+        self.draw_text_aligned(text, pos, scale, font, color, HAlign::Left, VAlign::Top)
+    }
+
+    /// Adds a piece of text to be drawn during the next frame, aligned relative to `pos`.
+    ///
+    /// Each line is aligned horizontally on its own. See `draw_text` for more details.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text_aligned(
+        &mut self,
+        text: &str,
+        pos: &Point2<f32>,
+        scale: f32,
+        font: &Rc<Font>,
+        color: &Point3<f32>,
+        h_align: HAlign,
+        v_align: VAlign,
+    ) {
+        self.draw_text_at_depth(text, pos, -1.0, scale, font, color, h_align, v_align)
+    }
+
+    /// Adds a piece of text to be drawn during the next frame at the given depth.
+    ///
+    /// The depth is in normalized device coordinates, from `-1.0` on the near plane to `1.0` on
+    /// the far plane, and is only used if the depth test is enabled with `set_depth_test`. See
+    /// `draw_text_aligned` for more details.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text_at_depth(
+        &mut self,
+        text: &str,
+        pos: &Point2<f32>,
+        depth: f32,
+        scale: f32,
+        font: &Rc<Font>,
+        color: &Point3<f32>,
+        h_align: HAlign,
+        v_align: VAlign,
+    ) {
+        let mut pos = *pos;
+
+        match v_align {
+            VAlign::Top => {}
+            VAlign::Middle => pos.y -= font.measure(text, scale).y / 2.0,
+            VAlign::Bottom => pos.y -= font.measure(text, scale).y,
+        }
+
         self.text.push_str(text);
         self.contexts.push(TextRenderContext {
             len: text.len(),
             scale,
+            align: h_align,
             color: *color,
-            pos: *pos,
+            pos,
+            depth,
             font: font.clone(),
         })
     }
@@ -155,14 +302,28 @@ impl TextRenderer {
             Context::ONE,
             Context::ONE_MINUS_SRC_ALPHA,
         ));
-        verify!(ctxt.disable(Context::DEPTH_TEST));
+
+        if self.depth_test {
+            verify!(ctxt.enable(Context::DEPTH_TEST));
+            verify!(ctxt.depth_mask(false));
+        } else {
+            verify!(ctxt.disable(Context::DEPTH_TEST));
+        }
 
         self.pos.enable();
         self.uvs.enable();
         self.tex.upload(&0);
         self.invsz.upload(&Vector2::new(1.0 / width, -1.0 / height));
 
-        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self.texture)));
+        // The distance field glyphs are drawn with their own texture.
+        let texture = match self.sdf {
+            Some(ref mut atlas) => {
+                atlas.clear_if_full();
+                atlas.texture()
+            }
+            None => &self.texture,
+        };
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(texture)));
         verify!(ctxt.tex_parameteri(
             Context::TEXTURE_2D,
             Context::TEXTURE_WRAP_S,
@@ -174,6 +335,11 @@ impl TextRenderer {
             Context::CLAMP_TO_EDGE as i32
         ));
 
+        // The distances are mapped to `[0, 1]` over twice the spread at the baking scale.
+        let distance_scale = SDF_SCALE / (2.0 * SDF_SPREAD as f32);
+        self.outline.upload(&(self.sdf_outline * distance_scale));
+        self.outline_color.upload(&self.sdf_outline_color);
+
         let mut pos = 0;
 
         for context in self.contexts.iter() {
@@ -181,70 +347,82 @@ impl TextRenderer {
             let vmetrics = context.font.font().v_metrics(scale);
             let line_height = vmetrics.ascent - vmetrics.descent;
             let text = &self.text[pos..pos + context.len];
-            let font_uid = Font::uid(&context.font);
             let mut vshift = 0.0;
+            // Half the width of the antialiased border of the distance field glyphs, in distance
+            // units, or zero for bitmap glyphs.
+            let smoothing = if self.sdf.is_some() {
+                0.7 * SDF_SCALE / (2.0 * SDF_SPREAD as f32 * context.scale)
+            } else {
+                0.0
+            };
 
             for line in text.lines() {
+                let shift = match context.align {
+                    HAlign::Left => 0.0,
+                    HAlign::Center => context.font.line_width(line, scale) / 2.0,
+                    HAlign::Right => context.font.line_width(line, scale),
+                };
                 let orig = rusttype::Point {
-                    x: context.pos.x,
+                    x: context.pos.x - shift,
                     y: context.pos.y + vshift,
                 };
 
                 vshift += line_height as f32;
-                let layout = context.font.font().layout(line, scale, orig);
-
-                for glyph in layout {
-                    let gly: rusttype::PositionedGlyph<'static> = context
-                        .font
-                        .font()
-                        .glyph(glyph.id())
-                        .scaled(glyph.scale())
-                        .positioned(glyph.position());
-                    self.cache.queue_glyph(font_uid, gly); // FIXME: is the call to `.standalone()` costly?
-                }
+                let layout = context.font.layout(line, scale, orig);
+
+                let coords = self.coords.data_mut().as_mut().unwrap();
+
+                match self.sdf {
+                    Some(ref mut atlas) => {
+                        let ratio = context.scale / SDF_SCALE;
+
+                        for (font_uid, glyph) in layout.iter() {
+                            if let Some(baked) = atlas.glyph(*font_uid, glyph) {
+                                let origin = glyph.position();
+                                let min = Point2::new(
+                                    origin.x + baked.bounds.min.x * ratio,
+                                    origin.y + baked.bounds.min.y * ratio + vmetrics.ascent,
+                                );
+                                let max = Point2::new(
+                                    origin.x + baked.bounds.max.x * ratio,
+                                    origin.y + baked.bounds.max.y * ratio + vmetrics.ascent,
+                                );
+
+                                push_quad(coords, min, max, &baked.uvs);
+                            }
+                        }
+                    }
+                    None => {
+                        for (font_uid, glyph) in layout.iter() {
+                            self.cache.queue_glyph(*font_uid, glyph.clone());
+                        }
 
-                let _ = self.cache.cache_queued(|rect, data| {
-                    verify!(ctxt.tex_sub_image2d(
-                        Context::TEXTURE_2D,
-                        0,
-                        rect.min.x as i32,
-                        rect.min.y as i32,
-                        rect.width() as i32,
-                        rect.height() as i32,
-                        Context::RED,
-                        Some(&data)
-                    ));
-                });
-
-                let layout = context.font.font().layout(line, scale, orig);
-
-                {
-                    let coords = self.coords.data_mut().as_mut().unwrap();
-                    for glyph in layout {
-                        if let Some(Some((tex, rect))) = self.cache.rect_for(font_uid, &glyph).ok()
-                        {
-                            let min_px = rect.min.x as f32;
-                            let min_py = rect.min.y as f32 + vmetrics.ascent;
-                            let max_px = rect.max.x as f32;
-                            let max_py = rect.max.y as f32 + vmetrics.ascent;
-
-                            coords.push(Point2::new(min_px, min_py));
-                            coords.push(Point2::new(tex.min.x, tex.min.y));
-
-                            coords.push(Point2::new(min_px, max_py));
-                            coords.push(Point2::new(tex.min.x, tex.max.y));
-
-                            coords.push(Point2::new(max_px, min_py));
-                            coords.push(Point2::new(tex.max.x, tex.min.y));
-
-                            coords.push(Point2::new(max_px, min_py));
-                            coords.push(Point2::new(tex.max.x, tex.min.y));
-
-                            coords.push(Point2::new(min_px, max_py));
-                            coords.push(Point2::new(tex.min.x, tex.max.y));
-
-                            coords.push(Point2::new(max_px, max_py));
-                            coords.push(Point2::new(tex.max.x, tex.max.y));
+                        let _ = self.cache.cache_queued(|rect, data| {
+                            verify!(ctxt.tex_sub_image2d(
+                                Context::TEXTURE_2D,
+                                0,
+                                rect.min.x as i32,
+                                rect.min.y as i32,
+                                rect.width() as i32,
+                                rect.height() as i32,
+                                Context::RED,
+                                Some(data)
+                            ));
+                        });
+
+                        for (font_uid, glyph) in layout.iter() {
+                            if let Ok(Some((tex, rect))) = self.cache.rect_for(*font_uid, glyph) {
+                                let min = Point2::new(
+                                    rect.min.x as f32,
+                                    rect.min.y as f32 + vmetrics.ascent,
+                                );
+                                let max = Point2::new(
+                                    rect.max.x as f32,
+                                    rect.max.y as f32 + vmetrics.ascent,
+                                );
+
+                                push_quad(coords, min, max, &tex);
+                            }
                         }
                     }
                 }
@@ -252,6 +430,8 @@ impl TextRenderer {
                 self.pos.bind_sub_buffer(&mut self.coords, 1, 0);
                 self.uvs.bind_sub_buffer(&mut self.coords, 1, 1);
                 self.color.upload(&context.color);
+                self.depth.upload(&context.depth);
+                self.smoothing.upload(&smoothing);
 
                 verify!(ctxt.draw_arrays(Context::TRIANGLES, 0, (self.coords.len() / 2) as i32));
             }
@@ -263,6 +443,7 @@ impl TextRenderer {
         self.pos.disable();
         self.uvs.enable();
 
+        verify!(ctxt.depth_mask(true));
         verify!(ctxt.enable(Context::DEPTH_TEST));
         verify!(ctxt.disable(Context::BLEND));
 
@@ -271,6 +452,29 @@ impl TextRenderer {
     }
 }
 
+// Pushes the two triangles covering the rectangle from `min` to `max`, and their texture
+// coordinates.
+fn push_quad(
+    coords: &mut Vec<Point2<f32>>,
+    min: Point2<f32>,
+    max: Point2<f32>,
+    tex: &rusttype::Rect<f32>,
+) {
+    let corners = [
+        (min.x, min.y, tex.min.x, tex.min.y),
+        (min.x, max.y, tex.min.x, tex.max.y),
+        (max.x, min.y, tex.max.x, tex.min.y),
+        (max.x, min.y, tex.max.x, tex.min.y),
+        (min.x, max.y, tex.min.x, tex.max.y),
+        (max.x, max.y, tex.max.x, tex.max.y),
+    ];
+
+    for &(x, y, u, v) in corners.iter() {
+        coords.push(Point2::new(x, y));
+        coords.push(Point2::new(u, v));
+    }
+}
+
 /// Vertex shader used by the material to display line.
 pub static TEXT_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
 /// Fragment shader used by the material to display line.
@@ -281,6 +485,7 @@ const A_VERY_LONG_STRING: &'static str = "
 
 uniform vec2 invsz;
 uniform vec3 color;
+uniform float depth;
 
 attribute vec2 pos;
 attribute vec2 uvs;
@@ -289,7 +494,7 @@ varying vec2 tex;
 varying vec3 Color;
 
 void main() {
-    gl_Position = vec4(pos.x * invsz.x - 1.0, pos.y * invsz.y + 1.0, -1.0, 1.0);
+    gl_Position = vec4(pos.x * invsz.x - 1.0, pos.y * invsz.y + 1.0, depth, 1.0);
     tex         = uvs;
     Color       = color;
 }
@@ -305,11 +510,25 @@ const ANOTHER_VERY_LONG_STRING: &'static str = "
 #endif
 
 uniform sampler2D tex0;
+uniform float smoothing;
+uniform vec2 outline;
+uniform vec3 outline_color;
 
 varying vec2 tex;
 varying vec3 Color;
 
 void main() {
-    gl_FragColor = vec4(Color, texture2D(tex0, tex).r);
+    float value = texture2D(tex0, tex).r;
+
+    if (smoothing > 0.0) {
+        // The texture contains the distance to the outline of the glyphs, which is at 0.5.
+        float fill = smoothstep(0.5 - smoothing, 0.5 + smoothing, value);
+        float edge = 0.5 - outline.x;
+        float alpha = smoothstep(edge - smoothing - outline.y, edge + smoothing, value);
+        vec3 color = outline.x + outline.y > 0.0 ? mix(outline_color, Color, fill) : Color;
+        gl_FragColor = vec4(color, alpha);
+    } else {
+        gl_FragColor = vec4(Color, value);
+    }
 }
 ";
@@ -0,0 +1,218 @@
+//! Glyphs baked into a signed distance field atlas.
+
+use rusttype;
+use rusttype::{GlyphId, PositionedGlyph, Rect};
+use std::collections::HashMap;
+
+use crate::context::{Context, Texture};
+
+#[path = "../error.rs"]
+mod error;
+
+/// The scale the glyphs are baked at.
+pub(crate) const SDF_SCALE: f32 = 48.0;
+/// The largest distance to the outline of a glyph stored in the atlas, in pixels at `SDF_SCALE`.
+pub(crate) const SDF_SPREAD: i32 = 6;
+
+const ATLAS_SIZE: i32 = 1024;
+// The empty space between two glyphs of the atlas.
+const GAP: i32 = 1;
+
+// A glyph baked into the atlas.
+pub(crate) struct SdfGlyph {
+    // The texture coordinates of the glyph.
+    pub uvs: Rect<f32>,
+    // The rectangle covered by the glyph relative to its origin, in pixels at `SDF_SCALE`.
+    pub bounds: Rect<f32>,
+}
+
+// A texture containing the distance field of glyphs, baked the first time they are drawn.
+//
+// The distance to the outline of a glyph is mapped to `[0, 1]`, with the outline at `0.5`,
+// values above it inside the glyph, and values below it outside.
+pub(crate) struct SdfAtlas {
+    texture: Texture,
+    // The glyphs without any outline, e.g., spaces, are `None`.
+    glyphs: HashMap<(usize, GlyphId), Option<SdfGlyph>>,
+    cursor: (i32, i32),
+    row_height: i32,
+    full: bool,
+}
+
+impl SdfAtlas {
+    pub fn new() -> SdfAtlas {
+        let ctxt = Context::get();
+        let texture = verify!(ctxt
+            .create_texture()
+            .expect("Font texture creation failed."));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&texture)));
+        verify!(ctxt.tex_image2d(
+            Context::TEXTURE_2D,
+            0,
+            Context::RED as i32,
+            ATLAS_SIZE,
+            ATLAS_SIZE,
+            0,
+            Context::RED,
+            None
+        ));
+
+        for &(param, value) in &[
+            (Context::TEXTURE_WRAP_S, Context::CLAMP_TO_EDGE),
+            (Context::TEXTURE_WRAP_T, Context::CLAMP_TO_EDGE),
+            (Context::TEXTURE_MIN_FILTER, Context::LINEAR),
+            (Context::TEXTURE_MAG_FILTER, Context::LINEAR),
+        ] {
+            verify!(ctxt.tex_parameteri(Context::TEXTURE_2D, param, value as i32));
+        }
+
+        SdfAtlas {
+            texture,
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            row_height: 0,
+            full: false,
+        }
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    // Empties the atlas if some glyphs did not fit in it since the last call, so the glyphs still
+    // in use are baked again.
+    pub fn clear_if_full(&mut self) {
+        if self.full {
+            self.glyphs.clear();
+            self.cursor = (0, 0);
+            self.row_height = 0;
+            self.full = false;
+        }
+    }
+
+    // The baked glyph, or `None` if it has no outline or the atlas is full.
+    //
+    // The atlas texture must be bound.
+    pub fn glyph(
+        &mut self,
+        font_uid: usize,
+        glyph: &PositionedGlyph<'static>,
+    ) -> Option<&SdfGlyph> {
+        let key = (font_uid, glyph.id());
+
+        if !self.glyphs.contains_key(&key) {
+            let base = glyph
+                .unpositioned()
+                .unscaled()
+                .clone()
+                .scaled(rusttype::Scale::uniform(SDF_SCALE))
+                .positioned(rusttype::point(0.0, 0.0));
+            let baked = match base.pixel_bounding_box() {
+                Some(bb) => Some(self.bake(&base, bb)?),
+                None => None,
+            };
+
+            let _ = self.glyphs.insert(key, baked);
+        }
+
+        self.glyphs[&key].as_ref()
+    }
+
+    fn bake(&mut self, glyph: &PositionedGlyph<'static>, bb: Rect<i32>) -> Option<SdfGlyph> {
+        let (w, h) = (bb.width(), bb.height());
+        let (pw, ph) = (w + 2 * SDF_SPREAD, h + 2 * SDF_SPREAD);
+
+        if self.cursor.0 + pw > ATLAS_SIZE {
+            self.cursor = (0, self.cursor.1 + self.row_height + GAP);
+            self.row_height = 0;
+        }
+
+        if pw > ATLAS_SIZE || self.cursor.1 + ph > ATLAS_SIZE {
+            if !self.full {
+                println!(
+                    "Warning: the atlas of distance field glyphs is full, it will be rebuilt."
+                );
+            }
+
+            self.full = true;
+            return None;
+        }
+
+        let mut inside = vec![false; (w * h) as usize];
+        glyph.draw(|x, y, v| inside[(y as i32 * w + x as i32) as usize] = v >= 0.5);
+
+        let is_inside =
+            |x: i32, y: i32| x >= 0 && y >= 0 && x < w && y < h && inside[(y * w + x) as usize];
+
+        // The distance from each pixel to the closest pixel on the other side of the outline,
+        // searched up to the spread.
+        let mut field = Vec::with_capacity((pw * ph) as usize);
+
+        for y in -SDF_SPREAD..h + SDF_SPREAD {
+            for x in -SDF_SPREAD..w + SDF_SPREAD {
+                let side = is_inside(x, y);
+                let mut dist2 = (SDF_SPREAD * SDF_SPREAD) as f32;
+
+                for dy in -SDF_SPREAD..=SDF_SPREAD {
+                    for dx in -SDF_SPREAD..=SDF_SPREAD {
+                        if is_inside(x + dx, y + dy) != side {
+                            dist2 = dist2.min((dx * dx + dy * dy) as f32);
+                        }
+                    }
+                }
+
+                // The outline is halfway between the two pixels.
+                let dist = (dist2.sqrt() - 0.5).max(0.0);
+                let signed = if side { dist } else { -dist };
+                let value = 0.5 + signed / (2.0 * SDF_SPREAD as f32);
+                field.push((value.clamp(0.0, 1.0) * 255.0) as u8);
+            }
+        }
+
+        let ctxt = Context::get();
+        let (x, y) = self.cursor;
+        verify!(ctxt.pixel_storei(Context::UNPACK_ALIGNMENT, 1));
+        verify!(ctxt.tex_sub_image2d(
+            Context::TEXTURE_2D,
+            0,
+            x,
+            y,
+            pw,
+            ph,
+            Context::RED,
+            Some(&field)
+        ));
+
+        self.cursor.0 += pw + GAP;
+        self.row_height = self.row_height.max(ph);
+
+        let size = ATLAS_SIZE as f32;
+
+        Some(SdfGlyph {
+            uvs: Rect {
+                min: rusttype::point(x as f32 / size, y as f32 / size),
+                max: rusttype::point((x + pw) as f32 / size, (y + ph) as f32 / size),
+            },
+            bounds: Rect {
+                min: rusttype::point(
+                    (bb.min.x - SDF_SPREAD) as f32,
+                    (bb.min.y - SDF_SPREAD) as f32,
+                ),
+                max: rusttype::point(
+                    (bb.max.x + SDF_SPREAD) as f32,
+                    (bb.max.y + SDF_SPREAD) as f32,
+                ),
+            },
+        })
+    }
+}
+
+impl Drop for SdfAtlas {
+    fn drop(&mut self) {
+        let ctxt = Context::get();
+
+        if verify!(ctxt.is_texture(Some(&self.texture))) {
+            verify!(ctxt.delete_texture(Some(&self.texture)));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::io::Result as IoResult;
 use std::sync::mpsc::Sender;
 
 use crate::event::{Action, Key, MouseButton, WindowEvent};
@@ -69,6 +70,21 @@ impl Canvas {
         }
     }
 
+    /// Initialize an OpenGL context without any window, rendering to an off-screen framebuffer.
+    ///
+    /// This uses OSMesa on Linux and BSD, which does not require any display server, and a
+    /// headless glutin context on Windows and macOS. This fails on the web, and if the backend is
+    /// not available, e.g., if the OSMesa library is not installed.
+    pub fn open_headless(
+        width: u32,
+        height: u32,
+        canvas_setup: Option<CanvasSetup>,
+        out_events: Sender<WindowEvent>,
+    ) -> IoResult<Self> {
+        let canvas = CanvasImpl::open_headless(width, height, canvas_setup, out_events)?;
+        Ok(Canvas { canvas })
+    }
+
     /// Run the platform-specific render loop.
     pub fn render_loop(data: impl FnMut(f64) -> bool + 'static) {
         CanvasImpl::render_loop(data)
@@ -84,12 +100,20 @@ impl Canvas {
         self.canvas.swap_buffers()
     }
 
-    /// The size of the window.
+    /// Enables or disables the synchronization of the buffer swaps with the display.
+    ///
+    /// Returns `false` if this is not supported by the platform or the OpenGL driver.
+    pub fn set_vsync(&mut self, vsync: bool) -> bool {
+        self.canvas.set_vsync(vsync)
+    }
+
+    /// The size of the window, in physical pixels.
     pub fn size(&self) -> (u32, u32) {
         self.canvas.size()
     }
 
-    /// The current position of the cursor, if known.
+    /// The current position of the cursor from the top-left corner of the window, in physical
+    /// pixels, if known.
     ///
     /// This position may not be known if, e.g., the cursor has not been moved since the
     /// window was open.
@@ -97,7 +121,7 @@ impl Canvas {
         self.canvas.cursor_pos()
     }
 
-    /// The scale factor.
+    /// The number of physical pixels per logical pixel.
     pub fn scale_factor(&self) -> f64 {
         self.canvas.scale_factor()
     }
@@ -113,14 +137,26 @@ impl Canvas {
     }
 
     /// Set the cursor grabbing behaviour.
-    pub fn set_cursor_grab(&self, grab: bool) {
-        self.canvas.set_cursor_grab(grab);
+    ///
+    /// Returns an error if this is not supported on this platform.
+    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), &'static str> {
+        self.canvas.set_cursor_grab(grab)
     }
 
     pub fn set_cursor_position(&self, x: f64, y: f64) {
         self.canvas.set_cursor_position(x, y);
     }
 
+    /// The text content of the clipboard. See `Window::get_clipboard` for details.
+    pub fn get_clipboard(&self) -> Option<String> {
+        self.canvas.get_clipboard()
+    }
+
+    /// Sets the text content of the clipboard. See `Window::set_clipboard` for details.
+    pub fn set_clipboard(&self, text: &str) -> Result<(), String> {
+        self.canvas.set_clipboard(text)
+    }
+
     pub fn hide_cursor(&self, hide: bool) {
         self.canvas.hide_cursor(hide);
     }
@@ -135,6 +171,24 @@ impl Canvas {
         self.canvas.show()
     }
 
+    /// The names of the monitors available, in the order of their indices.
+    pub fn monitor_names(&self) -> Vec<String> {
+        self.canvas.monitor_names()
+    }
+
+    /// Whether the window is fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        self.canvas.is_fullscreen()
+    }
+
+    /// Makes the window borderless fullscreen on the given monitor, or on its current monitor if
+    /// `monitor` is `None`, or restores it to its previous size and position.
+    ///
+    /// Returns `false` if this is not supported or if there is no monitor with the given index.
+    pub fn set_fullscreen(&mut self, fullscreen: bool, monitor: Option<usize>) -> bool {
+        self.canvas.set_fullscreen(fullscreen, monitor)
+    }
+
     /// The state of a mouse button.
     pub fn get_mouse_button(&self, button: MouseButton) -> Action {
         self.canvas.get_mouse_button(button)
@@ -146,7 +200,7 @@ impl Canvas {
     }
 }
 
-pub(crate) trait AbstractCanvas {
+pub(crate) trait AbstractCanvas: Sized {
     fn open(
         title: &str,
         hide: bool,
@@ -155,20 +209,32 @@ pub(crate) trait AbstractCanvas {
         window_setup: Option<CanvasSetup>,
         out_events: Sender<WindowEvent>,
     ) -> Self;
+    fn open_headless(
+        width: u32,
+        height: u32,
+        window_setup: Option<CanvasSetup>,
+        out_events: Sender<WindowEvent>,
+    ) -> IoResult<Self>;
     fn render_loop(data: impl FnMut(f64) -> bool + 'static);
     fn poll_events(&mut self);
     fn swap_buffers(&mut self);
+    fn set_vsync(&mut self, vsync: bool) -> bool;
     fn size(&self) -> (u32, u32);
     fn cursor_pos(&self) -> Option<(f64, f64)>;
     fn scale_factor(&self) -> f64;
 
     fn set_title(&mut self, title: &str);
     fn set_icon(&mut self, icon: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>);
-    fn set_cursor_grab(&self, grab: bool);
+    fn set_cursor_grab(&self, grab: bool) -> Result<(), &'static str>;
     fn set_cursor_position(&self, x: f64, y: f64);
     fn hide_cursor(&self, hide: bool);
+    fn get_clipboard(&self) -> Option<String>;
+    fn set_clipboard(&self, text: &str) -> Result<(), String>;
     fn hide(&mut self);
     fn show(&mut self);
+    fn monitor_names(&self) -> Vec<String>;
+    fn is_fullscreen(&self) -> bool;
+    fn set_fullscreen(&mut self, fullscreen: bool, monitor: Option<usize>) -> bool;
 
     fn get_mouse_button(&self, button: MouseButton) -> Action;
     fn get_key(&self, key: Key) -> Action;
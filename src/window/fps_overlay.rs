@@ -0,0 +1,135 @@
+//! A text overlay displaying the framerate.
+
+use std::rc::Rc;
+use std::time::Duration;
+
+use na::{Point2, Point3};
+
+use crate::text::{Font, TextRenderer};
+
+// The size of the text, in logical pixels.
+const TEXT_SIZE: f32 = 20.0;
+// The space between the text and the borders of the window, in logical pixels.
+const MARGIN: f32 = 5.0;
+// The displayed values are refreshed at this rate so they stay readable.
+const REFRESH_PERIOD: Duration = Duration::from_millis(500);
+
+/// A corner of the window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Corner {
+    /// The top-left corner.
+    TopLeft,
+    /// The top-right corner.
+    TopRight,
+    /// The bottom-left corner.
+    BottomLeft,
+    /// The bottom-right corner.
+    BottomRight,
+}
+
+// The state of the framerate overlay of a window.
+pub(crate) struct FpsOverlay {
+    visible: bool,
+    corner: Corner,
+    font: Option<Rc<Font>>,
+    // The frames since the last refresh of the text.
+    frames: u32,
+    time: Duration,
+    text: String,
+}
+
+impl FpsOverlay {
+    pub fn new() -> FpsOverlay {
+        FpsOverlay {
+            visible: false,
+            corner: Corner::TopLeft,
+            font: None,
+            frames: 0,
+            time: Duration::from_secs(0),
+            text: String::new(),
+        }
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        if visible && !self.visible {
+            self.frames = 0;
+            self.time = Duration::from_secs(0);
+            self.text = "... FPS".to_string();
+        }
+
+        self.visible = visible;
+    }
+
+    pub fn corner(&self) -> Corner {
+        self.corner
+    }
+
+    pub fn set_corner(&mut self, corner: Corner) {
+        self.corner = corner
+    }
+
+    // Accounts for a frame which lasted `frame_delta`.
+    pub fn update(&mut self, frame_delta: Duration, smoothed_frame_delta: Duration) {
+        if !self.visible {
+            return;
+        }
+
+        self.frames += 1;
+        self.time += frame_delta;
+
+        if self.time >= REFRESH_PERIOD {
+            let average = self.frames as f32 / self.time.as_secs_f32();
+            let instant = 1.0 / frame_delta.as_secs_f32().max(1.0e-6);
+
+            self.text = format!(
+                "{:.0} FPS (avg. {:.0})\n{:.2} ms",
+                instant,
+                average,
+                smoothed_frame_delta.as_secs_f32() * 1000.0
+            );
+            self.frames = 0;
+            self.time = Duration::from_secs(0);
+        }
+    }
+
+    // Queues the text of the overlay for the window of size `width` by `height` pixels.
+    pub fn draw(
+        &mut self,
+        text_renderer: &mut TextRenderer,
+        width: f32,
+        height: f32,
+        scale_factor: f32,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let font = self.font.get_or_insert_with(Font::default);
+        let size = TEXT_SIZE * scale_factor;
+        let margin = MARGIN * scale_factor;
+
+        let text_size = font.measure(&self.text, size);
+        let (text_width, text_height) = (text_size.x, text_size.y);
+
+        let x = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => margin,
+            Corner::TopRight | Corner::BottomRight => width - text_width - margin,
+        };
+        let y = match self.corner {
+            Corner::TopLeft | Corner::TopRight => margin,
+            Corner::BottomLeft | Corner::BottomRight => height - text_height - margin,
+        };
+
+        text_renderer.draw_text(
+            &self.text,
+            &Point2::new(x, y),
+            size,
+            font,
+            &Point3::new(1.0, 1.0, 0.0),
+        );
+    }
+}
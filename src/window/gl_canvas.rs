@@ -1,31 +1,146 @@
+#[cfg(feature = "clipboard")]
+use std::cell::RefCell;
+use std::io::{Error, Result as IoResult};
 use std::sync::mpsc::Sender;
 
 use crate::context::Context;
 use crate::event::{Action, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
+#[cfg(feature = "gamepad")]
+use crate::event::{GamepadAxis, GamepadButton};
 use crate::window::canvas::{CanvasSetup, NumSamples};
 use crate::window::AbstractCanvas;
+#[cfg(feature = "clipboard")]
+use copypasta::{ClipboardContext, ClipboardProvider};
 use glutin::{
     self,
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     event::TouchPhase,
     event_loop::{ControlFlow, EventLoop},
     platform::run_return::EventLoopExtRunReturn,
-    window::WindowBuilder,
+    window::{Fullscreen, WindowBuilder},
     ContextBuilder, GlRequest, PossiblyCurrent, WindowedContext,
 };
 use image::{GenericImage, Pixel};
 
+// The OpenGL context of a canvas, with or without a window.
+enum GLWindow {
+    Windowed(WindowedContext<PossiblyCurrent>),
+    Headless {
+        // Kept alive as long as the canvas.
+        _context: glutin::Context<PossiblyCurrent>,
+        // The size of the default framebuffer of the context.
+        size: (u32, u32),
+    },
+}
+
 /// A canvas based on glutin and OpenGL.
 pub struct GLCanvas {
-    window: WindowedContext<PossiblyCurrent>,
-    events: EventLoop<()>,
+    window: GLWindow,
+    // `None` for headless canvases, which do not receive any events.
+    events: Option<EventLoop<()>>,
     cursor_pos: Option<(f64, f64)>,
     key_states: [Action; Key::Unknown as usize + 1],
     button_states: [Action; MouseButton::Button8 as usize + 1],
     out_events: Sender<WindowEvent>,
+    focused: bool,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<gilrs::Gilrs>,
+    // The position and size of the window before it became fullscreen.
+    windowed_geometry: Option<(Option<PhysicalPosition<i32>>, PhysicalSize<u32>)>,
+    // Created on first use, and kept alive so the text set on some platforms stays available.
+    #[cfg(feature = "clipboard")]
+    clipboard: RefCell<Option<ClipboardContext>>,
     // listeners: Vec<EventListenerHandle>,
 }
 
+impl GLCanvas {
+    fn new(
+        window: GLWindow,
+        events: Option<EventLoop<()>>,
+        out_events: Sender<WindowEvent>,
+    ) -> Self {
+        let ctxt = Context::get();
+        let vao = ctxt.create_vertex_array();
+        ctxt.bind_vertex_array(vao.as_ref());
+
+        GLCanvas {
+            window,
+            events,
+            cursor_pos: None,
+            key_states: [Action::Release; Key::Unknown as usize + 1],
+            button_states: [Action::Release; MouseButton::Button8 as usize + 1],
+            out_events,
+            focused: true,
+            #[cfg(feature = "gamepad")]
+            gamepads: match gilrs::Gilrs::new() {
+                Ok(gamepads) => Some(gamepads),
+                Err(e) => {
+                    println!("Warning: the gamepads are not available: {}", e);
+                    None
+                }
+            },
+            windowed_geometry: None,
+            #[cfg(feature = "clipboard")]
+            clipboard: RefCell::new(None),
+        }
+    }
+
+    // Calls `f` with the clipboard, which is created if needed.
+    #[cfg(feature = "clipboard")]
+    fn with_clipboard<T>(
+        &self,
+        f: impl FnOnce(&mut ClipboardContext) -> Result<T, Box<dyn std::error::Error>>,
+    ) -> Result<T, String> {
+        let mut clipboard = self.clipboard.borrow_mut();
+
+        if clipboard.is_none() {
+            *clipboard = Some(ClipboardContext::new().map_err(|e| e.to_string())?);
+        }
+
+        f(clipboard.as_mut().unwrap()).map_err(|e| e.to_string())
+    }
+
+    // Sends the events of the gamepads since the last call to this method.
+    #[cfg(feature = "gamepad")]
+    fn poll_gamepad_events(&mut self) {
+        use gilrs::EventType;
+
+        let gamepads = match self.gamepads {
+            Some(ref mut gamepads) => gamepads,
+            None => return,
+        };
+
+        while let Some(gilrs::Event { id, event, .. }) = gamepads.next_event() {
+            let id = id.into();
+            let event = match event {
+                EventType::ButtonPressed(button, _) => {
+                    WindowEvent::GamepadButton(id, translate_gamepad_button(button), Action::Press)
+                }
+                EventType::ButtonReleased(button, _) => WindowEvent::GamepadButton(
+                    id,
+                    translate_gamepad_button(button),
+                    Action::Release,
+                ),
+                EventType::AxisChanged(axis, value, _) => {
+                    WindowEvent::GamepadAxis(id, translate_gamepad_axis(axis), value as f64)
+                }
+                EventType::Connected => WindowEvent::GamepadConnected(id),
+                EventType::Disconnected => WindowEvent::GamepadDisconnected(id),
+                _ => continue,
+            };
+
+            let _ = self.out_events.send(event);
+        }
+    }
+
+    fn windowed(&self) -> Option<&WindowedContext<PossiblyCurrent>> {
+        match self.window {
+            GLWindow::Windowed(ref window) => Some(window),
+            GLWindow::Headless { .. } => None,
+        }
+    }
+}
+
 impl AbstractCanvas for GLCanvas {
     fn open(
         title: &str,
@@ -69,32 +184,112 @@ impl AbstractCanvas for GLCanvas {
             vsync: true,
             samples: NumSamples::Zero,
         });
-        let window = ContextBuilder::new()
-            .with_vsync(canvas_setup.vsync)
+        let builder = |samples: u16| {
+            ContextBuilder::new()
+                .with_vsync(canvas_setup.vsync)
+                .with_multisampling(samples)
+                // The stencil buffer is used to outline objects.
+                .with_stencil_buffer(8)
+                .with_gl(GlRequest::GlThenGles {
+                    opengl_version: (3, 2),
+                    opengles_version: (2, 0),
+                })
+        };
+        let samples = canvas_setup.samples as u16;
+        let window = match builder(samples).build_windowed(window.clone(), &events) {
+            Ok(window) => window,
+            Err(_) if samples > 1 => {
+                println!(
+                    "Warning: {} samples per pixel are not supported, disabling multisampling.",
+                    samples
+                );
+                builder(0).build_windowed(window, &events).unwrap()
+            }
+            Err(e) => panic!("{:?}", e),
+        };
+        let window = unsafe { window.make_current().unwrap() };
+        Context::init_with_proc_address(
+            || unsafe {
+                glow::Context::from_loader_function(|name| {
+                    window.get_proc_address(name) as *const _
+                })
+            },
+            |name| window.get_proc_address(name) as *const _,
+        );
+
+        GLCanvas::new(GLWindow::Windowed(window), Some(events), out_events)
+    }
+
+    fn open_headless(
+        width: u32,
+        height: u32,
+        canvas_setup: Option<CanvasSetup>,
+        out_events: Sender<WindowEvent>,
+    ) -> IoResult<Self> {
+        let canvas_setup = canvas_setup.unwrap_or(CanvasSetup {
+            vsync: false,
+            samples: NumSamples::Zero,
+        });
+        let builder = ContextBuilder::new()
             .with_multisampling(canvas_setup.samples as u16)
+            .with_stencil_buffer(8)
             .with_gl(GlRequest::GlThenGles {
                 opengl_version: (3, 2),
                 opengles_version: (2, 0),
-            })
-            .build_windowed(window, &events)
-            .unwrap();
-        let window = unsafe { window.make_current().unwrap() };
-        Context::init(|| unsafe {
-            glow::Context::from_loader_function(|name| window.get_proc_address(name) as *const _)
-        });
+            });
 
-        let ctxt = Context::get();
-        let vao = ctxt.create_vertex_array();
-        ctxt.bind_vertex_array(vao.as_ref());
+        // NOTE: OSMesa does not need any display server, contrary to the other backends.
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        let (context, events) = {
+            use glutin::platform::unix::HeadlessContextExt;
+            let context = builder.build_osmesa((width, height).into());
+            (context, None)
+        };
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )))]
+        let (context, events) = {
+            let events = EventLoop::new();
+            let context = builder.build_headless(&events, PhysicalSize::new(width, height));
+            (context, Some(events))
+        };
 
-        GLCanvas {
-            window,
+        let context = context.map_err(|e| {
+            Error::other(format!("Failed to create a headless OpenGL context: {}", e))
+        })?;
+        let context = unsafe { context.make_current() }.map_err(|(_, e)| {
+            Error::other(format!(
+                "Failed to activate the headless OpenGL context: {}",
+                e
+            ))
+        })?;
+        Context::init_with_proc_address(
+            || unsafe {
+                glow::Context::from_loader_function(|name| {
+                    context.get_proc_address(name) as *const _
+                })
+            },
+            |name| context.get_proc_address(name) as *const _,
+        );
+
+        Ok(GLCanvas::new(
+            GLWindow::Headless {
+                _context: context,
+                size: (width, height),
+            },
             events,
-            cursor_pos: None,
-            key_states: [Action::Release; Key::Unknown as usize + 1],
-            button_states: [Action::Release; MouseButton::Button8 as usize + 1],
             out_events,
-        }
+        ))
     }
 
     fn render_loop(mut callback: impl FnMut(f64) -> bool + 'static) {
@@ -106,13 +301,20 @@ impl AbstractCanvas for GLCanvas {
     }
 
     fn poll_events(&mut self) {
+        #[cfg(feature = "gamepad")]
+        self.poll_gamepad_events();
+
+        let (window, events) = match (&mut self.window, &mut self.events) {
+            (GLWindow::Windowed(window), Some(events)) => (window, events),
+            _ => return,
+        };
         let out_events = &mut self.out_events;
-        let window = &mut self.window;
         let button_states = &mut self.button_states;
         let key_states = &mut self.key_states;
         let cursor_pos = &mut self.cursor_pos;
+        let focused = &mut self.focused;
 
-        self.events.run_return(|event, _, control_flow| {
+        events.run_return(|event, _, control_flow| {
             use glutin::event::Event;
 
             match event {
@@ -186,8 +388,27 @@ impl AbstractCanvas for GLCanvas {
                     glutin::event::WindowEvent::ReceivedCharacter(c) => {
                         let _ = out_events.send(WindowEvent::Char(c));
                     }
+                    glutin::event::WindowEvent::Focused(f) => {
+                        *focused = f;
+                    }
+                    glutin::event::WindowEvent::DroppedFile(path) => {
+                        let _ = out_events.send(WindowEvent::FileDropped(path));
+                    }
+                    glutin::event::WindowEvent::HoveredFile(path) => {
+                        let _ = out_events.send(WindowEvent::FileHovered(path));
+                    }
+                    glutin::event::WindowEvent::HoveredFileCancelled => {
+                        let _ = out_events.send(WindowEvent::FileHoveredCancelled);
+                    }
                     _ => {}
                 },
+                // The device events are received even if the window is not focused.
+                Event::DeviceEvent {
+                    event: glutin::event::DeviceEvent::MouseMotion { delta },
+                    ..
+                } if *focused => {
+                    let _ = out_events.send(WindowEvent::MouseMotion(delta.0, delta.1));
+                }
                 Event::RedrawEventsCleared => {
                     *control_flow = ControlFlow::Exit;
                 }
@@ -197,11 +418,86 @@ impl AbstractCanvas for GLCanvas {
     }
 
     fn swap_buffers(&mut self) {
-        let _ = self.window.swap_buffers();
+        if let GLWindow::Windowed(ref window) = self.window {
+            let _ = window.swap_buffers();
+        }
+    }
+
+    fn set_vsync(&mut self, vsync: bool) -> bool {
+        let window = match self.window {
+            GLWindow::Windowed(ref window) => window,
+            // Headless contexts do not swap any buffer.
+            GLWindow::Headless { .. } => return false,
+        };
+        let interval = if vsync { 1 } else { 0 };
+
+        // NOTE: glutin only sets the swap interval at the creation of the context, so we call
+        // the platform-specific extensions directly, like glutin does.
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        unsafe {
+            use glutin::platform::ContextTraitExt;
+            use std::os::raw::c_void;
+
+            if let Some(display) = window.context().get_egl_display() {
+                let swap_interval = window.get_proc_address("eglSwapInterval");
+
+                if swap_interval.is_null() {
+                    return false;
+                }
+
+                let swap_interval: extern "system" fn(*const c_void, i32) -> u32 =
+                    std::mem::transmute(swap_interval);
+                swap_interval(display, interval) != 0
+            } else {
+                let swap_interval = window.get_proc_address("glXSwapIntervalMESA");
+
+                if swap_interval.is_null() {
+                    return false;
+                }
+
+                let swap_interval: extern "system" fn(u32) -> i32 =
+                    std::mem::transmute(swap_interval);
+                swap_interval(interval as u32) == 0
+            }
+        }
+
+        #[cfg(windows)]
+        unsafe {
+            let swap_interval = window.get_proc_address("wglSwapIntervalEXT");
+
+            if swap_interval.is_null() {
+                return false;
+            }
+
+            let swap_interval: extern "system" fn(i32) -> i32 = std::mem::transmute(swap_interval);
+            swap_interval(interval) != 0
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            windows
+        )))]
+        {
+            let _ = (window, interval);
+            false
+        }
     }
 
     fn size(&self) -> (u32, u32) {
-        self.window.window().inner_size().into()
+        match self.window {
+            GLWindow::Windowed(ref window) => window.window().inner_size().into(),
+            GLWindow::Headless { size, .. } => size,
+        }
     }
 
     fn cursor_pos(&self) -> Option<(f64, f64)> {
@@ -209,44 +505,136 @@ impl AbstractCanvas for GLCanvas {
     }
 
     fn scale_factor(&self) -> f64 {
-        self.window.window().scale_factor() as f64
+        self.windowed()
+            .map(|window| window.window().scale_factor())
+            .unwrap_or(1.0)
     }
 
     fn set_title(&mut self, title: &str) {
-        self.window.window().set_title(title)
+        if let Some(window) = self.windowed() {
+            window.window().set_title(title)
+        }
     }
 
     fn set_icon(&mut self, icon: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>) {
-        let (width, height) = icon.dimensions();
-        let mut rgba = Vec::with_capacity((width * height) as usize * 4);
-        for (_, _, pixel) in icon.pixels() {
-            rgba.extend_from_slice(&pixel.to_rgba().0);
+        if let Some(window) = self.windowed() {
+            let (width, height) = icon.dimensions();
+            let mut rgba = Vec::with_capacity((width * height) as usize * 4);
+            for (_, _, pixel) in icon.pixels() {
+                rgba.extend_from_slice(&pixel.to_rgba().0);
+            }
+            let icon = glutin::window::Icon::from_rgba(rgba, width, height).unwrap();
+            window.window().set_window_icon(Some(icon))
+        }
+    }
+
+    fn set_cursor_grab(&self, grab: bool) -> Result<(), &'static str> {
+        match self.windowed() {
+            Some(window) => window
+                .window()
+                .set_cursor_grab(grab)
+                .map_err(|_| "Cursor grabbing is not supported on this platform."),
+            None => Err("Cursor grabbing is not supported by headless windows."),
         }
-        let icon = glutin::window::Icon::from_rgba(rgba, width, height).unwrap();
-        self.window.window().set_window_icon(Some(icon))
     }
 
-    fn set_cursor_grab(&self, grab: bool) {
-        let _ = self.window.window().set_cursor_grab(grab);
+    #[cfg(feature = "clipboard")]
+    fn get_clipboard(&self) -> Option<String> {
+        self.with_clipboard(|c| c.get_contents()).ok()
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn get_clipboard(&self) -> Option<String> {
+        None
+    }
+
+    #[cfg(feature = "clipboard")]
+    fn set_clipboard(&self, text: &str) -> Result<(), String> {
+        self.with_clipboard(|c| c.set_contents(text.to_string()))
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn set_clipboard(&self, _: &str) -> Result<(), String> {
+        Err("The clipboard requires the `clipboard` feature.".to_string())
     }
 
     fn set_cursor_position(&self, x: f64, y: f64) {
-        self.window
-            .window()
-            .set_cursor_position(glutin::dpi::PhysicalPosition::new(x, y))
-            .unwrap();
+        if let Some(window) = self.windowed() {
+            window
+                .window()
+                .set_cursor_position(PhysicalPosition::new(x, y))
+                .unwrap();
+        }
     }
 
     fn hide_cursor(&self, hide: bool) {
-        self.window.window().set_cursor_visible(!hide)
+        if let Some(window) = self.windowed() {
+            window.window().set_cursor_visible(!hide)
+        }
     }
 
     fn hide(&mut self) {
-        self.window.window().set_visible(false)
+        if let Some(window) = self.windowed() {
+            window.window().set_visible(false)
+        }
     }
 
     fn show(&mut self) {
-        self.window.window().set_visible(true)
+        if let Some(window) = self.windowed() {
+            window.window().set_visible(true)
+        }
+    }
+
+    fn monitor_names(&self) -> Vec<String> {
+        match self.windowed() {
+            Some(window) => window
+                .window()
+                .available_monitors()
+                .enumerate()
+                .map(|(i, m)| m.name().unwrap_or_else(|| format!("Monitor {}", i)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.windowed()
+            .is_some_and(|window| window.window().fullscreen().is_some())
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool, monitor: Option<usize>) -> bool {
+        let window = match self.window {
+            GLWindow::Windowed(ref window) => window.window(),
+            GLWindow::Headless { .. } => return false,
+        };
+
+        if fullscreen {
+            let monitor = match monitor {
+                Some(i) => match window.available_monitors().nth(i) {
+                    Some(monitor) => Some(monitor),
+                    None => return false,
+                },
+                None => None,
+            };
+
+            if window.fullscreen().is_none() {
+                self.windowed_geometry = Some((window.outer_position().ok(), window.inner_size()));
+            }
+
+            window.set_fullscreen(Some(Fullscreen::Borderless(monitor)));
+        } else if window.fullscreen().is_some() {
+            window.set_fullscreen(None);
+
+            if let Some((position, size)) = self.windowed_geometry.take() {
+                window.set_inner_size(size);
+
+                if let Some(position) = position {
+                    window.set_outer_position(position);
+                }
+            }
+        }
+
+        true
     }
 
     fn get_mouse_button(&self, button: MouseButton) -> Action {
@@ -462,3 +850,44 @@ fn translate_key(button: Option<glutin::event::VirtualKeyCode>) -> Key {
         Key::Unknown
     }
 }
+
+#[cfg(feature = "gamepad")]
+fn translate_gamepad_button(button: gilrs::Button) -> GamepadButton {
+    match button {
+        gilrs::Button::South => GamepadButton::South,
+        gilrs::Button::East => GamepadButton::East,
+        gilrs::Button::North => GamepadButton::North,
+        gilrs::Button::West => GamepadButton::West,
+        gilrs::Button::C => GamepadButton::C,
+        gilrs::Button::Z => GamepadButton::Z,
+        gilrs::Button::LeftTrigger => GamepadButton::LeftTrigger,
+        gilrs::Button::LeftTrigger2 => GamepadButton::LeftTrigger2,
+        gilrs::Button::RightTrigger => GamepadButton::RightTrigger,
+        gilrs::Button::RightTrigger2 => GamepadButton::RightTrigger2,
+        gilrs::Button::Select => GamepadButton::Select,
+        gilrs::Button::Start => GamepadButton::Start,
+        gilrs::Button::Mode => GamepadButton::Mode,
+        gilrs::Button::LeftThumb => GamepadButton::LeftThumb,
+        gilrs::Button::RightThumb => GamepadButton::RightThumb,
+        gilrs::Button::DPadUp => GamepadButton::DPadUp,
+        gilrs::Button::DPadDown => GamepadButton::DPadDown,
+        gilrs::Button::DPadLeft => GamepadButton::DPadLeft,
+        gilrs::Button::DPadRight => GamepadButton::DPadRight,
+        gilrs::Button::Unknown => GamepadButton::Unknown,
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn translate_gamepad_axis(axis: gilrs::Axis) -> GamepadAxis {
+    match axis {
+        gilrs::Axis::LeftStickX => GamepadAxis::LeftStickX,
+        gilrs::Axis::LeftStickY => GamepadAxis::LeftStickY,
+        gilrs::Axis::LeftZ => GamepadAxis::LeftZ,
+        gilrs::Axis::RightStickX => GamepadAxis::RightStickX,
+        gilrs::Axis::RightStickY => GamepadAxis::RightStickY,
+        gilrs::Axis::RightZ => GamepadAxis::RightZ,
+        gilrs::Axis::DPadX => GamepadAxis::DPadX,
+        gilrs::Axis::DPadY => GamepadAxis::DPadY,
+        gilrs::Axis::Unknown => GamepadAxis::Unknown,
+    }
+}
@@ -2,14 +2,16 @@
 
 pub(crate) use self::canvas::AbstractCanvas;
 pub use self::canvas::{Canvas, CanvasSetup, NumSamples};
+pub use self::fps_overlay::Corner;
 #[cfg(not(target_arch = "wasm32"))]
 pub use self::gl_canvas::GLCanvas;
 pub use self::state::State;
 #[cfg(target_arch = "wasm32")]
 pub use self::webgl_canvas::WebGLCanvas;
-pub use self::window::Window;
+pub use self::window::{EventHandler, Window};
 
 mod canvas;
+mod fps_overlay;
 #[cfg(not(target_arch = "wasm32"))]
 mod gl_canvas;
 mod state;
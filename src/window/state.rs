@@ -11,6 +11,8 @@ use crate::window::Window;
 /// post-processing effects to be used by the renderer.
 pub trait State: 'static {
     /// Method called at each render loop before a rendering.
+    ///
+    /// The duration of the last frame is given by `window.frame_delta()`.
     fn step(&mut self, window: &mut Window);
 
     /// Unless `cameras_and_effect_and_renderer` is implemented, this method called at each render loop to retrieve
@@ -1,6 +1,7 @@
 #![allow(unused_results)]
 
 use std::cell::RefCell;
+use std::io::{Error, ErrorKind, Result as IoResult};
 use std::ops::DerefMut;
 use std::rc::Rc;
 use std::sync::mpsc::Sender;
@@ -424,6 +425,18 @@ impl AbstractCanvas for WebGLCanvas {
         }
     }
 
+    fn open_headless(
+        _: u32,
+        _: u32,
+        _: Option<CanvasSetup>,
+        _: Sender<WindowEvent>,
+    ) -> IoResult<Self> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "Headless rendering is not supported by WebGL.",
+        ))
+    }
+
     fn render_loop(mut callback: impl FnMut(f64) -> bool + 'static) {
         // See https://rustwasm.github.io/docs/wasm-bindgen/examples/request-animation-frame.html
         if let Some(window) = web_sys::window() {
@@ -463,6 +476,11 @@ impl AbstractCanvas for WebGLCanvas {
         // Nothing to do.
     }
 
+    fn set_vsync(&mut self, _: bool) -> bool {
+        // Not supported: the browser decides when the frames are displayed.
+        false
+    }
+
     fn size(&self) -> (u32, u32) {
         let scale_factor = self.scale_factor();
         (
@@ -483,8 +501,17 @@ impl AbstractCanvas for WebGLCanvas {
         // Not supported.
     }
 
-    fn set_cursor_grab(&self, _: bool) {
+    fn set_cursor_grab(&self, _: bool) -> Result<(), &'static str> {
+        Err("Cursor grabbing is not supported on the web.")
+    }
+
+    fn get_clipboard(&self) -> Option<String> {
         // Not supported.
+        None
+    }
+
+    fn set_clipboard(&self, _: &str) -> Result<(), String> {
+        Err("The clipboard is not supported on the web.".to_string())
     }
 
     fn set_cursor_position(&self, _: f64, _: f64) {
@@ -503,6 +530,20 @@ impl AbstractCanvas for WebGLCanvas {
         // Not supported.
     }
 
+    fn monitor_names(&self) -> Vec<String> {
+        // Not supported.
+        Vec::new()
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        false
+    }
+
+    fn set_fullscreen(&mut self, _: bool, _: Option<usize>) -> bool {
+        // Not supported.
+        false
+    }
+
     fn get_mouse_button(&self, button: MouseButton) -> Action {
         self.data.borrow().button_states[button as usize]
     }
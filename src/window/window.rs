@@ -3,7 +3,9 @@
  * FIXME: this file is too big. Some heavy refactoring need to be done here.
  */
 use std::cell::RefCell;
+use std::io::Result as IoResult;
 use std::iter::repeat;
+use std::mem;
 use std::path::Path;
 use std::rc::Rc;
 use std::sync::mpsc::{self, Receiver};
@@ -11,28 +13,37 @@ use std::thread;
 use std::time::Duration;
 
 use instant::Instant;
-use na::{Point2, Point3, Vector2, Vector3};
+use na::{Isometry3, Point2, Point3, Vector2, Vector3};
 
-use crate::camera::{ArcBall, Camera};
-use crate::context::Context;
+use crate::camera::{self, ArcBall, Camera};
+use crate::context::{render_stats, Context, RenderStats};
 use crate::event::{Action, EventManager, Key, WindowEvent};
-use crate::light::Light;
+use crate::fog::Fog;
+use crate::light::{Light, LightId, Lights};
 use crate::planar_camera::{FixedView, PlanarCamera};
 use crate::planar_line_renderer::PlanarLineRenderer;
-use crate::post_processing::PostProcessingEffect;
+use crate::post_processing::{PostProcessingEffect, ScreenCopy};
+use crate::renderer::wide_line_renderer::WideLineRenderer;
 #[cfg(feature = "conrod")]
 use crate::renderer::ConrodRenderer;
-use crate::renderer::{LineRenderer, PointRenderer, Renderer};
+use crate::renderer::{LineRenderer, PointRenderer, Renderer, ShadowMapRenderer, SkyboxRenderer};
 use crate::resource::{
-    FramebufferManager, Mesh, PlanarMesh, RenderTarget, Texture, TextureManager,
+    CubemapTexture, FramebufferManager, Mesh, MultisampledRenderTarget, PlanarMesh, RenderTarget,
+    Texture, TextureFilter, TextureManager,
 };
-use crate::scene::{PlanarSceneNode, SceneNode};
-use crate::text::{Font, TextRenderer};
-use crate::window::canvas::CanvasSetup;
-use crate::window::{Canvas, State};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::scene::PendingNode;
+use crate::scene::{
+    set_default_backface_culling, set_visible_layers, InstancedNode, PlanarSceneNode,
+    PointCloudNode, SceneNode,
+};
+use crate::text::{Font, HAlign, TextRenderer, VAlign};
+use crate::window::canvas::{CanvasSetup, NumSamples};
+use crate::window::fps_overlay::FpsOverlay;
+use crate::window::{Canvas, Corner, State};
 use image::imageops;
 use image::{GenericImage, Pixel};
-use image::{ImageBuffer, Rgb};
+use image::{ImageBuffer, ImageFormat, ImageResult, Luma, RgbImage};
 use ncollide3d::procedural::TriMesh;
 
 #[cfg(feature = "conrod")]
@@ -46,6 +57,8 @@ struct ConrodContext {
     renderer: ConrodRenderer,
     textures: conrod::image::Map<(Rc<Texture>, (u32, u32))>,
     texture_ids: HashMap<String, conrod::image::Id>,
+    // Whether the user interface is drawn before the post-processing effects.
+    post_processed: bool,
 }
 
 #[cfg(feature = "conrod")]
@@ -55,29 +68,90 @@ impl ConrodContext {
             renderer: ConrodRenderer::new(width, height),
             textures: conrod::image::Map::new(),
             texture_ids: HashMap::new(),
+            post_processed: false,
         }
     }
 }
 
+/// A function handling an event received by a window, returning `true` if the event is consumed.
+///
+/// See `Window::add_event_handler`.
+pub type EventHandler = Box<dyn FnMut(&mut Window, &WindowEvent) -> bool>;
+
+// The ends and color of a line drawn until the given instant.
+type DebugLine = (Point3<f32>, Point3<f32>, Point3<f32>, Instant);
+
+// A text drawn at the projection of a point of the scene.
+struct Label {
+    text: String,
+    pos: Point3<f32>,
+    scale: f32,
+    font: Rc<Font>,
+    color: Point3<f32>,
+}
+
 /// Structure representing a window and a 3D scene.
 ///
 /// This is the main interface with the 3d engine.
 pub struct Window {
     events: Rc<Receiver<WindowEvent>>,
     unhandled_events: Rc<RefCell<Vec<WindowEvent>>>,
+    event_handlers: Vec<EventHandler>,
     max_dur_per_frame: Option<Duration>,
     scene: SceneNode,
     scene2: PlanarSceneNode,
-    light_mode: Light, // FIXME: move that to the scene graph
+    lights: Lights, // FIXME: move that to the scene graph
     background: Vector3<f32>,
+    frustum_culling: bool,
+    reversed_z: bool,
+    // Whether the reversed depth is mapped to `[0, 1]` with `glClipControl` and stored in
+    // floating-point depth buffers, see `set_reversed_z`.
+    float_depth: bool,
+    backface_culling: bool,
+    visible_layers: u32,
+    // The rectangle of the framebuffer the scene is drawn into, if not the whole framebuffer.
+    viewport: Option<(i32, i32, i32, i32)>,
+    auto_swap_buffers: bool,
+    // Whether something has been drawn since the buffers were last swapped.
+    frame_started: bool,
+    shadows: bool,
     line_renderer: LineRenderer,
     planar_line_renderer: PlanarLineRenderer,
     point_renderer: PointRenderer,
+    // Draws the outlines of the scene nodes.
+    outline_renderer: WideLineRenderer,
+    // The lines and points drawn at each frame until the given instant.
+    debug_lines: Vec<DebugLine>,
+    debug_points: Vec<(Point3<f32>, Point3<f32>, Instant)>,
+    skybox_renderer: SkyboxRenderer,
+    shadow_map_renderer: ShadowMapRenderer,
     text_renderer: TextRenderer,
+    // The texts drawn by `draw_text_3d`, projected with the camera of the next frame.
+    labels: Vec<Label>,
+    label_renderer: TextRenderer,
     framebuffer_manager: FramebufferManager,
-    post_process_render_target: RenderTarget,
-    #[cfg(not(target_arch = "wasm32"))]
+    // The scene is rendered into the first target, and the effects ping-pong between the two
+    // others so that the depth of the scene is kept.
+    post_process_render_targets: [RenderTarget; 3],
+    // The buffer the scene is rendered into before being resolved into
+    // `post_process_render_targets[0]`, if multisampling was requested.
+    multisampled_render_target: Option<MultisampledRenderTarget>,
+    // Whether the scene of the last frame was rendered into `post_process_render_targets[0]`.
+    post_processed: bool,
+    // The depth buffer used by `render_to_texture`, created at its first call.
+    texture_render_target: Option<RenderTarget>,
+    // The clip planes of the camera of the last frame.
+    clip_planes: (f32, f32),
+    post_processing_effects: Vec<Box<dyn PostProcessingEffect>>,
+    // Copies the scene to the screen when it is rendered off-screen without any effect.
+    screen_copy: ScreenCopy,
+    // The end of the last frame.
     curr_time: Instant,
+    start_time: Instant,
+    frame_delta: Duration,
+    smoothed_frame_delta: Duration,
+    fps_overlay: FpsOverlay,
+    render_stats: RenderStats,
     planar_camera: Rc<RefCell<FixedView>>,
     camera: Rc<RefCell<ArcBall>>,
     should_close: bool,
@@ -93,34 +167,154 @@ impl Window {
         self.should_close
     }
 
-    /// The window width.
+    /// The window width, in physical pixels.
     #[inline]
     pub fn width(&self) -> u32 {
         self.canvas.size().0
     }
 
-    /// The window height.
+    /// The window height, in physical pixels.
     #[inline]
     pub fn height(&self) -> u32 {
         self.canvas.size().1
     }
 
-    /// The size of the window.
+    /// The size of the window, in physical pixels.
+    ///
+    /// All the positions received in the events of the window, e.g., `WindowEvent::CursorPos`,
+    /// are in physical pixels too, so they can be given to `pick` or `Camera::unproject` along
+    /// with this size as is. This is the same as `physical_size`.
     #[inline]
     pub fn size(&self) -> Vector2<u32> {
         let (w, h) = self.canvas.size();
         Vector2::new(w, h)
     }
 
-    /// Sets the maximum number of frames per second. Cannot be 0. `None` means there is no limit.
+    /// The size of the window, in physical pixels.
+    #[inline]
+    pub fn physical_size(&self) -> Vector2<u32> {
+        self.size()
+    }
+
+    /// The size of the window, in logical pixels.
+    ///
+    /// This is the physical size divided by the scale factor.
+    #[inline]
+    pub fn logical_size(&self) -> Vector2<f32> {
+        self.size().map(|e| e as f32) / self.scale_factor() as f32
+    }
+
+    /// Finds the nearest visible object under the given screen point, using the default camera.
+    ///
+    /// Returns the node hit and the hit point, in world coordinates.
+    pub fn pick(&self, screen: Point2<f32>) -> Option<(SceneNode, Point3<f32>)> {
+        self.pick_with_camera(&*self.camera.borrow(), screen)
+    }
+
+    /// Finds the nearest visible object under the given screen point, using a specific camera.
+    ///
+    /// Returns the node hit and the hit point, in world coordinates. The objects outside of the
+    /// layers made visible with `set_visible_layers` are ignored.
+    pub fn pick_with_camera(
+        &self,
+        camera: &dyn Camera,
+        screen: Point2<f32>,
+    ) -> Option<(SceneNode, Point3<f32>)> {
+        let size = self.size();
+        let size = Vector2::new(size.x as f32, size.y as f32);
+        let (origin, dir) = camera.unproject(&screen, &size);
+
+        // This may be called before the first frame sets the visible layers.
+        set_visible_layers(self.visible_layers);
+        self.scene.cast_ray(&origin, &dir)
+    }
+
+    /// Sets the maximum number of frames per second. `None` means there is no limit.
+    ///
+    /// After each frame, the window sleeps for the remainder of the period corresponding to this
+    /// framerate, so the time spent by the application and for rendering is accounted for. This
+    /// has no effect on the web.
+    ///
+    /// # Failure
+    /// Panics if `fps` is not positive.
     #[inline]
-    pub fn set_framerate_limit(&mut self, fps: Option<u64>) {
+    pub fn set_framerate_limit(&mut self, fps: Option<f64>) {
         self.max_dur_per_frame = fps.map(|f| {
-            assert!(f != 0);
-            Duration::from_millis(1000 / f)
+            assert!(f > 0.0, "The framerate limit must be positive.");
+            Duration::from_secs_f64(1.0 / f)
         })
     }
 
+    /// Enables or disables vertical synchronization, i.e., waiting for the display to refresh
+    /// before showing a new frame.
+    ///
+    /// Vsync is enabled by default, unless disabled by the `CanvasSetup` given at the creation of
+    /// the window. A warning is printed if this cannot be changed on this platform, e.g., on the
+    /// web or on macOS.
+    pub fn set_vsync(&mut self, vsync: bool) {
+        if !self.canvas.set_vsync(vsync) {
+            println!("Warning: vsync cannot be changed on this platform.");
+        }
+    }
+
+    /// The duration of the last frame.
+    ///
+    /// It is measured between the ends of the last two frames, so it includes the time spent by
+    /// the application between frames, waiting for vsync, and the framerate limit. Before the
+    /// first frame, this is zero.
+    #[inline]
+    pub fn frame_delta(&self) -> Duration {
+        self.frame_delta
+    }
+
+    /// The duration of the last frames, smoothed with an exponential moving average.
+    ///
+    /// This is less noisy than `frame_delta`, e.g., for displaying a framerate.
+    #[inline]
+    pub fn smoothed_frame_delta(&self) -> Duration {
+        self.smoothed_frame_delta
+    }
+
+    /// The time elapsed since this window was created.
+    #[inline]
+    pub fn elapsed_time(&self) -> Duration {
+        self.start_time.elapsed()
+    }
+
+    /// Statistics about the rendering of the last frame.
+    #[inline]
+    pub fn render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    /// Whether the framerate is displayed on top of the scene.
+    #[inline]
+    pub fn show_fps(&self) -> bool {
+        self.fps_overlay.visible()
+    }
+
+    /// Shows or hides a text displaying the framerate and the duration of the frames.
+    ///
+    /// The values are refreshed twice per second. The text is drawn after the texts added with
+    /// `draw_text`, with the default font.
+    #[inline]
+    pub fn set_show_fps(&mut self, show: bool) {
+        self.fps_overlay.set_visible(show)
+    }
+
+    /// The corner of the window where the framerate is displayed.
+    #[inline]
+    pub fn fps_overlay_corner(&self) -> Corner {
+        self.fps_overlay.corner()
+    }
+
+    /// Sets the corner of the window where the framerate is displayed. Defaults to the top-left
+    /// corner.
+    #[inline]
+    pub fn set_fps_overlay_corner(&mut self, corner: Corner) {
+        self.fps_overlay.set_corner(corner)
+    }
+
     /// Set window title
     pub fn set_title(&mut self, title: &str) {
         self.canvas.set_title(title)
@@ -148,7 +342,39 @@ impl Window {
     /// If cursor grabbing is on, the cursor is prevented from leaving the window.
     /// Does nothing on web platforms.
     pub fn set_cursor_grab(&self, grab: bool) {
-        self.canvas.set_cursor_grab(grab);
+        let _ = self.canvas.set_cursor_grab(grab);
+    }
+
+    /// Prevents the cursor from leaving the window, e.g., for first-person controls.
+    ///
+    /// While the cursor is grabbed, the `WindowEvent::MouseMotion` events still report the
+    /// relative motion of the mouse. Returns an error if cursor grabbing is not supported on this
+    /// platform, e.g., on the web.
+    pub fn set_cursor_grabbed(&self, grabbed: bool) -> Result<(), &'static str> {
+        self.canvas.set_cursor_grab(grabbed)
+    }
+
+    /// Shows or hides the cursor while it is above the window. Does nothing on web platforms.
+    #[inline]
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.canvas.hide_cursor(!visible);
+    }
+
+    /// The text content of the system clipboard.
+    ///
+    /// This is the clipboard used by the text boxes of the `conrod` user interface. Returns `None`
+    /// if the clipboard does not contain text or is not available, e.g., on the web, without a
+    /// display server, or without the `clipboard` feature, which is enabled by `conrod`.
+    pub fn get_clipboard(&self) -> Option<String> {
+        self.canvas.get_clipboard()
+    }
+
+    /// Replaces the content of the system clipboard with `text`.
+    ///
+    /// Returns an error message if the clipboard is not available, e.g., on the web, without a
+    /// display server, or without the `clipboard` feature, which is enabled by `conrod`.
+    pub fn set_clipboard(&self, text: &str) -> Result<(), String> {
+        self.canvas.set_clipboard(text)
     }
 
     #[inline]
@@ -179,6 +405,42 @@ impl Window {
         self.canvas.show()
     }
 
+    /// Whether the window is fullscreen.
+    #[inline]
+    pub fn is_fullscreen(&self) -> bool {
+        self.canvas.is_fullscreen()
+    }
+
+    /// Makes the window borderless fullscreen on its current monitor, or restores its previous
+    /// size and position.
+    ///
+    /// The window receives a `WindowEvent::FramebufferSize` event once it has been resized. A
+    /// warning is printed if this is not supported on this platform, e.g., on the web.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        if !self.canvas.set_fullscreen(fullscreen, None) {
+            println!("Warning: fullscreen is not supported on this platform.");
+        }
+    }
+
+    /// The names of the monitors available, in the order of their indices.
+    #[inline]
+    pub fn monitor_names(&self) -> Vec<String> {
+        self.canvas.monitor_names()
+    }
+
+    /// Makes the window borderless fullscreen on the monitor with the given index.
+    ///
+    /// See `monitor_names` for the list of the monitors, and `set_fullscreen(false)` for
+    /// restoring the window. A warning is printed if there is no monitor with this index.
+    pub fn set_fullscreen_on_monitor(&mut self, index: usize) {
+        if !self.canvas.set_fullscreen(true, Some(index)) {
+            println!(
+                "Warning: cannot make the window fullscreen on the monitor {}.",
+                index
+            );
+        }
+    }
+
     /// Sets the background color.
     #[inline]
     pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
@@ -187,6 +449,311 @@ impl Window {
         self.background.z = b;
     }
 
+    /// Sets the cubemap drawn as the background of the scene.
+    ///
+    /// The skybox follows the camera rotation but not its translation, so it appears infinitely
+    /// far away.
+    #[inline]
+    pub fn set_skybox(&mut self, cubemap: Rc<CubemapTexture>) {
+        self.skybox_renderer.set_cubemap(Some(cubemap))
+    }
+
+    /// Removes the skybox, if any.
+    #[inline]
+    pub fn unset_skybox(&mut self) {
+        self.skybox_renderer.set_cubemap(None)
+    }
+
+    /// Sets the cubemap used for image-based lighting by the materials supporting it, e.g., the
+    /// `PbrMaterial`.
+    ///
+    /// This is independent from the skybox, though the same cubemap is usually used for both.
+    #[inline]
+    pub fn set_environment_map(&mut self, cubemap: Rc<CubemapTexture>) {
+        self.lights.set_environment_map(Some(cubemap))
+    }
+
+    /// Removes the cubemap used for image-based lighting, if any.
+    #[inline]
+    pub fn unset_environment_map(&mut self) {
+        self.lights.set_environment_map(None)
+    }
+
+    /// Enables a linear fog on the objects drawn by the `ObjectMaterial`.
+    ///
+    /// The fog starts at the view-space depth `start` and completely hides the objects from the
+    /// depth `end`. It does not affect the background color, nor the skybox, nor the overlays.
+    #[inline]
+    pub fn set_fog(&mut self, color: Point3<f32>, start: f32, end: f32) {
+        self.lights.set_fog(Some(Fog::linear(color, start, end)))
+    }
+
+    /// Enables an exponential fog on the objects drawn by the `ObjectMaterial`.
+    ///
+    /// The color of the current fog is kept, or the background color is used if there is no fog.
+    #[inline]
+    pub fn set_fog_exp(&mut self, density: f32) {
+        let color = self
+            .lights
+            .fog()
+            .map(|fog| fog.color)
+            .unwrap_or_else(|| Point3::from(self.background));
+
+        self.lights.set_fog(Some(Fog::exponential(color, density)))
+    }
+
+    /// Sets the color of the fog, if any, independently from the background color.
+    #[inline]
+    pub fn set_fog_color(&mut self, color: Point3<f32>) {
+        let fog = self.lights.fog().map(|fog| Fog { color, ..*fog });
+        self.lights.set_fog(fog)
+    }
+
+    /// The fog of the scene, if any.
+    #[inline]
+    pub fn fog(&self) -> Option<&Fog> {
+        self.lights.fog()
+    }
+
+    /// Disables the fog.
+    #[inline]
+    pub fn unset_fog(&mut self) {
+        self.lights.set_fog(None)
+    }
+
+    /// Enables or disables gamma-correct rendering of the objects drawn by the `ObjectMaterial`.
+    ///
+    /// If enabled, the colors of the objects and of their sRGB textures are converted to linear
+    /// space before the lighting is computed, and the result is converted back to sRGB when
+    /// written to the framebuffer. Textures containing data instead of colors should be marked
+    /// with `Texture::set_srgb(false)`. This is disabled by default.
+    #[inline]
+    pub fn set_gamma_correction(&mut self, enabled: bool) {
+        self.lights.set_gamma_correction(enabled)
+    }
+
+    /// Whether gamma-correct rendering is enabled.
+    #[inline]
+    pub fn gamma_correction(&self) -> bool {
+        self.lights.gamma_correction()
+    }
+
+    /// Replaces all the post-processing effects of this window by `effect`.
+    pub fn set_post_processing_effect(&mut self, effect: Box<dyn PostProcessingEffect>) {
+        self.post_processing_effects.clear();
+        self.post_processing_effects.push(effect);
+    }
+
+    /// Appends a post-processing effect to the chain of effects of this window.
+    ///
+    /// The effects are executed in the order they were added, each one using the output of the
+    /// previous one as its input. Only the first effect sees the depth buffer of the scene.
+    pub fn add_post_processing_effect(&mut self, effect: Box<dyn PostProcessingEffect>) {
+        self.post_processing_effects.push(effect);
+    }
+
+    /// Removes and returns the `index`-th post-processing effect of the chain.
+    ///
+    /// # Failure
+    /// Fails if `index` is out of bounds.
+    pub fn remove_post_processing_effect(&mut self, index: usize) -> Box<dyn PostProcessingEffect> {
+        self.post_processing_effects.remove(index)
+    }
+
+    /// Removes all the post-processing effects of this window.
+    pub fn clear_post_processing_effects(&mut self) {
+        self.post_processing_effects.clear();
+    }
+
+    /// Enables or disables frustum culling.
+    ///
+    /// If enabled, objects lying entirely outside of the frustum returned by
+    /// `Camera::frustum_planes` are not drawn. Each object is tested with the bounding box of its
+    /// mesh, which is cached until the vertices of the mesh are modified. This is disabled by
+    /// default.
+    #[inline]
+    pub fn set_frustum_culling(&mut self, enabled: bool) {
+        self.frustum_culling = enabled;
+    }
+
+    /// Whether frustum culling is enabled.
+    #[inline]
+    pub fn frustum_culling(&self) -> bool {
+        self.frustum_culling
+    }
+
+    /// Enables or disables the reversed depth buffer.
+    ///
+    /// If enabled, the projections of the builtin cameras map the near plane to a depth of 1.0
+    /// and the far plane to a depth of 0.0, the depth buffer is cleared to 0.0, and the depth
+    /// test keeps the greatest depth. Custom cameras must apply `camera::depth_reversal()` to
+    /// their projection, and `camera::inverse_depth_reversal()` to its inverse, for this to work.
+    /// This is disabled by default.
+    ///
+    /// With OpenGL 4.5 or the `ARB_clip_control` extension, the depth is mapped to `[0, 1]` in
+    /// normalized device coordinates with `glClipControl`, and the scene is drawn into an
+    /// off-screen buffer with a 32-bits floating-point depth buffer, which is then copied to the
+    /// screen. The precision of the floating-point depth then compensates the one lost by the
+    /// perspective division, so distant objects do not flicker, even with an infinite far plane.
+    ///
+    /// Otherwise, e.g., with OpenGL ES 2 and WebGL, this only changes the depth convention: the
+    /// depth is negated on the range `[-1, 1]` and stored in the usual fixed-point depth buffer,
+    /// so its precision is not improved.
+    pub fn set_reversed_z(&mut self, enabled: bool) {
+        let float_depth = enabled && Context::get().supports_clip_control();
+
+        self.reversed_z = enabled;
+        self.float_depth = float_depth;
+        camera::camera::set_reversed_z(enabled, float_depth);
+
+        self.post_process_render_targets[0].set_float_depth(float_depth);
+
+        if let Some(ref mut target) = self.multisampled_render_target {
+            target.set_float_depth(float_depth);
+        }
+
+        if let Some(ref mut target) = self.texture_render_target {
+            target.set_float_depth(float_depth);
+        }
+    }
+
+    /// Whether the reversed depth buffer is enabled.
+    #[inline]
+    pub fn reversed_z(&self) -> bool {
+        self.reversed_z
+    }
+
+    /// Enables or disables backface culling for the objects which do not override it with
+    /// `SceneNode::enable_backface_culling`.
+    ///
+    /// This is enabled by default.
+    #[inline]
+    pub fn set_backface_culling(&mut self, enabled: bool) {
+        self.backface_culling = enabled;
+    }
+
+    /// Whether backface culling is enabled for the objects which do not override it.
+    #[inline]
+    pub fn backface_culling(&self) -> bool {
+        self.backface_culling
+    }
+
+    /// Sets the layers whose objects are rendered, as a bit mask.
+    ///
+    /// The object of a node is rendered only if its layer mask, set by
+    /// `SceneNode::set_layer_mask`, intersects this mask. Every layer is visible by default.
+    #[inline]
+    pub fn set_visible_layers(&mut self, mask: u32) {
+        self.visible_layers = mask;
+    }
+
+    /// The layers whose objects are rendered, as a bit mask.
+    #[inline]
+    pub fn visible_layers(&self) -> u32 {
+        self.visible_layers
+    }
+
+    /// Sets the rectangle `(x, y, width, height)` of the framebuffer the next frames are drawn
+    /// into, or `None` to use the whole framebuffer.
+    ///
+    /// The rectangle is expressed in physical pixels, from the bottom-left corner of the
+    /// framebuffer. Only this rectangle is cleared, and the cameras, post-processing effects, and
+    /// texts use its size instead of the size of the window, with texts positioned from its
+    /// top-left corner. Together with `set_auto_swap_buffers`, this allows drawing the scene
+    /// several times per frame, e.g., for split-screen:
+    ///
+    /// ```no_run
+    /// # use kiss3d::camera::ArcBall;
+    /// # use kiss3d::window::Window;
+    /// # use nalgebra::Point3;
+    /// let mut window = Window::new("Split-screen");
+    /// let mut left = ArcBall::new(Point3::new(0.0, 0.0, 10.0), Point3::origin());
+    /// let mut right = ArcBall::new(Point3::new(10.0, 0.0, 0.0), Point3::origin());
+    /// window.set_auto_swap_buffers(false);
+    ///
+    /// loop {
+    ///     let (w, h) = (window.width() as i32, window.height() as i32);
+    ///     window.set_viewport(Some((0, 0, w / 2, h)));
+    ///     let open = window.render_with_camera(&mut left);
+    ///     window.set_viewport(Some((w / 2, 0, w - w / 2, h)));
+    ///     let open = window.render_with_camera(&mut right) && open;
+    ///     window.swap_buffers();
+    ///
+    ///     if !open {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn set_viewport(&mut self, viewport: Option<(i32, i32, i32, i32)>) {
+        self.viewport = viewport;
+    }
+
+    /// The rectangle of the framebuffer the frames are drawn into, or `None` if it is the whole
+    /// framebuffer.
+    #[inline]
+    pub fn viewport(&self) -> Option<(i32, i32, i32, i32)> {
+        self.viewport
+    }
+
+    /// Sets whether the rendering methods display the frame they draw by swapping the buffers.
+    ///
+    /// If this is disabled, the frame is displayed only when `swap_buffers` is called, so it can
+    /// be drawn by several calls to the rendering methods. This is enabled by default.
+    #[inline]
+    pub fn set_auto_swap_buffers(&mut self, enabled: bool) {
+        self.auto_swap_buffers = enabled;
+    }
+
+    /// Whether the rendering methods display the frame they draw by swapping the buffers.
+    #[inline]
+    pub fn auto_swap_buffers(&self) -> bool {
+        self.auto_swap_buffers
+    }
+
+    /// Enables or disables shadows.
+    ///
+    /// If enabled, the first `light::MAX_SHADOWS` directional lights or spotlights of the scene
+    /// cast shadows on the objects drawn with the default material, except those disabled with
+    /// `Lights::set_casts_shadow`. This is disabled by default.
+    #[inline]
+    pub fn enable_shadows(&mut self, enabled: bool) {
+        self.shadows = enabled;
+    }
+
+    /// Whether shadows are enabled.
+    #[inline]
+    pub fn shadows_enabled(&self) -> bool {
+        self.shadows
+    }
+
+    /// Sets the width and height of the shadow maps, in pixels.
+    ///
+    /// Each light casting shadows has its own shadow map. Higher resolutions give sharper
+    /// shadows. The default is 1024.
+    #[inline]
+    pub fn set_shadow_map_resolution(&mut self, resolution: u32) {
+        self.shadow_map_renderer.set_resolution(resolution)
+    }
+
+    /// Sets the depth offset used to avoid shadowed stripes on lit surfaces ("shadow acne").
+    ///
+    /// Too large values make the shadows detach from their casters. The default is 0.005. This
+    /// is overridden for some lights with `Lights::set_shadow_bias`.
+    #[inline]
+    pub fn set_shadow_bias(&mut self, bias: f32) {
+        self.shadow_map_renderer.set_bias(bias)
+    }
+
+    /// Sets the radius of the filter smoothing the borders of the shadows, in texels of the
+    /// shadow maps.
+    ///
+    /// Use `0.0` for hard shadows. Large radii give banded shadows. The default is 1.0.
+    #[inline]
+    pub fn set_shadow_softness(&mut self, softness: f32) {
+        self.shadow_map_renderer.set_softness(softness)
+    }
+
     /// Set the size of all subsequent points to be drawn until the next time this function is envoked.
     #[inline]
     pub fn set_point_size(&mut self, pt_size: f32) {
@@ -194,11 +761,24 @@ impl Window {
     }
 
     /// Set the width of all subsequent lines to be drawn until the next time this function is envoked.
+    ///
+    /// The width is in pixels. Lines wider than one pixel are expanded into quads facing the
+    /// camera, so they are supported by every context. The lines of a frame all have the width set
+    /// when the frame is rendered.
     #[inline]
     pub fn set_line_width(&mut self, line_width: f32) {
         self.line_renderer.set_line_width(line_width);
     }
 
+    /// Enables or disables the anti-aliasing of the lines drawn by `draw_line`.
+    ///
+    /// Anti-aliased lines are expanded into quads facing the camera, with edges fading out, and
+    /// blended with the scene behind them.
+    #[inline]
+    pub fn set_line_antialiasing(&mut self, antialiasing: bool) {
+        self.line_renderer.set_antialiasing(antialiasing);
+    }
+
     /// Adds a 3D line to be drawn during the next render.
     ///
     /// The line is being drawn only during the next frame after this call.
@@ -208,6 +788,92 @@ impl Window {
         self.line_renderer.draw_line(*a, *b, *color);
     }
 
+    /// Adds the 12 edges of an axis-aligned box to be drawn during the next render.
+    ///
+    /// The box is drawn with the lines of `draw_line`, between its corners `mins` and `maxs`.
+    pub fn draw_aabb(&mut self, mins: &Point3<f32>, maxs: &Point3<f32>, color: &Point3<f32>) {
+        let corner = |i: usize| {
+            Point3::new(
+                if i & 1 == 0 { mins.x } else { maxs.x },
+                if i & 2 == 0 { mins.y } else { maxs.y },
+                if i & 4 == 0 { mins.z } else { maxs.z },
+            )
+        };
+
+        for i in 0..8 {
+            // Link each corner to its neighbors with a greater index.
+            for bit in &[1, 2, 4] {
+                if i & bit == 0 {
+                    self.draw_line(&corner(i), &corner(i | bit), color);
+                }
+            }
+        }
+    }
+
+    /// Adds three circles outlining a sphere to be drawn during the next render.
+    ///
+    /// The circles lie on the planes orthogonal to the `x`, `y`, and `z` axes through `center`,
+    /// and are each approximated with `segments` lines of `draw_line`.
+    pub fn draw_wire_sphere(
+        &mut self,
+        center: &Point3<f32>,
+        radius: f32,
+        color: &Point3<f32>,
+        segments: u32,
+    ) {
+        let segments = segments.max(3);
+        let circle = |i: u32, axes: (usize, usize)| {
+            let angle = i as f32 * std::f32::consts::PI * 2.0 / segments as f32;
+            let mut pt = *center;
+            pt[axes.0] += angle.cos() * radius;
+            pt[axes.1] += angle.sin() * radius;
+            pt
+        };
+
+        for &axes in &[(1, 2), (0, 2), (0, 1)] {
+            for i in 0..segments {
+                self.draw_line(&circle(i, axes), &circle(i + 1, axes), color);
+            }
+        }
+    }
+
+    /// Adds the axes of a frame to be drawn during the next render.
+    ///
+    /// The local `x`, `y`, and `z` axes of `transform` are drawn in red, green, and blue
+    /// respectively, from its origin, with the length `scale` and the lines of `draw_line`.
+    pub fn draw_axes(&mut self, transform: &Isometry3<f32>, scale: f32) {
+        let origin = transform * Point3::origin();
+
+        for i in 0..3 {
+            let mut end = Point3::origin();
+            let mut color = Point3::origin();
+            end[i] = scale;
+            color[i] = 1.0;
+
+            self.draw_line(&origin, &(transform * end), &color);
+        }
+    }
+
+    /// Adds a 3D line drawn at each frame for the given duration.
+    ///
+    /// The line is drawn with the lines of `draw_line` until `duration` has elapsed according to
+    /// the frame clock, or until `clear_debug_lines` is called.
+    pub fn draw_line_for(
+        &mut self,
+        a: &Point3<f32>,
+        b: &Point3<f32>,
+        color: &Point3<f32>,
+        duration: Duration,
+    ) {
+        self.debug_lines
+            .push((*a, *b, *color, self.curr_time + duration));
+    }
+
+    /// Removes all the lines added by `draw_line_for`.
+    pub fn clear_debug_lines(&mut self) {
+        self.debug_lines.clear()
+    }
+
     /// Draws a 2D line to be drawn during the next render.
     ///
     /// The line is being drawn only during the next frame after this call.
@@ -217,12 +883,47 @@ impl Window {
         self.planar_line_renderer.draw_line(*a, *b, *color);
     }
 
+    /// Adds a 2D polyline drawn at each frame until it is removed.
+    ///
+    /// Each point of `points` is linked to the next one. The polyline is drawn with the lines of
+    /// `draw_planar_line`, through the planar camera. Returns the identifier of the polyline, to be
+    /// given to `remove_planar_polyline`.
+    pub fn add_planar_polyline(&mut self, points: Vec<Point2<f32>>, color: &Point3<f32>) -> usize {
+        self.planar_line_renderer.add_polyline(points, *color)
+    }
+
+    /// Removes the 2D polyline with the identifier `id`.
+    ///
+    /// Returns `false` if there was no such polyline.
+    pub fn remove_planar_polyline(&mut self, id: usize) -> bool {
+        self.planar_line_renderer.remove_polyline(id)
+    }
+
+    /// Removes all the 2D polylines added by `add_planar_polyline`.
+    pub fn clear_planar_polylines(&mut self) {
+        self.planar_line_renderer.clear_polylines()
+    }
+
     /// Adds a point to be drawn during the next frame.
     #[inline]
     pub fn draw_point(&mut self, pt: &Point3<f32>, color: &Point3<f32>) {
         self.point_renderer.draw_point(*pt, *color);
     }
 
+    /// Adds a point drawn at each frame for the given duration.
+    ///
+    /// The point is drawn with the points of `draw_point` until `duration` has elapsed according
+    /// to the frame clock, or until `clear_debug_points` is called.
+    pub fn draw_point_for(&mut self, pt: &Point3<f32>, color: &Point3<f32>, duration: Duration) {
+        self.debug_points
+            .push((*pt, *color, self.curr_time + duration));
+    }
+
+    /// Removes all the points added by `draw_point_for`.
+    pub fn clear_debug_points(&mut self) {
+        self.debug_points.clear()
+    }
+
     /// Adds a string to be drawn during the next frame.
     #[inline]
     pub fn draw_text(
@@ -236,6 +937,91 @@ impl Window {
         self.text_renderer.draw_text(text, pos, scale, font, color);
     }
 
+    /// Adds a string to be drawn during the next frame, aligned relative to `pos`.
+    ///
+    /// For example, `HAlign::Center` and `VAlign::Middle` center the text on `pos`.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text_aligned(
+        &mut self,
+        text: &str,
+        pos: &Point2<f32>,
+        scale: f32,
+        font: &Rc<Font>,
+        color: &Point3<f32>,
+        h_align: HAlign,
+        v_align: VAlign,
+    ) {
+        self.text_renderer
+            .draw_text_aligned(text, pos, scale, font, color, h_align, v_align);
+    }
+
+    /// Adds a string to be drawn during the next frame, centered on the projection of the point
+    /// `world_pos` of the scene.
+    ///
+    /// The point is projected with the camera the next frame is rendered with, and the text is
+    /// not drawn if the point is behind it. The size of the text does not depend on the distance
+    /// of the point. Unless disabled with `set_text_depth_test`, the text is hidden by the
+    /// objects of the scene in front of the point.
+    #[inline]
+    pub fn draw_text_3d(
+        &mut self,
+        text: &str,
+        world_pos: &Point3<f32>,
+        scale: f32,
+        font: &Rc<Font>,
+        color: &Point3<f32>,
+    ) {
+        self.labels.push(Label {
+            text: text.to_string(),
+            pos: *world_pos,
+            scale,
+            font: font.clone(),
+            color: *color,
+        })
+    }
+
+    /// Whether the texts drawn by `draw_text_3d` are hidden by the objects in front of them.
+    ///
+    /// This is `true` by default.
+    #[inline]
+    pub fn text_depth_test(&self) -> bool {
+        self.label_renderer.depth_test()
+    }
+
+    /// Sets whether the texts drawn by `draw_text_3d` are hidden by the objects in front of them.
+    ///
+    /// If disabled, these texts are drawn over the whole 3D scene, but still below the planar
+    /// scene and the texts drawn by `draw_text`.
+    #[inline]
+    pub fn set_text_depth_test(&mut self, enabled: bool) {
+        self.label_renderer.set_depth_test(enabled)
+    }
+
+    /// Whether the texts are drawn from signed distance fields.
+    #[inline]
+    pub fn text_sdf(&self) -> bool {
+        self.text_renderer.sdf()
+    }
+
+    /// Sets whether the texts are drawn from signed distance fields, which stay sharp at any
+    /// scale.
+    ///
+    /// This applies to the texts drawn by `draw_text` and `draw_text_3d`. See
+    /// `TextRenderer::set_sdf` for details.
+    pub fn set_text_sdf(&mut self, enabled: bool) {
+        self.text_renderer.set_sdf(enabled);
+        self.label_renderer.set_sdf(enabled);
+    }
+
+    /// Sets the outline drawn around the texts when they are drawn from signed distance fields.
+    ///
+    /// See `TextRenderer::set_sdf_outline` for details.
+    pub fn set_text_sdf_outline(&mut self, width: f32, softness: f32, color: &Point3<f32>) {
+        self.text_renderer.set_sdf_outline(width, softness, color);
+        self.label_renderer.set_sdf_outline(width, softness, color);
+    }
+
     /// Removes an object from the scene.
     #[deprecated(note = "Use `remove_node` instead.")]
     pub fn remove(&mut self, sn: &mut SceneNode) {
@@ -275,11 +1061,96 @@ impl Window {
         self.scene.add_obj(path, mtl_dir, scale)
     }
 
+    /// Adds an obj model to the scene, calling `progress` with the fraction of the file parsed so
+    /// far.
+    ///
+    /// See `SceneNode::add_obj_with_progress` for details.
+    pub fn add_obj_with_progress<F: FnMut(f32)>(
+        &mut self,
+        path: &Path,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+        progress: F,
+    ) -> SceneNode {
+        self.scene
+            .add_obj_with_progress(path, mtl_dir, scale, progress)
+    }
+
+    /// Starts loading an obj file in the background, and adds a new node to the scene to hold its
+    /// objects.
+    ///
+    /// See `SceneNode::add_obj_async` for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_obj_async(
+        &mut self,
+        path: &Path,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+    ) -> PendingNode {
+        self.scene.add_obj_async(path, mtl_dir, scale)
+    }
+
+    /// Adds a glTF model to the scene.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the `.gltf` or `.glb` file.
+    /// * `scale` - scale to apply to the model.
+    pub fn add_gltf(&mut self, path: &Path, scale: Vector3<f32>) -> SceneNode {
+        self.scene.add_gltf(path, scale)
+    }
+
+    /// Adds a glTF model to the scene, calling `progress` with the fraction of the file loaded so
+    /// far.
+    ///
+    /// See `SceneNode::add_gltf_with_progress` for details.
+    pub fn add_gltf_with_progress<F: FnMut(f32)>(
+        &mut self,
+        path: &Path,
+        scale: Vector3<f32>,
+        progress: F,
+    ) -> SceneNode {
+        self.scene.add_gltf_with_progress(path, scale, progress)
+    }
+
+    /// Adds a stl model to the scene.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the stl file.
+    /// * `scale` - scale to apply to the model.
+    pub fn add_stl(&mut self, path: &Path, scale: Vector3<f32>) -> SceneNode {
+        self.scene.add_stl(path, scale)
+    }
+
     /// Adds an unnamed mesh to the scene.
     pub fn add_mesh(&mut self, mesh: Rc<RefCell<Mesh>>, scale: Vector3<f32>) -> SceneNode {
         self.scene.add_mesh(mesh, scale)
     }
 
+    /// Adds a node drawing many instances of the same mesh to the scene.
+    ///
+    /// Each instance is given a transformation relative to the new node, and a color. If `colors`
+    /// is empty, all the instances are white.
+    pub fn add_instanced(
+        &mut self,
+        mesh: Rc<RefCell<Mesh>>,
+        transforms: &[Isometry3<f32>],
+        colors: &[Point3<f32>],
+    ) -> InstancedNode {
+        self.scene.add_instanced(mesh, transforms, colors)
+    }
+
+    /// Adds a node drawing a point cloud to the scene.
+    ///
+    /// If `colors` is empty, all the points are white. Otherwise, it must have the same length as
+    /// `points`. The point size can be changed with `PointCloudNode::set_point_size`.
+    pub fn add_point_cloud(
+        &mut self,
+        points: Vec<Point3<f32>>,
+        colors: Vec<Point3<f32>>,
+    ) -> PointCloudNode {
+        self.scene.add_point_cloud(points, colors)
+    }
+
     /// Adds an unnamed planar mesh to the scene.
     pub fn add_planar_mesh(
         &mut self,
@@ -322,6 +1193,53 @@ impl Window {
         self.scene.add_sphere(r)
     }
 
+    /// Adds a sphere made of almost equal triangles to the scene. The sphere is initially
+    /// centered at (0, 0, 0).
+    ///
+    /// Contrary to `add_sphere`, there is no pinching at the poles. The sphere has
+    /// `20 * 4^subdivisions` triangles.
+    ///
+    /// # Arguments
+    /// * `radius` - the sphere radius
+    /// * `subdivisions` - the number of times the faces of the initial icosahedron are split. It
+    ///   is clamped to `procedural::MAX_ICOSPHERE_SUBDIVISIONS`.
+    pub fn add_icosphere(&mut self, radius: f32, subdivisions: u32) -> SceneNode {
+        self.scene.add_icosphere(radius, subdivisions)
+    }
+
+    /// Adds a torus to the scene. The torus is initially centered at (0, 0, 0) and has its axis
+    /// aligned with the `y` axis.
+    ///
+    /// The texture coordinates have a seam along the tube, where the vertices are duplicated.
+    ///
+    /// # Arguments
+    /// * `major_radius` - the distance from the center of the torus to the center of the tube
+    /// * `minor_radius` - the radius of the tube
+    /// * `major_segments` - the number of subdivisions around the `y` axis
+    /// * `minor_segments` - the number of subdivisions around the tube
+    ///
+    /// `major_segments` and `minor_segments` are clamped to at least 3.
+    pub fn add_torus(
+        &mut self,
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    ) -> SceneNode {
+        self.scene
+            .add_torus(major_radius, minor_radius, major_segments, minor_segments)
+    }
+
+    /// Adds the convex hull of a set of points to the scene.
+    ///
+    /// The hull is shaded flat, with its normals pointing outward.
+    ///
+    /// Returns an error, without adding anything to the scene, if there are less than 4 points or
+    /// if they are all coplanar.
+    pub fn add_convex_hull(&mut self, points: &[Point3<f32>]) -> Result<SceneNode, &'static str> {
+        self.scene.add_convex_hull(points)
+    }
+
     /// Adds a cone to the scene. The cone is initially centered at (0, 0, 0) and points toward the
     /// positive `y` axis.
     ///
@@ -332,6 +1250,20 @@ impl Window {
         self.scene.add_cone(r, h)
     }
 
+    /// Adds a cone with the given tessellation to the scene.
+    ///
+    /// See `SceneNode::add_cone_with_subdivisions` for details.
+    pub fn add_cone_with_subdivisions(
+        &mut self,
+        r: f32,
+        h: f32,
+        radial_segments: u32,
+        height_segments: u32,
+    ) -> SceneNode {
+        self.scene
+            .add_cone_with_subdivisions(r, h, radial_segments, height_segments)
+    }
+
     /// Adds a cylinder to the scene. The cylinder is initially centered at (0, 0, 0) and has its
     /// principal axis aligned with the `y` axis.
     ///
@@ -342,6 +1274,20 @@ impl Window {
         self.scene.add_cylinder(r, h)
     }
 
+    /// Adds a cylinder with the given tessellation to the scene.
+    ///
+    /// See `SceneNode::add_cylinder_with_subdivisions` for details.
+    pub fn add_cylinder_with_subdivisions(
+        &mut self,
+        r: f32,
+        h: f32,
+        radial_segments: u32,
+        height_segments: u32,
+    ) -> SceneNode {
+        self.scene
+            .add_cylinder_with_subdivisions(r, h, radial_segments, height_segments)
+    }
+
     /// Adds a capsule to the scene. The capsule is initially centered at (0, 0, 0) and has its
     /// principal axis aligned with the `y` axis.
     ///
@@ -352,6 +1298,26 @@ impl Window {
         self.scene.add_capsule(r, h)
     }
 
+    /// Adds a capsule with the given tessellation to the scene.
+    ///
+    /// See `SceneNode::add_capsule_with_subdivisions` for details.
+    pub fn add_capsule_with_subdivisions(
+        &mut self,
+        r: f32,
+        h: f32,
+        radial_segments: u32,
+        height_segments: u32,
+        cap_segments: u32,
+    ) -> SceneNode {
+        self.scene.add_capsule_with_subdivisions(
+            r,
+            h,
+            radial_segments,
+            height_segments,
+            cap_segments,
+        )
+    }
+
     /// Adds a 2D capsule to the scene. The capsule is initially centered at (0, 0) and has its
     /// principal axis aligned with the `y` axis.
     ///
@@ -378,6 +1344,67 @@ impl Window {
         self.scene.add_quad(w, h, usubdivs, vsubdivs)
     }
 
+    /// Adds a terrain built from a grid of heights to the scene.
+    ///
+    /// The vertices of the mesh are in the same order as `heights`, i.e., the vertex at column
+    /// `ix` and row `iz` has the index `iz * nx + ix`, so the heights can be updated later by
+    /// modifying the coordinates of the mesh.
+    ///
+    /// A mesh cannot have more than 65536 vertices. Larger grids are split into tiles of at most
+    /// `procedural::MAX_HEIGHTMAP_TILE_SIZE` vertices along each axis, and the returned node is
+    /// a group with one child per tile. See `procedural::heightmap_tiles`.
+    ///
+    /// # Arguments
+    /// * `heights` - the heights of the vertices, of length `nx * nz`
+    /// * `nx` - the number of vertices along the `x` axis. Must be at least 2.
+    /// * `nz` - the number of vertices along the `z` axis. Must be at least 2.
+    /// * `scale` - the extents of the terrain along the `x` and `z` axis, and the factor applied
+    ///   to the heights
+    pub fn add_heightmap(
+        &mut self,
+        heights: &[f32],
+        nx: usize,
+        nz: usize,
+        scale: Vector3<f32>,
+    ) -> SceneNode {
+        self.scene.add_heightmap(heights, nx, nz, scale)
+    }
+
+    /// Adds a terrain built from a grayscale image to the scene.
+    ///
+    /// Each pixel gives the height of one vertex, black being 0 and white being 1 before
+    /// `scale.y` is applied. Images of more than 65536 pixels are split into several meshes as
+    /// described by `add_heightmap`.
+    ///
+    /// # Arguments
+    /// * `path` - relative path to the image file.
+    /// * `scale` - the extents of the terrain along the `x` and `z` axis, and the factor applied
+    ///   to the heights
+    pub fn add_heightmap_from_image(&mut self, path: &Path, scale: Vector3<f32>) -> SceneNode {
+        self.scene.add_heightmap_from_image(path, scale)
+    }
+
+    /// Adds a grid of lines on the `xz` plane, centered at (0, 0, 0), to the scene.
+    ///
+    /// See `SceneNode::add_grid` for details.
+    pub fn add_grid(&mut self, size: f32, subdivisions: u32, color: Point3<f32>) -> SceneNode {
+        self.scene.add_grid(size, subdivisions, color)
+    }
+
+    /// Adds a grid of lines on the `xz` plane with a filled square underneath it to the scene.
+    ///
+    /// See `SceneNode::add_grid_with_floor` for details.
+    pub fn add_grid_with_floor(
+        &mut self,
+        size: f32,
+        subdivisions: u32,
+        color: Point3<f32>,
+        floor_color: Point3<f32>,
+    ) -> SceneNode {
+        self.scene
+            .add_grid_with_floor(size, subdivisions, color, floor_color)
+    }
+
     /// Adds a double-sided quad with the specified vertices.
     pub fn add_quad_with_vertices(
         &mut self,
@@ -403,6 +1430,17 @@ impl Window {
         self.scene2.add_rectangle(wx, wy)
     }
 
+    /// Adds a textured rectangle to the scene. The sprite is initially axis-aligned and centered at
+    /// (0, 0).
+    ///
+    /// # Arguments
+    /// * `width` - the sprite extent along the x axis
+    /// * `height` - the sprite extent along the y axis
+    /// * `texture` - the texture displayed by the sprite
+    pub fn add_sprite(&mut self, width: f32, height: f32, texture: Rc<Texture>) -> PlanarSceneNode {
+        self.scene2.add_sprite(width, height, texture)
+    }
+
     /// Adds a circle to the scene. The circle is initially centered at (0, 0, 0).
     ///
     /// # Arguments
@@ -428,14 +1466,44 @@ impl Window {
         false // FIXME
     }
 
-    /// The scale factor of this screen.
+    /// The scale factor of this screen, i.e., the number of physical pixels per logical pixel.
+    ///
+    /// This is greater than 1.0 on high-DPI screens.
     pub fn scale_factor(&self) -> f64 {
         self.canvas.scale_factor()
     }
 
-    /// Sets the light mode. Only one light is supported.
-    pub fn set_light(&mut self, pos: Light) {
-        self.light_mode = pos;
+    /// Replaces all the lights of the scene by a single light.
+    pub fn set_light(&mut self, light: Light) {
+        self.lights.clear();
+        let _ = self.lights.add(light);
+    }
+
+    /// Adds a light to the scene.
+    ///
+    /// Returns an error message, and leaves the scene unchanged, if it already contains
+    /// `light::MAX_LIGHTS` lights.
+    pub fn add_light(&mut self, light: Light) -> Result<LightId, String> {
+        self.lights.add(light)
+    }
+
+    /// Removes a light from the scene, and returns it.
+    ///
+    /// Returns `None` if this light has already been removed.
+    pub fn remove_light(&mut self, id: LightId) -> Option<Light> {
+        self.lights.remove(id)
+    }
+
+    /// The lights of the scene.
+    #[inline]
+    pub fn lights(&self) -> &Lights {
+        &self.lights
+    }
+
+    /// The lights of the scene.
+    #[inline]
+    pub fn lights_mut(&mut self) -> &mut Lights {
+        &mut self.lights
     }
 
     /// Retrieve a mutable reference to the UI based on Conrod.
@@ -484,6 +1552,46 @@ impl Window {
         state.widget_capturing_keyboard.is_some() && state.widget_capturing_keyboard != window_id
     }
 
+    /// Returns `true` if the mouse is interacting with a Conrod widget or is over one.
+    ///
+    /// The cameras do not receive the mouse events while a widget captures the mouse, e.g., while
+    /// dragging a slider. They do not receive the button presses and the scrolls over a widget
+    /// either, but still receive the motions and releases of the mouse, so a rotation started
+    /// over the scene is not interrupted when the cursor passes over a widget.
+    #[cfg(feature = "conrod")]
+    pub fn ui_wants_mouse(&self) -> bool {
+        let ui = self.conrod_ui();
+        let state = &ui.global_input().current;
+        let window_id = Some(ui.window);
+
+        self.is_conrod_ui_capturing_mouse()
+            || (state.widget_under_mouse.is_some() && state.widget_under_mouse != window_id)
+    }
+
+    /// Returns `true` if the keyboard is interacting with a Conrod widget, e.g., a text box.
+    ///
+    /// The cameras do not receive the keyboard events meanwhile.
+    #[cfg(feature = "conrod")]
+    pub fn ui_wants_keyboard(&self) -> bool {
+        self.is_conrod_ui_capturing_keyboard()
+    }
+
+    /// Whether the Conrod user interface is drawn before the post-processing effects.
+    #[cfg(feature = "conrod")]
+    pub fn ui_post_processed(&self) -> bool {
+        self.conrod_context.post_processed
+    }
+
+    /// Sets whether the Conrod user interface is drawn before the post-processing effects, so
+    /// they are also applied to it (default at `false`).
+    ///
+    /// By default, the user interface is drawn on top of the post-processed scene. This has no
+    /// effect on the frames rendered without any post-processing effect.
+    #[cfg(feature = "conrod")]
+    pub fn set_ui_post_processed(&mut self, post_processed: bool) {
+        self.conrod_context.post_processed = post_processed;
+    }
+
     /// Opens a window, hide it then calls a user-defined procedure.
     ///
     /// # Arguments
@@ -515,6 +1623,59 @@ impl Window {
         Window::do_new(title, false, width, height, Some(setup))
     }
 
+    /// Opens a window with multisample anti-aliasing.
+    ///
+    /// The scene is also rendered with `samples` samples per pixel when post-processing effects
+    /// are active. If this number of samples is not supported, a warning is printed and the
+    /// window is rendered without multisampling.
+    ///
+    /// # Arguments
+    /// * `title` - the window title.
+    /// * `width` - the window width.
+    /// * `height` - the window height.
+    /// * `samples` - the number of samples per pixel, usually 2, 4, or 8.
+    pub fn new_with_samples(title: &str, width: u32, height: u32, samples: u32) -> Window {
+        let samples = NumSamples::from_u32(samples).unwrap_or_else(|| {
+            println!(
+                "Warning: {} samples per pixel are not supported, disabling multisampling.",
+                samples
+            );
+            NumSamples::Zero
+        });
+        let setup = CanvasSetup {
+            vsync: true,
+            samples,
+        };
+
+        Window::do_new(title, false, width, height, Some(setup))
+    }
+
+    /// Creates a window without any visible window nor event, rendering to an off-screen
+    /// framebuffer.
+    ///
+    /// The frames are rendered by explicit calls to `render` or `render_to_image`, and the
+    /// window is closed only by `close`. This requires the OSMesa library on Linux and BSD, but
+    /// no display server. On Windows and macOS, a headless OpenGL context is used. An error is
+    /// returned on the web, or if the context cannot be created.
+    ///
+    /// # Arguments
+    /// * `title` - the window title, unused.
+    /// * `width` - the width of the rendered images.
+    /// * `height` - the height of the rendered images.
+    pub fn new_headless(title: &str, width: u32, height: u32) -> IoResult<Window> {
+        let _ = title;
+        let (event_send, event_receive) = mpsc::channel();
+        let canvas = Canvas::open_headless(width, height, None, event_send)?;
+
+        Ok(Window::with_canvas(
+            canvas,
+            event_receive,
+            false,
+            width,
+            height,
+        ))
+    }
+
     // FIXME: make this pub?
     fn do_new(
         title: &str,
@@ -525,7 +1686,35 @@ impl Window {
     ) -> Window {
         let (event_send, event_receive) = mpsc::channel();
         let canvas = Canvas::open(title, hide, width, height, setup, event_send);
+        let mut window = Window::with_canvas(canvas, event_receive, hide, width, height);
+        let samples = setup.map(|s| s.samples as u32).unwrap_or(0);
+
+        if samples > 1 {
+            window.multisampled_render_target = FramebufferManager::new_multisampled_render_target(
+                width as usize,
+                height as usize,
+                samples,
+            );
+
+            if window.multisampled_render_target.is_none() {
+                println!(
+                    "Warning: {} samples per pixel are not supported by off-screen buffers, \
+                     post-processing effects will be rendered without multisampling.",
+                    samples
+                );
+            }
+        }
+
+        window
+    }
 
+    fn with_canvas(
+        canvas: Canvas,
+        event_receive: Receiver<WindowEvent>,
+        hide: bool,
+        width: u32,
+        height: u32,
+    ) -> Window {
         init_gl();
 
         let mut usr_window = Window {
@@ -534,24 +1723,56 @@ impl Window {
             canvas: canvas,
             events: Rc::new(event_receive),
             unhandled_events: Rc::new(RefCell::new(Vec::new())),
+            event_handlers: Vec::new(),
             scene: SceneNode::new_empty(),
             scene2: PlanarSceneNode::new_empty(),
-            light_mode: Light::Absolute(Point3::new(0.0, 10.0, 0.0)),
+            lights: Lights::from(Light::Absolute(Point3::new(0.0, 10.0, 0.0))),
             background: Vector3::new(0.0, 0.0, 0.0),
+            frustum_culling: false,
+            reversed_z: false,
+            float_depth: false,
+            backface_culling: true,
+            visible_layers: u32::MAX,
+            viewport: None,
+            auto_swap_buffers: true,
+            frame_started: false,
+            shadows: false,
             line_renderer: LineRenderer::new(),
             planar_line_renderer: PlanarLineRenderer::new(),
             point_renderer: PointRenderer::new(),
+            outline_renderer: WideLineRenderer::new(),
+            debug_lines: Vec::new(),
+            debug_points: Vec::new(),
+            skybox_renderer: SkyboxRenderer::new(),
+            shadow_map_renderer: ShadowMapRenderer::new(),
             text_renderer: TextRenderer::new(),
+            labels: Vec::new(),
+            label_renderer: TextRenderer::new(),
             #[cfg(feature = "conrod")]
             conrod_context: ConrodContext::new(width as f64, height as f64),
-            post_process_render_target: FramebufferManager::new_render_target(
-                width as usize,
-                height as usize,
-                true,
-            ),
+            post_process_render_targets: [
+                // The stencil buffer is used to outline objects.
+                FramebufferManager::new_stencil_render_target(
+                    width as usize,
+                    height as usize,
+                    true,
+                ),
+                FramebufferManager::new_render_target(width as usize, height as usize, false),
+                FramebufferManager::new_render_target(width as usize, height as usize, false),
+            ],
+            multisampled_render_target: None,
+            post_processed: false,
+            texture_render_target: None,
+            clip_planes: (0.1, 1024.0),
+            post_processing_effects: Vec::new(),
+            screen_copy: ScreenCopy::new(),
             framebuffer_manager: FramebufferManager::new(),
-            #[cfg(not(target_arch = "wasm32"))]
             curr_time: Instant::now(),
+            start_time: Instant::now(),
+            frame_delta: Duration::from_secs(0),
+            smoothed_frame_delta: Duration::from_secs(0),
+            fps_overlay: FpsOverlay::new(),
+            render_stats: RenderStats::default(),
             planar_camera: Rc::new(RefCell::new(FixedView::new())),
             camera: Rc::new(RefCell::new(ArcBall::new(
                 Point3::new(0.0f32, 0.0, -1.0),
@@ -559,13 +1780,13 @@ impl Window {
             ))),
         };
 
+        usr_window.label_renderer.set_depth_test(true);
+
         if hide {
             usr_window.canvas.hide()
         }
 
         // usr_window.framebuffer_size_callback(DEFAULT_WIDTH, DEFAULT_HEIGHT);
-        let light = usr_window.light_mode.clone();
-        usr_window.set_light(light);
 
         usr_window
     }
@@ -621,7 +1842,11 @@ impl Window {
     }
 
     /// Get the current screen as an image
-    pub fn snap_image(&self) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    ///
+    /// The image has the size of the framebuffer, which is larger than the logical size of the
+    /// window on high-DPI screens. Its origin is its top-left corner, and it includes the
+    /// post-processing effects.
+    pub fn snap_image(&self) -> RgbImage {
         let (width, height) = self.canvas.size();
         let mut buf = Vec::new();
         self.snap(&mut buf);
@@ -630,11 +1855,146 @@ impl Window {
         imageops::flip_vertical(&img)
     }
 
+    /// Renders the scene with the default camera, and returns the resulting image.
+    ///
+    /// This is mostly useful for windows created with `new_headless`.
+    pub fn render_to_image(&mut self) -> RgbImage {
+        let _ = self.render();
+        self.snap_image()
+    }
+
+    /// Saves the current screen as a PNG image.
+    ///
+    /// See `snap_image` for the content of the image.
+    pub fn save_screenshot(&self, path: &Path) -> ImageResult<()> {
+        self.snap_image().save_with_format(path, ImageFormat::Png)
+    }
+
+    /// Read the depth of the scene displayed to the screen.
+    ///
+    /// The depth of each pixel is given in normalized device coordinates, i.e., on the range
+    /// `[-1.0, 1.0]` from the near to the far clip plane, with the same resolution and row order
+    /// as `snap`. The depth is converted back to this convention if it is reversed with
+    /// `set_reversed_z`. This works whether post-processing effects are used or not, but is not
+    /// supported by WebGL.
+    ///
+    /// Returns the near and far clip planes of the camera used for the last frame. With a
+    /// perspective projection, the view-space depth of a pixel is
+    /// `2.0 * znear / (1.0 + znear / zfar - depth * (1.0 - znear / zfar))`, which also holds if
+    /// `zfar` is `f32::INFINITY`.
+    ///
+    /// # Arguments:
+    /// * `out` - the output buffer. It is automatically resized.
+    pub fn snap_depth(&self, out: &mut Vec<f32>) -> (f32, f32) {
+        let (width, height) = self.canvas.size();
+        let size = (width * height) as usize;
+
+        out.clear();
+        out.resize(size, 0.0);
+
+        let target = if self.post_processed {
+            &self.post_process_render_targets[0]
+        } else {
+            &FramebufferManager::screen()
+        };
+
+        self.framebuffer_manager.read_depth(
+            target,
+            0,
+            0,
+            width as usize,
+            height as usize,
+            &mut out[..],
+        );
+
+        for depth in out.iter_mut() {
+            if self.reversed_z {
+                *depth = 1.0 - *depth;
+            }
+
+            *depth = *depth * 2.0 - 1.0;
+        }
+
+        self.clip_planes
+    }
+
+    /// Get the depth of the current screen as a grayscale image.
+    ///
+    /// The view-space depth is mapped linearly from black at the near clip plane to white at the
+    /// far clip plane, assuming a perspective projection. If the far clip plane is at infinity,
+    /// the depth is mapped to `1.0 - znear / depth` instead.
+    pub fn snap_depth_image(&self) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        let (width, height) = self.canvas.size();
+        let mut depths = Vec::new();
+        let (znear, zfar) = self.snap_depth(&mut depths);
+        let buf = depths
+            .iter()
+            .map(|depth| {
+                let ratio = znear / zfar;
+                let z = 2.0 * znear / (1.0 + ratio - depth * (1.0 - ratio));
+                let t = if zfar.is_finite() {
+                    (z - znear) / (zfar - znear)
+                } else {
+                    1.0 - znear / z
+                };
+                (na::clamp(t, 0.0, 1.0) * 255.0) as u8
+            })
+            .collect();
+        let img_opt = ImageBuffer::from_vec(width, height, buf);
+        let img = img_opt.expect("Buffer created from window was not big enough for image.");
+        imageops::flip_vertical(&img)
+    }
+
     /// Gets the events manager that gives access to an event iterator.
     pub fn events(&self) -> EventManager {
         EventManager::new(self.events.clone(), self.unhandled_events.clone())
     }
 
+    /// Adds a function called with each event received by the window.
+    ///
+    /// The handlers are called in the order they were added, before the window and the cameras
+    /// handle the event. If a handler returns `true`, the event is consumed: the next handlers and
+    /// the cameras will not receive it. The events inhibited while iterating through `events`
+    /// are not given to the handlers.
+    ///
+    /// ```no_run
+    /// # use kiss3d::event::{Action, Key, WindowEvent};
+    /// # use kiss3d::window::Window;
+    /// let mut window = Window::new("Event handlers");
+    /// let mut cube = window.add_cube(1.0, 1.0, 1.0);
+    ///
+    /// window.add_event_handler(Box::new(move |_, event| match *event {
+    ///     WindowEvent::Key(Key::Space, Action::Press, _) => {
+    ///         cube.set_visible(!cube.is_visible());
+    ///         true
+    ///     }
+    ///     _ => false,
+    /// }));
+    ///
+    /// while window.render() {}
+    /// ```
+    pub fn add_event_handler(&mut self, handler: EventHandler) {
+        self.event_handlers.push(handler)
+    }
+
+    /// Removes all the event handlers of this window.
+    pub fn clear_event_handlers(&mut self) {
+        self.event_handlers.clear()
+    }
+
+    // Calls the event handlers until one of them consumes `event`.
+    fn run_event_handlers(&mut self, event: &WindowEvent) -> bool {
+        // The handlers are moved out so they can borrow the window mutably.
+        let mut handlers = mem::take(&mut self.event_handlers);
+        let consumed = handlers.iter_mut().any(|handler| handler(self, event));
+
+        // Keep the handlers added by the handlers themselves.
+        handlers.append(&mut self.event_handlers);
+        self.event_handlers = handlers;
+
+        consumed
+    }
+
     /// Gets the status of a key.
     pub fn get_key(&self, key: Key) -> Action {
         self.canvas.get_key(key)
@@ -674,6 +2034,15 @@ impl Window {
         planar_camera: &mut Option<&mut dyn PlanarCamera>,
         event: &WindowEvent,
     ) {
+        if self.run_event_handlers(event) {
+            // The viewport must follow the framebuffer even if the handlers consumed the event.
+            if let WindowEvent::FramebufferSize(w, h) = *event {
+                self.update_viewport(w as f32, h as f32);
+            }
+
+            return;
+        }
+
         match *event {
             WindowEvent::Key(Key::Escape, Action::Release, _) | WindowEvent::Close => {
                 self.close();
@@ -876,24 +2245,25 @@ impl Window {
         {
             let (size, scale) = (self.size(), self.scale_factor());
             let conrod_ui = self.conrod_ui_mut();
-            if let Some(input) = window_event_to_conrod_input(*event, size, scale) {
+            if let Some(input) = window_event_to_conrod_input(event.clone(), size, scale) {
                 conrod_ui.handle_event(input);
             }
 
-            let state = &conrod_ui.global_input().current;
-            let window_id = Some(conrod_ui.window);
+            if event.is_keyboard_event() && self.ui_wants_keyboard() {
+                return;
+            }
 
-            if event.is_keyboard_event()
-                && state.widget_capturing_keyboard.is_some()
-                && state.widget_capturing_keyboard != window_id
-            {
+            if event.is_mouse_event() && self.is_conrod_ui_capturing_mouse() {
                 return;
             }
 
-            if event.is_mouse_event()
-                && state.widget_capturing_mouse.is_some()
-                && state.widget_capturing_mouse != window_id
-            {
+            // Only the interactions starting over a widget are kept from the cameras.
+            let starts_interaction = matches!(
+                *event,
+                WindowEvent::MouseButton(_, Action::Press, _) | WindowEvent::Scroll(..)
+            );
+
+            if starts_interaction && self.ui_wants_mouse() {
                 return;
             }
         }
@@ -943,6 +2313,8 @@ impl Window {
 
     /// Render using a specific post processing effect.
     ///
+    /// The effect is used instead of the post-processing effects of this window for this frame.
+    ///
     /// Returns `false` if the window should be closed.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn render_with_effect(&mut self, effect: &mut (dyn PostProcessingEffect)) -> bool {
@@ -994,6 +2366,93 @@ impl Window {
         self.render_with(Some(camera), Some(planar_camera), Some(effect))
     }
 
+    /// Draws the 3D scene seen by `camera` into `texture`, which is resized to `width` by
+    /// `height` pixels.
+    ///
+    /// The texture can then be used by a material or an object, e.g., to display a live view of
+    /// the scene on a screen inside the scene or in a Conrod image widget. Its filtering is set to
+    /// linear without mipmaps. The objects using `texture` should be hidden meanwhile, since it
+    /// cannot be read and written at the same time. The depth buffer is managed by the window and
+    /// the screen framebuffer is selected again afterwards.
+    ///
+    /// The scene is drawn with the lights and shadows of the last frame, without the lines and
+    /// points drawn with `draw_line` and `draw_point`, the planar scene, nor any post-processing
+    /// effect. The aspect ratio of `camera` is set to the one of the texture until it receives
+    /// the size of the window again, which the window does at each frame for the cameras it
+    /// renders with.
+    pub fn render_to_texture(
+        &mut self,
+        camera: &mut dyn Camera,
+        texture: &Texture,
+        width: u32,
+        height: u32,
+    ) {
+        let ctxt = Context::get();
+        let (width, height) = (width.max(1), height.max(1));
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(texture)));
+        verify!(ctxt.tex_image2d(
+            Context::TEXTURE_2D,
+            0,
+            Context::RGBA as i32,
+            width as i32,
+            height as i32,
+            0,
+            Context::RGBA,
+            None
+        ));
+        texture.set_filtering(TextureFilter::Linear, TextureFilter::Linear);
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+
+        let target = match self.texture_render_target.take() {
+            Some(mut target) => {
+                if target.size() != Some((width as usize, height as usize)) {
+                    target.resize(width as f32, height as f32);
+                }
+
+                target
+            }
+            None => {
+                let mut target =
+                    FramebufferManager::new_render_target(width as usize, height as usize, false);
+                target.set_float_depth(self.float_depth);
+                target
+            }
+        };
+
+        self.framebuffer_manager
+            .select_with_texture(&target, texture);
+        verify!(ctxt.viewport(0, 0, width as i32, height as i32));
+        verify!(ctxt.scissor(0, 0, width as i32, height as i32));
+        set_depth_test(self.reversed_z);
+        set_zero_to_one_depth(self.float_depth);
+        set_visible_layers(self.visible_layers);
+
+        camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(width, height));
+
+        for pass in 0usize..camera.num_passes() {
+            camera.start_pass(pass, &self.canvas);
+
+            verify!(ctxt.clear_color(self.background.x, self.background.y, self.background.z, 1.0));
+            verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+
+            self.skybox_renderer.render(pass, camera);
+            self.render_objects(camera, pass);
+        }
+
+        camera.render_complete(&self.canvas);
+        set_zero_to_one_depth(false);
+
+        self.texture_render_target = Some(target);
+        self.framebuffer_manager
+            .select(&FramebufferManager::screen());
+
+        let (w, h) = self.canvas.size();
+        verify!(ctxt.viewport(0, 0, w as i32, h as i32));
+        verify!(ctxt.scissor(0, 0, w as i32, h as i32));
+    }
+
     /// Draws the scene with the given camera and post-processing effect.
     ///
     /// Returns `false` if the window should be closed.
@@ -1049,31 +2508,70 @@ impl Window {
         camera: &mut dyn Camera,
         planar_camera: &mut dyn PlanarCamera,
         mut renderer: Option<&mut dyn Renderer>,
-        mut post_processing: Option<&mut dyn PostProcessingEffect>,
+        post_processing: Option<&mut dyn PostProcessingEffect>,
     ) -> bool {
+        if !self.frame_started {
+            // Ignore the work done since the end of the last frame, e.g., for loading textures.
+            let _ = render_stats::take();
+            self.frame_started = true;
+        }
+
+        #[cfg(feature = "hot-reload")]
+        crate::builtin::poll_shader_files();
+
         // XXX: too bad we have to do this at each frame…
         let w = self.width();
         let h = self.height();
+        let (vx, vy, vw, vh) = self.viewport.unwrap_or((0, 0, w as i32, h as i32));
+        let (vw, vh) = (vw.max(1) as u32, vh.max(1) as u32);
 
-        planar_camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(w, h));
-        camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(w, h));
+        // The cameras use the aspect ratio of the viewport.
+        planar_camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(vw, vh));
+        camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(vw, vh));
         planar_camera.update(&self.canvas);
         camera.update(&self.canvas);
 
-        match self.light_mode {
-            Light::StickToCamera => self.set_light(Light::StickToCamera),
-            _ => {}
-        }
+        set_visible_layers(self.visible_layers);
+        self.draw_debug_primitives();
+        self.scene.data_mut().update_look_at_targets();
 
-        if post_processing.is_some() {
+        let shadow_maps = if self.shadows {
+            self.shadow_map_renderer.render(
+                &self.scene.data(),
+                &self.lights,
+                &mut self.framebuffer_manager,
+            )
+        } else {
+            Vec::new()
+        };
+
+        // The shadow pass modifies the viewport and the depth test.
+        verify!(Context::get().viewport(vx, vy, vw as i32, vh as i32));
+        verify!(Context::get().scissor(vx, vy, vw as i32, vh as i32));
+        set_depth_test(self.reversed_z);
+
+        self.lights.set_shadow_maps(shadow_maps);
+
+        // The floating-point depth buffer is only available off-screen.
+        self.post_processed = post_processing.is_some()
+            || !self.post_processing_effects.is_empty()
+            || self.float_depth;
+
+        if self.post_processed {
             // if we need post-processing, render to our own frame buffer
-            self.framebuffer_manager
-                .select(&self.post_process_render_target);
+            match self.multisampled_render_target {
+                Some(ref target) => self.framebuffer_manager.select_multisampled(target),
+                None => self
+                    .framebuffer_manager
+                    .select(&self.post_process_render_targets[0]),
+            }
         } else {
             self.framebuffer_manager
                 .select(&FramebufferManager::screen());
         }
 
+        set_zero_to_one_depth(self.float_depth);
+
         for pass in 0usize..camera.num_passes() {
             camera.start_pass(pass, &self.canvas);
             self.render_scene(camera, pass);
@@ -1085,9 +2583,32 @@ impl Window {
 
         camera.render_complete(&self.canvas);
 
+        // The labels are drawn while the depth of the scene is still available.
+        verify!(Context::get().viewport(vx, vy, vw as i32, vh as i32));
+        self.render_labels(camera, vw as f32, vh as f32);
+        set_zero_to_one_depth(false);
+
         self.render_planar_scene(planar_camera);
 
+        #[cfg(feature = "conrod")]
+        {
+            if self.post_processed && self.conrod_context.post_processed {
+                self.render_conrod_ui(w, h);
+                verify!(Context::get().viewport(vx, vy, vw as i32, vh as i32));
+                verify!(Context::get().scissor(vx, vy, vw as i32, vh as i32));
+            }
+        }
+
+        if self.post_processed {
+            if let Some(ref target) = self.multisampled_render_target {
+                // The effects sample the resolved scene.
+                self.framebuffer_manager
+                    .resolve(target, &self.post_process_render_targets[0]);
+            }
+        }
+
         let (znear, zfar) = camera.clip_planes();
+        self.clip_planes = (znear, zfar);
 
         // FIXME: remove this completely?
         // swatch off the wireframe mode for post processing and text rendering.
@@ -1095,27 +2616,86 @@ impl Window {
         //     verify!(gl::PolygonMode(Context::FRONT_AND_BACK, Context::FILL));
         // }
 
-        if let Some(ref mut p) = post_processing {
-            // switch back to the screen framebuffer …
-            self.framebuffer_manager
-                .select(&FramebufferManager::screen());
-            // … and execute the post-process
+        // An effect given for this frame is used as a one-element chain.
+        let mut effects: Vec<&mut dyn PostProcessingEffect> = match post_processing {
+            Some(p) => vec![p],
+            None => self
+                .post_processing_effects
+                .iter_mut()
+                .map(|p| &mut **p as &mut dyn PostProcessingEffect)
+                .collect(),
+        };
+
+        // The scene rendered off-screen for its floating-point depth is copied to the screen.
+        if effects.is_empty() && self.post_processed {
+            effects.push(&mut self.screen_copy);
+        }
+        let num_effects = effects.len();
+
+        let screen = FramebufferManager::screen();
+
+        if num_effects != 0 {
+            // The effects map the whole render targets to the whole screen, so only the scissor
+            // restricts them to the viewport.
+            verify!(Context::get().viewport(0, 0, w as i32, h as i32));
+        }
+
+        for (i, p) in effects.iter_mut().enumerate() {
+            // Each effect reads the output of the previous one, and the last one draws to the
+            // screen framebuffer.
+            let input = if i == 0 {
+                &self.post_process_render_targets[0]
+            } else {
+                &self.post_process_render_targets[1 + (i - 1) % 2]
+            };
+            let output = if i + 1 == num_effects {
+                &screen
+            } else {
+                &self.post_process_render_targets[1 + i % 2]
+            };
+
             // FIXME: use the real time value instead of 0.016!
             p.update(0.016, w as f32, h as f32, znear, zfar);
-            p.draw(&self.post_process_render_target);
+            p.draw_to_with_depth(
+                &mut self.framebuffer_manager,
+                input,
+                &self.post_process_render_targets[0],
+                output,
+            );
         }
 
-        self.text_renderer.render(w as f32, h as f32);
-        #[cfg(feature = "conrod")]
-        self.conrod_context.renderer.render(
-            w as f32,
-            h as f32,
+        verify!(Context::get().viewport(vx, vy, vw as i32, vh as i32));
+        self.fps_overlay.draw(
+            &mut self.text_renderer,
+            vw as f32,
+            vh as f32,
             self.canvas.scale_factor() as f32,
-            &self.conrod_context.textures,
         );
+        self.text_renderer.render(vw as f32, vh as f32);
+
+        verify!(Context::get().viewport(0, 0, w as i32, h as i32));
+        verify!(Context::get().scissor(0, 0, w as i32, h as i32));
+        #[cfg(feature = "conrod")]
+        {
+            if !(self.post_processed && self.conrod_context.post_processed) {
+                self.render_conrod_ui(w, h);
+            }
+        }
+
+        if self.auto_swap_buffers {
+            self.swap_buffers();
+        }
+
+        !self.should_close()
+    }
 
-        // We are done: swap buffers
+    /// Displays the frame drawn since the last call to this method.
+    ///
+    /// This is called by the rendering methods unless disabled with `set_auto_swap_buffers`.
+    pub fn swap_buffers(&mut self) {
         self.canvas.swap_buffers();
+        self.render_stats = render_stats::take();
+        self.frame_started = false;
 
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -1126,14 +2706,68 @@ impl Window {
                     thread::sleep(dur - elapsed);
                 }
             }
-
-            self.curr_time = Instant::now();
         }
 
+        let now = Instant::now();
+        self.frame_delta = now - self.curr_time;
+        self.curr_time = now;
+
+        // Exponential moving average, starting from the first measured frame.
+        self.smoothed_frame_delta = if self.smoothed_frame_delta == Duration::from_secs(0) {
+            self.frame_delta
+        } else {
+            self.smoothed_frame_delta.mul_f32(0.9) + self.frame_delta.mul_f32(0.1)
+        };
+        self.fps_overlay
+            .update(self.frame_delta, self.smoothed_frame_delta);
+
         // self.transparent_objects.clear();
         // self.opaque_objects.clear();
+    }
 
-        !self.should_close()
+    // Draws the texts added by `draw_text_3d` since the last frame, at the depth of their anchor.
+    fn render_labels(&mut self, camera: &dyn Camera, width: f32, height: f32) {
+        let size = Vector2::new(width, height);
+
+        for label in self.labels.drain(..) {
+            let pos = match camera.project(&label.pos, &size) {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let h_pos = camera.transformation() * label.pos.to_homogeneous();
+
+            // The text renderer maps twice the size of the viewport to the whole viewport, from
+            // its top-left corner.
+            self.label_renderer.draw_text_at_depth(
+                &label.text,
+                &Point2::new(pos.x * 2.0, (height - pos.y) * 2.0),
+                h_pos.z / h_pos.w,
+                label.scale,
+                &label.font,
+                &label.color,
+                HAlign::Center,
+                VAlign::Middle,
+            );
+        }
+
+        self.label_renderer.render(width, height);
+    }
+
+    // Removes the expired lines and points added by `draw_line_for` and `draw_point_for`, and adds
+    // the others to the renderers.
+    fn draw_debug_primitives(&mut self) {
+        let now = self.curr_time;
+
+        self.debug_lines.retain(|line| line.3 > now);
+        self.debug_points.retain(|point| point.2 > now);
+
+        for &(a, b, color, _) in self.debug_lines.iter() {
+            self.line_renderer.draw_line(a, b, color);
+        }
+
+        for &(pt, color, _) in self.debug_points.iter() {
+            self.point_renderer.draw_point(pt, color);
+        }
     }
 
     fn render_scene(&mut self, camera: &mut dyn Camera, pass: usize) {
@@ -1145,9 +2779,28 @@ impl Window {
         verify!(ctxt.clear(Context::COLOR_BUFFER_BIT));
         verify!(ctxt.clear(Context::DEPTH_BUFFER_BIT));
 
+        self.skybox_renderer.render(pass, camera);
         self.line_renderer.render(pass, camera);
         self.point_renderer.render(pass, camera);
-        self.scene.data_mut().render(pass, camera, &self.light_mode);
+        self.render_objects(camera, pass);
+
+        self.scene
+            .data()
+            .render_outlines(pass, camera, &self.lights, &mut self.outline_renderer);
+    }
+
+    fn render_objects(&mut self, camera: &mut dyn Camera, pass: usize) {
+        set_default_backface_culling(self.backface_culling);
+
+        if self.frustum_culling {
+            // NOTE: multi-pass cameras return a frustum enclosing all their passes.
+            let frustum = camera.frustum_planes();
+            self.scene
+                .data_mut()
+                .render_with_frustum_culling(pass, camera, &self.lights, &frustum);
+        } else {
+            self.scene.data_mut().render(pass, camera, &self.lights);
+        }
     }
 
     fn render_planar_scene(&mut self, camera: &mut dyn PlanarCamera) {
@@ -1167,11 +2820,29 @@ impl Window {
         self.scene2.data_mut().render(camera);
     }
 
+    // Draws the user interface over the whole window, into the current framebuffer.
+    #[cfg(feature = "conrod")]
+    fn render_conrod_ui(&mut self, w: u32, h: u32) {
+        verify!(Context::get().viewport(0, 0, w as i32, h as i32));
+        verify!(Context::get().scissor(0, 0, w as i32, h as i32));
+        self.conrod_context.renderer.render(
+            w as f32,
+            h as f32,
+            self.canvas.scale_factor() as f32,
+            &self.conrod_context.textures,
+        );
+    }
+
     fn update_viewport(&mut self, w: f32, h: f32) {
         // Update the viewport
         verify!(Context::get().scissor(0, 0, w as i32, h as i32));
         FramebufferManager::screen().resize(w, h);
-        self.post_process_render_target.resize(w, h);
+        for target in self.post_process_render_targets.iter_mut() {
+            target.resize(w, h);
+        }
+        if let Some(ref mut target) = self.multisampled_render_target {
+            target.resize(w, h);
+        }
     }
 }
 
@@ -1192,3 +2863,32 @@ fn init_gl() {
     verify!(ctxt.enable(Context::CULL_FACE));
     verify!(ctxt.cull_face(Context::BACK));
 }
+
+// Maps the depth to `[0, 1]` in normalized device coordinates if `enabled` is `true`, and to the
+// default `[-1, 1]` otherwise. Only the 3D scene is drawn with the former, see `set_reversed_z`,
+// since the other renderers place their primitives on `[-1, 1]`.
+fn set_zero_to_one_depth(enabled: bool) {
+    let ctxt = Context::get();
+
+    if ctxt.supports_clip_control() {
+        let depth = if enabled {
+            Context::ZERO_TO_ONE
+        } else {
+            Context::NEGATIVE_ONE_TO_ONE
+        };
+        verify!(ctxt.clip_control(Context::LOWER_LEFT, depth));
+    }
+}
+
+// Sets the depth clear value and comparison, which keeps the closest fragments.
+fn set_depth_test(reversed_z: bool) {
+    let ctxt = Context::get();
+
+    if reversed_z {
+        verify!(ctxt.clear_depth(0.0));
+        verify!(ctxt.depth_func(Context::GEQUAL));
+    } else {
+        verify!(ctxt.clear_depth(1.0));
+        verify!(ctxt.depth_func(Context::LEQUAL));
+    }
+}